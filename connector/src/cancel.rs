@@ -1,23 +1,75 @@
-use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use crate::error::ConnectorError;
 
+type Hook = Box<dyn FnOnce() + Send>;
+
+tokio::task_local! {
+  static CURRENT: CancelToken;
+}
+
 /// Cooperative cancellation token shared across async tasks / blocking work.
-#[derive(Debug, Clone, Default)]
+///
+/// Drivers that can stop work on the server register an [`on_cancel`](Self::on_cancel)
+/// hook (interrupt, `KILL QUERY`, ...) for as long as their statement runs.
+#[derive(Clone, Default)]
 pub struct CancelToken {
   cancelled: Arc<AtomicBool>,
+  hooks: Arc<Mutex<Vec<(u64, Hook)>>>,
+  next_hook: Arc<AtomicU64>,
+}
+
+impl std::fmt::Debug for CancelToken {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("CancelToken")
+      .field("cancelled", &self.is_cancelled())
+      .finish_non_exhaustive()
+  }
 }
 
 impl CancelToken {
   pub fn new() -> Self {
-    Self {
-      cancelled: Arc::new(AtomicBool::new(false)),
-    }
+    Self::default()
   }
 
+  /// Set the flag and run the registered hooks once.
   pub fn cancel(&self) {
-    self.cancelled.store(true, Ordering::SeqCst);
+    if self.cancelled.swap(true, Ordering::SeqCst) {
+      return;
+    }
+    let hooks = match self.hooks.lock() {
+      Ok(mut hooks) => std::mem::take(&mut *hooks),
+      Err(_) => return,
+    };
+    for (_, hook) in hooks {
+      hook();
+    }
+  }
+
+  /// Run `hook` when cancel is requested while the returned guard is alive;
+  /// immediately when it already was. Hooks run on the cancelling thread, so
+  /// anything slower than flipping a flag should be spawned.
+  pub fn on_cancel(&self, hook: impl FnOnce() + Send + 'static) -> CancelHook {
+    let id = self.next_hook.fetch_add(1, Ordering::Relaxed);
+    let guard = self.hooks.lock();
+    let registered = match guard {
+      Ok(mut hooks) if !self.is_cancelled() => {
+        hooks.push((id, Box::new(hook)));
+        true
+      }
+      guard => {
+        // Release the lock first: the hook may register or drop hooks itself.
+        drop(guard);
+        hook();
+        false
+      }
+    };
+    CancelHook {
+      hooks: registered.then(|| Arc::clone(&self.hooks)),
+      id,
+    }
   }
 
   pub fn is_cancelled(&self) -> bool {
@@ -44,7 +96,26 @@ impl CancelToken {
   }
 }
 
-/// Race an async operation against cancellation.
+/// Unregisters an [`on_cancel`](CancelToken::on_cancel) hook when dropped, so a
+/// late cancel cannot reach a statement that has already finished.
+#[must_use]
+pub struct CancelHook {
+  hooks: Option<Arc<Mutex<Vec<(u64, Hook)>>>>,
+  id: u64,
+}
+
+impl Drop for CancelHook {
+  fn drop(&mut self) {
+    if let Some(hooks) = &self.hooks
+      && let Ok(mut hooks) = hooks.lock()
+    {
+      hooks.retain(|(id, _)| *id != self.id);
+    }
+  }
+}
+
+/// Race an async operation against cancellation. Drivers called inside `fut`
+/// see `token` through [`current`] and can hook server-side cancellation to it.
 pub async fn with_cancel<T, F>(token: Option<&CancelToken>, fut: F) -> anyhow::Result<T>
 where
   F: std::future::Future<Output = anyhow::Result<T>>,
@@ -52,18 +123,119 @@ where
   match token {
     None => fut.await,
     Some(token) => {
-      tokio::select! {
-        _ = token.cancelled() => Err(ConnectorError::Cancelled.into()),
-        res = fut => res,
+      let race = async {
+        tokio::select! {
+          _ = token.cancelled() => Err(ConnectorError::Cancelled.into()),
+          res = fut => res,
+        }
+      };
+      CURRENT.scope(token.clone(), race).await
+    }
+  }
+}
+
+/// How long [`with_timeout`] waits for an interrupted statement to stop.
+const TIMEOUT_GRACE: Duration = Duration::from_secs(1);
+
+/// Run `fut` with a deadline. On expiry its token is cancelled, so drivers stop
+/// the statement server-side, and the call fails with a timeout error.
+pub async fn with_timeout<T, F>(timeout: Duration, fut: F) -> anyhow::Result<T>
+where
+  F: std::future::Future<Output = anyhow::Result<T>>,
+{
+  let token = CancelToken::new();
+  // Cancelling the enclosing request still reaches the statement.
+  let _link = current().map(|outer| {
+    let token = token.clone();
+    outer.on_cancel(move || token.cancel())
+  });
+  let race = async {
+    let mut fut = std::pin::pin!(fut);
+    tokio::select! {
+      res = &mut fut => res,
+      _ = tokio::time::sleep(timeout) => {
+        // Cancel while `fut` still holds its interrupt, then let the statement
+        // wind down before it is dropped.
+        token.cancel();
+        let _ = tokio::time::timeout(TIMEOUT_GRACE, &mut fut).await;
+        Err(anyhow::anyhow!("statement timed out after {} ms", timeout.as_millis()))
       }
     }
+  };
+  CURRENT.scope(token.clone(), race).await
+}
+
+/// Token of the enclosing [`with_cancel`]. Capture it before moving work to a
+/// blocking thread; the task-local does not follow.
+pub fn current() -> Option<CancelToken> {
+  CURRENT.try_with(CancelToken::clone).ok()
+}
+
+/// Register `interrupt` on `token` for the life of the returned guard, then
+/// fail if cancel was already requested (the interrupt may not outlive a
+/// statement that has not started yet).
+pub(crate) fn interrupt_on(
+  token: Option<&CancelToken>,
+  interrupt: impl FnOnce() + Send + 'static,
+) -> Result<Option<CancelHook>, ConnectorError> {
+  let Some(token) = token else {
+    return Ok(None);
+  };
+  let hook = token.on_cancel(interrupt);
+  token.check()?;
+  Ok(Some(hook))
+}
+
+/// [`interrupt_on`] for a statement on a connection that outlives it (pooled
+/// or cached). `interrupt` runs on a thread of its own; dropping the guard
+/// waits out one already under way and disarms any later one, so it never
+/// reaches the connection's next statement. Drop the guard before releasing
+/// the connection.
+pub(crate) fn interrupt_statement(
+  token: Option<&CancelToken>,
+  interrupt: impl FnOnce() + Send + 'static,
+) -> Result<StatementGuard, ConnectorError> {
+  let running = Arc::new(Mutex::new(true));
+  let armed = Arc::clone(&running);
+  let hook = interrupt_on(token, move || {
+    std::thread::spawn(move || {
+      // Held across the interrupt, so the guard's drop waits it out.
+      let Ok(armed) = armed.lock() else {
+        return;
+      };
+      if *armed {
+        interrupt();
+      }
+    });
+  });
+  let mut guard = StatementGuard {
+    hook: None,
+    running,
+  };
+  // On error the guard drops here and disarms an interrupt already spawned.
+  guard.hook = hook?;
+  Ok(guard)
+}
+
+/// Keeps an [`interrupt_statement`] hook armed while its statement runs.
+#[must_use]
+pub(crate) struct StatementGuard {
+  hook: Option<CancelHook>,
+  running: Arc<Mutex<bool>>,
+}
+
+impl Drop for StatementGuard {
+  fn drop(&mut self) {
+    self.hook.take();
+    if let Ok(mut running) = self.running.lock() {
+      *running = false;
+    }
   }
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
-  use std::time::Duration;
 
   #[test]
   fn cancel_sets_flag() {
@@ -95,6 +267,63 @@ mod tests {
     assert!(err.to_string().contains("cancel") || err.to_string().contains("cancelled"));
   }
 
+  #[tokio::test]
+  async fn hooks_run_once_while_registered() {
+    use std::sync::atomic::AtomicUsize;
+
+    let token = CancelToken::new();
+    let runs = Arc::new(AtomicUsize::new(0));
+    let counter = |runs: &Arc<AtomicUsize>| {
+      let runs = Arc::clone(runs);
+      move || {
+        runs.fetch_add(1, Ordering::SeqCst);
+      }
+    };
+    drop(token.on_cancel(counter(&runs)));
+    let _live = token.on_cancel(counter(&runs));
+    token.cancel();
+    token.cancel();
+    assert_eq!(runs.load(Ordering::SeqCst), 1);
+    // Registered after the fact: runs right away.
+    let _late = token.on_cancel(counter(&runs));
+    assert_eq!(runs.load(Ordering::SeqCst), 2);
+    // A late hook may touch the token without deadlocking on its own lock.
+    let inner = token.clone();
+    let nested = counter(&runs);
+    let _nested = token.on_cancel(move || drop(inner.on_cancel(nested)));
+    assert_eq!(runs.load(Ordering::SeqCst), 3);
+  }
+
+  #[tokio::test]
+  async fn with_cancel_exposes_current_token() {
+    assert!(current().is_none());
+    let token = CancelToken::new();
+    let seen = with_cancel(Some(&token), async { Ok(current()) }).await.unwrap();
+    seen.unwrap().cancel();
+    assert!(token.is_cancelled());
+  }
+
+  #[tokio::test]
+  async fn cancel_interrupts_running_sqlite_statement() {
+    use crate::dialect::Connection;
+    use crate::dialect::sqlite::SqliteConnection;
+
+    let conn = SqliteConnection {
+      path: ":memory:".into(),
+    };
+    let token = CancelToken::new();
+    let canceller = token.clone();
+    tokio::spawn(async move {
+      tokio::time::sleep(Duration::from_millis(50)).await;
+      canceller.cancel();
+    });
+    let slow = "with recursive r(i) as (select 1 union all select i + 1 from r where i < 1e10)
+      select count(*) from r";
+    // Await the driver directly (no race) so the error is the engine's own.
+    let err = CURRENT.scope(token, conn.query(slow, 0, 0)).await.unwrap_err();
+    assert!(err.to_string().contains("interrupt"), "got {err}");
+  }
+
   #[tokio::test]
   async fn with_cancel_none_runs_to_completion() {
     let res = with_cancel(None, async { Ok::<_, anyhow::Error>(7) }).await;
@@ -107,4 +336,28 @@ mod tests {
     let res = with_cancel(Some(&token), async { Ok::<_, anyhow::Error>(1) }).await;
     assert_eq!(res.unwrap(), 1);
   }
+
+  #[test]
+  fn statement_guard_disarms_on_drop() {
+    let hits = Arc::new(AtomicU64::new(0));
+    let interrupt = |hits: &Arc<AtomicU64>| {
+      let hits = Arc::clone(hits);
+      move || {
+        hits.fetch_add(1, Ordering::SeqCst);
+      }
+    };
+
+    let token = CancelToken::new();
+    drop(interrupt_statement(Some(&token), interrupt(&hits)).unwrap());
+    token.cancel();
+    std::thread::sleep(Duration::from_millis(50));
+    assert_eq!(hits.load(Ordering::SeqCst), 0);
+
+    let token = CancelToken::new();
+    let guard = interrupt_statement(Some(&token), interrupt(&hits)).unwrap();
+    token.cancel();
+    std::thread::sleep(Duration::from_millis(50));
+    drop(guard);
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+  }
 }
@@ -3,6 +3,7 @@ use crate::dialect::clickhouse::ClickhouseConnection;
 use crate::dialect::duckdb::DuckDbConnection;
 use crate::dialect::file::FileConnection;
 use crate::dialect::folder::FolderConnection;
+use crate::dialect::mssql::MssqlConnection;
 use crate::dialect::mysql::MySqlConnection;
 use crate::dialect::postgres::PostgresConnection;
 use crate::dialect::quack::QuackConnection;
@@ -23,6 +24,8 @@ pub struct ConnectionConfig {
   pub uri: Option<String>,
   pub token: Option<String>,
   pub disable_ssl: Option<bool>,
+  /// SQL Server: accept any server certificate (`TrustServerCertificate=true`).
+  pub trust_server_certificate: Option<bool>,
   /// Quack: glob pattern(s) for file discovery via `SELECT * FROM glob(...)`.
   pub glob: Option<String>,
   /// Postgres: `disable` | `require` (default disable).
@@ -132,6 +135,21 @@ pub fn open(config: ConnectionConfig) -> anyhow::Result<Box<dyn Connection>> {
       config.database,
       config.ssh,
     ))),
+    "mssql" => {
+      let mut conn = MssqlConnection::new(
+        config
+          .host
+          .ok_or_else(|| anyhow::anyhow!("host required for mssql"))?,
+        config.port.unwrap_or_default(),
+        config.username.unwrap_or_default(),
+        config.password.unwrap_or_default(),
+        config.database,
+        config.ssh,
+      );
+      conn.encrypt = !config.disable_ssl.unwrap_or(false);
+      conn.trust_cert = config.trust_server_certificate.unwrap_or(false);
+      Ok(Box::new(conn))
+    }
     "postgres" => {
       let ssl_mode = config
         .ssl_mode
@@ -197,6 +215,7 @@ mod tests {
       "mysql",
       "postgres",
       "clickhouse",
+      "mssql",
       "duckdb",
       "sqlite",
       "file",
@@ -245,7 +264,7 @@ mod tests {
 
   #[test]
   fn open_network_dialects() {
-    for dialect in ["mysql", "postgres", "clickhouse"] {
+    for dialect in ["mysql", "postgres", "clickhouse", "mssql"] {
       let cfg = ConnectionConfig {
         dialect: dialect.into(),
         host: Some("127.0.0.1".into()),
@@ -1,5 +1,9 @@
-use sqlparser::ast::Statement;
+use sqlparser::ast::{
+  Expr, GroupByExpr, LimitClause, Query, SelectItem, SetExpr, Statement, TableFactor, Value,
+};
 use sqlparser::parser::Parser;
+use sqlparser::keywords::Keyword;
+use sqlparser::tokenizer::{Location, Token, TokenWithSpan, Tokenizer};
 
 pub fn count_sql(sql: &str) -> String {
   format!("select count(*) from ({sql}) ____")
@@ -17,6 +21,16 @@ pub fn limit_sql(sql: &str, limit: Option<usize>, offset: Option<usize>) -> Stri
 }
 
 pub fn count_stmt(dialect: &str, stmt: &Statement) -> Option<String> {
+  // T-SQL rejects ORDER BY in a derived table unless it also pages.
+  if dialect == "mssql"
+    && let Statement::Query(query) = stmt
+    && query.order_by.is_some()
+    && !is_paged(query)
+  {
+    let mut query = query.clone();
+    query.order_by = None;
+    return count_stmt(dialect, &Statement::Query(query));
+  }
   let dialect = convert_dialect(dialect);
   let dialect = &*dialect;
   match stmt {
@@ -48,6 +62,61 @@ pub fn count_stmt(dialect: &str, stmt: &Statement) -> Option<String> {
   }
 }
 
+/// Table read by `stmt` when it returns exactly one row per table row: a plain
+/// `select <columns> from t` without filters, grouping, joins or limits.
+/// Its row count is then the table's, so catalog estimates apply.
+pub fn scanned_table(stmt: &Statement) -> Option<String> {
+  let Statement::Query(query) = stmt else {
+    return None;
+  };
+  if query.with.is_some() || query.limit_clause.is_some() || query.fetch.is_some() {
+    return None;
+  }
+  let SetExpr::Select(select) = query.body.as_ref() else {
+    return None;
+  };
+  let plain_group = matches!(
+    &select.group_by,
+    GroupByExpr::Expressions(e, m) if e.is_empty() && m.is_empty()
+  );
+  if select.distinct.is_some()
+    || select.top.is_some()
+    || select.selection.is_some()
+    || select.prewhere.is_some()
+    || select.having.is_some()
+    || select.qualify.is_some()
+    || !plain_group
+    || !select.lateral_views.is_empty()
+  {
+    return None;
+  }
+  let plain_item = |item: &SelectItem| match item {
+    SelectItem::Wildcard(_) | SelectItem::QualifiedWildcard(..) => true,
+    SelectItem::UnnamedExpr(e) | SelectItem::ExprWithAlias { expr: e, .. } => {
+      matches!(e, Expr::Identifier(_) | Expr::CompoundIdentifier(_))
+    }
+  };
+  if !select.projection.iter().all(plain_item) {
+    return None;
+  }
+  let [from] = select.from.as_slice() else {
+    return None;
+  };
+  if !from.joins.is_empty() {
+    return None;
+  }
+  match &from.relation {
+    TableFactor::Table { sample: None, .. } => {
+      let mut relation = from.relation.clone();
+      if let TableFactor::Table { alias, .. } = &mut relation {
+        *alias = None;
+      }
+      Some(relation.to_string())
+    }
+    _ => None,
+  }
+}
+
 pub fn first_stmt(dialect: &str, sql: &str) -> Option<Statement> {
   let dialect = convert_dialect(dialect);
   let dialect = &*dialect;
@@ -66,6 +135,12 @@ pub fn limit_stmt(
   limit: Option<usize>,
   offset: Option<usize>,
 ) -> Option<String> {
+  if dialect == "mssql" {
+    return match stmt {
+      Statement::Query(query) => Some(offset_fetch_sql(query, limit, offset)),
+      _ => None,
+    };
+  }
   let dialect = convert_dialect(dialect);
   let dialect = &*dialect;
   match stmt {
@@ -97,6 +172,71 @@ pub fn limit_stmt(
   }
 }
 
+/// `stmt` limited to `limit` rows on the query itself rather than through a
+/// derived table, so duplicate output column names stay legal. `None` for
+/// statements that are not queries or that already page.
+pub fn top_limit_stmt(dialect: &str, stmt: &Statement, limit: usize) -> Option<String> {
+  let Statement::Query(query) = stmt else {
+    return None;
+  };
+  if is_paged(query) {
+    return None;
+  }
+  if dialect == "mssql" {
+    return Some(offset_fetch_sql(query, Some(limit), None));
+  }
+  let mut query = query.clone();
+  query.limit_clause = Some(LimitClause::LimitOffset {
+    limit: Some(Expr::value(Value::Number(limit.to_string(), false))),
+    offset: None,
+    limit_by: vec![],
+  });
+  Some(query.to_string())
+}
+
+/// Whether `query` already limits its rows (LIMIT, FETCH or TOP).
+fn is_paged(query: &Query) -> bool {
+  query.limit_clause.is_some()
+    || query.fetch.is_some()
+    || matches!(query.body.as_ref(), SetExpr::Select(select) if select.top.is_some())
+}
+
+/// T-SQL paging: `OFFSET n ROWS FETCH NEXT m ROWS ONLY`, which needs an ORDER
+/// BY (`(SELECT NULL)` keeps the server's order when the query has none).
+/// Queries that already page are wrapped as a derived table first.
+pub fn offset_fetch_sql(query: &Query, limit: Option<usize>, offset: Option<usize>) -> String {
+  if let Some(ref with) = query.with {
+    let mut body = query.clone();
+    body.with = None;
+    return format!("{with} {}", offset_fetch_sql(&body, limit, offset));
+  }
+  if is_paged(query) {
+    return offset_fetch_wrapped(&query.to_string(), limit, offset);
+  }
+  let sql = if query.order_by.is_none() {
+    format!("{query} ORDER BY (SELECT NULL)")
+  } else {
+    query.to_string()
+  };
+  offset_fetch(sql, limit, offset)
+}
+
+/// [`offset_fetch_sql`] for SQL that does not parse: paged as a derived table.
+pub fn offset_fetch_wrapped(sql: &str, limit: Option<usize>, offset: Option<usize>) -> String {
+  offset_fetch(
+    format!("SELECT * FROM ({sql}) ____ ORDER BY (SELECT NULL)"),
+    limit,
+    offset,
+  )
+}
+
+fn offset_fetch(sql: String, limit: Option<usize>, offset: Option<usize>) -> String {
+  let sql = format!("{sql} OFFSET {} ROWS", offset.unwrap_or(0));
+  match limit {
+    Some(limit) => format!("{sql} FETCH NEXT {limit} ROWS ONLY"),
+    None => sql,
+  }
+}
 
 /// Map connector dialect names to sqlparser dialect implementations.
 pub fn convert_dialect(d: &str) -> Box<dyn sqlparser::dialect::Dialect> {
@@ -106,6 +246,7 @@ pub fn convert_dialect(d: &str) -> Box<dyn sqlparser::dialect::Dialect> {
     "mysql" => Box::new(sqlparser::dialect::MySqlDialect {}),
     "postgres" => Box::new(sqlparser::dialect::PostgreSqlDialect {}),
     "sqlite" => Box::new(sqlparser::dialect::SQLiteDialect {}),
+    "mssql" => Box::new(sqlparser::dialect::MsSqlDialect {}),
     _ => Box::new(sqlparser::dialect::GenericDialect {}),
   }
 }
@@ -153,6 +294,288 @@ pub fn parse_location_from_message(message: &str) -> Option<(u64, u64)> {
   Some((line, column))
 }
 
+/// One top-level statement of a script, sliced verbatim from the source text.
+#[derive(Debug, Clone)]
+pub struct ScriptStatement {
+  pub sql: String,
+  /// 1-based position of the statement's first token within the script.
+  pub line: u64,
+  pub column: u64,
+  /// `None` when sqlparser rejects the statement; it is still sent to the server.
+  pub stmt: Option<Statement>,
+  /// sqlparser failure for this statement (location relative to the whole script).
+  pub parse_error: Option<SqlParseLocation>,
+}
+
+/// Split `sql` into top-level statements with the dialect's tokenizer and parser.
+///
+/// Statements end at a top-level `;` (see [`statement_end`]), so one that fails
+/// to parse does not stop the rest of the script from being split. The script
+/// is tokenized and parsed in a single pass. If tokenizing fails (e.g.
+/// unterminated literal), the whole script is returned as a single statement.
+pub fn split_statements(dialect: &str, sql: &str) -> Vec<ScriptStatement> {
+  let d = convert_dialect(dialect);
+  let d = &*d;
+  let tokens = match Tokenizer::new(d, sql).tokenize_with_location() {
+    Ok(tokens) => tokens,
+    Err(err) => {
+      let trimmed = sql.trim();
+      if trimmed.is_empty() {
+        return vec![];
+      }
+      let (line, column) = location_at(sql, sql.len() - sql.trim_start().len());
+      return vec![ScriptStatement {
+        sql: trimmed.to_string(),
+        line,
+        column,
+        stmt: None,
+        parse_error: Some(SqlParseLocation {
+          line: err.location.line.max(1),
+          column: err.location.column.max(1),
+          message: err.to_string(),
+        }),
+      }];
+    }
+  };
+
+  let mut parser = Parser::new(d).with_tokens_with_locations(tokens.clone());
+  let mut offsets = Offsets::new(sql);
+  let mut out = vec![];
+  loop {
+    while parser.consume_token(&Token::SemiColon) {}
+    let first = parser.peek_token();
+    if first.token == Token::EOF {
+      break;
+    }
+    let start = first.span.start;
+    let begin = (parser.index()..tokens.len())
+      .find(|&i| !matches!(tokens[i].token, Token::Whitespace(_)))
+      .unwrap_or(tokens.len());
+    let end = statement_end(&tokens, begin);
+    let parsed = parser.parse_statement().and_then(|stmt| {
+      // The parser must stop exactly where the statement does; stopping at a
+      // `;` inside a body, or before the next statement, is an error.
+      let next = parser.peek_token();
+      let at_end = match tokens.get(end) {
+        Some(t) => next.span.start == t.span.start,
+        None => next.token == Token::EOF,
+      };
+      if at_end {
+        Ok(stmt)
+      } else {
+        parser.expected("end of statement", next)
+      }
+    });
+    let (stmt, parse_error) = match parsed {
+      Ok(stmt) => (Some(stmt), None),
+      Err(err) => {
+        let message = err.to_string();
+        let (line, column) =
+          parse_location_from_message(&message).unwrap_or((start.line, start.column));
+        let loc = SqlParseLocation {
+          line: line.max(1),
+          column: column.max(1),
+          message,
+        };
+        (None, Some(loc))
+      }
+    };
+    // Resume after the statement whatever the parser consumed.
+    while parser.index() > end {
+      parser.prev_token();
+    }
+    while parser.index() < end {
+      parser.next_token_no_skip();
+    }
+    let last = tokens[begin..end]
+      .iter()
+      .rev()
+      .find(|t| !matches!(t.token, Token::Whitespace(_) | Token::EOF))
+      .map_or(start, |t| t.span.end);
+    let text = &sql[offsets.at(start)..offsets.at(last)];
+    out.push(ScriptStatement {
+      sql: text.trim().to_string(),
+      line: start.line,
+      column: start.column,
+      stmt,
+      parse_error,
+    });
+  }
+  out
+}
+
+/// Words that open the body of a `BEGIN ... END` block. `BEGIN` before
+/// anything else starts a transaction (`BEGIN;`, `BEGIN WORK`) or is a column.
+const BODY_STARTS: &[&str] = &[
+  "ALTER", "ATOMIC", "BEGIN", "CALL", "CASE", "CATCH", "CLOSE", "COMMIT", "CREATE", "DECLARE",
+  "DELETE", "DROP", "END", "EXEC", "EXECUTE", "FETCH", "FOR", "GET", "IF", "INSERT", "ITERATE",
+  "LEAVE", "LOOP", "MERGE", "NULL", "OPEN", "PERFORM", "PRINT", "RAISE", "REPEAT", "REPLACE",
+  "RESIGNAL", "RETURN", "ROLLBACK", "SELECT", "SET", "SIGNAL", "THROW", "TRUNCATE", "TRY",
+  "UPDATE", "VALUES", "WHILE", "WITH",
+];
+
+/// Index of the `;` that ends the statement starting at `start`, or
+/// `tokens.len()`. A `;` inside a `BEGIN ... END` body (triggers, procedures)
+/// or a `CASE ... END` does not end it; `END IF` / `END LOOP` style closers
+/// do not nest.
+fn statement_end(tokens: &[TokenWithSpan], start: usize) -> usize {
+  let word = |t: &TokenWithSpan| match &t.token {
+    Token::Word(w) if w.quote_style.is_none() => Some(w.value.to_ascii_uppercase()),
+    _ => None,
+  };
+  let mut depth = 0usize;
+  for (i, token) in tokens.iter().enumerate().skip(start) {
+    if depth == 0 && token.token == Token::SemiColon {
+      return i;
+    }
+    let Some(keyword) = word(token) else {
+      continue;
+    };
+    let next = tokens[i + 1..]
+      .iter()
+      .find(|t| !matches!(t.token, Token::Whitespace(_)));
+    let next_word = next.and_then(word);
+    match keyword.as_str() {
+      "BEGIN" if next_word.is_some_and(|w| BODY_STARTS.contains(&w.as_str())) => depth += 1,
+      "CASE" => depth += 1,
+      "END" => {
+        if !matches!(next_word.as_deref(), Some("IF" | "LOOP" | "WHILE" | "REPEAT" | "FOR")) {
+          depth = depth.saturating_sub(1);
+        }
+      }
+      _ => {}
+    }
+  }
+  tokens.len()
+}
+
+/// A query split before its trailing top-level `SETTINGS` / `FORMAT` clauses
+/// (ClickHouse), without surrounding comments, whitespace or `;`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryTail<'a> {
+  pub body: &'a str,
+  pub settings: Option<&'a str>,
+  pub format: Option<&'a str>,
+}
+
+/// Split `sql` per [`QueryTail`]. SQL that does not tokenize is all body.
+pub fn split_query_tail<'a>(dialect: &str, sql: &'a str) -> QueryTail<'a> {
+  let d = convert_dialect(dialect);
+  let Ok(tokens) = Tokenizer::new(&*d, sql).tokenize_with_location() else {
+    return QueryTail {
+      body: sql.trim().trim_end_matches(';').trim_end(),
+      settings: None,
+      format: None,
+    };
+  };
+  let meaningful = |t: &&TokenWithSpan| {
+    !matches!(t.token, Token::Whitespace(_) | Token::SemiColon | Token::EOF)
+  };
+  let (Some(first), Some(last)) = (
+    tokens.iter().find(meaningful),
+    tokens.iter().rev().find(meaningful),
+  ) else {
+    return QueryTail {
+      body: "",
+      settings: None,
+      format: None,
+    };
+  };
+  let (start, end) = (offset_at(sql, first.span.start), offset_at(sql, last.span.end));
+
+  let (mut settings, mut format) = (None, None);
+  let mut depth = 0usize;
+  for (i, t) in tokens.iter().enumerate() {
+    match &t.token {
+      Token::LParen => depth += 1,
+      Token::RParen => depth = depth.saturating_sub(1),
+      Token::Word(w) if depth == 0 && w.quote_style.is_none() => {
+        // Tell the clauses from a `format(...)` call or a column named `settings`:
+        // `SETTINGS name = ...`, and `FORMAT name` last or before SETTINGS.
+        let mut next = tokens[i + 1..].iter().filter(meaningful).map(|t| &t.token);
+        let at = offset_at(sql, t.span.start);
+        match (w.keyword, next.next(), next.next()) {
+          (Keyword::SETTINGS, Some(Token::Word(_)), Some(Token::Eq)) if settings.is_none() => {
+            settings = Some(at)
+          }
+          (Keyword::FORMAT, Some(Token::Word(_)), None) if format.is_none() => format = Some(at),
+          (Keyword::FORMAT, Some(Token::Word(_)), Some(Token::Word(k)))
+            if k.keyword == Keyword::SETTINGS && format.is_none() =>
+          {
+            format = Some(at)
+          }
+          _ => {}
+        }
+      }
+      _ => {}
+    }
+  }
+  let body_end = [settings, format].into_iter().flatten().min().unwrap_or(end);
+  let clause = |from: Option<usize>, to: Option<usize>| {
+    from.map(|from| sql[from..to.filter(|to| *to > from).unwrap_or(end)].trim_end())
+  };
+  QueryTail {
+    body: sql[start..body_end].trim_end(),
+    settings: clause(settings, format),
+    format: clause(format, None),
+  }
+}
+
+/// Byte offset of a tokenizer [`Location`] (lines split on `\n`, columns count chars).
+fn offset_at(sql: &str, loc: Location) -> usize {
+  Offsets::new(sql).at(loc)
+}
+
+/// Byte offsets of tokenizer [`Location`]s, found by walking `sql` forward
+/// from the previous lookup, so increasing locations cost one pass in total.
+struct Offsets<'a> {
+  sql: &'a str,
+  byte: usize,
+  line: u64,
+  column: u64,
+}
+
+impl<'a> Offsets<'a> {
+  fn new(sql: &'a str) -> Self {
+    Self {
+      sql,
+      byte: 0,
+      line: 1,
+      column: 1,
+    }
+  }
+
+  fn at(&mut self, loc: Location) -> usize {
+    if (loc.line, loc.column) < (self.line, self.column) {
+      *self = Self::new(self.sql);
+    }
+    for ch in self.sql[self.byte..].chars() {
+      if self.line == loc.line && self.column == loc.column {
+        return self.byte;
+      }
+      if ch == '\n' {
+        self.line += 1;
+        self.column = 1;
+      } else {
+        self.column += 1;
+      }
+      self.byte += ch.len_utf8();
+    }
+    self.sql.len()
+  }
+}
+
+fn location_at(sql: &str, offset: usize) -> (u64, u64) {
+  let before = &sql[..offset];
+  let line = before.matches('\n').count() as u64 + 1;
+  let column = before
+    .rsplit('\n')
+    .next()
+    .map_or(0, |l| l.chars().count()) as u64
+    + 1;
+  (line, column)
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -205,12 +628,30 @@ mod tests {
       "quack",
       "clickhouse",
       "sqlite",
+      "mssql",
       "unknown-dialect",
     ] {
       let _ = convert_dialect(name);
     }
   }
 
+  #[test]
+  fn scanned_table_only_for_row_preserving_selects() {
+    let table = |sql: &str| scanned_table(&first_stmt("duckdb", sql).unwrap());
+    assert_eq!(table("select * from main.t"), Some("main.t".into()));
+    assert_eq!(table("select a, t.b as c from t x"), Some("t".into()));
+    assert_eq!(
+      table("select * from read_parquet('a.parquet')"),
+      Some("read_parquet('a.parquet')".into())
+    );
+    assert_eq!(table("select * from t where a > 1"), None);
+    assert_eq!(table("select count(*) from t"), None);
+    assert_eq!(table("select distinct a from t"), None);
+    assert_eq!(table("select * from t limit 5"), None);
+    assert_eq!(table("select * from t join u on t.id = u.id"), None);
+    assert_eq!(table("select a from t group by a"), None);
+  }
+
   #[test]
   fn limit_sql_pages_for_export() {
     let sql = limit_sql("select * from t", Some(5000), Some(10000));
@@ -218,6 +659,57 @@ mod tests {
     assert!(sql.contains("offset 10000"));
   }
 
+  #[test]
+  fn top_limit_keeps_duplicate_columns_legal() {
+    let limited = |dialect: &str, sql: &str| {
+      let stmt = first_stmt(dialect, sql).unwrap();
+      top_limit_stmt(dialect, &stmt, 11)
+    };
+    assert_eq!(
+      limited("mysql", "select a.id, b.id from a join b on a.id = b.id").as_deref(),
+      Some("SELECT a.id, b.id FROM a JOIN b ON a.id = b.id LIMIT 11")
+    );
+    assert_eq!(limited("mysql", "select * from t limit 5"), None);
+    assert_eq!(limited("mysql", "delete from t"), None);
+  }
+
+  #[test]
+  fn split_query_tail_finds_top_level_clauses() {
+    let tail = split_query_tail(
+      "clickhouse",
+      "select format('{}', x) as settings from t settings max_threads = 2 format JSON -- note\n;",
+    );
+    assert_eq!(tail.body, "select format('{}', x) as settings from t");
+    assert_eq!(tail.settings, Some("settings max_threads = 2"));
+    assert_eq!(tail.format, Some("format JSON"));
+
+    let tail = split_query_tail("clickhouse", "select 'settings' from t -- trailing");
+    assert_eq!(tail.body, "select 'settings' from t");
+    assert_eq!((tail.settings, tail.format), (None, None));
+  }
+
+  #[test]
+  fn mssql_pages_with_offset_fetch() {
+    let page = |sql: &str| {
+      let stmt = first_stmt("mssql", sql).unwrap();
+      limit_stmt("mssql", &stmt, Some(10), Some(20))
+    };
+    assert_eq!(
+      page("select a from t order by a").unwrap(),
+      "SELECT a FROM t ORDER BY a OFFSET 20 ROWS FETCH NEXT 10 ROWS ONLY"
+    );
+    assert_eq!(
+      page("select top 5 a from t").unwrap(),
+      "SELECT * FROM (SELECT TOP 5 a FROM t) ____ ORDER BY (SELECT NULL) OFFSET 20 ROWS FETCH NEXT 10 ROWS ONLY"
+    );
+    assert_eq!(
+      page("with c as (select a from t) select a from c").unwrap(),
+      "WITH c AS (SELECT a FROM t) SELECT a FROM c ORDER BY (SELECT NULL) OFFSET 20 ROWS FETCH NEXT 10 ROWS ONLY"
+    );
+    let count = count_stmt("mssql", &first_stmt("mssql", "select a from t order by a").unwrap());
+    assert_eq!(count.unwrap(), "select count(*) from (SELECT a FROM t) ____");
+  }
+
   #[test]
   fn locate_sql_parse_error_returns_none_for_valid_sql() {
     assert!(
@@ -242,4 +734,63 @@ mod tests {
       Some((3, 12))
     );
   }
+
+  #[test]
+  fn split_statements_keeps_source_text() {
+    let sql = "select 'a;b' as x;\n-- note\nupdate t set a = 1 where id = 2;\n\n";
+    let stmts = split_statements("generic", sql);
+    assert_eq!(stmts.len(), 2);
+    assert_eq!(stmts[0].sql, "select 'a;b' as x");
+    assert_eq!((stmts[0].line, stmts[0].column), (1, 1));
+    assert_eq!(stmts[1].sql, "update t set a = 1 where id = 2");
+    assert_eq!((stmts[1].line, stmts[1].column), (3, 1));
+    assert!(stmts.iter().all(|s| s.stmt.is_some() && s.parse_error.is_none()));
+  }
+
+  #[test]
+  fn split_statements_isolates_parse_errors() {
+    let sql = "select 1;\nselect from where;\n  select 2";
+    let stmts = split_statements("generic", sql);
+    assert_eq!(stmts.len(), 3);
+    assert_eq!(stmts[1].sql, "select from where");
+    let loc = stmts[1].parse_error.as_ref().expect("parse error");
+    assert_eq!(loc.line, 2);
+    assert!(stmts[1].stmt.is_none());
+    assert_eq!(stmts[2].sql, "select 2");
+    assert_eq!((stmts[2].line, stmts[2].column), (3, 3));
+  }
+
+  #[test]
+  fn split_statements_keeps_trigger_bodies_whole() {
+    let sql = "create table t (a int, begin int);
+create trigger bump after insert on t
+begin
+  update t set a = case when a > 0 then a + 1 else 1 end where rowid = new.rowid;
+  insert into log values (new.a);
+end;
+begin;
+select begin, a from t;
+commit;";
+    let stmts = split_statements("sqlite", sql);
+    let texts: Vec<_> = stmts.iter().map(|s| s.sql.lines().next().unwrap()).collect();
+    assert_eq!(
+      texts,
+      [
+        "create table t (a int, begin int)",
+        "create trigger bump after insert on t",
+        "begin",
+        "select begin, a from t",
+        "commit",
+      ]
+    );
+    assert!(stmts[1].sql.ends_with("insert into log values (new.a);\nend"));
+    assert_eq!((stmts[3].line, stmts[3].column), (8, 1));
+  }
+
+  #[test]
+  fn split_statements_requires_delimiter() {
+    let stmts = split_statements("generic", "select 1 select 2");
+    assert_eq!(stmts.len(), 1);
+    assert!(stmts[0].parse_error.is_some());
+  }
 }
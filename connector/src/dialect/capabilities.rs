@@ -11,9 +11,13 @@ impl Caps {
   pub const EXPORT: Caps = Caps(1 << 3);
   pub const FIND: Caps = Caps(1 << 4);
   pub const DROP_TABLE: Caps = Caps(1 << 5);
-  /// Reserved for non-query SQL execution (DDL/DML). Not assigned to dialects yet.
+  /// Non-query SQL execution (DDL/DML) returning rows affected.
   pub const EXECUTE: Caps = Caps(1 << 6);
   pub const TABLE_BROWSE: Caps = Caps(1 << 7);
+  /// Explicit begin/commit/rollback on a dedicated connection.
+  pub const TRANSACTION: Caps = Caps(1 << 8);
+  /// Grid edits applied as generated DML (needs primary-key discovery).
+  pub const EDIT: Caps = Caps(1 << 9);
 
   /// Common SQL dialect set: query + metadata + paging + export + table browse.
   pub const SQL_CORE: Caps = Caps(
@@ -71,6 +75,12 @@ impl Caps {
     if self.contains(Self::TABLE_BROWSE) {
       out.push("table_browse");
     }
+    if self.contains(Self::TRANSACTION) {
+      out.push("transaction");
+    }
+    if self.contains(Self::EDIT) {
+      out.push("edit");
+    }
     out
   }
 
@@ -86,6 +96,8 @@ impl Caps {
         "drop_table" => Self::DROP_TABLE,
         "execute" => Self::EXECUTE,
         "table_browse" => Self::TABLE_BROWSE,
+        "transaction" => Self::TRANSACTION,
+        "edit" => Self::EDIT,
         _ => Caps::empty(),
       });
     }
@@ -112,9 +124,22 @@ impl Serialize for Caps {
 /// Per-dialect capability table used by factories and tests.
 pub fn caps_for_dialect(dialect: &str) -> Caps {
   match dialect {
-    "mysql" | "postgres" | "clickhouse" | "quack" => Caps::SQL_CORE,
-    "duckdb" => Caps::SQL_CORE | Caps::DROP_TABLE,
-    "sqlite" => Caps::QUERY | Caps::METADATA | Caps::PAGING | Caps::EXPORT | Caps::TABLE_BROWSE,
+    "mysql" | "postgres" => {
+      Caps::SQL_CORE | Caps::EXECUTE | Caps::TRANSACTION | Caps::EDIT
+    }
+    "clickhouse" | "mssql" => Caps::SQL_CORE | Caps::EXECUTE,
+    "quack" => Caps::SQL_CORE,
+    "duckdb" => Caps::SQL_CORE | Caps::DROP_TABLE | Caps::EXECUTE | Caps::EDIT,
+    "sqlite" => {
+      Caps::QUERY
+        | Caps::METADATA
+        | Caps::PAGING
+        | Caps::EXPORT
+        | Caps::TABLE_BROWSE
+        | Caps::EXECUTE
+        | Caps::TRANSACTION
+        | Caps::EDIT
+    }
     "folder" => {
       Caps::QUERY
         | Caps::METADATA
@@ -162,9 +187,16 @@ mod tests {
     assert!(!caps_for_dialect("file").contains(Caps::FIND));
     assert!(!caps_for_dialect("sqlite").contains(Caps::DROP_TABLE));
     assert!(!caps_for_dialect("mysql").contains(Caps::FIND));
-    // EXECUTE is reserved; no dialect advertises it yet.
-    assert!(!caps_for_dialect("duckdb").contains(Caps::EXECUTE));
-    assert!(!caps_for_dialect("mysql").contains(Caps::EXECUTE));
+    for dialect in ["mysql", "postgres", "sqlite", "duckdb", "clickhouse", "mssql"] {
+      assert!(caps_for_dialect(dialect).contains(Caps::EXECUTE), "{dialect}");
+    }
+    assert!(!caps_for_dialect("folder").contains(Caps::EXECUTE));
+    assert!(!caps_for_dialect("file").contains(Caps::EXECUTE));
+    assert!(caps_for_dialect("postgres").contains(Caps::TRANSACTION));
+    assert!(!caps_for_dialect("clickhouse").contains(Caps::TRANSACTION));
+    assert!(!caps_for_dialect("mssql").contains(Caps::TRANSACTION));
+    assert!(caps_for_dialect("duckdb").contains(Caps::EDIT));
+    assert!(!caps_for_dialect("folder").contains(Caps::EDIT));
     assert!(!caps_for_dialect("file").contains(Caps::METADATA));
   }
 
@@ -1,12 +1,29 @@
+mod type_arrow;
+
+use std::collections::VecDeque;
 use std::fs::File;
 use std::io::Write;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
+use crate::cancel::CancelHook;
+use crate::dialect::cursor::{self, ResultCursor};
 use crate::dialect::Connection;
+use crate::dialect::ast;
+use crate::dialect::ddl::{self, ObjectKind, join_statements};
+use crate::dialect::explain::{self, QueryPlan};
+use crate::dialect::params::{self, ParamValue, Params};
+use crate::dialect::detail::{ColumnDetail, IndexDetail, TableDetail, non_empty, split_expr_list};
+pub(crate) use crate::dialect::clickhouse::type_arrow::{base_type, declared_arrow_type};
 use crate::ssh_tunnel::{DbSshConfig, SshTunnel};
-use crate::utils::{build_tree, json_to_arrow, FunctionMeta, Metadata, RawArrowData, Table, TreeNode};
-use arrow::datatypes::*;
+use crate::utils::{build_tree, FunctionMeta, Metadata, RawArrowData, Table, TreeNode};
+use arrow::array::RecordBatch;
+use arrow::buffer::Buffer;
+use arrow::compute::concat_batches;
+use arrow::datatypes::{Schema, SchemaRef};
+use arrow::ipc::reader::StreamDecoder;
 use async_trait::async_trait;
+use clickhouse::query::{BytesCursor, Query};
 use clickhouse::{Client, Row};
 use serde::{Deserialize, Serialize};
 
@@ -70,7 +87,15 @@ impl Connection for ClickhouseConnection {
   }
 
   async fn query(&self, sql: &str, _limit: usize, _offset: usize) -> anyhow::Result<RawArrowData> {
-    self.fetch_all(sql).await
+    self.fetch_all(sql, &[]).await
+  }
+
+  /// Placeholders become typed `{p1:Int64}` server-side parameters.
+  async fn query_params(&self, sql: &str, params: &Params) -> anyhow::Result<RawArrowData> {
+    let bound = params::bind(self.dialect(), sql, params)?;
+    let mut raw = self.fetch_all(&bound.sql, &bound.values).await?;
+    raw.sql = Some(sql.to_string());
+    Ok(raw)
   }
 
   async fn query_count(&self, sql: &str) -> anyhow::Result<usize> {
@@ -83,6 +108,35 @@ impl Connection for ClickhouseConnection {
     "clickhouse"
   }
 
+  /// `SETTINGS max_execution_time` (whole seconds) on a query that does not
+  /// already carry its own settings, placed before any `FORMAT` clause and
+  /// clear of trailing comments.
+  fn statement_timeout_sql(&self, sql: &str, timeout: Duration) -> Option<String> {
+    let tail = ast::split_query_tail(self.dialect(), sql);
+    let lower = tail.body.to_ascii_lowercase();
+    let is_query = lower.starts_with("select") || lower.starts_with("with");
+    if !is_query || tail.settings.is_some() {
+      return None;
+    }
+    let secs = timeout.as_millis().div_ceil(1000);
+    let mut sql = format!("{} SETTINGS max_execution_time = {secs}", tail.body);
+    if let Some(format) = tail.format {
+      sql = format!("{sql} {format}");
+    }
+    Some(sql)
+  }
+
+  /// ClickHouse has no affected-row count; report `written_rows` from the
+  /// `X-ClickHouse-Summary` header (0 for DDL).
+  async fn execute(&self, sql: &str) -> anyhow::Result<usize> {
+    self.execute_bound(sql, &[]).await
+  }
+
+  async fn execute_params(&self, sql: &str, params: &Params) -> anyhow::Result<usize> {
+    let bound = params::bind(self.dialect(), sql, params)?;
+    self.execute_bound(&bound.sql, &bound.values).await
+  }
+
   async fn show_schema(&self, schema: &str) -> anyhow::Result<RawArrowData> {
     let sql = format!(
       "select * except(uuid) from system.tables where database='{schema}' order by engine, name"
@@ -124,10 +178,79 @@ impl Connection for ClickhouseConnection {
       .collect())
   }
 
+  /// `EXPLAIN PLAN json = 1`; ClickHouse cannot analyze, so the plan only
+  /// carries estimates.
+  async fn explain(&self, sql: &str, analyze: bool) -> anyhow::Result<QueryPlan> {
+    if analyze {
+      explain::check_analyze("clickhouse", sql)?;
+    }
+    let raw = explain::plan_text(self, &format!("EXPLAIN PLAN json = 1, indexes = 1 {sql}")).await?;
+    explain::parse_postgres_json(&raw, false)
+  }
+
+  async fn estimate_row_count(&self, table: &str) -> anyhow::Result<usize> {
+    let client = self.get_client()?;
+    let unquote = |s: &str| s.trim_matches('`').to_string();
+    let (database, table) = match table.rsplit_once('.') {
+      Some((db, table)) => (unquote(db), unquote(table)),
+      None => (
+        client.query("select currentDatabase()").fetch_one::<String>().await?,
+        unquote(table),
+      ),
+    };
+    // Only MergeTree-family tables have parts; others fall back to count().
+    let (rows, parts) = client
+      .query(
+        "select sum(rows), count() from system.parts
+        where active and database = ? and table = ?",
+      )
+      .bind(&database)
+      .bind(&table)
+      .fetch_one::<(u64, u64)>()
+      .await?;
+    if parts == 0 {
+      anyhow::bail!("no parts for {database}.{table}");
+    }
+    Ok(rows as usize)
+  }
+
   async fn table_row_count(&self, table: &str, r#where: &str) -> anyhow::Result<usize> {
     self._table_row_count(table, r#where).await
   }
 
+  async fn table_detail(&self, schema: Option<&str>, table: &str) -> anyhow::Result<TableDetail> {
+    self._table_detail(schema, table).await
+  }
+
+  async fn object_ddl(
+    &self,
+    schema: Option<&str>,
+    name: &str,
+    kind: ObjectKind,
+  ) -> anyhow::Result<String> {
+    let client = self.get_client()?;
+    let ddl = match kind {
+      ObjectKind::Function => client
+        .query("select create_query from system.functions where name = ? and origin = 'SQLUserDefined'")
+        .bind(name)
+        .fetch_optional::<String>()
+        .await?
+        .ok_or_else(|| ddl::not_found(kind, name))?,
+      _ => {
+        let target = match schema {
+          Some(s) => format!("{}.{}", self.quote(s), self.quote(name)),
+          None => self.quote(name),
+        };
+        let what = if kind == ObjectKind::View { "VIEW" } else { "TABLE" };
+        client
+          .query(&format!("SHOW CREATE {what} {target}"))
+          .fetch_one::<String>()
+          .await?
+      }
+    };
+    Ok(join_statements([ddl]))
+  }
+
   async fn export(
     &self,
     sql: &str,
@@ -157,6 +280,139 @@ impl Connection for ClickhouseConnection {
     }
     Ok(())
   }
+
+  async fn open_cursor(&self, sql: &str) -> anyhow::Result<Box<dyn ResultCursor>> {
+    let client = self.get_client()?;
+    let types = self.column_types(&client, sql, &[]).await?;
+    let mut stream = ArrowStream::new(arrow_query(client.query(sql)).fetch_bytes("ArrowStream")?);
+    // Read ahead to the first batch so the schema is known up front.
+    let pending = stream.next_batch().await?;
+    let titles = column_titles(&stream.schema(), &types);
+    Ok(Box::new(ClickhouseCursor {
+      _owner: self.clone(),
+      titles,
+      types,
+      sql: sql.to_string(),
+      state: tokio::sync::Mutex::new(Some(CursorState {
+        stream,
+        buffered: pending.into_iter().collect(),
+      })),
+    }))
+  }
+}
+
+/// Incrementally decodes an `ArrowStream` response as its chunks arrive.
+struct ArrowStream {
+  bytes: BytesCursor,
+  decoder: StreamDecoder,
+  buf: Buffer,
+  eof: bool,
+}
+
+impl ArrowStream {
+  fn new(bytes: BytesCursor) -> Self {
+    Self {
+      bytes,
+      decoder: StreamDecoder::new(),
+      buf: Buffer::from(Vec::<u8>::new()),
+      eof: false,
+    }
+  }
+
+  /// The next record batch of the response, `None` at the end.
+  async fn next_batch(&mut self) -> anyhow::Result<Option<RecordBatch>> {
+    loop {
+      // `decode` only stops early when it has a batch; otherwise the whole
+      // chunk has been consumed.
+      if let Some(batch) = self.decoder.decode(&mut self.buf)? {
+        return Ok(Some(batch));
+      }
+      if self.eof {
+        return Ok(None);
+      }
+      match self.bytes.next().await? {
+        Some(chunk) => self.buf = Buffer::from(chunk),
+        None => {
+          self.decoder.finish()?;
+          self.eof = true;
+        }
+      }
+    }
+  }
+
+  /// Empty when the statement produced no result set (DDL, `SYSTEM ...`).
+  fn schema(&self) -> SchemaRef {
+    self.decoder.schema().unwrap_or_else(|| Arc::new(Schema::empty()))
+  }
+}
+
+struct CursorState {
+  stream: ArrowStream,
+  /// Batches read off the stream and not yet returned.
+  buffered: VecDeque<RecordBatch>,
+}
+
+impl CursorState {
+  fn buffered_rows(&self) -> usize {
+    self.buffered.iter().map(RecordBatch::num_rows).sum()
+  }
+}
+
+/// Streams `ArrowStream` record batches off a single HTTP response.
+pub struct ClickhouseCursor {
+  /// Keeps the SSH tunnel alive while the cursor is open.
+  _owner: ClickhouseConnection,
+  titles: Vec<Title>,
+  types: Vec<String>,
+  sql: String,
+  /// Held across each fetch, so a failed or dropped fetch leaves it in place.
+  state: tokio::sync::Mutex<Option<CursorState>>,
+}
+
+#[async_trait]
+impl ResultCursor for ClickhouseCursor {
+  fn dialect(&self) -> &'static str {
+    "clickhouse"
+  }
+
+  async fn fetch(&self, rows: usize) -> anyhow::Result<RawArrowData> {
+    let mut guard = self.state.lock().await;
+    let state = guard.as_mut().ok_or_else(cursor::closed)?;
+    // Read ahead first; rows leave the buffer only after the last await, so a
+    // fetch that fails or is dropped loses none.
+    while state.buffered_rows() < rows {
+      match state.stream.next_batch().await? {
+        Some(batch) => state.buffered.push_back(batch),
+        None => break,
+      }
+    }
+    let mut batches = Vec::new();
+    let mut read = 0;
+    while read < rows
+      && let Some(batch) = state.buffered.pop_front()
+    {
+      let take = (rows - read).min(batch.num_rows());
+      if take < batch.num_rows() {
+        state.buffered.push_front(batch.slice(take, batch.num_rows() - take));
+      }
+      batches.push(batch.slice(0, take));
+      read += take;
+    }
+    let schema = state.stream.schema();
+    drop(guard);
+    let batch = type_arrow::retype(concat_batches(&schema, &batches)?, &self.types)?;
+    Ok(RawArrowData {
+      titles: Some(self.titles.clone()),
+      sql: Some(self.sql.clone()),
+      ..RawArrowData::from_batch(batch)
+    })
+  }
+
+  async fn close(&self) -> anyhow::Result<()> {
+    // Dropping the byte stream aborts the HTTP response.
+    self.state.lock().await.take().ok_or_else(cursor::closed)?;
+    Ok(())
+  }
 }
 
 #[derive(Row, Serialize, Deserialize)]
@@ -174,25 +430,28 @@ struct MetaRow {
   table: String,
   columns: Vec<(String, String)>,
 }
-#[derive(Debug, Deserialize)]
-#[allow(dead_code)]
-struct Statistics {
-  bytes_read: u64,
-  elapsed: f64,
+#[derive(Row, Serialize, Deserialize)]
+struct TableInfoRow {
+  engine: String,
+  partition_key: String,
+  sorting_key: String,
+  primary_key: String,
+  comment: String,
 }
-#[derive(Debug, Clone, Deserialize)]
-struct MetaItem {
+
+#[derive(Row, Serialize, Deserialize)]
+struct ColumnInfoRow {
   name: String,
-  r#type: String, // 注意: "type" 是 Rust 关键字，可使用 #[serde(rename = "type")]
+  r#type: String,
+  default_expression: String,
+  comment: String,
 }
-#[derive(Debug, Deserialize)]
-#[allow(dead_code)]
-struct JSONColumnsWithMetadataResponse {
-  meta: Vec<MetaItem>,
-  rows: u32,
-  statistics: Statistics,
-  data: serde_json::Value,
-  // 其他字段可以选择性定义
+
+#[derive(Row, Serialize, Deserialize)]
+struct SkipIndexRow {
+  name: String,
+  r#type: String,
+  expr: String,
 }
 
 impl ClickhouseConnection {
@@ -318,6 +577,73 @@ impl ClickhouseConnection {
     }
     Ok(metadata)
   }
+  /// ClickHouse has no foreign keys or unique constraints; keys come from the
+  /// MergeTree `ORDER BY` / `PRIMARY KEY` / `PARTITION BY` expressions.
+  async fn _table_detail(&self, schema: Option<&str>, table: &str) -> anyhow::Result<TableDetail> {
+    let client = self.get_client()?;
+    let database = match schema {
+      Some(s) => s.to_string(),
+      None => client.query("select currentDatabase()").fetch_one::<String>().await?,
+    };
+    let info = client
+      .query(
+        "select engine, partition_key, sorting_key, primary_key, comment
+        from system.tables where database = ? and name = ?",
+      )
+      .bind(&database)
+      .bind(table)
+      .fetch_optional::<TableInfoRow>()
+      .await?
+      .ok_or_else(|| anyhow::anyhow!("table not found: {table}"))?;
+
+    let columns = client
+      .query(
+        "select name, type, default_expression, comment
+        from system.columns where database = ? and table = ? order by position",
+      )
+      .bind(&database)
+      .bind(table)
+      .fetch_all::<ColumnInfoRow>()
+      .await?
+      .into_iter()
+      .map(|c| ColumnDetail {
+        nullable: c.r#type.contains("Nullable("),
+        name: c.name,
+        r#type: c.r#type,
+        default: non_empty(Some(c.default_expression)),
+        comment: non_empty(Some(c.comment)),
+      })
+      .collect();
+
+    let indexes = client
+      .query("select name, type, expr from system.data_skipping_indices where database = ? and table = ?")
+      .bind(&database)
+      .bind(table)
+      .fetch_all::<SkipIndexRow>()
+      .await?
+      .into_iter()
+      .map(|i| IndexDetail {
+        name: i.name,
+        columns: vec![i.expr],
+        unique: false,
+        method: Some(i.r#type),
+      })
+      .collect();
+
+    Ok(TableDetail {
+      schema: Some(database),
+      table: table.to_string(),
+      comment: non_empty(Some(info.comment)),
+      columns,
+      primary_key: split_expr_list(&info.primary_key),
+      indexes,
+      sorting_key: split_expr_list(&info.sorting_key),
+      partition_key: non_empty(Some(info.partition_key)),
+      engine: Some(info.engine),
+      ..Default::default()
+    })
+  }
+
   async fn _table_row_count(&self, table: &str, r#where: &str) -> anyhow::Result<usize> {
     let conn = self.get_client()?;
     let sql = self._table_count_sql(table, r#where);
@@ -325,42 +651,133 @@ impl ClickhouseConnection {
     Ok(total as usize)
   }
 
-  async fn _fetch_all(&self, sql: &str) -> anyhow::Result<JSONColumnsWithMetadataResponse> {
+  /// `KILL QUERY` for `query_id` when the enclosing request is cancelled.
+  fn kill_on_cancel(
+    &self,
+    client: &Client,
+    query_id: &str,
+  ) -> anyhow::Result<Option<CancelHook>> {
+    let Some(cancel) = crate::cancel::current() else {
+      return Ok(None);
+    };
+    let client = client.clone();
+    let runtime = tokio::runtime::Handle::current();
+    let sql = format!("KILL QUERY WHERE query_id = '{query_id}' ASYNC");
+    let kill = move || {
+      runtime.spawn(async move {
+        if let Err(e) = client.query(&sql).execute().await {
+          log::warn!("{sql} failed: {e}");
+        }
+      });
+    };
+    Ok(crate::cancel::interrupt_on(Some(&cancel), kill)?)
+  }
+
+  /// ClickHouse type names of the result columns, via `DESCRIBE TABLE (sql)`
+  /// without its `FORMAT` / `SETTINGS` tail. Empty for statements other than
+  /// queries and for queries that cannot be described.
+  async fn column_types(
+    &self,
+    client: &Client,
+    sql: &str,
+    values: &[ParamValue],
+  ) -> anyhow::Result<Vec<String>> {
+    let tail = ast::split_query_tail(self.dialect(), sql);
+    let lower = tail.body.to_ascii_lowercase();
+    if !(lower.starts_with("select") || lower.starts_with("with")) {
+      return Ok(Vec::new());
+    }
+    let describe = format!("DESCRIBE TABLE ({})", tail.body);
+    let query_id = uuid::Uuid::new_v4().to_string();
+    let _hook = self.kill_on_cancel(client, &query_id)?;
+    let query = bind_params(client.query(&describe), values).with_setting("query_id", &query_id);
+    let Ok(mut cursor) = query.fetch_bytes("JSONCompactEachRow") else {
+      return Ok(Vec::new());
+    };
+    let Ok(bytes) = cursor.collect().await else {
+      return Ok(Vec::new());
+    };
+    Ok(
+      bytes
+        .split(|b| *b == b'\n')
+        .filter_map(|line| serde_json::from_slice::<Vec<serde_json::Value>>(line).ok())
+        .map(|row| row.get(1).and_then(|t| t.as_str()).unwrap_or_default().to_string())
+        .collect(),
+    )
+  }
+
+  async fn execute_bound(&self, sql: &str, values: &[ParamValue]) -> anyhow::Result<usize> {
     let client = self.get_client()?;
-    // https://clickhouse.com/docs/interfaces/formats/JSONStrings
-    let mut cursor = client.query(sql).fetch_bytes("JSONStrings")?;
-    let bytes = cursor.collect().await?;
-    let res: JSONColumnsWithMetadataResponse = serde_json::from_slice(&bytes)?;
-    Ok(res)
-  }
-  async fn fetch_all(&self, sql: &str) -> anyhow::Result<RawArrowData> {
-    let res = self._fetch_all(sql).await?;
-    let titles = res
-      .meta
-      .clone()
-      .into_iter()
-      .map(|m| Title {
-        name: m.name,
-        r#type: m.r#type,
-      })
-      .collect::<Vec<_>>();
+    let query_id = uuid::Uuid::new_v4().to_string();
+    let _hook = self.kill_on_cancel(&client, &query_id)?;
+    let mut cursor = bind_params(client.query(sql), values)
+      .with_setting("wait_end_of_query", "1")
+      .with_setting("query_id", &query_id)
+      .fetch_bytes("TabSeparated")?;
+    while cursor.next().await?.is_some() {}
+    let written = cursor
+      .summary()
+      .and_then(|s| s.written_rows())
+      .unwrap_or_default();
+    Ok(written as usize)
+  }
 
-    let default: Vec<serde_json::Value> = Vec::default();
-    let data = res.data.as_array().unwrap_or(&default);
-    let fields = res
-      .meta
-      .into_iter()
-      .map(|m| Field::new(m.name, DataType::LargeUtf8, true))
-      .collect::<Vec<Field>>();
-    let schema = Arc::new(Schema::new(fields));
-    let batch = json_to_arrow(data, schema)?;
+  /// Runs the query as `ArrowStream`, so columns arrive typed; the titles
+  /// carry the ClickHouse type names.
+  async fn fetch_all(&self, sql: &str, values: &[ParamValue]) -> anyhow::Result<RawArrowData> {
+    let client = self.get_client()?;
+    let types = self.column_types(&client, sql, values).await?;
+    let query_id = uuid::Uuid::new_v4().to_string();
+    let _hook = self.kill_on_cancel(&client, &query_id)?;
+    let query = bind_params(client.query(sql), values).with_setting("query_id", &query_id);
+    let mut stream = ArrowStream::new(arrow_query(query).fetch_bytes("ArrowStream")?);
+    let mut batches = Vec::new();
+    while let Some(batch) = stream.next_batch().await? {
+      batches.push(batch);
+    }
+    let schema = stream.schema();
+    let batch = type_arrow::retype(concat_batches(&schema, &batches)?, &types)?;
     Ok(RawArrowData {
-      total: batch.num_rows(),
-      batch,
-      titles: Some(titles),
+      titles: Some(column_titles(&schema, &types)),
       sql: Some(sql.to_string()),
+      ..RawArrowData::from_batch(batch)
+    })
+  }
+}
+
+/// Strings as Utf8 rather than Binary.
+fn arrow_query(query: Query) -> Query {
+  query.with_setting("output_format_arrow_string_as_string", "1")
+}
+
+/// Titles from the ClickHouse types, or the Arrow types when DESCRIBE did not
+/// match the result.
+fn column_titles(schema: &Schema, types: &[String]) -> Vec<Title> {
+  let described = types.len() == schema.fields().len();
+  schema
+    .fields()
+    .iter()
+    .enumerate()
+    .map(|(i, field)| Title {
+      name: field.name().clone(),
+      r#type: if described { types[i].clone() } else { field.data_type().to_string() },
     })
+    .collect()
+}
+
+/// Values for the `{pN:Type}` placeholders written by [`params::bind`].
+fn bind_params(mut query: Query, values: &[ParamValue]) -> Query {
+  for (i, value) in values.iter().enumerate() {
+    let name = format!("p{}", i + 1);
+    query = match value {
+      ParamValue::Null => query.param(&name, None::<String>),
+      ParamValue::Bool(v) => query.param(&name, v),
+      ParamValue::Int(v) => query.param(&name, v),
+      ParamValue::Float(v) => query.param(&name, v),
+      ParamValue::Text(v) => query.param(&name, v),
+    };
   }
+  query
 }
 
 #[tokio::test]
@@ -369,7 +786,7 @@ async fn test_clickhouse() {
   use arrow::util::pretty::print_batches;
   let conn = ClickhouseConnection::new("https://play.clickhouse.com", "", "play", "");
   let res = conn
-    .fetch_all("SELECT * FROM system.tables limit 10")
+    .fetch_all("SELECT * FROM system.tables limit 10", &[])
     .await
     .unwrap();
   let batch = res.batch;
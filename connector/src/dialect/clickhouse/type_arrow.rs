@@ -0,0 +1,320 @@
+//! ClickHouse writes a few types to `ArrowStream` as bare integers: `Date` as
+//! days in UInt16, `DateTime` as seconds in UInt32, `Enum` as its code and
+//! `IPv4` as UInt32. `UUID`, `IPv6` and the 128/256-bit integers arrive as raw
+//! fixed-size bytes. These are turned back into Arrow dates, timestamps and
+//! strings using the column's ClickHouse type.
+
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::sync::Arc;
+
+use arrow::array::{
+  ArrayRef, AsArray, FixedSizeBinaryArray, RecordBatch, RecordBatchOptions, StringArray,
+};
+use arrow::compute::cast;
+use arrow::datatypes::{DataType, Int64Type, Schema, TimeUnit, i256};
+
+/// Convert each column of `batch` according to the ClickHouse type at the
+/// same position. A `types` list that does not line up with the batch leaves
+/// it untouched.
+pub fn retype(batch: RecordBatch, types: &[String]) -> anyhow::Result<RecordBatch> {
+  if types.len() != batch.num_columns() {
+    return Ok(batch);
+  }
+  let schema = batch.schema();
+  let mut fields = Vec::with_capacity(types.len());
+  let mut columns = Vec::with_capacity(types.len());
+  for ((field, array), ty) in schema.fields().iter().zip(batch.columns()).zip(types) {
+    let array = retype_column(array, ty)?;
+    fields.push(
+      field
+        .as_ref()
+        .clone()
+        .with_data_type(array.data_type().clone()),
+    );
+    columns.push(array);
+  }
+  let options = RecordBatchOptions::new().with_row_count(Some(batch.num_rows()));
+  Ok(RecordBatch::try_new_with_options(
+    Arc::new(Schema::new(fields)),
+    columns,
+    &options,
+  )?)
+}
+
+fn retype_column(array: &ArrayRef, ty: &str) -> anyhow::Result<ArrayRef> {
+  let ty = base_type(ty);
+  if let DataType::FixedSizeBinary(_) = array.data_type() {
+    let text = fixed_text(array.as_fixed_size_binary(), ty);
+    return Ok(text.unwrap_or_else(|| array.clone()));
+  }
+  if !array.data_type().is_integer() {
+    return Ok(array.clone());
+  }
+  if ty == "Date" {
+    return Ok(cast(&cast(array, &DataType::Int32)?, &DataType::Date32)?);
+  }
+  if let Some(tz) = datetime_tz(ty) {
+    let ts = DataType::Timestamp(TimeUnit::Second, tz.map(Into::into));
+    return Ok(cast(&cast(array, &DataType::Int64)?, &ts)?);
+  }
+  if ty.starts_with("Enum8(") || ty.starts_with("Enum16(") {
+    let labels = enum_labels(ty);
+    let codes = cast(array, &DataType::Int64)?;
+    let strings: StringArray = codes
+      .as_primitive::<Int64Type>()
+      .iter()
+      .map(|code| code.map(|c| labels.get(&c).cloned().unwrap_or_else(|| c.to_string())))
+      .collect();
+    return Ok(Arc::new(strings));
+  }
+  if ty == "IPv4" {
+    let addrs = cast(array, &DataType::Int64)?;
+    let strings: StringArray = addrs
+      .as_primitive::<Int64Type>()
+      .iter()
+      .map(|addr| addr.map(|a| Ipv4Addr::from(a as u32).to_string()))
+      .collect();
+    return Ok(Arc::new(strings));
+  }
+  Ok(array.clone())
+}
+
+/// Text of a column ClickHouse writes as raw bytes; `None` for other types
+/// (`FixedString` stays binary) or an unexpected width.
+fn fixed_text(array: &FixedSizeBinaryArray, ty: &str) -> Option<ArrayRef> {
+  let (width, text): (i32, fn(&[u8]) -> String) = match ty {
+    "UUID" => (16, uuid_text),
+    "IPv6" => (16, |b| Ipv6Addr::from(<[u8; 16]>::try_from(b).unwrap()).to_string()),
+    "Int128" => (16, |b| i128::from_le_bytes(b.try_into().unwrap()).to_string()),
+    "UInt128" => (16, |b| u128::from_le_bytes(b.try_into().unwrap()).to_string()),
+    "Int256" => (32, |b| i256::from_le_bytes(b.try_into().unwrap()).to_string()),
+    "UInt256" => (32, u256_text),
+    _ => return None,
+  };
+  if array.value_length() != width {
+    return None;
+  }
+  let strings: StringArray = array.iter().map(|v| v.map(text)).collect();
+  Some(Arc::new(strings))
+}
+
+/// A `UUID` is stored as two little-endian UInt64 halves, high half first.
+fn uuid_text(bytes: &[u8]) -> String {
+  let high = u64::from_le_bytes(bytes[..8].try_into().unwrap());
+  let low = u64::from_le_bytes(bytes[8..].try_into().unwrap());
+  uuid::Uuid::from_u64_pair(high, low).to_string()
+}
+
+/// Decimal text of a little-endian UInt256.
+fn u256_text(bytes: &[u8]) -> String {
+  let mut limbs: Vec<u64> = bytes
+    .chunks(8)
+    .rev()
+    .map(|c| u64::from_le_bytes(c.try_into().unwrap()))
+    .collect();
+  // Peel off 19 decimal digits at a time, most significant limb first.
+  const CHUNK: u64 = 10_000_000_000_000_000_000;
+  let mut parts = vec![];
+  loop {
+    let mut rem = 0u128;
+    for limb in &mut limbs {
+      let cur = (rem << 64) | u128::from(*limb);
+      *limb = (cur / u128::from(CHUNK)) as u64;
+      rem = cur % u128::from(CHUNK);
+    }
+    parts.push(rem as u64);
+    if limbs.iter().all(|l| *l == 0) {
+      break;
+    }
+  }
+  let mut text = parts.pop().unwrap_or_default().to_string();
+  for part in parts.iter().rev() {
+    text.push_str(&format!("{part:019}"));
+  }
+  text
+}
+
+/// Arrow type of a declared column type (`DateTime64(3, 'UTC')`) for comparing
+/// schemas; wrappers are stripped first. Dates and timestamps map to the types
+/// [`retype`] gives them. `None` for types without an Arrow counterpart.
+pub fn declared_arrow_type(ty: &str) -> Option<DataType> {
+  let ty = base_type(ty);
+  let (name, args) = match ty.split_once('(') {
+    Some((name, args)) => (name, args.strip_suffix(')')?),
+    None => (ty, ""),
+  };
+  Some(match name {
+    "Bool" => DataType::Boolean,
+    "Int8" => DataType::Int8,
+    "Int16" => DataType::Int16,
+    "Int32" => DataType::Int32,
+    "Int64" => DataType::Int64,
+    "UInt8" => DataType::UInt8,
+    "UInt16" => DataType::UInt16,
+    "UInt32" => DataType::UInt32,
+    "UInt64" => DataType::UInt64,
+    "Float32" => DataType::Float32,
+    "Float64" => DataType::Float64,
+    "Decimal32" | "Decimal64" | "Decimal128" | "Decimal256" => {
+      let scale = args.trim().parse().ok()?;
+      match name {
+        "Decimal32" => DataType::Decimal128(9, scale),
+        "Decimal64" => DataType::Decimal128(18, scale),
+        "Decimal128" => DataType::Decimal128(38, scale),
+        _ => DataType::Decimal256(76, scale),
+      }
+    }
+    "Date" | "Date32" => DataType::Date32,
+    "DateTime" => DataType::Timestamp(TimeUnit::Second, datetime_tz(ty)?.map(Into::into)),
+    "DateTime64" => {
+      let tz = args.split_once(',').map(|(_, tz)| tz.trim().trim_matches('\''));
+      DataType::Timestamp(TimeUnit::Microsecond, tz.map(Into::into))
+    }
+    _ => return None,
+  })
+}
+
+/// The type inside any `Nullable(...)` / `LowCardinality(...)` wrappers.
+pub fn base_type(mut ty: &str) -> &str {
+  loop {
+    let inner = ["Nullable(", "LowCardinality("]
+      .iter()
+      .find_map(|w| ty.strip_prefix(w).and_then(|t| t.strip_suffix(')')));
+    match inner {
+      Some(inner) => ty = inner,
+      None => return ty,
+    }
+  }
+}
+
+/// `Some(tz)` for `DateTime` / `DateTime('tz')`; `DateTime64` already arrives
+/// as an Arrow timestamp.
+fn datetime_tz(ty: &str) -> Option<Option<&str>> {
+  if ty == "DateTime" {
+    return Some(None);
+  }
+  let tz = ty.strip_prefix("DateTime(")?.strip_suffix(')')?;
+  Some(Some(tz.trim().trim_matches('\'')))
+}
+
+/// Code to label map of an `Enum8('a' = 1, 'b' = 2)` type.
+fn enum_labels(ty: &str) -> HashMap<i64, String> {
+  let mut labels = HashMap::new();
+  let Some(body) = ty.split_once('(').and_then(|(_, b)| b.strip_suffix(')')) else {
+    return labels;
+  };
+  let mut chars = body.chars();
+  while chars.by_ref().any(|c| c == '\'') {
+    let mut label = String::new();
+    while let Some(c) = chars.next() {
+      match c {
+        '\\' => label.extend(chars.next()),
+        '\'' => break,
+        _ => label.push(c),
+      }
+    }
+    let code: String = chars
+      .by_ref()
+      .skip_while(|c| *c == ' ' || *c == '=')
+      .take_while(|c| *c == '-' || c.is_ascii_digit())
+      .collect();
+    if let Ok(code) = code.parse() {
+      labels.insert(code, label);
+    }
+  }
+  labels
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use arrow::array::{Array, Int8Array, StringArray, UInt16Array, UInt32Array};
+  use arrow::datatypes::Field;
+
+  #[test]
+  fn parses_enum_labels() {
+    let labels = enum_labels(r"Enum8('a' = 1, 'it\'s' = -2, 'x, y' = 3)");
+    assert_eq!(labels.get(&1).map(String::as_str), Some("a"));
+    assert_eq!(labels.get(&-2).map(String::as_str), Some("it's"));
+    assert_eq!(labels.get(&3).map(String::as_str), Some("x, y"));
+    assert_eq!(base_type("LowCardinality(Nullable(String))"), "String");
+  }
+
+  #[test]
+  fn retypes_integer_encoded_columns() {
+    let schema = Arc::new(Schema::new(vec![
+      Field::new("d", DataType::UInt16, true),
+      Field::new("t", DataType::UInt32, false),
+      Field::new("e", DataType::Int8, true),
+      Field::new("ip", DataType::UInt32, false),
+      Field::new("s", DataType::Utf8, false),
+    ]));
+    let batch = RecordBatch::try_new(
+      schema,
+      vec![
+        Arc::new(UInt16Array::from(vec![Some(19723), None])),
+        Arc::new(UInt32Array::from(vec![0, 1_700_000_000])),
+        Arc::new(Int8Array::from(vec![Some(1), None])),
+        Arc::new(UInt32Array::from(vec![0x7f000001, 0])),
+        Arc::new(StringArray::from(vec!["a", "b"])),
+      ],
+    )
+    .unwrap();
+    let types = [
+      "Nullable(Date)",
+      "DateTime('UTC')",
+      "Nullable(Enum8('on' = 1, 'off' = 0))",
+      "IPv4",
+      "String",
+    ]
+    .map(String::from);
+    let batch = retype(batch, &types).unwrap();
+    let schema = batch.schema();
+    assert_eq!(schema.field(0).data_type(), &DataType::Date32);
+    assert!(schema.field(0).is_nullable());
+    assert_eq!(
+      schema.field(1).data_type(),
+      &DataType::Timestamp(TimeUnit::Second, Some("UTC".into()))
+    );
+    let enums = batch.column(2).as_string::<i32>();
+    assert_eq!(enums.value(0), "on");
+    assert!(enums.is_null(1));
+    assert_eq!(batch.column(3).as_string::<i32>().value(0), "127.0.0.1");
+    assert_eq!(schema.field(4).data_type(), &DataType::Utf8);
+  }
+
+  #[test]
+  fn wide_values_become_text() {
+    let uuid = hex("e711b35c04c4f061a0dbd36a00a67b90");
+    let ip = hex("20010db8000000000000000000000001");
+    let mut minus_one = [0xff; 16].to_vec();
+    minus_one.extend([0xff; 16]);
+    let mut big = vec![0; 32];
+    big[31] = 0x80;
+    let column = |values: Vec<Vec<u8>>| -> ArrayRef {
+      Arc::new(FixedSizeBinaryArray::try_from_iter(values.into_iter()).unwrap())
+    };
+    let text = |array: ArrayRef, ty| {
+      let array = retype_column(&array, ty).unwrap();
+      array.as_string::<i32>().value(0).to_string()
+    };
+    assert_eq!(text(column(vec![uuid]), "UUID"), "61f0c404-5cb3-11e7-907b-a6006ad3dba0");
+    assert_eq!(text(column(vec![ip]), "Nullable(IPv6)"), "2001:db8::1");
+    assert_eq!(text(column(vec![vec![0xff; 16]]), "Int128"), "-1");
+    assert_eq!(text(column(vec![minus_one]), "Int256"), "-1");
+    assert_eq!(
+      text(column(vec![big]), "UInt256"),
+      "57896044618658097711785492504343953926634992332820282019728792003956564819968"
+    );
+    let fixed = column(vec![b"ab".to_vec()]);
+    assert_eq!(retype_column(&fixed, "FixedString(2)").unwrap().data_type(), fixed.data_type());
+  }
+
+  fn hex(s: &str) -> Vec<u8> {
+    (0..s.len())
+      .step_by(2)
+      .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+      .collect()
+  }
+}
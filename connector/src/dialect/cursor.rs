@@ -0,0 +1,170 @@
+//! Server-side result cursors: run a query once and fetch its rows in pages.
+
+use std::sync::Mutex;
+use std::sync::mpsc::{Receiver, Sender, SyncSender, channel, sync_channel};
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+
+use crate::utils::RawArrowData;
+
+/// An executed query whose rows are pulled incrementally.
+///
+/// `fetch` returns fewer than `rows` rows (possibly none, schema intact) once
+/// the result is exhausted. Dropping the handle releases the server resources.
+#[async_trait]
+pub trait ResultCursor: Send + Sync {
+  fn dialect(&self) -> &'static str;
+
+  /// The next page of at most `rows` rows.
+  async fn fetch(&self, rows: usize) -> anyhow::Result<RawArrowData>;
+
+  async fn close(&self) -> anyhow::Result<()>;
+}
+
+pub(crate) fn closed() -> anyhow::Error {
+  anyhow!("cursor already closed")
+}
+
+/// One `fetch` sent to a [`ThreadCursor`] worker.
+pub(crate) struct FetchRequest {
+  pub rows: usize,
+  reply: Sender<anyhow::Result<RawArrowData>>,
+}
+
+impl FetchRequest {
+  pub fn reply(self, page: anyhow::Result<RawArrowData>) {
+    let _ = self.reply.send(page);
+  }
+}
+
+/// Fetch requests as seen by the worker thread.
+pub(crate) struct FetchRequests {
+  rx: Receiver<FetchRequest>,
+  ready: Option<SyncSender<anyhow::Result<()>>>,
+}
+
+impl FetchRequests {
+  /// Block until the next fetch; `None` once the cursor is closed. The first
+  /// call also tells [`ThreadCursor::spawn`] the query started successfully.
+  pub fn next_fetch(&mut self) -> Option<FetchRequest> {
+    if let Some(ready) = self.ready.take() {
+      let _ = ready.send(Ok(()));
+    }
+    self.rx.recv().ok()
+  }
+}
+
+/// Cursor for blocking drivers: a dedicated thread owns the connection and the
+/// live statement (which borrows it), and serves pages over a channel.
+pub(crate) struct ThreadCursor {
+  dialect: &'static str,
+  requests: Mutex<Option<Sender<FetchRequest>>>,
+}
+
+impl ThreadCursor {
+  /// Start `run` on its own thread. `run` opens the query, then answers
+  /// [`FetchRequests::next_fetch`] until it returns `None`. Errors before the
+  /// first `next_fetch` call are returned from here.
+  pub async fn spawn<F>(dialect: &'static str, run: F) -> anyhow::Result<Self>
+  where
+    F: FnOnce(&mut FetchRequests) -> anyhow::Result<()> + Send + 'static,
+  {
+    let (tx, rx) = channel();
+    let (ready_tx, ready_rx) = sync_channel(1);
+    std::thread::Builder::new()
+      .name(format!("{dialect}-cursor"))
+      .spawn(move || {
+        let mut requests = FetchRequests {
+          rx,
+          ready: Some(ready_tx),
+        };
+        if let Err(e) = run(&mut requests) {
+          match requests.ready.take() {
+            Some(ready) => {
+              let _ = ready.send(Err(e));
+            }
+            None => log::warn!("{dialect} cursor stopped: {e:#}"),
+          }
+        }
+      })?;
+    crate::dialect::run_blocking(move || {
+      ready_rx
+        .recv()
+        .map_err(|_| anyhow!("{dialect} cursor thread exited"))?
+    })
+    .await?;
+    Ok(Self {
+      dialect,
+      requests: Mutex::new(Some(tx)),
+    })
+  }
+}
+
+#[async_trait]
+impl ResultCursor for ThreadCursor {
+  fn dialect(&self) -> &'static str {
+    self.dialect
+  }
+
+  async fn fetch(&self, rows: usize) -> anyhow::Result<RawArrowData> {
+    let tx = self
+      .requests
+      .lock()
+      .map_err(|_| anyhow!("cursor lock poisoned"))?
+      .clone()
+      .ok_or_else(closed)?;
+    let (reply, page) = channel();
+    tx.send(FetchRequest { rows, reply }).map_err(|_| closed())?;
+    crate::dialect::run_blocking(move || page.recv().map_err(|_| closed())?).await
+  }
+
+  async fn close(&self) -> anyhow::Result<()> {
+    // Dropping the sender ends the worker loop, which drops the connection.
+    self
+      .requests
+      .lock()
+      .map_err(|_| anyhow!("cursor lock poisoned"))?
+      .take()
+      .ok_or_else(closed)?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::sync::Arc;
+
+  use arrow::array::{ArrayRef, Int64Array, RecordBatch};
+
+  fn page(n: usize, start: i64) -> RawArrowData {
+    let values: ArrayRef = Arc::new(Int64Array::from_iter_values(start..start + n as i64));
+    RawArrowData::from_batch(RecordBatch::try_from_iter([("v", values)]).unwrap())
+  }
+
+  #[tokio::test]
+  async fn thread_cursor_serves_pages_until_closed() {
+    let cursor = ThreadCursor::spawn("test", |requests| {
+      let mut next = 0i64;
+      while let Some(req) = requests.next_fetch() {
+        let n = req.rows.min((5 - next) as usize);
+        req.reply(Ok(page(n, next)));
+        next += n as i64;
+      }
+      Ok(())
+    })
+    .await
+    .unwrap();
+    assert_eq!(cursor.fetch(3).await.unwrap().batch.num_rows(), 3);
+    assert_eq!(cursor.fetch(3).await.unwrap().batch.num_rows(), 2);
+    cursor.close().await.unwrap();
+    assert!(cursor.fetch(3).await.is_err());
+  }
+
+  #[tokio::test]
+  async fn open_errors_surface_from_spawn() {
+    let res = ThreadCursor::spawn("test", |_| Err(anyhow!("no such table"))).await;
+    assert!(res.err().unwrap().to_string().contains("no such table"));
+  }
+}
@@ -0,0 +1,583 @@
+//! Row-level comparison of one table across two connections.
+//!
+//! Both sides are paged through [`Connection::query_table`] ordered by the key
+//! and merged like a sort-merge join, so memory stays bounded by the page size.
+//! Cells of numeric columns compare by value (`1.5` vs `1.50` across engines is
+//! not a change); everything else compares by its text, code point by code
+//! point. Text keys are ordered the same way on the server, whatever the
+//! column's collation, so both sides agree with the merge.
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, VecDeque};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail};
+use arrow::array::{ArrayRef, Int64Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::util::display::array_value_to_string;
+use rust_decimal::Decimal;
+
+use super::{Connection, CountOptions};
+use crate::utils::RawArrowData;
+
+type Cell = Option<String>;
+
+/// How a column's cells compare, from its Arrow type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+  /// Integers and decimals.
+  Exact,
+  /// Floating point.
+  Approx,
+  Text,
+}
+
+impl Kind {
+  fn of(data_type: &DataType) -> Self {
+    match data_type {
+      t if t.is_integer() => Kind::Exact,
+      DataType::Decimal32(..)
+      | DataType::Decimal64(..)
+      | DataType::Decimal128(..)
+      | DataType::Decimal256(..) => Kind::Exact,
+      t if t.is_floating() => Kind::Approx,
+      _ => Kind::Text,
+    }
+  }
+}
+
+/// Totals are unused here; skip the full count where statistics exist.
+const ESTIMATE: CountOptions = CountOptions {
+  estimate: true,
+  timeout_ms: None,
+};
+
+#[derive(Debug, Clone)]
+pub struct DataDiffOptions {
+  /// Key columns; empty = primary key of the source (falling back to the target's).
+  pub key: Vec<String>,
+  /// Rows fetched per `query_table` call on each side.
+  pub page_size: usize,
+  /// Differing rows kept in [`DataDiff::rows`]; counts cover all rows.
+  pub max_rows: usize,
+}
+
+impl Default for DataDiffOptions {
+  fn default() -> Self {
+    Self {
+      key: vec![],
+      page_size: 5000,
+      max_rows: 1000,
+    }
+  }
+}
+
+#[derive(Debug)]
+pub struct DataDiff {
+  pub key: Vec<String>,
+  /// Rows only in the source.
+  pub inserted: usize,
+  /// Rows only in the target.
+  pub deleted: usize,
+  pub changed: usize,
+  pub unchanged: usize,
+  /// Differing rows: `_diff` (`insert`/`delete`/`change`), `_changed` (column list),
+  /// the key columns, then `source.<col>` / `target.<col>` for each compared column.
+  pub rows: RawArrowData,
+  /// `column`, `changes`: rows whose value differs, per compared column.
+  pub column_changes: RawArrowData,
+  /// More than `max_rows` rows differed.
+  pub truncated: bool,
+  /// Non-key columns present on one side only (not compared).
+  pub source_only_columns: Vec<String>,
+  pub target_only_columns: Vec<String>,
+}
+
+/// One side of the merge: a key-ordered page buffer over `query_table`.
+struct Side<'a> {
+  conn: &'a dyn Connection,
+  table: &'a str,
+  order_by: String,
+  page_size: usize,
+  offset: usize,
+  /// Keyset cursor of the last page, when the table can be sought by `key`.
+  after: Option<String>,
+  done: bool,
+  columns: Vec<String>,
+  kinds: Vec<Kind>,
+  rows: VecDeque<Vec<Cell>>,
+  key_idx: Vec<usize>,
+  last_key: Option<Vec<Cell>>,
+}
+
+impl<'a> Side<'a> {
+  async fn open(
+    conn: &'a dyn Connection,
+    table: &'a str,
+    key: &[String],
+    page_size: usize,
+  ) -> anyhow::Result<Side<'a>> {
+    // One row tells the column types, and so how each key must be ordered.
+    let probe = conn
+      .query_table(table, 1, 0, None, "", "", "", &ESTIMATE)
+      .await?;
+    let schema = probe.batch.schema();
+    let mut side = Side {
+      conn,
+      table,
+      order_by: String::new(),
+      page_size: page_size.max(1),
+      offset: 0,
+      after: None,
+      done: false,
+      columns: schema.fields().iter().map(|f| f.name().clone()).collect(),
+      kinds: schema.fields().iter().map(|f| Kind::of(f.data_type())).collect(),
+      rows: VecDeque::new(),
+      key_idx: vec![],
+      last_key: None,
+    };
+    side.key_idx = key
+      .iter()
+      .map(|k| {
+        side
+          .position(k)
+          .ok_or_else(|| anyhow!("key column {k} not found in {table}"))
+      })
+      .collect::<anyhow::Result<_>>()?;
+    side.order_by = key
+      .iter()
+      .zip(&side.key_idx)
+      .map(|(k, &i)| match side.kinds[i] {
+        Kind::Text => code_point_order(conn.dialect(), &conn.quote(k)),
+        _ => conn.quote(k),
+      })
+      .collect::<Vec<_>>()
+      .join(", ");
+    side.fetch().await?;
+    Ok(side)
+  }
+
+  fn position(&self, column: &str) -> Option<usize> {
+    self.columns.iter().position(|c| c.eq_ignore_ascii_case(column))
+  }
+
+  async fn fetch(&mut self) -> anyhow::Result<()> {
+    let raw = self
+      .conn
+      .query_table(
+        self.table,
+        self.page_size,
+        self.offset,
+        self.after.as_deref(),
+        "",
+        &self.order_by,
+        "",
+        &ESTIMATE,
+      )
+      .await?;
+    self.after = raw.cursor;
+    let batch = raw.batch;
+    let n = batch.num_rows();
+    for row in 0..n {
+      let cells = batch
+        .columns()
+        .iter()
+        .map(|col| cell(col, row))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+      self.rows.push_back(cells);
+    }
+    self.offset += n;
+    self.done = n < self.page_size;
+    Ok(())
+  }
+
+  /// Key of the current row, fetching the next page when the buffer runs dry.
+  async fn head_key(&mut self) -> anyhow::Result<Option<Vec<Cell>>> {
+    if self.rows.is_empty() && !self.done {
+      self.fetch().await?;
+    }
+    Ok(self.rows.front().map(|r| self.key_of(r)))
+  }
+
+  fn key_of(&self, row: &[Cell]) -> Vec<Cell> {
+    self.key_idx.iter().map(|&i| row[i].clone()).collect()
+  }
+
+  fn key_kinds(&self) -> Vec<Kind> {
+    self.key_idx.iter().map(|&i| self.kinds[i]).collect()
+  }
+
+  /// Pop the current row, checking the server's ordering agrees with ours.
+  fn pop(&mut self) -> anyhow::Result<Vec<Cell>> {
+    let row = self.rows.pop_front().expect("pop after head");
+    let key = self.key_of(&row);
+    let kinds = self.key_kinds();
+    if let Some(last) = &self.last_key
+      && compare_keys(last, &kinds, &key, &kinds) == Ordering::Greater
+    {
+      bail!("{} is not ordered by its key the way the diff expects", self.table);
+    }
+    self.last_key = Some(key);
+    Ok(row)
+  }
+}
+
+fn cell(col: &ArrayRef, row: usize) -> anyhow::Result<Cell> {
+  if col.is_null(row) {
+    return Ok(None);
+  }
+  Ok(Some(array_value_to_string(col, row)?))
+}
+
+/// `ORDER BY` item for text column `col` in code point order, which is how
+/// [`compare_cells`] orders text.
+fn code_point_order(dialect: &str, col: &str) -> String {
+  match dialect {
+    "postgres" => format!("{col} COLLATE \"C\""),
+    "mysql" => format!("CAST({col} AS BINARY)"),
+    "sqlite" => format!("{col} COLLATE BINARY"),
+    "mssql" => format!("{col} COLLATE Latin1_General_BIN2"),
+    // DuckDB and ClickHouse compare strings by bytes already.
+    _ => col.to_string(),
+  }
+}
+
+/// Order of two cells of kinds `ka` / `kb`: by value when both are numeric,
+/// otherwise by text. Strings are never read as numbers (`'0123'` is not `'123'`).
+fn compare_cells(a: &Cell, ka: Kind, b: &Cell, kb: Kind) -> Ordering {
+  match (a, b) {
+    (None, None) => Ordering::Equal,
+    (None, Some(_)) => Ordering::Less,
+    (Some(_), None) => Ordering::Greater,
+    (Some(a), Some(b)) => compare_numbers(a, ka, b, kb).unwrap_or_else(|| a.cmp(b)),
+  }
+}
+
+fn compare_numbers(a: &str, ka: Kind, b: &str, kb: Kind) -> Option<Ordering> {
+  match (ka, kb) {
+    (Kind::Exact, Kind::Exact) => match (Decimal::from_str(a), Decimal::from_str(b)) {
+      (Ok(x), Ok(y)) => Some(x.cmp(&y)),
+      _ => a.parse::<f64>().ok()?.partial_cmp(&b.parse::<f64>().ok()?),
+    },
+    (Kind::Text, _) | (_, Kind::Text) => None,
+    _ => a.parse::<f64>().ok()?.partial_cmp(&b.parse::<f64>().ok()?),
+  }
+}
+
+fn compare_keys(a: &[Cell], ka: &[Kind], b: &[Cell], kb: &[Kind]) -> Ordering {
+  (0..a.len().min(b.len()))
+    .map(|i| compare_cells(&a[i], ka[i], &b[i], kb[i]))
+    .find(|o| o.is_ne())
+    .unwrap_or(Ordering::Equal)
+}
+
+/// Collects differing rows into string columns for the result batch.
+struct RowSink {
+  limit: usize,
+  kinds: Vec<&'static str>,
+  changed: Vec<String>,
+  keys: Vec<Vec<Cell>>,
+  source: Vec<Vec<Cell>>,
+  target: Vec<Vec<Cell>>,
+  truncated: bool,
+}
+
+impl RowSink {
+  fn push(&mut self, kind: &'static str, changed: String, key: Vec<Cell>, src: Vec<Cell>, dst: Vec<Cell>) {
+    if self.kinds.len() >= self.limit {
+      self.truncated = true;
+      return;
+    }
+    self.kinds.push(kind);
+    self.changed.push(changed);
+    self.keys.push(key);
+    self.source.push(src);
+    self.target.push(dst);
+  }
+}
+
+/// Compare `source_table` on `source` with `target_table` on `target` by key.
+pub async fn diff_table_data(
+  source: &dyn Connection,
+  source_table: &str,
+  target: &dyn Connection,
+  target_table: &str,
+  options: &DataDiffOptions,
+) -> anyhow::Result<DataDiff> {
+  let key = if options.key.is_empty() {
+    // Files and folders have no keys to report; an error there just means the
+    // key has to come from the other side.
+    let pk = source.primary_key(None, source_table).await.unwrap_or_default();
+    if pk.is_empty() {
+      target.primary_key(None, target_table).await.unwrap_or_default()
+    } else {
+      pk
+    }
+  } else {
+    options.key.clone()
+  };
+  if key.is_empty() {
+    bail!("no key columns given and no primary key found for {source_table}");
+  }
+
+  let mut src = Side::open(source, source_table, &key, options.page_size).await?;
+  let mut dst = Side::open(target, target_table, &key, options.page_size).await?;
+
+  // Compared columns: non-key columns present on both sides, in source order.
+  let is_key = |c: &str| key.iter().any(|k| k.eq_ignore_ascii_case(c));
+  let mut compared: Vec<(String, usize, usize)> = vec![];
+  let mut source_only_columns = vec![];
+  for (i, name) in src.columns.iter().enumerate() {
+    if is_key(name) {
+      continue;
+    }
+    match dst.position(name) {
+      Some(j) => compared.push((name.clone(), i, j)),
+      None => source_only_columns.push(name.clone()),
+    }
+  }
+  let target_only_columns = dst
+    .columns
+    .iter()
+    .filter(|c| !is_key(c) && src.position(c).is_none())
+    .cloned()
+    .collect::<Vec<_>>();
+
+  let (src_keys, dst_keys) = (src.key_kinds(), dst.key_kinds());
+  let pick = |row: &[Cell], source_side: bool| -> Vec<Cell> {
+    compared
+      .iter()
+      .map(|(_, i, j)| row[if source_side { *i } else { *j }].clone())
+      .collect()
+  };
+  let nulls = vec![None; compared.len()];
+  let mut counts: HashMap<usize, usize> = HashMap::new();
+  let mut sink = RowSink {
+    limit: options.max_rows,
+    kinds: vec![],
+    changed: vec![],
+    keys: vec![],
+    source: vec![],
+    target: vec![],
+    truncated: false,
+  };
+  let (mut inserted, mut deleted, mut changed, mut unchanged) = (0, 0, 0, 0);
+
+  loop {
+    let s = src.head_key().await?;
+    let d = dst.head_key().await?;
+    let order = match (&s, &d) {
+      (None, None) => break,
+      (Some(_), None) => Ordering::Less,
+      (None, Some(_)) => Ordering::Greater,
+      (Some(a), Some(b)) => compare_keys(a, &src_keys, b, &dst_keys),
+    };
+    match order {
+      Ordering::Less => {
+        let row = src.pop()?;
+        inserted += 1;
+        sink.push("insert", String::new(), s.unwrap_or_default(), pick(&row, true), nulls.clone());
+      }
+      Ordering::Greater => {
+        let row = dst.pop()?;
+        deleted += 1;
+        sink.push("delete", String::new(), d.unwrap_or_default(), nulls.clone(), pick(&row, false));
+      }
+      Ordering::Equal => {
+        let (a, b) = (src.pop()?, dst.pop()?);
+        let (a, b) = (pick(&a, true), pick(&b, false));
+        let diffs = (0..compared.len())
+          .filter(|&c| {
+            let (_, i, j) = &compared[c];
+            compare_cells(&a[c], src.kinds[*i], &b[c], dst.kinds[*j]).is_ne()
+          })
+          .collect::<Vec<_>>();
+        if diffs.is_empty() {
+          unchanged += 1;
+          continue;
+        }
+        changed += 1;
+        for &c in &diffs {
+          *counts.entry(c).or_default() += 1;
+        }
+        let names = diffs
+          .iter()
+          .map(|&c| compared[c].0.as_str())
+          .collect::<Vec<_>>()
+          .join(",");
+        sink.push("change", names, s.unwrap_or_default(), a, b);
+      }
+    }
+  }
+
+  let rows = rows_batch(&key, &compared, &sink)?;
+  let column_changes = RecordBatch::try_new(
+    Arc::new(Schema::new(vec![
+      Field::new("column", DataType::Utf8, false),
+      Field::new("changes", DataType::Int64, false),
+    ])),
+    vec![
+      Arc::new(StringArray::from_iter_values(compared.iter().map(|c| c.0.as_str()))),
+      Arc::new(Int64Array::from_iter_values(
+        (0..compared.len()).map(|c| counts.get(&c).copied().unwrap_or(0) as i64),
+      )),
+    ],
+  )?;
+
+  Ok(DataDiff {
+    key,
+    inserted,
+    deleted,
+    changed,
+    unchanged,
+    rows: RawArrowData {
+      total: inserted + deleted + changed,
+      ..RawArrowData::from_batch(rows)
+    },
+    column_changes: RawArrowData::from_batch(column_changes),
+    truncated: sink.truncated,
+    source_only_columns,
+    target_only_columns,
+  })
+}
+
+fn rows_batch(
+  key: &[String],
+  compared: &[(String, usize, usize)],
+  sink: &RowSink,
+) -> anyhow::Result<RecordBatch> {
+  let mut fields = vec![
+    Field::new("_diff", DataType::Utf8, false),
+    Field::new("_changed", DataType::Utf8, false),
+  ];
+  let mut arrays: Vec<ArrayRef> = vec![
+    Arc::new(StringArray::from(sink.kinds.clone())),
+    Arc::new(StringArray::from(sink.changed.clone())),
+  ];
+  let column = |rows: &[Vec<Cell>], i: usize| -> ArrayRef {
+    Arc::new(StringArray::from(rows.iter().map(|r| r[i].clone()).collect::<Vec<_>>()))
+  };
+  for (i, k) in key.iter().enumerate() {
+    fields.push(Field::new(k, DataType::Utf8, true));
+    arrays.push(column(&sink.keys, i));
+  }
+  for (i, (name, _, _)) in compared.iter().enumerate() {
+    fields.push(Field::new(format!("source.{name}"), DataType::Utf8, true));
+    arrays.push(column(&sink.source, i));
+    fields.push(Field::new(format!("target.{name}"), DataType::Utf8, true));
+    arrays.push(column(&sink.target, i));
+  }
+  Ok(RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays)?)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::dialect::folder::FolderConnection;
+  use crate::dialect::sqlite::SqliteConnection;
+
+  fn db(sql: &str) -> (SqliteConnection, std::path::PathBuf) {
+    let path = std::env::temp_dir().join(format!("duckling_diff_{}.db", nanoid::nanoid!(8)));
+    rusqlite::Connection::open(&path)
+      .unwrap()
+      .execute_batch(sql)
+      .unwrap();
+    let conn = SqliteConnection {
+      path: path.to_string_lossy().to_string(),
+    };
+    (conn, path)
+  }
+
+  #[tokio::test]
+  async fn merges_pages_and_counts_changes() {
+    let (a, pa) = db("create table t (id integer primary key, name text, score real, extra text);
+      insert into t values (1, 'a', 1.5, 'x'), (2, 'b', 2.0, 'x'), (3, 'c', 3.0, 'x'),
+        (10, 'j', 1.0, 'x'), (11, 'k', 1.0, 'x');");
+    let (b, pb) = db("create table t (id integer primary key, name text, score real);
+      insert into t values (2, 'B', 2), (3, 'c', 3.5), (4, 'd', 4),
+        (10, 'j', 1.0), (11, 'k', 1);");
+    let options = DataDiffOptions {
+      page_size: 2,
+      ..Default::default()
+    };
+    let diff = diff_table_data(&a, "t", &b, "t", &options).await.unwrap();
+    assert_eq!(diff.key, vec!["id"]);
+    assert_eq!((diff.inserted, diff.deleted, diff.changed, diff.unchanged), (1, 1, 2, 2));
+    assert_eq!(diff.source_only_columns, vec!["extra"]);
+    assert_eq!(diff.rows.batch.num_rows(), 4);
+
+    let counts = &diff.column_changes.batch;
+    let cols = counts.column(0).as_any().downcast_ref::<StringArray>().unwrap();
+    let n = counts.column(1).as_any().downcast_ref::<Int64Array>().unwrap();
+    assert_eq!((cols.value(0), n.value(0)), ("name", 1));
+    assert_eq!((cols.value(1), n.value(1)), ("score", 1));
+
+    let truncated = diff_table_data(
+      &a,
+      "t",
+      &b,
+      "t",
+      &DataDiffOptions {
+        max_rows: 1,
+        ..options
+      },
+    )
+    .await
+    .unwrap();
+    assert!(truncated.truncated);
+    assert_eq!(truncated.rows.batch.num_rows(), 1);
+    assert_eq!(truncated.rows.total, 4);
+    let _ = std::fs::remove_file(pa);
+    let _ = std::fs::remove_file(pb);
+  }
+
+  #[test]
+  fn only_numeric_cells_compare_by_value() {
+    let c = |s: &str| Some(s.to_string());
+    let text = |a: &str, b: &str| compare_cells(&c(a), Kind::Text, &c(b), Kind::Text);
+    assert_eq!(compare_cells(&c("2"), Kind::Exact, &c("10"), Kind::Exact), Ordering::Less);
+    assert_eq!(compare_cells(&c("1.50"), Kind::Exact, &c("1.5"), Kind::Approx), Ordering::Equal);
+    assert_eq!(compare_cells(&None, Kind::Text, &c("a"), Kind::Text), Ordering::Less);
+    assert_eq!(text("b", "a"), Ordering::Greater);
+    assert_eq!(text("0123", "123"), Ordering::Less);
+    assert_eq!(text("10", "9"), Ordering::Less);
+  }
+
+  #[tokio::test]
+  async fn text_keys_merge_in_code_point_order() {
+    let rows = "insert into t values ('0123', 1), ('123', 2), ('B', 3), ('a', 4), ('c', 5);";
+    let (a, pa) = db(&format!("create table t (k text collate nocase primary key, v int); {rows}"));
+    let (b, pb) = db(&format!("create table t (k text primary key, v int); {rows}"));
+    let options = DataDiffOptions {
+      page_size: 2,
+      ..Default::default()
+    };
+    let diff = diff_table_data(&a, "t", &b, "t", &options).await.unwrap();
+    assert_eq!((diff.inserted, diff.deleted, diff.changed, diff.unchanged), (0, 0, 0, 5));
+    let _ = std::fs::remove_file(pa);
+    let _ = std::fs::remove_file(pb);
+  }
+
+  #[tokio::test]
+  async fn folder_sources_take_the_key_from_the_target() {
+    let dir = std::env::temp_dir().join(format!("duckling_diff_{}", nanoid::nanoid!(8)));
+    std::fs::create_dir_all(&dir).unwrap();
+    let csv = dir.join("t.csv");
+    std::fs::write(&csv, "id,name\n1,a\n2,b\n3,c\n").unwrap();
+    let source = FolderConnection {
+      path: dir.to_string_lossy().to_string(),
+      cwd: None,
+    };
+    let table = format!("read_csv('{}')", csv.to_string_lossy());
+    let (b, pb) = db("create table t (id integer primary key, name text);
+      insert into t values (1, 'a'), (2, 'B'), (4, 'd');");
+    let diff = diff_table_data(&source, &table, &b, "t", &DataDiffOptions::default())
+      .await
+      .unwrap();
+    assert_eq!(diff.key, vec!["id"]);
+    assert_eq!((diff.inserted, diff.deleted, diff.changed, diff.unchanged), (1, 1, 1, 1));
+    let _ = std::fs::remove_dir_all(dir);
+    let _ = std::fs::remove_file(pb);
+  }
+}
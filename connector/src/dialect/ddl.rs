@@ -0,0 +1,59 @@
+//! "Show CREATE statement" support shared by the dialects.
+
+use serde::{Deserialize, Serialize};
+
+/// Kind of catalog object passed to [`super::Connection::object_ddl`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ObjectKind {
+  Table,
+  View,
+  Function,
+}
+
+impl ObjectKind {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      ObjectKind::Table => "table",
+      ObjectKind::View => "view",
+      ObjectKind::Function => "function",
+    }
+  }
+}
+
+/// Join DDL statements into one script, one `;`-terminated statement per block.
+pub(crate) fn join_statements<I, S>(stmts: I) -> String
+where
+  I: IntoIterator<Item = S>,
+  S: AsRef<str>,
+{
+  stmts
+    .into_iter()
+    .map(|s| s.as_ref().trim().trim_end_matches(';').to_string())
+    .filter(|s| !s.is_empty())
+    .map(|s| format!("{s};"))
+    .collect::<Vec<_>>()
+    .join("\n\n")
+}
+
+pub(crate) fn not_found(kind: ObjectKind, name: &str) -> anyhow::Error {
+  anyhow::anyhow!("{} not found: {name}", kind.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn joins_with_single_terminator() {
+    let out = join_statements(["create table t (a int);", "", "create index i on t (a)\n"]);
+    assert_eq!(out, "create table t (a int);\n\ncreate index i on t (a);");
+  }
+
+  #[test]
+  fn kind_round_trips_as_lowercase() {
+    let kind: ObjectKind = serde_json::from_str("\"view\"").unwrap();
+    assert_eq!(kind, ObjectKind::View);
+    assert_eq!(serde_json::to_string(&ObjectKind::Function).unwrap(), "\"function\"");
+  }
+}
@@ -0,0 +1,136 @@
+//! Structured table metadata: columns, keys, indexes and foreign keys.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColumnDetail {
+  pub name: String,
+  /// Type as the engine spells it (e.g. `varchar(20)`, `Nullable(UInt8)`).
+  pub r#type: String,
+  pub nullable: bool,
+  /// Default expression, verbatim.
+  pub default: Option<String>,
+  pub comment: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UniqueConstraint {
+  /// `None` when the engine does not name the constraint.
+  pub name: Option<String>,
+  pub columns: Vec<String>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndexDetail {
+  pub name: String,
+  /// Indexed columns or expressions, in key order.
+  pub columns: Vec<String>,
+  pub unique: bool,
+  /// Access method / index type (`btree`, `hash`, `minmax`, ...).
+  pub method: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ForeignKey {
+  /// `None` when the engine does not name the constraint.
+  pub name: Option<String>,
+  pub columns: Vec<String>,
+  pub ref_schema: Option<String>,
+  pub ref_table: String,
+  /// Referenced columns; empty when the key targets the parent's primary key implicitly.
+  pub ref_columns: Vec<String>,
+  pub on_update: Option<String>,
+  pub on_delete: Option<String>,
+}
+
+/// Everything the ER view and join completion need to know about one table.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableDetail {
+  pub schema: Option<String>,
+  pub table: String,
+  pub comment: Option<String>,
+  pub columns: Vec<ColumnDetail>,
+  /// Primary-key columns in key order (ClickHouse: primary key expressions).
+  pub primary_key: Vec<String>,
+  pub unique_constraints: Vec<UniqueConstraint>,
+  /// Indexes other than the one backing the primary key.
+  pub indexes: Vec<IndexDetail>,
+  pub foreign_keys: Vec<ForeignKey>,
+  /// ClickHouse `ORDER BY` expressions.
+  pub sorting_key: Vec<String>,
+  /// ClickHouse `PARTITION BY` expression.
+  pub partition_key: Option<String>,
+  /// ClickHouse table engine.
+  pub engine: Option<String>,
+}
+
+/// Group `(key, item)` rows that arrive sorted by key, keeping first-seen order.
+pub(crate) fn group_sorted<K: PartialEq, T>(rows: impl IntoIterator<Item = (K, T)>) -> Vec<(K, Vec<T>)> {
+  let mut out: Vec<(K, Vec<T>)> = vec![];
+  for (key, item) in rows {
+    match out.last_mut() {
+      Some((last, items)) if *last == key => items.push(item),
+      _ => out.push((key, vec![item])),
+    }
+  }
+  out
+}
+
+/// Split a comma-separated expression list, ignoring commas nested in
+/// parentheses or string literals (`a, toDate(b, 'x,y')` -> 2 items).
+pub(crate) fn split_expr_list(s: &str) -> Vec<String> {
+  let mut out = vec![];
+  let mut depth = 0i32;
+  let mut quote: Option<char> = None;
+  let mut current = String::new();
+  for ch in s.chars() {
+    match quote {
+      Some(q) => {
+        if ch == q {
+          quote = None;
+        }
+      }
+      None => match ch {
+        '\'' | '"' | '`' => quote = Some(ch),
+        '(' | '[' => depth += 1,
+        ')' | ']' => depth -= 1,
+        ',' if depth == 0 => {
+          out.push(current.trim().to_string());
+          current.clear();
+          continue;
+        }
+        _ => {}
+      },
+    }
+    current.push(ch);
+  }
+  if !current.trim().is_empty() {
+    out.push(current.trim().to_string());
+  }
+  out
+}
+
+/// Empty strings from catalog columns mean "not set".
+pub(crate) fn non_empty(s: Option<String>) -> Option<String> {
+  s.filter(|s| !s.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn groups_consecutive_rows() {
+    let rows = vec![("a", 1), ("a", 2), ("b", 3)];
+    assert_eq!(group_sorted(rows), vec![("a", vec![1, 2]), ("b", vec![3])]);
+  }
+
+  #[test]
+  fn splits_top_level_commas_only() {
+    assert_eq!(
+      split_expr_list("id, toDate(ts, 'UTC'), tuple('a,b')"),
+      vec!["id", "toDate(ts, 'UTC')", "tuple('a,b')"]
+    );
+    assert!(split_expr_list("").is_empty());
+  }
+}
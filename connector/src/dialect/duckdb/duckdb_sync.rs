@@ -1,3 +1,11 @@
+use crate::cancel::{CancelHook, CancelToken};
+use crate::dialect::cursor::FetchRequests;
+use crate::dialect::ddl::{self, ObjectKind, join_statements};
+use crate::dialect::params::ParamValue;
+use crate::dialect::detail::{
+  ColumnDetail, ForeignKey, IndexDetail, TableDetail, UniqueConstraint, group_sorted, non_empty,
+  split_expr_list,
+};
 use crate::utils::{FunctionMeta, Metadata, RawArrowData};
 use crate::utils::{Table, Title, TreeNode, build_tree, get_file_name};
 use arrow::array::RecordBatch;
@@ -70,6 +78,242 @@ impl DuckDbSyncConnection {
     Ok(metadata)
   }
 
+  pub fn primary_key(&self, schema: Option<&str>, table: &str) -> anyhow::Result<Vec<String>> {
+    let sql = "
+    select unnest(constraint_column_names)
+    from duckdb_constraints()
+    where constraint_type = 'PRIMARY KEY'
+      and table_name = ?
+      and schema_name = coalesce(?, current_schema())
+    ";
+    let mut stmt = self.inner.prepare(sql)?;
+    let cols = stmt
+      .query_map(duckdb::params![table, schema], |row| row.get::<_, String>(0))?
+      .collect::<Result<Vec<_>, _>>()?;
+    Ok(cols)
+  }
+
+  pub fn table_detail(&self, schema: Option<&str>, table: &str) -> anyhow::Result<TableDetail> {
+    let sql = "
+    select schema_name, comment from duckdb_tables()
+    where schema_name = coalesce($1, current_schema()) and table_name = $2
+    union all
+    select schema_name, comment from duckdb_views()
+    where schema_name = coalesce($1, current_schema()) and view_name = $2
+    ";
+    let mut stmt = self.inner.prepare(sql)?;
+    let (schema_name, comment) = stmt
+      .query_map(duckdb::params![schema, table], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?))
+      })?
+      .next()
+      .transpose()?
+      .ok_or_else(|| anyhow::anyhow!("table not found: {table}"))?;
+    let args: &[&dyn duckdb::ToSql] = &[&schema_name, &table];
+
+    let sql = "
+    select column_name, data_type, is_nullable, column_default, comment
+    from duckdb_columns()
+    where schema_name = ? and table_name = ?
+    order by column_index
+    ";
+    let mut stmt = self.inner.prepare(sql)?;
+    let columns = stmt
+      .query_map(args, |row| {
+        Ok(ColumnDetail {
+          name: row.get(0)?,
+          r#type: row.get(1)?,
+          nullable: row.get(2)?,
+          default: row.get(3)?,
+          comment: non_empty(row.get(4)?),
+        })
+      })?
+      .collect::<Result<Vec<_>, _>>()?;
+
+    // Parallel unnest keeps key columns and referenced columns aligned.
+    let sql = "
+    select constraint_index, constraint_type, constraint_name, referenced_table,
+      unnest(constraint_column_names), unnest(referenced_column_names),
+      unnest(range(len(constraint_column_names))) as pos
+    from duckdb_constraints()
+    where schema_name = ? and table_name = ?
+      and constraint_type in ('PRIMARY KEY', 'UNIQUE', 'FOREIGN KEY')
+    order by constraint_index, pos
+    ";
+    let mut stmt = self.inner.prepare(sql)?;
+    let rows = stmt
+      .query_map(args, |row| {
+        Ok((
+          (
+            row.get::<_, i64>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, Option<String>>(2)?,
+            row.get::<_, Option<String>>(3)?,
+          ),
+          (row.get::<_, String>(4)?, row.get::<_, Option<String>>(5)?),
+        ))
+      })?
+      .collect::<Result<Vec<_>, _>>()?;
+    let mut primary_key = vec![];
+    let mut unique_constraints = vec![];
+    let mut foreign_keys = vec![];
+    for ((_, kind, name, ref_table), pairs) in group_sorted(rows) {
+      let (columns, ref_columns): (Vec<_>, Vec<_>) = pairs.into_iter().unzip();
+      match kind.as_str() {
+        "PRIMARY KEY" => primary_key = columns,
+        "UNIQUE" => unique_constraints.push(UniqueConstraint { name, columns }),
+        _ => foreign_keys.push(ForeignKey {
+          name,
+          columns,
+          ref_schema: None,
+          ref_table: ref_table.unwrap_or_default(),
+          ref_columns: ref_columns.into_iter().flatten().collect(),
+          on_update: None,
+          on_delete: None,
+        }),
+      }
+    }
+
+    let sql = "
+    with idx as (
+      select index_name, is_unique, cast(expressions as varchar[]) as exprs
+      from duckdb_indexes()
+      where schema_name = ? and table_name = ?
+    )
+    select index_name, is_unique, unnest(exprs), unnest(range(len(exprs))) as pos
+    from idx
+    order by index_name, pos
+    ";
+    let mut stmt = self.inner.prepare(sql)?;
+    let rows = stmt
+      .query_map(args, |row| {
+        Ok((
+          (row.get::<_, String>(0)?, row.get::<_, bool>(1)?),
+          row.get::<_, String>(2)?,
+        ))
+      })?
+      .collect::<Result<Vec<_>, _>>()?;
+    let indexes = group_sorted(rows)
+      .into_iter()
+      .map(|((name, unique), columns)| IndexDetail {
+        name,
+        columns,
+        unique,
+        method: Some("art".to_string()),
+      })
+      .collect();
+
+    Ok(TableDetail {
+      schema: Some(schema_name),
+      table: table.to_string(),
+      comment: non_empty(comment),
+      columns,
+      primary_key,
+      unique_constraints,
+      indexes,
+      foreign_keys,
+      ..Default::default()
+    })
+  }
+
+  pub fn object_ddl(
+    &self,
+    schema: Option<&str>,
+    name: &str,
+    kind: ObjectKind,
+  ) -> anyhow::Result<String> {
+    let args: &[&dyn duckdb::ToSql] = &[&schema, &name];
+    let stmts: Vec<String> = match kind {
+      ObjectKind::Table => {
+        let sql = "
+        select 0 as ord, '' as index_name, sql from duckdb_tables()
+        where schema_name = coalesce($1, current_schema()) and table_name = $2
+        union all
+        select 1, index_name, sql from duckdb_indexes()
+        where schema_name = coalesce($1, current_schema()) and table_name = $2
+          and sql is not null
+        order by ord, index_name
+        ";
+        let mut stmt = self.inner.prepare(sql)?;
+        stmt
+          .query_map(args, |row| row.get(2))?
+          .collect::<Result<_, _>>()?
+      }
+      ObjectKind::View => {
+        let sql = "
+        select sql from duckdb_views()
+        where schema_name = coalesce($1, current_schema()) and view_name = $2
+        ";
+        let mut stmt = self.inner.prepare(sql)?;
+        stmt
+          .query_map(args, |row| row.get(0))?
+          .collect::<Result<_, _>>()?
+      }
+      // Macros are stored as parameter list + body; rebuild the CREATE MACRO.
+      ObjectKind::Function => {
+        let sql = "
+        select schema_name, array_to_string(parameters, ', '), macro_definition,
+          function_type = 'table_macro'
+        from duckdb_functions()
+        where schema_name = coalesce($1, current_schema()) and function_name = $2
+          and function_type in ('macro', 'table_macro') and not internal
+        ";
+        let mut stmt = self.inner.prepare(sql)?;
+        stmt
+          .query_map(args, |row| {
+            let schema: String = row.get(0)?;
+            let params: String = row.get(1)?;
+            let body: String = row.get(2)?;
+            let table = if row.get::<_, bool>(3)? { "TABLE " } else { "" };
+            Ok(format!("CREATE MACRO {schema}.{name}({params}) AS {table}{body}"))
+          })?
+          .collect::<Result<_, _>>()?
+      }
+    };
+    if stmts.is_empty() {
+      return Err(ddl::not_found(kind, name));
+    }
+    Ok(join_statements(stmts))
+  }
+
+  /// Run `stmts` in one transaction; rows affected per statement.
+  pub fn execute_atomic(&mut self, stmts: &[String]) -> anyhow::Result<Vec<usize>> {
+    let tx = self.inner.transaction()?;
+    let mut affected = Vec::with_capacity(stmts.len());
+    for sql in stmts {
+      let n = tx
+        .execute(sql, [])
+        .map_err(|e| anyhow::anyhow!(e).context(format!("failed statement: {sql}")))?;
+      affected.push(n);
+    }
+    tx.commit()?;
+    Ok(affected)
+  }
+
+  /// `estimated_size` of a catalog table, or the footer row count of a Parquet source.
+  pub fn estimate_row_count(&self, table: &str) -> anyhow::Result<usize> {
+    if let Some(rows) = parquet_row_count(&self.inner, table)? {
+      return Ok(rows);
+    }
+    let mut parts: Vec<String> = table
+      .split('.')
+      .map(|p| p.trim_matches('"').to_string())
+      .collect();
+    let name = parts.pop().unwrap_or_default();
+    let schema = parts.pop();
+    let database = parts.pop();
+    let sql = "
+    select estimated_size from duckdb_tables()
+    where table_name = ?
+      and schema_name = coalesce(?, current_schema())
+      and database_name = coalesce(?, current_database())
+    ";
+    let rows = self
+      .inner
+      .query_row(sql, duckdb::params![name, schema, database], |row| row.get::<_, i64>(0))?;
+    Ok(rows.max(0) as usize)
+  }
+
   pub fn drop_table(&self, table: &str) -> anyhow::Result<()> {
     let sql = format!("DROP VIEW IF EXISTS {table}");
     log::warn!("drop: {}", &sql);
@@ -120,8 +364,17 @@ impl DuckDbSyncConnection {
     list_functions(&self.inner)
   }
   pub fn query(&self, sql: &str) -> anyhow::Result<(Vec<Title>, RecordBatch)> {
+    self.query_with(sql, &[])
+  }
+
+  /// [`query`](Self::query) with `?` placeholders bound to `params`.
+  pub fn query_with(
+    &self,
+    sql: &str,
+    params: &[ParamValue],
+  ) -> anyhow::Result<(Vec<Title>, RecordBatch)> {
     let mut stmt = self.inner.prepare(sql)?;
-    let frames = stmt.query_arrow(duckdb::params![])?;
+    let frames = stmt.query_arrow(duckdb::params_from_iter(params))?;
     let schema = frames.get_schema();
     let records: Vec<_> = frames.collect();
 
@@ -245,6 +498,79 @@ pub(crate) fn list_functions(conn: &duckdb::Connection) -> anyhow::Result<Vec<Fu
   Ok(out)
 }
 
+/// Rows recorded in the Parquet footers behind `table`: a `read_parquet(...)`
+/// call or a `.parquet` path. `None` for any other relation.
+pub fn parquet_row_count(
+  conn: &duckdb::Connection,
+  table: &str,
+) -> anyhow::Result<Option<usize>> {
+  let table = table.trim();
+  let source = match table.to_lowercase().strip_prefix("read_parquet(") {
+    Some(_) if table.ends_with(')') => {
+      let args = &table["read_parquet(".len()..table.len() - 1];
+      split_expr_list(args).into_iter().next()
+    }
+    Some(_) => None,
+    None => {
+      let path = table.trim_matches(|c| c == '\'' || c == '"');
+      path
+        .to_lowercase()
+        .ends_with(".parquet")
+        .then(|| format!("'{}'", path.replace('\'', "''")))
+    }
+  };
+  let Some(source) = source else {
+    return Ok(None);
+  };
+  let sql =
+    format!("select coalesce(sum(num_rows), 0)::bigint from parquet_file_metadata({source})");
+  let rows = conn.query_row(&sql, [], |row| row.get::<_, i64>(0))?;
+  Ok(Some(rows.max(0) as usize))
+}
+
+/// `duckdb_interrupt` on `conn` when `cancel` fires.
+pub(crate) fn interrupt_on_cancel(
+  conn: &duckdb::Connection,
+  cancel: Option<&CancelToken>,
+) -> anyhow::Result<Option<CancelHook>> {
+  let handle = conn.interrupt_handle();
+  Ok(crate::cancel::interrupt_on(cancel, move || handle.interrupt())?)
+}
+
+/// Run `sql` on `conn` and answer cursor fetches from its Arrow chunks,
+/// re-sliced to the requested page size.
+pub(crate) fn serve_pages(
+  conn: &duckdb::Connection,
+  sql: &str,
+  requests: &mut FetchRequests,
+) -> anyhow::Result<()> {
+  let mut stmt = conn.prepare(sql)?;
+  let mut frames = stmt.query_arrow(duckdb::params![])?;
+  let schema = frames.get_schema();
+  let mut pending: Option<RecordBatch> = None;
+  while let Some(req) = requests.next_fetch() {
+    let mut batches = vec![];
+    let mut have = 0;
+    while have < req.rows {
+      let Some(batch) = pending.take().or_else(|| frames.next()) else {
+        break;
+      };
+      let take = batch.num_rows().min(req.rows - have);
+      if take < batch.num_rows() {
+        pending = Some(batch.slice(take, batch.num_rows() - take));
+      }
+      have += take;
+      batches.push(batch.slice(0, take));
+    }
+    let page = arrow::compute::concat_batches(&schema, &batches).map(|batch| RawArrowData {
+      sql: Some(sql.to_string()),
+      ..RawArrowData::from_batch(batch)
+    });
+    req.reply(page.map_err(Into::into));
+  }
+  Ok(())
+}
+
 pub fn query(conn: &duckdb::Connection, sql: &str) -> anyhow::Result<RawArrowData> {
   log::debug!("sql: {sql}");
 
@@ -272,6 +598,9 @@ pub fn query(conn: &duckdb::Connection, sql: &str) -> anyhow::Result<RawArrowDat
     batch,
     titles: Some(titles),
     sql: Some(sql.to_string()),
+    estimated: false,
+    cursor: None,
+    truncated: false,
   })
 }
 
@@ -401,6 +730,94 @@ fn test_duckdb_functions_lists_builtins() {
   }
 }
 
+#[test]
+fn primary_key_and_atomic_execute() {
+  let mut conn = DuckDbSyncConnection::new(None, None).unwrap();
+  conn
+    .inner
+    .execute_batch("create table t (a int, b int, v text, primary key (b, a))")
+    .unwrap();
+  assert_eq!(conn.primary_key(None, "t").unwrap(), vec!["b", "a"]);
+  assert!(conn.primary_key(Some("main"), "missing").unwrap().is_empty());
+
+  let ok = conn
+    .execute_atomic(&[
+      "insert into t values (1, 1, 'x'), (1, 2, 'y')".to_string(),
+      "update t set v = 'z' where a = 1".to_string(),
+    ])
+    .unwrap();
+  assert_eq!(ok, vec![2, 2]);
+
+  let err = conn.execute_atomic(&[
+    "delete from t".to_string(),
+    "insert into t values (1, 1, 'dup'), (1, 1, 'dup')".to_string(),
+  ]);
+  assert!(err.is_err());
+  let n = conn
+    .inner
+    .query_row("select count(*) from t", [], |row| row.get::<_, i64>(0))
+    .unwrap();
+  assert_eq!(n, 2);
+}
+
+#[test]
+fn table_detail_from_catalog() {
+  let conn = DuckDbSyncConnection::new(None, None).unwrap();
+  conn
+    .inner
+    .execute_batch(
+      "create table p (id int primary key, code text unique);
+      create table c (
+        id int, pid int references p(id), note text default 'n' not null,
+        primary key (pid, id)
+      );
+      comment on table c is 'children';
+      create index c_note on c (note);",
+    )
+    .unwrap();
+  let detail = conn.table_detail(None, "c").unwrap();
+  assert_eq!(detail.schema.as_deref(), Some("main"));
+  assert_eq!(detail.comment.as_deref(), Some("children"));
+  assert_eq!(detail.primary_key, vec!["pid", "id"]);
+  let note = &detail.columns[2];
+  assert_eq!(note.name, "note");
+  assert!(!note.nullable);
+  assert!(note.default.as_deref().unwrap().contains('n'));
+  assert_eq!(detail.foreign_keys.len(), 1);
+  assert_eq!(detail.foreign_keys[0].ref_table, "p");
+  assert_eq!(detail.foreign_keys[0].columns, vec!["pid"]);
+  assert_eq!(detail.foreign_keys[0].ref_columns, vec!["id"]);
+  assert_eq!(detail.indexes.len(), 1);
+  assert_eq!(detail.indexes[0].columns, vec!["note"]);
+
+  let parent = conn.table_detail(None, "p").unwrap();
+  assert_eq!(parent.unique_constraints.len(), 1);
+  assert_eq!(parent.unique_constraints[0].columns, vec!["code"]);
+  assert!(conn.table_detail(None, "missing").is_err());
+}
+
+#[test]
+fn object_ddl_for_tables_views_and_macros() {
+  let conn = DuckDbSyncConnection::new(None, None).unwrap();
+  conn
+    .inner
+    .execute_batch(
+      "create table t (a int, b text);
+      create index t_b on t (b);
+      create view v as select a from t;
+      create macro add_one(x) as x + 1;",
+    )
+    .unwrap();
+  let ddl = conn.object_ddl(None, "t", ObjectKind::Table).unwrap();
+  assert!(ddl.starts_with("CREATE TABLE t"), "{ddl}");
+  assert!(ddl.contains("CREATE INDEX t_b"), "{ddl}");
+  let ddl = conn.object_ddl(None, "v", ObjectKind::View).unwrap();
+  assert!(ddl.starts_with("CREATE VIEW v"), "{ddl}");
+  let ddl = conn.object_ddl(None, "add_one", ObjectKind::Function).unwrap();
+  assert!(ddl.starts_with("CREATE MACRO main.add_one(x) AS"), "{ddl}");
+  assert!(conn.object_ddl(None, "nope", ObjectKind::Table).is_err());
+}
+
 #[test]
 fn build_copy_sql_csv_and_parquet() {
   let opts = crate::utils::ExportOptions::default();
@@ -443,3 +860,16 @@ fn export_csv_streams_without_materializing_preview() {
   assert!(content.contains("hello"));
   let _ = std::fs::remove_dir_all(&dir);
 }
+
+impl duckdb::ToSql for ParamValue {
+  fn to_sql(&self) -> duckdb::Result<duckdb::types::ToSqlOutput<'_>> {
+    use duckdb::types::ToSqlOutput;
+    Ok(match self {
+      ParamValue::Null => ToSqlOutput::from(duckdb::types::Null),
+      ParamValue::Bool(v) => ToSqlOutput::from(*v),
+      ParamValue::Int(v) => ToSqlOutput::from(*v),
+      ParamValue::Float(v) => ToSqlOutput::from(*v),
+      ParamValue::Text(v) => ToSqlOutput::from(v.as_str()),
+    })
+  }
+}
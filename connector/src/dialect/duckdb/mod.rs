@@ -1,11 +1,20 @@
 use crate::dialect::Connection;
+use crate::dialect::cursor::{ResultCursor, ThreadCursor};
+use crate::dialect::ddl::ObjectKind;
+use crate::dialect::detail::TableDetail;
 use crate::dialect::duckdb::duckdb_sync::DuckDbSyncConnection;
+use crate::dialect::explain::{self, QueryPlan};
+use crate::dialect::params::{self, Params};
+use crate::dialect::transaction::{self, Transaction};
 use crate::utils::{FunctionMeta, Metadata, RawArrowData, TreeNode};
 use async_trait::async_trait;
 use regex::Regex;
-use std::sync::OnceLock;
+use std::sync::{Arc, Mutex, OnceLock};
 
 pub mod duckdb_sync;
+mod type_arrow;
+
+pub(crate) use crate::dialect::duckdb::type_arrow::declared_arrow_type;
 
 #[derive(Debug, Default, Clone)]
 pub struct DuckDbConnection {
@@ -47,14 +56,38 @@ impl Connection for DuckDbConnection {
   async fn query(&self, sql: &str, _limit: usize, _offset: usize) -> anyhow::Result<RawArrowData> {
     let this = self.clone();
     let sql = sql.to_string();
+    let cancel = crate::cancel::current();
     crate::dialect::run_blocking(move || {
-      let (titles, batch) = this.connect()?.query(&sql)?;
+      let conn = this.connect()?;
+      let _hook = duckdb_sync::interrupt_on_cancel(&conn.inner, cancel.as_ref())?;
+      let (titles, batch) = conn.query(&sql)?;
       let total = batch.num_rows();
       Ok(RawArrowData {
         total,
         batch,
         titles: Some(titles),
         sql: Some(sql),
+        estimated: false,
+        cursor: None,
+        truncated: false,
+      })
+    })
+    .await
+  }
+
+  async fn query_params(&self, sql: &str, params: &Params) -> anyhow::Result<RawArrowData> {
+    let this = self.clone();
+    let bound = params::bind(self.dialect(), sql, params)?;
+    let sql = sql.to_string();
+    let cancel = crate::cancel::current();
+    crate::dialect::run_blocking(move || {
+      let conn = this.connect()?;
+      let _hook = duckdb_sync::interrupt_on_cancel(&conn.inner, cancel.as_ref())?;
+      let (titles, batch) = conn.query_with(&bound.sql, &bound.values)?;
+      Ok(RawArrowData {
+        titles: Some(titles),
+        sql: Some(sql),
+        ..RawArrowData::from_batch(batch)
       })
     })
     .await
@@ -77,6 +110,96 @@ impl Connection for DuckDbConnection {
     "duckdb"
   }
 
+  async fn execute(&self, sql: &str) -> anyhow::Result<usize> {
+    let this = self.clone();
+    let sql = sql.to_string();
+    let cancel = crate::cancel::current();
+    crate::dialect::run_blocking(move || {
+      let conn = this.connect()?;
+      let _hook = duckdb_sync::interrupt_on_cancel(&conn.inner, cancel.as_ref())?;
+      Ok(conn.inner.execute(&sql, [])?)
+    })
+    .await
+  }
+
+  async fn execute_params(&self, sql: &str, params: &Params) -> anyhow::Result<usize> {
+    let this = self.clone();
+    let bound = params::bind(self.dialect(), sql, params)?;
+    let cancel = crate::cancel::current();
+    crate::dialect::run_blocking(move || {
+      let conn = this.connect()?;
+      let _hook = duckdb_sync::interrupt_on_cancel(&conn.inner, cancel.as_ref())?;
+      let args = duckdb::params_from_iter(&bound.values);
+      Ok(conn.inner.execute(&bound.sql, args)?)
+    })
+    .await
+  }
+
+  async fn primary_key(&self, schema: Option<&str>, table: &str) -> anyhow::Result<Vec<String>> {
+    let this = self.clone();
+    let schema = schema.map(str::to_string);
+    let table = table.to_string();
+    crate::dialect::run_blocking(move || this.connect()?.primary_key(schema.as_deref(), &table))
+      .await
+  }
+
+  async fn table_detail(&self, schema: Option<&str>, table: &str) -> anyhow::Result<TableDetail> {
+    let this = self.clone();
+    let schema = schema.map(str::to_string);
+    let table = table.to_string();
+    crate::dialect::run_blocking(move || this.connect()?.table_detail(schema.as_deref(), &table))
+      .await
+  }
+
+  async fn object_ddl(
+    &self,
+    schema: Option<&str>,
+    name: &str,
+    kind: ObjectKind,
+  ) -> anyhow::Result<String> {
+    let this = self.clone();
+    let schema = schema.map(str::to_string);
+    let name = name.to_string();
+    crate::dialect::run_blocking(move || this.connect()?.object_ddl(schema.as_deref(), &name, kind))
+      .await
+  }
+
+  /// Single connection with a native transaction; no long-lived handle is kept
+  /// because it would hold the database file open across requests.
+  async fn execute_atomic(&self, stmts: &[String]) -> anyhow::Result<Vec<usize>> {
+    let this = self.clone();
+    let stmts = stmts.to_vec();
+    crate::dialect::run_blocking(move || this.connect()?.execute_atomic(&stmts)).await
+  }
+
+  /// The transaction keeps its connection, and so the database file, open
+  /// until it finishes. DuckDB has no `Caps::TRANSACTION`, so only
+  /// request-scoped ones (grid edits) are opened.
+  async fn begin_transaction(&self) -> anyhow::Result<Box<dyn Transaction>> {
+    let this = self.clone();
+    let conn = crate::dialect::run_blocking(move || {
+      let conn = this.connect()?;
+      conn.inner.execute_batch("BEGIN TRANSACTION")?;
+      Ok(conn)
+    })
+    .await?;
+    Ok(Box::new(DuckDbTransaction {
+      conn: Arc::new(Mutex::new(Some(conn))),
+    }))
+  }
+
+  /// Arrow chunks of one executed statement, re-sliced to the requested page size.
+  async fn open_cursor(&self, sql: &str) -> anyhow::Result<Box<dyn ResultCursor>> {
+    let this = self.clone();
+    let sql = sql.to_string();
+    let cursor = ThreadCursor::spawn("duckdb", move |requests| {
+      let conn = this.connect()?;
+      duckdb_sync::serve_pages(&conn.inner, &sql, requests)
+    })
+    .await?;
+    Ok(Box::new(cursor))
+  }
+
   async fn show_schema(&self, schema: &str) -> anyhow::Result<RawArrowData> {
     let this = self.clone();
     let schema = schema.to_string();
@@ -147,6 +270,16 @@ impl Connection for DuckDbConnection {
     .await
   }
 
+  async fn explain(&self, sql: &str, analyze: bool) -> anyhow::Result<QueryPlan> {
+    explain::duckdb(self, sql, analyze).await
+  }
+
+  async fn estimate_row_count(&self, table: &str) -> anyhow::Result<usize> {
+    let this = self.clone();
+    let table = table.to_string();
+    crate::dialect::run_blocking(move || this.connect()?.estimate_row_count(&table)).await
+  }
+
   async fn table_row_count(&self, table: &str, r#where: &str) -> anyhow::Result<usize> {
     let this = self.clone();
     let sql = self._table_count_sql(table, r#where);
@@ -225,6 +358,77 @@ impl DuckDbConnection {
   }
 }
 
+pub struct DuckDbTransaction {
+  conn: Arc<Mutex<Option<DuckDbSyncConnection>>>,
+}
+
+impl DuckDbTransaction {
+  async fn with_conn<T, F>(&self, f: F) -> anyhow::Result<T>
+  where
+    T: Send + 'static,
+    F: FnOnce(&DuckDbSyncConnection) -> anyhow::Result<T> + Send + 'static,
+  {
+    let conn = Arc::clone(&self.conn);
+    crate::dialect::run_blocking(move || {
+      let guard = conn
+        .lock()
+        .map_err(|_| anyhow::anyhow!("duckdb transaction lock poisoned"))?;
+      f(guard.as_ref().ok_or_else(transaction::finished)?)
+    })
+    .await
+  }
+
+  async fn finish(&self, sql: &'static str) -> anyhow::Result<()> {
+    let conn = Arc::clone(&self.conn);
+    crate::dialect::run_blocking(move || {
+      let conn = conn
+        .lock()
+        .map_err(|_| anyhow::anyhow!("duckdb transaction lock poisoned"))?
+        .take()
+        .ok_or_else(transaction::finished)?;
+      conn.inner.execute_batch(sql)?;
+      Ok(())
+    })
+    .await
+  }
+}
+
+#[async_trait]
+impl Transaction for DuckDbTransaction {
+  fn dialect(&self) -> &'static str {
+    "duckdb"
+  }
+
+  async fn query(&self, sql: &str) -> anyhow::Result<RawArrowData> {
+    let sql = sql.to_string();
+    self
+      .with_conn(move |conn| {
+        let (titles, batch) = conn.query(&sql)?;
+        Ok(RawArrowData {
+          titles: Some(titles),
+          sql: Some(sql),
+          ..RawArrowData::from_batch(batch)
+        })
+      })
+      .await
+  }
+
+  async fn execute(&self, sql: &str) -> anyhow::Result<usize> {
+    let sql = sql.to_string();
+    self
+      .with_conn(move |conn| Ok(conn.inner.execute(&sql, [])?))
+      .await
+  }
+
+  async fn commit(&self) -> anyhow::Result<()> {
+    self.finish("COMMIT").await
+  }
+
+  async fn rollback(&self) -> anyhow::Result<()> {
+    self.finish("ROLLBACK").await
+  }
+}
+
 #[tokio::test]
 #[ignore = "requires a local duckdb file"]
 async fn test_duckdb() {
@@ -258,3 +462,48 @@ mod file_function_tests {
     assert!(!is_file_function("SELECT read_parquet('f.parquet')"));
   }
 }
+
+#[cfg(test)]
+mod cursor_tests {
+  use super::*;
+
+  #[tokio::test]
+  async fn cursor_reslices_arrow_chunks() {
+    let conn = DuckDbConnection {
+      path: ":memory:".into(),
+      cwd: None,
+    };
+    let cursor = conn.open_cursor("SELECT * FROM range(5000)").await.unwrap();
+    let sizes = [3000, 3000, 3000];
+    let mut got = vec![];
+    for n in sizes {
+      got.push(cursor.fetch(n).await.unwrap().batch.num_rows());
+    }
+    assert_eq!(got, vec![3000, 2000, 0]);
+    cursor.close().await.unwrap();
+  }
+}
+
+#[cfg(test)]
+mod row_count_tests {
+  use super::*;
+
+  #[tokio::test]
+  async fn parquet_estimates_come_from_footers() {
+    let path = std::env::temp_dir().join(format!("duckling_rows_{}.parquet", std::process::id()));
+    let path = path.to_string_lossy().replace('\\', "/");
+    duckdb::Connection::open_in_memory()
+      .unwrap()
+      .execute(&format!("COPY (SELECT * FROM range(1234)) TO '{path}' (FORMAT parquet)"), [])
+      .unwrap();
+    let conn = DuckDbConnection {
+      path: ":memory:".into(),
+      cwd: None,
+    };
+    let source = format!("read_parquet('{path}', union_by_name=true)");
+    assert_eq!(conn.estimate_row_count(&source).await.unwrap(), 1234);
+    assert_eq!(conn.estimate_row_count(&path).await.unwrap(), 1234);
+    assert!(conn.estimate_row_count("missing_table").await.is_err());
+    let _ = std::fs::remove_file(&path);
+  }
+}
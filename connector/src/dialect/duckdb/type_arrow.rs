@@ -0,0 +1,35 @@
+//! DuckDB hands results over as Arrow already; this only maps declared type
+//! names to the Arrow types DuckDB exports them as, for comparing schemas.
+
+use arrow::datatypes::{DataType, TimeUnit};
+
+/// Arrow type DuckDB exports a declared column type as: `base` is the
+/// lower-cased name without its parameters. `None` for types without an Arrow
+/// counterpart.
+pub fn declared_arrow_type(base: &str) -> Option<DataType> {
+  Some(match base {
+    "boolean" | "bool" | "logical" => DataType::Boolean,
+    "tinyint" | "int1" => DataType::Int8,
+    "smallint" | "int2" | "short" => DataType::Int16,
+    "integer" | "int" | "int4" | "signed" => DataType::Int32,
+    "bigint" | "int8" | "long" => DataType::Int64,
+    // HUGEINT leaves DuckDB as a 38-digit decimal.
+    "hugeint" => DataType::Decimal128(38, 0),
+    "utinyint" => DataType::UInt8,
+    "usmallint" => DataType::UInt16,
+    "uinteger" => DataType::UInt32,
+    "ubigint" => DataType::UInt64,
+    "float" | "float4" | "real" => DataType::Float32,
+    "double" | "float8" => DataType::Float64,
+    "date" => DataType::Date32,
+    "time" => DataType::Time64(TimeUnit::Microsecond),
+    "timestamp" | "datetime" | "timestamp without time zone" => {
+      DataType::Timestamp(TimeUnit::Microsecond, None)
+    }
+    "timestamptz" | "timestamp with time zone" => {
+      DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into()))
+    }
+    "blob" | "bytea" | "binary" | "varbinary" => DataType::Binary,
+    _ => return None,
+  })
+}
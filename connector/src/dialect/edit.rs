@@ -0,0 +1,359 @@
+//! DML generation for the editable result grid.
+
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{Connection, Transaction};
+use crate::error::ConnectorError;
+
+/// Column name -> cell value, as sent by the grid.
+pub type Row = BTreeMap<String, Value>;
+
+/// One edited row: primary-key values identify it, `changes` holds new values.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RowUpdate {
+  pub key: Row,
+  pub changes: Row,
+}
+
+/// Pending grid edits for a single table.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChangeSet {
+  #[serde(default)]
+  pub schema: Option<String>,
+  pub table: String,
+  #[serde(default)]
+  pub updates: Vec<RowUpdate>,
+  #[serde(default)]
+  pub inserts: Vec<Row>,
+  /// Primary-key values of rows to delete.
+  #[serde(default)]
+  pub deletes: Vec<Row>,
+}
+
+impl ChangeSet {
+  pub fn is_empty(&self) -> bool {
+    self.updates.is_empty() && self.inserts.is_empty() && self.deletes.is_empty()
+  }
+
+  fn table_ref<C: Connection + ?Sized>(&self, conn: &C) -> String {
+    match self.schema.as_deref().filter(|s| !s.is_empty()) {
+      Some(schema) => format!("{}.{}", quote_ident(conn, schema), quote_ident(conn, &self.table)),
+      None => quote_ident(conn, &self.table),
+    }
+  }
+
+  /// Statements from [`build_statements`] that target one row by key: the
+  /// leading deletes and updates.
+  fn keyed_statements(&self) -> usize {
+    self.deletes.len() + self.updates.iter().filter(|u| !u.changes.is_empty()).count()
+  }
+}
+
+/// `ident` in the dialect's quotes whatever it looks like, so reserved words
+/// and dialects whose validator accepts everything still get valid DML.
+fn quote_ident<C: Connection + ?Sized>(conn: &C, ident: &str) -> String {
+  let end = conn.end_quote();
+  format!("{}{}{end}", conn.start_quote(), ident.replace(end, &end.repeat(2)))
+}
+
+/// Render a JSON cell value as a SQL literal for `dialect`.
+pub fn sql_literal(dialect: &str, value: &Value) -> String {
+  match value {
+    Value::Null => "NULL".to_string(),
+    Value::Bool(b) if matches!(dialect, "sqlite" | "mssql") => {
+      (if *b { "1" } else { "0" }).to_string()
+    }
+    Value::Bool(b) => (if *b { "TRUE" } else { "FALSE" }).to_string(),
+    Value::Number(n) => n.to_string(),
+    Value::String(s) => quote_string(dialect, s),
+    // Nested values are stored as JSON text.
+    other => quote_string(dialect, &other.to_string()),
+  }
+}
+
+pub fn quote_string(dialect: &str, s: &str) -> String {
+  let escaped = s.replace('\'', "''");
+  // MySQL / ClickHouse treat backslash as an escape character inside literals.
+  let escaped = match dialect {
+    "mysql" | "clickhouse" => escaped.replace('\\', "\\\\"),
+    _ => escaped,
+  };
+  // T-SQL reads an unprefixed literal in the database code page.
+  let prefix = if dialect == "mssql" { "N" } else { "" };
+  format!("{prefix}'{escaped}'")
+}
+
+fn where_clause<C: Connection + ?Sized>(conn: &C, key: &Row) -> String {
+  key
+    .iter()
+    .map(|(col, value)| match value {
+      Value::Null => format!("{} IS NULL", quote_ident(conn, col)),
+      _ => format!("{} = {}", quote_ident(conn, col), sql_literal(conn.dialect(), value)),
+    })
+    .collect::<Vec<_>>()
+    .join(" AND ")
+}
+
+fn check_key(key: &Row, pk: &[String], what: &str) -> anyhow::Result<()> {
+  if pk.is_empty() {
+    bail!("table has no primary key; {what} requires one");
+  }
+  let matches = key.len() == pk.len() && pk.iter().all(|c| key.contains_key(c));
+  if !matches {
+    let got = key.keys().cloned().collect::<Vec<_>>().join(", ");
+    return Err(anyhow!(
+      "{what} key ({got}) does not match primary key ({})",
+      pk.join(", ")
+    ));
+  }
+  Ok(())
+}
+
+/// Build DML for `changes`: deletes first, then updates, then inserts, so a
+/// re-inserted key does not collide with the row it replaces.
+///
+/// `pk` is the table's primary key (see [`Connection::primary_key`]); every
+/// update/delete key must name exactly those columns.
+pub fn build_statements<C: Connection + ?Sized>(
+  conn: &C,
+  changes: &ChangeSet,
+  pk: &[String],
+) -> anyhow::Result<Vec<String>> {
+  let table = changes.table_ref(conn);
+  let mut out = vec![];
+
+  for key in &changes.deletes {
+    check_key(key, pk, "delete")?;
+    out.push(format!("DELETE FROM {table} WHERE {}", where_clause(conn, key)));
+  }
+
+  for update in &changes.updates {
+    check_key(&update.key, pk, "update")?;
+    if update.changes.is_empty() {
+      continue;
+    }
+    let set = update
+      .changes
+      .iter()
+      .map(|(col, value)| {
+        format!("{} = {}", quote_ident(conn, col), sql_literal(conn.dialect(), value))
+      })
+      .collect::<Vec<_>>()
+      .join(", ");
+    out.push(format!(
+      "UPDATE {table} SET {set} WHERE {}",
+      where_clause(conn, &update.key)
+    ));
+  }
+
+  for row in &changes.inserts {
+    if row.is_empty() {
+      continue;
+    }
+    let cols = row
+      .keys()
+      .map(|c| quote_ident(conn, c))
+      .collect::<Vec<_>>()
+      .join(", ");
+    let values = row
+      .values()
+      .map(|v| sql_literal(conn.dialect(), v))
+      .collect::<Vec<_>>()
+      .join(", ");
+    out.push(format!("INSERT INTO {table} ({cols}) VALUES ({values})"));
+  }
+  Ok(out)
+}
+
+/// Run `statements` built from `changes` on `tx`; rows affected per statement.
+/// Each keyed UPDATE / DELETE must touch exactly one row, else the row was
+/// changed or removed since it was read and a [`ConnectorError::Conflict`] is
+/// returned. The caller rolls `tx` back on any error.
+pub async fn execute_checked(
+  tx: &dyn Transaction,
+  changes: &ChangeSet,
+  statements: &[String],
+) -> anyhow::Result<Vec<usize>> {
+  let keyed = changes.keyed_statements();
+  let mut affected = Vec::with_capacity(statements.len());
+  for (i, sql) in statements.iter().enumerate() {
+    let n = tx
+      .execute(sql)
+      .await
+      .map_err(|e| e.context(format!("failed statement: {sql}")))?;
+    if i < keyed && n != 1 {
+      let msg = format!("{sql} affected {n} rows instead of 1");
+      return Err(ConnectorError::Conflict(msg).into());
+    }
+    affected.push(n);
+  }
+  Ok(affected)
+}
+
+#[cfg(test)]
+mod tests {
+  use arrow::array::AsArray;
+
+  use super::*;
+  use crate::dialect::duckdb::DuckDbConnection;
+  use crate::dialect::mysql::MySqlConnection;
+  use crate::dialect::sqlite::SqliteConnection;
+  use serde_json::json;
+
+  fn row(v: Value) -> Row {
+    serde_json::from_value(v).unwrap()
+  }
+
+  #[test]
+  fn literals_escape_per_dialect() {
+    assert_eq!(sql_literal("postgres", &json!("it's")), "'it''s'");
+    assert_eq!(sql_literal("mysql", &json!(r"a\b")), r"'a\\b'");
+    assert_eq!(sql_literal("postgres", &json!(r"a\b")), r"'a\b'");
+    assert_eq!(sql_literal("sqlite", &json!(true)), "1");
+    assert_eq!(sql_literal("duckdb", &json!(false)), "FALSE");
+    assert_eq!(sql_literal("duckdb", &json!(null)), "NULL");
+    assert_eq!(sql_literal("mssql", &json!(true)), "1");
+    assert_eq!(sql_literal("mssql", &json!("it's")), "N'it''s'");
+    assert_eq!(sql_literal("duckdb", &json!(1.5)), "1.5");
+    assert_eq!(sql_literal("duckdb", &json!({"a": 1})), r#"'{"a":1}'"#);
+  }
+
+  #[test]
+  fn builds_quoted_dml_in_safe_order() {
+    let conn = SqliteConnection::default();
+    let changes = ChangeSet {
+      schema: None,
+      table: "my items".into(),
+      updates: vec![RowUpdate {
+        key: row(json!({"id": 1})),
+        changes: row(json!({"name": "x", "note col": null})),
+      }],
+      inserts: vec![row(json!({"id": 3, "name": "c"}))],
+      deletes: vec![row(json!({"id": 2}))],
+    };
+    let stmts = build_statements(&conn, &changes, &["id".to_string()]).unwrap();
+    assert_eq!(
+      stmts,
+      vec![
+        r#"DELETE FROM "my items" WHERE "id" = 2"#,
+        r#"UPDATE "my items" SET "name" = 'x', "note col" = NULL WHERE "id" = 1"#,
+        r#"INSERT INTO "my items" ("id", "name") VALUES (3, 'c')"#,
+      ]
+    );
+  }
+
+  #[test]
+  fn qualified_table_uses_dialect_quotes() {
+    let conn = MySqlConnection::default();
+    let changes = ChangeSet {
+      schema: Some("app".into()),
+      table: "order-lines".into(),
+      deletes: vec![row(json!({"a": 1, "b": null}))],
+      ..Default::default()
+    };
+    let pk = vec!["a".to_string(), "b".to_string()];
+    let stmts = build_statements(&conn, &changes, &pk).unwrap();
+    assert_eq!(
+      stmts,
+      vec!["DELETE FROM `app`.`order-lines` WHERE `a` = 1 AND `b` IS NULL"]
+    );
+  }
+
+  #[test]
+  fn rejects_keys_that_are_not_the_primary_key() {
+    let conn = SqliteConnection::default();
+    let changes = ChangeSet {
+      table: "t".into(),
+      deletes: vec![row(json!({"name": "a"}))],
+      ..Default::default()
+    };
+    assert!(build_statements(&conn, &changes, &["id".to_string()]).is_err());
+    assert!(build_statements(&conn, &changes, &[]).is_err());
+
+    let inserts_only = ChangeSet {
+      table: "t".into(),
+      inserts: vec![row(json!({"name": "a"}))],
+      ..Default::default()
+    };
+    assert_eq!(build_statements(&conn, &inserts_only, &[]).unwrap().len(), 1);
+  }
+
+  #[tokio::test]
+  async fn missing_rows_are_a_conflict() {
+    let conn = SqliteConnection {
+      path: ":memory:".into(),
+    };
+    let tx = conn.begin_transaction().await.unwrap();
+    tx.execute("create table t (id integer primary key, \"order\" text)").await.unwrap();
+    tx.execute("insert into t values (1, 'a')").await.unwrap();
+
+    let update = ChangeSet {
+      table: "t".into(),
+      updates: vec![RowUpdate {
+        key: row(json!({"id": 1})),
+        changes: row(json!({"order": "b"})),
+      }],
+      ..Default::default()
+    };
+    let pk = ["id".to_string()];
+    let stmts = build_statements(&conn, &update, &pk).unwrap();
+    assert_eq!(execute_checked(tx.as_ref(), &update, &stmts).await.unwrap(), vec![1]);
+    // Writing the value the row already holds still matches it.
+    assert_eq!(execute_checked(tx.as_ref(), &update, &stmts).await.unwrap(), vec![1]);
+
+    let gone = ChangeSet {
+      table: "t".into(),
+      deletes: vec![row(json!({"id": 2}))],
+      ..Default::default()
+    };
+    let stmts = build_statements(&conn, &gone, &pk).unwrap();
+    let err = execute_checked(tx.as_ref(), &gone, &stmts).await.unwrap_err();
+    assert_eq!(crate::error::error_code(&err), "conflict");
+  }
+
+  #[tokio::test]
+  async fn duckdb_edits_run_in_a_transaction() {
+    let path = std::env::temp_dir().join(format!("duckling_edit_{}.duckdb", nanoid::nanoid!(8)));
+    let conn = DuckDbConnection {
+      path: path.to_string_lossy().to_string(),
+      cwd: None,
+    };
+    conn
+      .execute("create table t (id integer primary key, name varchar)")
+      .await
+      .unwrap();
+    conn.execute("insert into t values (1, 'a')").await.unwrap();
+
+    let update = ChangeSet {
+      table: "t".into(),
+      updates: vec![RowUpdate {
+        key: row(json!({"id": 1})),
+        changes: row(json!({"name": "b"})),
+      }],
+      ..Default::default()
+    };
+    let pk = ["id".to_string()];
+    let stmts = build_statements(&conn, &update, &pk).unwrap();
+    let tx = conn.begin_transaction().await.unwrap();
+    assert_eq!(execute_checked(tx.as_ref(), &update, &stmts).await.unwrap(), vec![1]);
+    tx.commit().await.unwrap();
+    let name = conn.query("select name from t", 0, 0).await.unwrap();
+    assert_eq!(name.batch.column(0).as_string::<i32>().value(0), "b");
+
+    let gone = ChangeSet {
+      table: "t".into(),
+      deletes: vec![row(json!({"id": 2}))],
+      ..Default::default()
+    };
+    let stmts = build_statements(&conn, &gone, &pk).unwrap();
+    let tx = conn.begin_transaction().await.unwrap();
+    let err = execute_checked(tx.as_ref(), &gone, &stmts).await.unwrap_err();
+    assert_eq!(crate::error::error_code(&err), "conflict");
+    tx.rollback().await.unwrap();
+    let _ = std::fs::remove_file(path);
+  }
+}
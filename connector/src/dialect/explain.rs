@@ -0,0 +1,468 @@
+//! `EXPLAIN` output of every dialect normalized into one operator tree.
+//!
+//! Each dialect runs its own EXPLAIN form and hands the text to the matching
+//! parser here; the UI renders [`PlanNode`]s without knowing where they came from.
+
+use std::sync::LazyLock;
+
+use anyhow::{anyhow, bail};
+use arrow::util::display::array_value_to_string;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use sqlparser::ast::{Query, SetExpr, Statement};
+
+use crate::dialect::Connection;
+use crate::dialect::ast::first_stmt;
+
+/// One operator of a query plan. Costs are in the engine's own units.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanNode {
+  pub operator: String,
+  /// Relation, index, condition or other operator-specific text.
+  pub detail: Option<String>,
+  pub estimated_rows: Option<f64>,
+  /// Rows produced across all loops (`analyze` only).
+  pub actual_rows: Option<f64>,
+  pub cost: Option<f64>,
+  /// Wall time in milliseconds (`analyze` only).
+  pub time_ms: Option<f64>,
+  pub children: Vec<PlanNode>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryPlan {
+  /// Whether the statement was executed; engines without EXPLAIN ANALYZE
+  /// return an estimated plan even when it was requested.
+  pub analyze: bool,
+  pub root: PlanNode,
+  /// The engine's EXPLAIN output, verbatim.
+  pub raw: String,
+}
+
+/// EXPLAIN ANALYZE executes the statement, so only plain queries may be
+/// analyzed: no data-modifying CTE (`WITH d AS (DELETE ...)`) and no
+/// `SELECT ... INTO`, which creates a table.
+pub(crate) fn check_analyze(dialect: &str, sql: &str) -> anyhow::Result<()> {
+  match first_stmt(dialect, sql) {
+    Some(Statement::Query(query)) if read_only(&query) => Ok(()),
+    Some(Statement::Query(_)) => {
+      bail!("EXPLAIN ANALYZE is not allowed for a query that writes data")
+    }
+    _ => bail!("EXPLAIN ANALYZE is only allowed for a single SELECT query"),
+  }
+}
+
+fn read_only(query: &Query) -> bool {
+  let ctes_read_only = query
+    .with
+    .as_ref()
+    .is_none_or(|with| with.cte_tables.iter().all(|cte| read_only(&cte.query)));
+  ctes_read_only && read_only_body(&query.body)
+}
+
+fn read_only_body(body: &SetExpr) -> bool {
+  match body {
+    SetExpr::Select(select) => select.into.is_none(),
+    SetExpr::Query(query) => read_only(query),
+    SetExpr::SetOperation { left, right, .. } => read_only_body(left) && read_only_body(right),
+    SetExpr::Values(_) | SetExpr::Table(_) => true,
+    _ => false,
+  }
+}
+
+/// Run `explain_sql` and join the last column of every row: the plan text
+/// (DuckDB puts a key column before it).
+pub(crate) async fn plan_text<C: Connection + ?Sized>(
+  conn: &C,
+  explain_sql: &str,
+) -> anyhow::Result<String> {
+  let raw = conn.query(explain_sql, 0, 0).await?;
+  let batch = raw.batch;
+  let Some(col) = batch.columns().last() else {
+    bail!("EXPLAIN returned no columns");
+  };
+  let lines = (0..batch.num_rows())
+    .map(|row| array_value_to_string(col, row))
+    .collect::<Result<Vec<_>, _>>()?;
+  Ok(lines.join("\n"))
+}
+
+/// Wrap several top-level operators under one synthetic root.
+fn single_root(mut nodes: Vec<PlanNode>) -> PlanNode {
+  if nodes.len() == 1 {
+    return nodes.remove(0);
+  }
+  PlanNode {
+    operator: "Query".into(),
+    children: nodes,
+    ..Default::default()
+  }
+}
+
+fn plan(analyze: bool, root: PlanNode, raw: &str) -> QueryPlan {
+  QueryPlan {
+    analyze,
+    root,
+    raw: raw.to_string(),
+  }
+}
+
+fn number(v: Option<&Value>) -> Option<f64> {
+  match v? {
+    Value::Number(n) => n.as_f64(),
+    Value::String(s) => s.trim().parse().ok(),
+    _ => None,
+  }
+}
+
+fn text(v: Option<&Value>) -> Option<String> {
+  match v? {
+    Value::String(s) if !s.is_empty() => Some(s.clone()),
+    Value::Null | Value::String(_) => None,
+    other => Some(other.to_string()),
+  }
+}
+
+/// Postgres `EXPLAIN (FORMAT JSON)`; ClickHouse `EXPLAIN PLAN json = 1` uses
+/// the same `Plan` / `Node Type` / `Plans` shape.
+pub(crate) fn parse_postgres_json(raw: &str, analyze: bool) -> anyhow::Result<QueryPlan> {
+  let doc: Value = serde_json::from_str(raw)?;
+  let entries = doc.as_array().cloned().unwrap_or_else(|| vec![doc]);
+  let roots = entries
+    .iter()
+    .map(|e| e.get("Plan").map(pg_node).ok_or_else(|| anyhow!("EXPLAIN JSON has no Plan")))
+    .collect::<anyhow::Result<Vec<_>>>()?;
+  Ok(plan(analyze, single_root(roots), raw))
+}
+
+fn pg_node(v: &Value) -> PlanNode {
+  const DETAIL: [&str; 6] = [
+    "Relation Name",
+    "Index Name",
+    "Description",
+    "Hash Cond",
+    "Filter",
+    "Join Filter",
+  ];
+  let detail = DETAIL
+    .iter()
+    .filter_map(|k| text(v.get(*k)))
+    .collect::<Vec<_>>();
+  let loops = number(v.get("Actual Loops")).unwrap_or(1.0);
+  let mut operator = text(v.get("Node Type")).unwrap_or_else(|| "?".into());
+  if let Some(join) = text(v.get("Join Type")) {
+    operator = format!("{join} {operator}");
+  }
+  PlanNode {
+    operator,
+    detail: (!detail.is_empty()).then(|| detail.join(" · ")),
+    estimated_rows: number(v.get("Plan Rows")),
+    actual_rows: number(v.get("Actual Rows")).map(|r| r * loops),
+    cost: number(v.get("Total Cost")),
+    time_ms: number(v.get("Actual Total Time")).map(|t| t * loops),
+    children: v
+      .get("Plans")
+      .and_then(Value::as_array)
+      .map(|plans| plans.iter().map(pg_node).collect())
+      .unwrap_or_default(),
+  }
+}
+
+/// Operators nested inside MySQL `EXPLAIN FORMAT=JSON` objects.
+const MYSQL_OPERATIONS: &[&str] = &[
+  "query_block",
+  "union_result",
+  "query_specifications",
+  "ordering_operation",
+  "grouping_operation",
+  "duplicates_removal",
+  "windowing",
+  "buffer_result",
+  "nested_loop",
+  "table",
+  "materialized_from_subquery",
+  "attached_subqueries",
+  "optimized_away_subqueries",
+];
+
+/// MySQL `EXPLAIN FORMAT=JSON`.
+pub(crate) fn parse_mysql_json(raw: &str) -> anyhow::Result<QueryPlan> {
+  let doc: Value = serde_json::from_str(raw)?;
+  let obj = doc.as_object().ok_or_else(|| anyhow!("EXPLAIN JSON is not an object"))?;
+  Ok(plan(false, single_root(mysql_children(obj)), raw))
+}
+
+fn mysql_children(obj: &Map<String, Value>) -> Vec<PlanNode> {
+  let mut out = vec![];
+  for key in MYSQL_OPERATIONS {
+    match obj.get(*key) {
+      Some(Value::Object(child)) => out.push(mysql_node(key, child)),
+      // Arrays hold single-key wrappers like `{"table": {...}}`.
+      Some(Value::Array(items)) => {
+        for item in items.iter().filter_map(Value::as_object) {
+          out.extend(mysql_children(item));
+        }
+      }
+      _ => {}
+    }
+  }
+  out
+}
+
+fn mysql_node(key: &str, obj: &Map<String, Value>) -> PlanNode {
+  let cost_info = obj.get("cost_info");
+  let cost = cost_info.and_then(|c| number(c.get("query_cost")).or(number(c.get("prefix_cost"))));
+  let (operator, detail) = match key {
+    "table" => {
+      let access = text(obj.get("access_type")).unwrap_or_default();
+      let mut detail = text(obj.get("table_name")).unwrap_or_default();
+      if let Some(key) = text(obj.get("key")) {
+        detail = format!("{detail} using {key}");
+      }
+      if let Some(cond) = text(obj.get("attached_condition")) {
+        detail = format!("{detail} · {cond}");
+      }
+      (format!("table {access}").trim().to_string(), Some(detail))
+    }
+    _ => (key.replace('_', " "), None),
+  };
+  PlanNode {
+    operator,
+    detail,
+    estimated_rows: number(obj.get("rows_produced_per_join")),
+    cost,
+    children: mysql_children(obj),
+    ..Default::default()
+  }
+}
+
+static MYSQL_TREE_COST: LazyLock<Regex> =
+  LazyLock::new(|| Regex::new(r"\(cost=([\d.e+]+)(?:\.\.[\d.e+]+)? rows=([\d.e+]+)\)").unwrap());
+static MYSQL_TREE_ACTUAL: LazyLock<Regex> = LazyLock::new(|| {
+  Regex::new(r"\(actual time=[\d.e+]+\.\.([\d.e+]+) rows=([\d.e+]+) loops=(\d+)\)").unwrap()
+});
+
+/// MySQL `EXPLAIN ANALYZE` tree text: one `-> Operator` line per node,
+/// children indented by four spaces.
+pub(crate) fn parse_mysql_tree(raw: &str) -> anyhow::Result<QueryPlan> {
+  // (depth, node) stack; a node is attached to its parent once complete.
+  let mut stack: Vec<(usize, PlanNode)> = vec![];
+  let mut roots = vec![];
+  for line in raw.lines() {
+    let Some(pos) = line.find("-> ") else {
+      continue;
+    };
+    let depth = pos / 4;
+    let body = &line[pos + 3..];
+    let operator = body.split("  (").next().unwrap_or(body).trim().to_string();
+    let mut node = PlanNode {
+      operator,
+      ..Default::default()
+    };
+    if let Some(c) = MYSQL_TREE_COST.captures(body) {
+      node.cost = c[1].parse().ok();
+      node.estimated_rows = c[2].parse().ok();
+    }
+    if let Some(c) = MYSQL_TREE_ACTUAL.captures(body) {
+      let loops: f64 = c[3].parse().unwrap_or(1.0);
+      node.time_ms = c[1].parse::<f64>().ok().map(|t| t * loops);
+      node.actual_rows = c[2].parse::<f64>().ok().map(|r| r * loops);
+    }
+    close_deeper(&mut stack, &mut roots, depth);
+    stack.push((depth, node));
+  }
+  close_deeper(&mut stack, &mut roots, 0);
+  if roots.is_empty() {
+    bail!("EXPLAIN ANALYZE returned no plan");
+  }
+  Ok(plan(true, single_root(roots), raw))
+}
+
+/// Pop nodes at `depth` or deeper, attaching each to the node below it.
+fn close_deeper(stack: &mut Vec<(usize, PlanNode)>, roots: &mut Vec<PlanNode>, depth: usize) {
+  while stack.last().is_some_and(|(d, _)| *d >= depth) {
+    let (_, node) = stack.pop().unwrap();
+    match stack.last_mut() {
+      Some((_, parent)) => parent.children.push(node),
+      None => roots.push(node),
+    }
+  }
+}
+
+/// DuckDB `EXPLAIN (FORMAT json)` (`name` / `extra_info`) or the profiler
+/// output of `EXPLAIN (ANALYZE, FORMAT json)` (`operator_*` fields).
+pub(crate) fn parse_duckdb_json(raw: &str, analyze: bool) -> anyhow::Result<QueryPlan> {
+  let doc: Value = serde_json::from_str(raw)?;
+  let roots = match &doc {
+    Value::Array(nodes) => nodes.iter().map(duckdb_node).collect(),
+    // The profiler wraps operators in a query-level object.
+    Value::Object(obj) if !obj.contains_key("operator_type") && !obj.contains_key("name") => obj
+      .get("children")
+      .and_then(Value::as_array)
+      .map(|c| c.iter().map(duckdb_node).collect())
+      .unwrap_or_default(),
+    other => vec![duckdb_node(other)],
+  };
+  Ok(plan(analyze, single_root(roots), raw))
+}
+
+/// Plan of `sql` on a DuckDB-backed connection. The profiler output of
+/// `EXPLAIN ANALYZE` carries actual cardinalities and timings.
+pub(crate) async fn duckdb<C: Connection + ?Sized>(
+  conn: &C,
+  sql: &str,
+  analyze: bool,
+) -> anyhow::Result<QueryPlan> {
+  let options = if analyze {
+    check_analyze("duckdb", sql)?;
+    "ANALYZE, FORMAT json"
+  } else {
+    "FORMAT json"
+  };
+  let raw = plan_text(conn, &format!("EXPLAIN ({options}) {sql}")).await?;
+  parse_duckdb_json(&raw, analyze)
+}
+
+fn duckdb_node(v: &Value) -> PlanNode {
+  let operator = text(v.get("operator_name"))
+    .or_else(|| text(v.get("name")))
+    .or_else(|| text(v.get("operator_type")))
+    .unwrap_or_else(|| "?".into());
+  let extra = v.get("extra_info").and_then(Value::as_object);
+  let estimated_rows = extra.and_then(|e| number(e.get("Estimated Cardinality")));
+  let detail = extra
+    .map(|e| {
+      e.iter()
+        .filter(|(k, _)| k.as_str() != "Estimated Cardinality")
+        .filter_map(|(k, v)| text(Some(v)).map(|t| format!("{k}: {t}")))
+        .collect::<Vec<_>>()
+        .join(" · ")
+    })
+    .filter(|d| !d.is_empty());
+  PlanNode {
+    operator: operator.trim().to_string(),
+    detail,
+    estimated_rows,
+    actual_rows: number(v.get("operator_cardinality")),
+    cost: None,
+    time_ms: number(v.get("operator_timing")).map(|s| s * 1000.0),
+    children: v
+      .get("children")
+      .and_then(Value::as_array)
+      .map(|c| c.iter().map(duckdb_node).collect())
+      .unwrap_or_default(),
+  }
+}
+
+/// SQLite `EXPLAIN QUERY PLAN` rows as `(id, parent, detail)`.
+pub(crate) fn sqlite_tree(rows: &[(i64, i64, String)], raw: &str) -> QueryPlan {
+  fn children(rows: &[(i64, i64, String)], parent: i64) -> Vec<PlanNode> {
+    rows
+      .iter()
+      .filter(|(_, p, _)| *p == parent)
+      .map(|(id, _, detail)| PlanNode {
+        operator: detail.clone(),
+        children: children(rows, *id),
+        ..Default::default()
+      })
+      .collect()
+  }
+  plan(false, single_root(children(rows, 0)), raw)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::dialect::sqlite::SqliteConnection;
+
+  #[test]
+  fn postgres_json_keeps_estimates_and_actuals() {
+    let raw = r#"[{"Plan": {"Node Type": "Hash Join", "Join Type": "Inner",
+      "Total Cost": 42.5, "Plan Rows": 10, "Actual Rows": 3, "Actual Loops": 2,
+      "Actual Total Time": 1.5, "Hash Cond": "(a.id = b.id)",
+      "Plans": [{"Node Type": "Seq Scan", "Relation Name": "a", "Plan Rows": 100}]},
+      "Execution Time": 3.1}]"#;
+    let plan = parse_postgres_json(raw, true).unwrap();
+    let root = &plan.root;
+    assert_eq!(root.operator, "Inner Hash Join");
+    assert_eq!(root.detail.as_deref(), Some("(a.id = b.id)"));
+    assert_eq!((root.estimated_rows, root.actual_rows), (Some(10.0), Some(6.0)));
+    assert_eq!((root.cost, root.time_ms), (Some(42.5), Some(3.0)));
+    assert_eq!(root.children[0].operator, "Seq Scan");
+    assert_eq!(root.children[0].detail.as_deref(), Some("a"));
+  }
+
+  #[test]
+  fn mysql_json_and_tree_formats() {
+    let raw = r#"{"query_block": {"select_id": 1, "cost_info": {"query_cost": "1.20"},
+      "ordering_operation": {"using_filesort": true, "table": {"table_name": "t",
+      "access_type": "ALL", "rows_produced_per_join": 3,
+      "cost_info": {"prefix_cost": "0.55"}}}}}"#;
+    let root = parse_mysql_json(raw).unwrap().root;
+    assert_eq!((root.operator.as_str(), root.cost), ("query block", Some(1.2)));
+    let table = &root.children[0].children[0];
+    assert_eq!(table.operator, "table ALL");
+    assert_eq!((table.estimated_rows, table.cost), (Some(3.0), Some(0.55)));
+
+    let raw = "-> Sort: t.id  (cost=0.55 rows=3) (actual time=0.05..0.06 rows=3 loops=1)
+    -> Filter: (t.id > 1)  (cost=0.55 rows=1) (actual time=0.02..0.025 rows=2 loops=2)
+        -> Table scan on t  (cost=0.55 rows=3) (actual time=0.01..0.02 rows=3 loops=2)
+    -> Index lookup on u using PRIMARY (id=t.id)  (cost=0.3 rows=1)";
+    let root = parse_mysql_tree(raw).unwrap().root;
+    assert_eq!(root.operator, "Sort: t.id");
+    assert_eq!(root.children.len(), 2);
+    let filter = &root.children[0];
+    assert_eq!((filter.actual_rows, filter.time_ms), (Some(4.0), Some(0.05)));
+    assert_eq!(filter.children[0].operator, "Table scan on t");
+    assert_eq!(root.children[1].operator, "Index lookup on u using PRIMARY (id=t.id)");
+  }
+
+  #[test]
+  fn duckdb_plan_and_profile_shapes() {
+    let raw = r#"[{"name": "PROJECTION", "children": [{"name": "SEQ_SCAN ",
+      "children": [], "extra_info": {"Table": "t", "Estimated Cardinality": "5"}}],
+      "extra_info": {"Projections": "a"}}]"#;
+    let root = parse_duckdb_json(raw, false).unwrap().root;
+    assert_eq!(root.operator, "PROJECTION");
+    assert_eq!(root.children[0].operator, "SEQ_SCAN");
+    assert_eq!(root.children[0].estimated_rows, Some(5.0));
+    assert_eq!(root.children[0].detail.as_deref(), Some("Table: t"));
+
+    let raw = r#"{"query_name": "select 1", "latency": 0.001, "children": [
+      {"operator_name": "PROJECTION", "operator_type": "PROJECTION",
+       "operator_cardinality": 7, "operator_timing": 0.002, "extra_info": {},
+       "children": []}]}"#;
+    let root = parse_duckdb_json(raw, true).unwrap().root;
+    assert_eq!((root.actual_rows, root.time_ms), (Some(7.0), Some(2.0)));
+  }
+
+  #[test]
+  fn analyze_rejects_queries_that_write() {
+    assert!(check_analyze("postgres", "with s as (select 1) select * from s").is_ok());
+    let cte = "with d as (delete from t returning *) select * from d";
+    assert!(check_analyze("postgres", cte).is_err());
+    assert!(check_analyze("postgres", "select * into t2 from t").is_err());
+    assert!(check_analyze("postgres", "update t set a = 1").is_err());
+  }
+
+  #[tokio::test]
+  async fn sqlite_explain_query_plan() {
+    let conn = SqliteConnection {
+      path: ":memory:".into(),
+    };
+    let plan = conn
+      .explain("select * from sqlite_master where name = 'x' order by type", false)
+      .await
+      .unwrap();
+    assert!(!plan.analyze);
+    let ops = std::iter::once(&plan.root)
+      .chain(plan.root.children.iter())
+      .map(|n| n.operator.as_str())
+      .collect::<Vec<_>>();
+    assert!(ops.iter().any(|op| op.starts_with("SCAN")), "{ops:?}");
+    assert!(conn.explain("delete from sqlite_master", true).await.is_err());
+  }
+}
@@ -4,6 +4,7 @@ use async_trait::async_trait;
 
 use crate::utils::RawArrowData;
 use crate::dialect::Connection;
+use crate::dialect::cursor::{ResultCursor, ThreadCursor};
 use crate::dialect::duckdb::duckdb_sync;
 use crate::utils::{FunctionMeta, TreeNode, get_file_name};
 
@@ -44,14 +45,26 @@ impl Connection for FileConnection {
   async fn query(&self, sql: &str, _limit: usize, _offset: usize) -> anyhow::Result<RawArrowData> {
     let path = self.path.clone();
     let sql = sql.to_string();
+    let cancel = crate::cancel::current();
     crate::dialect::run_blocking(move || {
       let conn = duckdb::Connection::open_in_memory()?;
       let _ = path;
+      let _hook = duckdb_sync::interrupt_on_cancel(&conn, cancel.as_ref())?;
       duckdb_sync::query(&conn, &sql)
     })
     .await
   }
 
+  async fn open_cursor(&self, sql: &str) -> anyhow::Result<Box<dyn ResultCursor>> {
+    let sql = sql.to_string();
+    let cursor = ThreadCursor::spawn("file", move |requests| {
+      let conn = duckdb::Connection::open_in_memory()?;
+      duckdb_sync::serve_pages(&conn, &sql, requests)
+    })
+    .await?;
+    Ok(Box::new(cursor))
+  }
+
   async fn functions(&self) -> anyhow::Result<Vec<FunctionMeta>> {
     crate::dialect::run_blocking(move || {
       let conn = duckdb::Connection::open_in_memory()?;
@@ -71,23 +84,7 @@ impl Connection for FileConnection {
   }
 
   async fn show_column(&self, _schema: Option<&str>, table: &str) -> anyhow::Result<RawArrowData> {
-    let source = if crate::dialect::duckdb::is_file_function(table) {
-      table.to_string()
-    } else {
-      // File path → build read_xxx() from extension
-      let ext = std::path::Path::new(table)
-        .extension()
-        .and_then(|e| e.to_str())
-        .unwrap_or("");
-      match ext {
-        "parquet" => format!("read_parquet('{table}')"),
-        "csv" => format!("read_csv('{table}', union_by_name=true)"),
-        "tsv" => format!("read_csv('{table}', union_by_name=true, delim='\\t')"),
-        "json" | "jsonl" => format!("read_json('{table}', union_by_name=true)"),
-        "xlsx" => format!("read_xlsx('{table}')"),
-        _ => format!("'{table}'"),
-      }
-    };
+    let source = file_source(table, None);
     let sql = format!("DESCRIBE SELECT * FROM {source}");
     log::info!("file show_column: {}", &sql);
     self.query(&sql, 0, 0).await
@@ -97,3 +94,27 @@ impl Connection for FileConnection {
     name.to_string()
   }
 }
+
+/// DuckDB table expression reading `path`; `format` overrides the extension.
+/// `read_xxx(...)` expressions pass through unchanged.
+pub(crate) fn file_source(path: &str, format: Option<&str>) -> String {
+  if crate::dialect::duckdb::is_file_function(path) {
+    return path.to_string();
+  }
+  let ext = format.map(str::to_ascii_lowercase).unwrap_or_else(|| {
+    std::path::Path::new(path)
+      .extension()
+      .and_then(|e| e.to_str())
+      .unwrap_or("")
+      .to_ascii_lowercase()
+  });
+  let table = path.replace('\'', "''");
+  match ext.as_str() {
+    "parquet" => format!("read_parquet('{table}')"),
+    "csv" => format!("read_csv('{table}', union_by_name=true)"),
+    "tsv" => format!("read_csv('{table}', union_by_name=true, delim='\\t')"),
+    "json" | "jsonl" => format!("read_json('{table}', union_by_name=true)"),
+    "xlsx" => format!("read_xlsx('{table}')"),
+    _ => format!("'{table}'"),
+  }
+}
@@ -7,6 +7,7 @@ use async_trait::async_trait;
 use glob::glob;
 
 use crate::dialect::Connection;
+use crate::dialect::explain::{self, QueryPlan};
 use crate::dialect::duckdb::duckdb_sync;
 use crate::utils::{FunctionMeta, Metadata, RawArrowData, TreeNode};
 
@@ -38,9 +39,11 @@ impl Connection for FolderConnection {
   async fn query(&self, sql: &str, _limit: usize, _offset: usize) -> anyhow::Result<RawArrowData> {
     let path = self.path.clone();
     let sql = sql.to_string();
+    let cancel = crate::cancel::current();
     crate::dialect::run_blocking(move || {
       let conn = duckdb::Connection::open_in_memory()?;
       conn.execute(&format!("SET file_search_path='{path}'"), [])?;
+      let _hook = duckdb_sync::interrupt_on_cancel(&conn, cancel.as_ref())?;
       duckdb_sync::query(&conn, &sql)
     })
     .await
@@ -123,6 +126,22 @@ impl Connection for FolderConnection {
     }
     Ok(String::new())
   }
+  async fn explain(&self, sql: &str, analyze: bool) -> anyhow::Result<QueryPlan> {
+    explain::duckdb(self, sql, analyze).await
+  }
+
+  async fn estimate_row_count(&self, table: &str) -> anyhow::Result<usize> {
+    let path = self.path.clone();
+    let table = table.to_string();
+    crate::dialect::run_blocking(move || {
+      let conn = duckdb::Connection::open_in_memory()?;
+      conn.execute(&format!("SET file_search_path='{path}'"), [])?;
+      duckdb_sync::parquet_row_count(&conn, &table)?
+        .ok_or_else(|| anyhow::anyhow!("no row count metadata for {table}"))
+    })
+    .await
+  }
+
   async fn table_row_count(&self, table: &str, r#where: &str) -> anyhow::Result<usize> {
     let path = self.path.clone();
     let sql = self._table_count_sql(table, r#where);
@@ -0,0 +1,494 @@
+//! Load a CSV/TSV/Parquet/JSON/XLSX file into a table on any writable connection.
+//!
+//! DuckDB reads the file and infers its schema (the same `DESCRIBE` the `file`
+//! dialect uses); rows are then written with the INSERT builder from
+//! [`super::transfer`]. Values that do not fit an overridden column type, and
+//! rows the target refuses for their values (SQLSTATE classes 22 and 23), are
+//! reported instead of aborting the load. Errors that are not about the row (a
+//! missing table or column, a dropped connection) abort it.
+
+use arrow::array::{Array, AsArray, BooleanArray, StringArray};
+use arrow::compute::filter_record_batch;
+use serde::{Deserialize, Serialize};
+
+use super::Connection;
+use super::file::{FileConnection, file_source};
+use super::schema_diff::{normalize_type, render_type};
+use super::transfer::{
+  INSERT_CHUNK_ROWS, TransferMode, TransferProgress, create_columns_sql, insert_sql, nullable,
+  table_ref,
+};
+use crate::cancel::CancelToken;
+use crate::error::ConnectorError;
+use crate::utils::EXPORT_BATCH_ROWS;
+
+/// Rejected rows kept in [`ImportResult::rejected`]; the count covers all of them.
+const MAX_REJECTED: usize = 1000;
+
+/// A column as DuckDB infers it from the file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileColumn {
+  pub name: String,
+  /// DuckDB type (`BIGINT`, `VARCHAR`, ...).
+  pub r#type: String,
+  /// Canonical type, see [`normalize_type`].
+  pub normalized: String,
+}
+
+/// User adjustments for one file column, matched by its inferred `source` name.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnOverride {
+  pub source: String,
+  /// Target column name (default: the file's).
+  #[serde(default)]
+  pub name: Option<String>,
+  /// Target type, spelled for the target dialect. Values that do not convert
+  /// are rejected row by row.
+  #[serde(default)]
+  pub r#type: Option<String>,
+  #[serde(default)]
+  pub skip: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportOptions {
+  pub file: String,
+  /// `csv`, `tsv`, `parquet`, `json`, `jsonl` or `xlsx`; default from the extension.
+  #[serde(default)]
+  pub format: Option<String>,
+  #[serde(default)]
+  pub schema: Option<String>,
+  pub table: String,
+  #[serde(default)]
+  pub mode: TransferMode,
+  #[serde(default)]
+  pub columns: Vec<ColumnOverride>,
+  #[serde(default)]
+  pub batch_rows: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RejectedRow {
+  /// 1-based data row in the file (header excluded).
+  pub row: usize,
+  pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportResult {
+  /// Rows written to the target.
+  pub rows: usize,
+  pub rejected_count: usize,
+  /// First [`MAX_REJECTED`] rejected rows.
+  pub rejected: Vec<RejectedRow>,
+}
+
+impl ImportResult {
+  fn reject(&mut self, row: usize, reason: String) {
+    self.rejected_count += 1;
+    if self.rejected.len() < MAX_REJECTED {
+      self.rejected.push(RejectedRow { row, reason });
+    }
+  }
+}
+
+/// Columns of `file` as DuckDB infers them.
+pub async fn infer_columns(file: &str, format: Option<&str>) -> anyhow::Result<Vec<FileColumn>> {
+  let conn = FileConnection {
+    path: file.to_string(),
+  };
+  let sql = format!("DESCRIBE SELECT * FROM {}", file_source(file, format));
+  let batch = conn.query(&sql, 0, 0).await?.batch;
+  let column = |name: &str| {
+    batch
+      .column_by_name(name)
+      .and_then(|c| c.as_any().downcast_ref::<StringArray>())
+      .ok_or_else(|| anyhow::anyhow!("DESCRIBE returned no {name} column"))
+  };
+  let (names, types) = (column("column_name")?, column("column_type")?);
+  Ok(
+    (0..batch.num_rows())
+      .map(|i| FileColumn {
+        name: names.value(i).to_string(),
+        r#type: types.value(i).to_string(),
+        normalized: normalize_type("duckdb", types.value(i)),
+      })
+      .collect(),
+  )
+}
+
+/// One target column and the DuckDB expression that produces it.
+struct Planned {
+  source: String,
+  name: String,
+  /// Target type, spelled for the target dialect.
+  r#type: String,
+  /// DuckDB type to `TRY_CAST` to when the user overrode the type.
+  cast: Option<String>,
+}
+
+fn plan(
+  dialect: &str,
+  columns: &[FileColumn],
+  overrides: &[ColumnOverride],
+) -> anyhow::Result<Vec<Planned>> {
+  for o in overrides {
+    if !columns.iter().any(|c| c.name == o.source) {
+      anyhow::bail!("column {} is not in the file", o.source);
+    }
+  }
+  let planned = columns
+    .iter()
+    .filter_map(|c| {
+      let o = overrides.iter().find(|o| o.source == c.name);
+      if o.is_some_and(|o| o.skip) {
+        return None;
+      }
+      let name = o
+        .and_then(|o| o.name.clone())
+        .filter(|n| !n.trim().is_empty())
+        .unwrap_or_else(|| c.name.clone());
+      let user_type = o.and_then(|o| o.r#type.clone()).filter(|t| !t.trim().is_empty());
+      Some(match user_type {
+        Some(ty) => Planned {
+          source: c.name.clone(),
+          name,
+          cast: Some(render_type("duckdb", &normalize_type(dialect, &ty))),
+          r#type: ty,
+        },
+        None => Planned {
+          source: c.name.clone(),
+          name,
+          r#type: nullable(dialect, render_type(dialect, &c.normalized)),
+          cast: None,
+        },
+      })
+    })
+    .collect::<Vec<_>>();
+  if planned.is_empty() {
+    anyhow::bail!("no columns left to import");
+  }
+  Ok(planned)
+}
+
+fn duck_quote(name: &str) -> String {
+  format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// `SELECT <target columns>, (<cast failed>, <raw text>)... FROM <file>`.
+fn select_sql(source: &str, planned: &[Planned]) -> String {
+  let mut exprs = vec![];
+  for p in planned {
+    let col = duck_quote(&p.source);
+    exprs.push(match &p.cast {
+      Some(ty) => format!("TRY_CAST({col} AS {ty}) AS {}", duck_quote(&p.name)),
+      None => format!("{col} AS {}", duck_quote(&p.name)),
+    });
+  }
+  for (i, p) in planned.iter().enumerate() {
+    if let Some(ty) = &p.cast {
+      let col = duck_quote(&p.source);
+      exprs.push(format!(
+        "({col} IS NOT NULL AND TRY_CAST({col} AS {ty}) IS NULL) AS __bad_{i}"
+      ));
+      exprs.push(format!("CAST({col} AS VARCHAR) AS __raw_{i}"));
+    }
+  }
+  format!("SELECT {} FROM {source}", exprs.join(", "))
+}
+
+/// Import `options.file` into `options.table` on `target`.
+pub async fn import_file(
+  target: &dyn Connection,
+  options: &ImportOptions,
+  cancel: Option<&CancelToken>,
+  progress: &(dyn Fn(&TransferProgress) + Send + Sync),
+) -> anyhow::Result<ImportResult> {
+  let format = options.format.as_deref().filter(|f| !f.is_empty());
+  let columns = infer_columns(&options.file, format).await?;
+  let planned = plan(target.dialect(), &columns, &options.columns)?;
+  let table = table_ref(target, options.schema.as_deref(), &options.table);
+
+  if options.mode == TransferMode::Replace {
+    target.execute(&format!("DROP TABLE IF EXISTS {table}")).await?;
+  }
+  if options.mode != TransferMode::Append {
+    let defs = planned
+      .iter()
+      .map(|p| (p.name.clone(), p.r#type.clone()))
+      .collect::<Vec<_>>();
+    target.execute(&create_columns_sql(target, &table, &defs)).await?;
+  }
+
+  let reader = FileConnection {
+    path: options.file.clone(),
+  };
+  let select = select_sql(&file_source(&options.file, format), &planned);
+  let checks = planned
+    .iter()
+    .enumerate()
+    .filter(|(_, p)| p.cast.is_some())
+    .collect::<Vec<_>>();
+  let page = options.batch_rows.unwrap_or(EXPORT_BATCH_ROWS).max(1);
+  let mut result = ImportResult::default();
+  let mut state = TransferProgress::default();
+  // One streamed scan keeps file order (DuckDB preserves insertion order by
+  // default), so row numbers are counted here rather than in SQL.
+  let cursor = reader.open_cursor(&select).await?;
+  let mut offset = 0;
+
+  loop {
+    if let Some(t) = cancel {
+      t.check()?;
+    }
+    let batch = cursor.fetch(page).await?.batch;
+    let n = batch.num_rows();
+
+    // Drop rows whose overridden columns did not convert.
+    let mut keep = vec![true; n];
+    for (slot, (_, p)) in checks.iter().enumerate() {
+      let bad = batch.column(planned.len() + 2 * slot).as_boolean();
+      let raw = batch.column(1 + planned.len() + 2 * slot).as_string::<i32>();
+      for (row, k) in keep.iter_mut().enumerate() {
+        if *k && bad.value(row) {
+          *k = false;
+          result.reject(
+            offset + row + 1,
+            format!("{}: cannot convert '{}' to {}", p.source, raw.value(row), p.r#type),
+          );
+        }
+      }
+    }
+    let rows = (0..n)
+      .filter(|&row| keep[row])
+      .map(|row| offset + row + 1)
+      .collect::<Vec<_>>();
+    offset += n;
+    let data = batch.project(&(0..planned.len()).collect::<Vec<_>>())?;
+    let data = filter_record_batch(&data, &BooleanArray::from(keep))?;
+
+    let mut start = 0;
+    while start < data.num_rows() {
+      if let Some(t) = cancel {
+        t.check()?;
+      }
+      let end = (start + INSERT_CHUNK_ROWS).min(data.num_rows());
+      let sql = insert_sql(target, &table, &data, start..end)?;
+      if target.execute(&sql).await.is_ok() {
+        result.rows += end - start;
+      } else {
+        // Retry row by row so one bad row does not sink the chunk.
+        for row in start..end {
+          let sql = insert_sql(target, &table, &data, row..row + 1)?;
+          match target.execute(&sql).await {
+            Ok(_) => result.rows += 1,
+            Err(e) if !row_error(&e) => {
+              return Err(e.context(format!("insert into {table} failed at row {}", rows[row])));
+            }
+            Err(e) => result.reject(rows[row], format!("{e:#}")),
+          }
+        }
+      }
+      start = end;
+    }
+
+    state.rows = result.rows;
+    state.batches += 1;
+    if n < page {
+      break;
+    }
+    progress(&state);
+  }
+  cursor.close().await?;
+
+  state.done = true;
+  progress(&state);
+  Ok(result)
+}
+
+/// Whether a failed insert may be down to the row itself. Connection, login
+/// and cancellation errors would fail every later row too, as would the
+/// SQLSTATE classes about the statement or session rather than its values
+/// (42 syntax or a missing table/column, 08 connection, 28 authorization,
+/// 3D/3F missing database or schema, 57 operator intervention). Data (22) and
+/// integrity (23) errors, and anything unclassified, reject just the row.
+fn row_error(err: &anyhow::Error) -> bool {
+  let code = err.downcast_ref::<ConnectorError>().map(ConnectorError::code);
+  if matches!(code, Some("network" | "auth" | "cancelled")) {
+    return false;
+  }
+  let state = crate::error::sql_state(err).unwrap_or_default();
+  !matches!(state.get(..2), Some("42" | "08" | "28" | "3D" | "3F" | "57"))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::dialect::sqlite::SqliteConnection;
+
+  #[tokio::test]
+  async fn imports_csv_and_reports_rejected_rows() {
+    let dir = std::env::temp_dir();
+    let id = nanoid::nanoid!(8);
+    let csv = dir.join(format!("duckling_import_{id}.csv"));
+    std::fs::write(&csv, "id,name,age\n1,a,30\n2,,40\n3,c,abc\n1,d,5\n").unwrap();
+    let db = dir.join(format!("duckling_import_{id}.db"));
+    rusqlite::Connection::open(&db)
+      .unwrap()
+      .execute_batch("create table people (id integer primary key, name text not null, age integer)")
+      .unwrap();
+    let target = SqliteConnection {
+      path: db.to_string_lossy().to_string(),
+    };
+    let file = csv.to_string_lossy().to_string();
+
+    let columns = infer_columns(&file, None).await.unwrap();
+    assert_eq!(
+      columns.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(),
+      vec!["id", "name", "age"]
+    );
+
+    let options = ImportOptions {
+      file,
+      table: "people".into(),
+      mode: TransferMode::Append,
+      columns: vec![ColumnOverride {
+        source: "age".into(),
+        r#type: Some("integer".into()),
+        ..Default::default()
+      }],
+      ..Default::default()
+    };
+    let res = import_file(&target, &options, None, &|_| {}).await.unwrap();
+    assert_eq!(res.rows, 1);
+    assert_eq!(res.rejected_count, 3);
+    let mut rows = res.rejected.iter().map(|r| r.row).collect::<Vec<_>>();
+    rows.sort();
+    assert_eq!(rows, vec![2, 3, 4]);
+    assert!(
+      res
+        .rejected
+        .iter()
+        .any(|r| r.row == 3 && r.reason == "age: cannot convert 'abc' to integer")
+    );
+
+    let count: i64 = rusqlite::Connection::open(&db)
+      .unwrap()
+      .query_row("select count(*) from people", [], |r| r.get(0))
+      .unwrap();
+    assert_eq!(count, 1);
+    let _ = std::fs::remove_file(csv);
+    let _ = std::fs::remove_file(db);
+  }
+
+  #[tokio::test]
+  async fn import_into_a_missing_table_fails() {
+    let dir = std::env::temp_dir();
+    let id = nanoid::nanoid!(8);
+    let csv = dir.join(format!("duckling_import_{id}.csv"));
+    std::fs::write(&csv, "id,name\n1,a\n2,b\n3,c\n").unwrap();
+    let db = dir.join(format!("duckling_import_{id}.db"));
+    rusqlite::Connection::open(&db).unwrap();
+    let target = SqliteConnection {
+      path: db.to_string_lossy().to_string(),
+    };
+    let options = ImportOptions {
+      file: csv.to_string_lossy().to_string(),
+      table: "missing".into(),
+      mode: TransferMode::Append,
+      ..Default::default()
+    };
+    let err = import_file(&target, &options, None, &|_| {})
+      .await
+      .unwrap_err();
+    assert!(format!("{err:#}").contains("no such table"), "{err:#}");
+    let _ = std::fs::remove_file(csv);
+    let _ = std::fs::remove_file(db);
+  }
+
+  #[tokio::test]
+  async fn a_chunk_of_rows_that_are_all_too_long_is_rejected() {
+    let dir = std::env::temp_dir();
+    let id = nanoid::nanoid!(8);
+    let csv = dir.join(format!("duckling_import_{id}.csv"));
+    std::fs::write(&csv, "id,name\n1,abcd\n2,bcde\n3,cdef\n").unwrap();
+    let db = dir.join(format!("duckling_import_{id}.db"));
+    let ddl = "create table people (id integer, name text check (length(name) <= 3))";
+    rusqlite::Connection::open(&db).unwrap().execute_batch(ddl).unwrap();
+    let target = SqliteConnection {
+      path: db.to_string_lossy().to_string(),
+    };
+    let options = ImportOptions {
+      file: csv.to_string_lossy().to_string(),
+      table: "people".into(),
+      mode: TransferMode::Append,
+      ..Default::default()
+    };
+    let res = import_file(&target, &options, None, &|_| {}).await.unwrap();
+    assert_eq!(res.rows, 0);
+    let mut rows = res.rejected.iter().map(|r| r.row).collect::<Vec<_>>();
+    rows.sort();
+    assert_eq!(rows, vec![1, 2, 3]);
+    let _ = std::fs::remove_file(csv);
+    let _ = std::fs::remove_file(db);
+  }
+
+  #[test]
+  fn insert_errors_are_classified_by_sql_state() {
+    let mysql = |state: &str| {
+      anyhow::Error::from(mysql::Error::MySqlError(mysql::MySqlError {
+        state: state.into(),
+        message: "failed".into(),
+        code: 0,
+      }))
+    };
+    assert!(row_error(&mysql("22001")));
+    assert!(row_error(&mysql("23000")));
+    assert!(!row_error(&mysql("42S02")));
+    assert!(!row_error(&mysql("42S22")));
+    assert!(!row_error(&ConnectorError::Network("reset".into()).into()));
+  }
+
+  #[test]
+  fn plan_applies_overrides() {
+    let columns = vec![
+      FileColumn {
+        name: "a".into(),
+        r#type: "BIGINT".into(),
+        normalized: "int64".into(),
+      },
+      FileColumn {
+        name: "b".into(),
+        r#type: "VARCHAR".into(),
+        normalized: "text".into(),
+      },
+    ];
+    let overrides = vec![
+      ColumnOverride {
+        source: "a".into(),
+        name: Some("id".into()),
+        r#type: Some("numeric(10,2)".into()),
+        ..Default::default()
+      },
+      ColumnOverride {
+        source: "b".into(),
+        skip: true,
+        ..Default::default()
+      },
+    ];
+    let planned = plan("postgres", &columns, &overrides).unwrap();
+    assert_eq!(planned.len(), 1);
+    assert_eq!(planned[0].name, "id");
+    assert_eq!(planned[0].cast.as_deref(), Some("decimal(10,2)"));
+    let bad = ColumnOverride {
+      source: "zzz".into(),
+      ..Default::default()
+    };
+    assert!(plan("postgres", &columns, &[bad]).is_err());
+  }
+}
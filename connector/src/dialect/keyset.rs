@@ -0,0 +1,471 @@
+//! Keyset ("seek") paging for [`Connection::query_table`].
+//!
+//! When the browse order is a unique, non-null key, the next page is read with
+//! a `key > last_key` predicate instead of `OFFSET`, so deep pages cost the same
+//! as the first one. The last key of each page travels to the caller as an
+//! opaque token in [`RawArrowData::cursor`](crate::utils::RawArrowData).
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, bail};
+use arrow::array::{Array, ArrayRef, RecordBatch};
+use arrow::datatypes::DataType;
+use arrow::util::display::array_value_to_string;
+use serde::{Deserialize, Serialize};
+use serde_json::{Number, Value};
+
+use crate::dialect::Connection;
+use crate::dialect::detail::{TableDetail, split_expr_list};
+use crate::dialect::edit::sql_literal;
+
+/// Columns a page is ordered and sought by.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Keyset {
+  pub columns: Vec<String>,
+  pub descending: bool,
+}
+
+/// How long a planned keyset is reused before the table's keys are read again.
+const PLAN_TTL: Duration = Duration::from_secs(60);
+
+/// Keysets planned per table and order, so paging through a table looks its
+/// keys up once rather than on every page.
+#[derive(Default)]
+pub struct PlanCache {
+  plans: Mutex<HashMap<(String, String), (Instant, Option<Keyset>)>>,
+}
+
+impl PlanCache {
+  fn get(&self, table: &str, order_by: &str) -> Option<Option<Keyset>> {
+    let plans = self.plans.lock().ok()?;
+    let (planned, keyset) = plans.get(&(table.to_string(), order_by.to_string()))?;
+    (planned.elapsed() < PLAN_TTL).then(|| keyset.clone())
+  }
+
+  fn put(&self, table: &str, order_by: &str, keyset: Option<Keyset>) {
+    if let Ok(mut plans) = self.plans.lock() {
+      plans.retain(|_, (planned, _)| planned.elapsed() < PLAN_TTL);
+      let key = (table.to_string(), order_by.to_string());
+      plans.insert(key, (Instant::now(), keyset));
+    }
+  }
+}
+
+/// Contents of a cursor token. `after` holds the key of the last row as JSON
+/// scalars; they are rendered as literals on the server, never pasted as SQL.
+#[derive(Debug, Serialize, Deserialize)]
+struct Token {
+  key: Vec<String>,
+  after: Vec<Value>,
+}
+
+/// Keys that identify a row: the primary key plus unique constraints and
+/// indexes over non-null columns.
+pub(crate) fn unique_keys(detail: &TableDetail) -> Vec<Vec<String>> {
+  let not_null = |c: &String| {
+    detail
+      .columns
+      .iter()
+      .any(|d| d.name.eq_ignore_ascii_case(c) && !d.nullable)
+  };
+  let unique = detail
+    .unique_constraints
+    .iter()
+    .map(|u| &u.columns)
+    .chain(detail.indexes.iter().filter(|i| i.unique).map(|i| &i.columns))
+    .filter(|cols| cols.iter().all(not_null))
+    .cloned();
+  std::iter::once(detail.primary_key.clone())
+    .chain(unique)
+    .filter(|cols| !cols.is_empty())
+    .collect()
+}
+
+/// Keyset for `order_by` given the table's unique `keys`; empty keys are
+/// ignored. A blank order pages by the first key; otherwise every item must be
+/// a bare column with the same direction, and together they must cover one of
+/// `keys`.
+pub(crate) fn plan(dialect: &str, order_by: &str, keys: &[Vec<String>]) -> Option<Keyset> {
+  let mut keys = keys.iter().filter(|k| !k.is_empty());
+  if order_by.trim().is_empty() {
+    return keys.next().map(|k| Keyset {
+      columns: k.clone(),
+      descending: false,
+    });
+  }
+  let items = split_expr_list(order_by)
+    .iter()
+    .map(|item| order_item(dialect, item))
+    .collect::<Option<Vec<_>>>()?;
+  let descending = items.first()?.1;
+  if items.iter().any(|(_, desc)| *desc != descending) {
+    return None;
+  }
+  let columns: Vec<String> = items.into_iter().map(|(c, _)| c).collect();
+  let covered = keys.any(|k| {
+    k.iter()
+      .all(|c| columns.iter().any(|o| same_name(dialect, o, c)))
+  });
+  covered.then_some(Keyset {
+    columns,
+    descending,
+  })
+}
+
+/// `col [asc|desc]` with `col` optionally quoted; `None` for anything else.
+/// A bare Postgres `col` is folded to lower case, as the server does.
+fn order_item(dialect: &str, item: &str) -> Option<(String, bool)> {
+  let mut parts = item.split_whitespace();
+  let column = match unquote(parts.next()?)? {
+    (name, false) if dialect == "postgres" => name.to_ascii_lowercase(),
+    (name, _) => name,
+  };
+  let descending = match parts.next().map(str::to_ascii_lowercase).as_deref() {
+    None | Some("asc") => false,
+    Some("desc") => true,
+    _ => return None,
+  };
+  parts.next().is_none().then_some((column, descending))
+}
+
+/// The name inside `ident` and whether it was quoted.
+fn unquote(ident: &str) -> Option<(String, bool)> {
+  for (open, close) in [('"', '"'), ('`', '`'), ('[', ']')] {
+    if let Some(inner) = ident.strip_prefix(open).and_then(|s| s.strip_suffix(close)) {
+      return Some((inner.to_string(), true));
+    }
+  }
+  let bare = ident.chars().all(|c| c.is_alphanumeric() || c == '_');
+  bare.then(|| (ident.to_string(), false))
+}
+
+/// Whether two column names denote the same column. Postgres names are case
+/// sensitive once folded; the other dialects compare them case-insensitively.
+fn same_name(dialect: &str, a: &str, b: &str) -> bool {
+  match dialect {
+    "postgres" => a == b,
+    _ => a.eq_ignore_ascii_case(b),
+  }
+}
+
+/// Keyset for browsing `table` in `order_by`, or `None` when it has no usable
+/// unique key (paging then falls back to `OFFSET`).
+pub(crate) async fn for_table<C: Connection + ?Sized>(
+  conn: &C,
+  table: &str,
+  order_by: &str,
+) -> Option<Keyset> {
+  // ClickHouse primary keys are sort keys, not unique ones.
+  if conn.dialect() == "clickhouse" {
+    return None;
+  }
+  let cache = conn.keyset_plans();
+  if let Some(keyset) = cache.and_then(|c| c.get(table, order_by)) {
+    return keyset;
+  }
+  let (schema, name) = match table.rsplit_once('.') {
+    Some((schema, name)) => (Some(schema), name),
+    None => (None, table),
+  };
+  let keys = if order_by.trim().is_empty() {
+    let key = conn.primary_key(schema, name).await.ok()?;
+    std::iter::once(key).filter(|k| !k.is_empty()).collect()
+  } else {
+    unique_keys(&conn.table_detail(schema, name).await.ok()?)
+  };
+  let keyset = plan(conn.dialect(), order_by, &keys);
+  if let Some(cache) = cache {
+    cache.put(table, order_by, keyset.clone());
+  }
+  keyset
+}
+
+/// `ORDER BY` text for a keyset chosen without an explicit order.
+pub(crate) fn order_by_sql<C: Connection + ?Sized>(conn: &C, keyset: &Keyset) -> String {
+  let dir = if keyset.descending { " desc" } else { "" };
+  keyset
+    .columns
+    .iter()
+    .map(|c| format!("{}{dir}", conn.quote(c)))
+    .collect::<Vec<_>>()
+    .join(", ")
+}
+
+/// Rows strictly after `after` in keyset order, spelled without row-value
+/// comparison so every dialect accepts it: `a > 1 or (a = 1 and b > 2)`.
+/// `after` holds the literals rendered by [`parse_token`].
+pub(crate) fn seek_predicate<C: Connection + ?Sized>(
+  conn: &C,
+  keyset: &Keyset,
+  after: &[String],
+) -> String {
+  let op = if keyset.descending { "<" } else { ">" };
+  let columns: Vec<String> = keyset.columns.iter().map(|c| conn.quote(c)).collect();
+  let terms: Vec<String> = (0..columns.len())
+    .map(|i| {
+      let mut parts: Vec<String> = (0..i)
+        .map(|j| format!("{} = {}", columns[j], after[j]))
+        .collect();
+      parts.push(format!("{} {op} {}", columns[i], after[i]));
+      parts.join(" and ")
+    })
+    .collect();
+  match terms.as_slice() {
+    [single] => single.clone(),
+    _ => terms
+      .iter()
+      .map(|t| format!("({t})"))
+      .collect::<Vec<_>>()
+      .join(" or "),
+  }
+}
+
+/// Token for the page after `batch`: the key of its last row. `None` when the
+/// keyset or batch is empty, a key column is missing or the key holds a NULL
+/// or a value with no JSON scalar form.
+pub(crate) fn token(
+  dialect: &str,
+  keyset: &Keyset,
+  batch: &RecordBatch,
+) -> anyhow::Result<Option<String>> {
+  let Some(row) = batch.num_rows().checked_sub(1) else {
+    return Ok(None);
+  };
+  if keyset.columns.is_empty() {
+    return Ok(None);
+  }
+  let schema = batch.schema();
+  let mut after = Vec::with_capacity(keyset.columns.len());
+  for column in &keyset.columns {
+    let Some(idx) = schema
+      .fields()
+      .iter()
+      .position(|f| same_name(dialect, f.name(), column))
+    else {
+      return Ok(None);
+    };
+    let Some(value) = key_value(batch.column(idx), row)? else {
+      return Ok(None);
+    };
+    after.push(value);
+  }
+  let token = Token {
+    key: keyset.columns.clone(),
+    after,
+  };
+  Ok(Some(serde_json::to_string(&token)?))
+}
+
+/// One key cell as a JSON scalar: numbers and booleans as themselves,
+/// temporal and text values as text. `None` for NULL, NaN and infinities,
+/// and binary or nested values.
+fn key_value(col: &ArrayRef, row: usize) -> anyhow::Result<Option<Value>> {
+  if col.is_null(row) {
+    return Ok(None);
+  }
+  let dt = col.data_type();
+  let binary = matches!(
+    dt,
+    DataType::Binary | DataType::LargeBinary | DataType::FixedSizeBinary(_)
+  );
+  if binary || dt.is_nested() {
+    return Ok(None);
+  }
+  let text = array_value_to_string(col, row)?;
+  Ok(match dt {
+    DataType::Boolean => Some(Value::Bool(text == "true")),
+    // Decimals stay text so they keep every digit; engines cast the literal.
+    dt if dt.is_integer() || dt.is_floating() => text.parse::<Number>().ok().map(Value::Number),
+    DataType::Timestamp(..) => Some(Value::String(text.replacen('T', " ", 1))),
+    _ => Some(Value::String(text)),
+  })
+}
+
+/// Key literals for `dialect` from a token issued for the same keyset. Only
+/// JSON scalars are accepted as key values.
+pub(crate) fn parse_token(
+  dialect: &str,
+  keyset: &Keyset,
+  token: &str,
+) -> anyhow::Result<Vec<String>> {
+  let token: Token =
+    serde_json::from_str(token).map_err(|e| anyhow!("invalid page cursor: {e}"))?;
+  if keyset.columns.is_empty() {
+    bail!("this table has no key to page by");
+  }
+  if token.key != keyset.columns || token.after.len() != token.key.len() {
+    bail!("page cursor does not match the current sort order");
+  }
+  token
+    .after
+    .iter()
+    .map(|v| match v {
+      Value::Bool(_) | Value::Number(_) | Value::String(_) => Ok(sql_literal(dialect, v)),
+      _ => bail!("invalid page cursor: key values must be scalars"),
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use arrow::util::display::array_value_to_string;
+
+  use crate::dialect::CountOptions;
+  use crate::dialect::detail::{ColumnDetail, IndexDetail};
+  use crate::dialect::sqlite::SqliteConnection;
+
+  fn keys() -> Vec<Vec<String>> {
+    vec![vec!["id".into()], vec!["email".into()]]
+  }
+
+  #[test]
+  fn plans_only_orders_covering_a_unique_key() {
+    let asc = |cols: &[&str]| {
+      Some(Keyset {
+        columns: cols.iter().map(|c| c.to_string()).collect(),
+        descending: false,
+      })
+    };
+    let plan = |order_by| plan("sqlite", order_by, &keys());
+    assert_eq!(plan(""), asc(&["id"]));
+    assert_eq!(plan("\"Email\" asc"), asc(&["Email"]));
+    assert_eq!(plan("name, id"), asc(&["name", "id"]));
+    assert!(plan("name desc, id desc").unwrap().descending);
+    assert_eq!(plan("name"), None);
+    assert_eq!(plan("name desc, id"), None);
+    assert_eq!(plan("lower(email)"), None);
+    assert_eq!(plan("id nulls first"), None);
+    assert_eq!(super::plan("sqlite", "", &[]), None);
+    assert_eq!(super::plan("sqlite", "", &[vec![]]), None);
+    assert_eq!(super::plan("sqlite", "name", &[vec![]]), None);
+  }
+
+  #[test]
+  fn postgres_plans_fold_bare_names_only() {
+    let keys = vec![vec!["id".to_string()], vec!["Code".to_string()]];
+    let columns = |order_by| plan("postgres", order_by, &keys).map(|k| k.columns);
+    assert_eq!(columns("ID"), Some(vec!["id".to_string()]));
+    assert_eq!(columns("\"Code\""), Some(vec!["Code".to_string()]));
+    assert_eq!(columns("Code"), None);
+    assert_eq!(columns("\"ID\""), None);
+  }
+
+  #[test]
+  fn nullable_unique_indexes_are_not_keys() {
+    let column = |name: &str, nullable| ColumnDetail {
+      name: name.into(),
+      nullable,
+      ..Default::default()
+    };
+    let index = |name: &str, column: &str| IndexDetail {
+      name: name.into(),
+      columns: vec![column.into()],
+      unique: true,
+      method: None,
+    };
+    let detail = TableDetail {
+      columns: vec![column("id", false), column("code", false), column("nick", true)],
+      primary_key: vec!["id".into()],
+      indexes: vec![index("u_code", "code"), index("u_nick", "nick")],
+      ..Default::default()
+    };
+    assert_eq!(
+      unique_keys(&detail),
+      vec![vec!["id".to_string()], vec!["code".to_string()]]
+    );
+  }
+
+  #[tokio::test]
+  async fn query_table_seeks_with_page_cursor() {
+    let path = std::env::temp_dir().join(format!("duckling_keyset_{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let conn = SqliteConnection {
+      path: path.to_string_lossy().to_string(),
+    };
+    conn
+      .execute("create table t (a integer not null, b text not null, primary key (a, b))")
+      .await
+      .unwrap();
+    conn
+      .execute("insert into t values (1, 'x'), (1, 'y'), (2, 'x'), (3, 'z'), (3, 'zz')")
+      .await
+      .unwrap();
+
+    let mut seen = vec![];
+    let mut after = None;
+    loop {
+      let page = conn
+        .query_table("t", 2, 0, after.as_deref(), "", "", "", &Default::default())
+        .await
+        .unwrap();
+      assert_eq!(page.total, 5);
+      for row in 0..page.batch.num_rows() {
+        let cell = |i| array_value_to_string(page.batch.column(i), row).unwrap();
+        seen.push(format!("{}{}", cell(0), cell(1)));
+      }
+      match page.cursor {
+        Some(c) => after = Some(c),
+        None => break,
+      }
+    }
+    assert_eq!(seen, ["1x", "1y", "2x", "3z", "3zz"]);
+
+    let count = CountOptions::default();
+    let err = conn
+      .query_table("t", 2, 0, Some(r#"{"key":["b"],"after":["'x'"]}"#), "", "", "", &count)
+      .await
+      .unwrap_err();
+    assert!(err.to_string().contains("does not match"));
+
+    let quoted = r#"{"key":["a","b"],"after":[1,"x' or '1'='1"]}"#;
+    let page = conn
+      .query_table("t", 5, 0, Some(quoted), "", "", "", &count)
+      .await
+      .unwrap();
+    assert_eq!(page.batch.num_rows(), 4);
+    let err = conn
+      .query_table("t", 2, 0, Some(r#"{"key":["a","b"],"after":["1",{}]}"#), "", "", "", &count)
+      .await
+      .unwrap_err();
+    assert!(err.to_string().contains("scalars"));
+    let _ = std::fs::remove_file(path);
+  }
+
+  #[tokio::test]
+  async fn query_table_without_key_pages_by_offset() {
+    let path =
+      std::env::temp_dir().join(format!("duckling_keyset_nokey_{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let conn = SqliteConnection {
+      path: path.to_string_lossy().to_string(),
+    };
+    conn.execute("create table t (a integer)").await.unwrap();
+    conn
+      .execute("insert into t values (1), (2), (3), (4), (5), (6), (7)")
+      .await
+      .unwrap();
+
+    for where_ in ["", "a > 1"] {
+      let mut seen = vec![];
+      for page in 0..3 {
+        let res = conn
+          .query_table("t", 2, page * 2, None, where_, "", "", &Default::default())
+          .await
+          .unwrap();
+        assert_eq!(res.cursor, None);
+        for row in 0..res.batch.num_rows() {
+          seen.push(array_value_to_string(res.batch.column(0), row).unwrap());
+        }
+      }
+      let expected = match where_ {
+        "" => ["1", "2", "3", "4", "5", "6"],
+        _ => ["2", "3", "4", "5", "6", "7"],
+      };
+      assert_eq!(seen, expected);
+    }
+    let _ = std::fs::remove_file(path);
+  }
+}
@@ -0,0 +1,338 @@
+//! Guards against unbounded results: a statement timeout plus caps on the rows
+//! and Arrow bytes materialized. Hitting a cap truncates the result and flags
+//! it instead of failing.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use sqlparser::ast::Statement;
+
+use arrow::array::RecordBatch;
+use arrow::compute::{cast, concat_batches};
+use arrow::datatypes::SchemaRef;
+
+use crate::dialect::Connection;
+use crate::dialect::ast::{first_stmt, top_limit_stmt};
+use crate::dialect::cursor::ResultCursor;
+use crate::error::ConnectorError;
+use crate::utils::RawArrowData;
+
+/// Limits for one statement; unset fields are unbounded.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct QueryLimits {
+  /// Stop the statement on the server after this many milliseconds.
+  pub timeout_ms: Option<u64>,
+  /// Rows kept; reading a `SELECT` stops once the cap is passed.
+  pub max_rows: Option<usize>,
+  /// Arrow buffer bytes kept; reading stops at the first row past the budget.
+  pub max_bytes: Option<usize>,
+}
+
+impl QueryLimits {
+  /// These limits with unset fields taken from `fallback` (request over connection).
+  pub fn or(&self, fallback: &QueryLimits) -> QueryLimits {
+    QueryLimits {
+      timeout_ms: self.timeout_ms.or(fallback.timeout_ms),
+      max_rows: self.max_rows.or(fallback.max_rows),
+      max_bytes: self.max_bytes.or(fallback.max_bytes),
+    }
+  }
+
+  pub fn timeout(&self) -> Option<Duration> {
+    self.timeout_ms.filter(|ms| *ms > 0).map(Duration::from_millis)
+  }
+}
+
+/// Rows pulled per cursor fetch while a row or byte cap is being enforced.
+pub(crate) const FETCH_ROWS: usize = 1024;
+
+/// [`Connection::query_limited`] for any dialect. A capped query is read from
+/// a cursor and reading stops at the cap; without a cursor the query is
+/// limited in SQL and trimmed afterwards.
+pub(crate) async fn query<C: Connection + ?Sized>(
+  conn: &C,
+  sql: &str,
+  limits: &QueryLimits,
+) -> anyhow::Result<RawArrowData> {
+  match limits.timeout() {
+    Some(timeout) => {
+      let hinted = conn.statement_timeout_sql(sql, timeout);
+      let run = hinted.as_deref().unwrap_or(sql);
+      crate::cancel::with_timeout(timeout, read(conn, sql, run, limits)).await
+    }
+    None => read(conn, sql, sql, limits).await,
+  }
+}
+
+/// Read `run` (`sql` with any timeout hint) within the row and byte caps.
+async fn read<C: Connection + ?Sized>(
+  conn: &C,
+  sql: &str,
+  run: &str,
+  limits: &QueryLimits,
+) -> anyhow::Result<RawArrowData> {
+  if limits.max_rows.is_none() && limits.max_bytes.is_none() {
+    return conn.query(run, 0, 0).await;
+  }
+  let stmt = first_stmt(conn.dialect(), sql);
+  if matches!(stmt, Some(Statement::Query(_))) {
+    match conn.open_cursor(run).await {
+      Ok(cursor) => return read_cursor(cursor.as_ref(), limits).await,
+      Err(e) if !is_unsupported(&e) => return Err(e),
+      Err(_) => {}
+    }
+  }
+  let capped = match (&stmt, limits.max_rows) {
+    // The hinted SQL no longer parses as a plain query on every dialect.
+    (Some(stmt), Some(max)) if run == sql => top_limit_stmt(conn.dialect(), stmt, max + 1),
+    _ => None,
+  };
+  let mut raw = conn.query(capped.as_deref().unwrap_or(run), 0, 0).await?;
+  truncate(&mut raw, limits);
+  Ok(raw)
+}
+
+/// Fetch pages until the result ends or a cap is hit, then close the cursor.
+async fn read_cursor(
+  cursor: &dyn ResultCursor,
+  limits: &QueryLimits,
+) -> anyhow::Result<RawArrowData> {
+  let mut budget = Budget::new(limits);
+  let mut pages = vec![];
+  let mut last: Option<RawArrowData> = None;
+  loop {
+    let wanted = budget.wanted(FETCH_ROWS);
+    let page = cursor.fetch(wanted).await?;
+    let fetched = page.batch.num_rows();
+    pages.push(budget.admit_batch(&page.batch));
+    last = Some(page);
+    if fetched < wanted || budget.truncated {
+      break;
+    }
+  }
+  if let Err(e) = cursor.close().await {
+    log::debug!("closing capped cursor: {e}");
+  }
+  // The last page carries the final column types.
+  let last = last.expect("at least one page is fetched");
+  let schema = last.batch.schema();
+  let pages = pages
+    .into_iter()
+    .map(|page| conform(page, &schema))
+    .collect::<anyhow::Result<Vec<_>>>()?;
+  let batch = concat_batches(&schema, &pages)?;
+  Ok(RawArrowData {
+    titles: last.titles,
+    sql: last.sql,
+    truncated: budget.truncated,
+    ..RawArrowData::from_batch(batch)
+  })
+}
+
+/// `page` cast to `schema`. A cursor may widen a column to text on a later
+/// page (SQLite does for mixed-type columns), so earlier pages follow it.
+fn conform(page: RecordBatch, schema: &SchemaRef) -> anyhow::Result<RecordBatch> {
+  if page.schema().fields() == schema.fields() {
+    return Ok(page);
+  }
+  let columns = page
+    .columns()
+    .iter()
+    .zip(schema.fields())
+    .map(|(col, field)| cast(col, field.data_type()))
+    .collect::<Result<Vec<_>, _>>()?;
+  Ok(RecordBatch::try_new(schema.clone(), columns)?)
+}
+
+pub(crate) fn is_unsupported(err: &anyhow::Error) -> bool {
+  matches!(
+    err.downcast_ref::<ConnectorError>(),
+    Some(ConnectorError::Unsupported(_))
+  )
+}
+
+/// Running row and byte totals while a result is read in chunks.
+pub(crate) struct Budget {
+  max_rows: Option<usize>,
+  max_bytes: Option<usize>,
+  rows: usize,
+  bytes: usize,
+  /// A chunk did not fit; stop reading.
+  pub truncated: bool,
+}
+
+impl Budget {
+  pub fn new(limits: &QueryLimits) -> Self {
+    Self {
+      max_rows: limits.max_rows,
+      max_bytes: limits.max_bytes,
+      rows: 0,
+      bytes: 0,
+      truncated: false,
+    }
+  }
+
+  /// Rows to read next: at most `chunk`, and one past `max_rows` so a result
+  /// that fits exactly is not flagged.
+  pub fn wanted(&self, chunk: usize) -> usize {
+    self
+      .max_rows
+      .map_or(chunk, |max| chunk.min(max + 1 - self.rows))
+  }
+
+  /// How many of the next `rows` rows, together costing `bytes`, fit; flags
+  /// the budget when any are dropped.
+  pub fn admit(&mut self, rows: usize, bytes: usize) -> usize {
+    let mut keep = self
+      .max_rows
+      .map_or(rows, |max| rows.min(max.saturating_sub(self.rows)));
+    let per_row = if rows > 0 { bytes.div_ceil(rows) } else { 0 };
+    if let Some(max_bytes) = self.max_bytes
+      && per_row > 0
+    {
+      keep = keep.min(max_bytes.saturating_sub(self.bytes) / per_row);
+    }
+    self.rows += keep;
+    self.bytes += per_row * keep;
+    self.truncated |= keep < rows;
+    keep
+  }
+
+  /// The leading rows of `batch` that fit. A slice reports its parent's
+  /// buffers, so rows are costed at the batch average.
+  pub fn admit_batch(&mut self, batch: &RecordBatch) -> RecordBatch {
+    let keep = self.admit(batch.num_rows(), batch.get_array_memory_size());
+    batch.slice(0, keep)
+  }
+}
+
+/// Cap for a table page of `limit` rows (0 = all): one past `max_rows` when
+/// the page would be larger, so [`truncate`] can flag it.
+pub fn page_limit(limit: usize, limits: &QueryLimits) -> usize {
+  match limits.max_rows {
+    Some(max) if limit == 0 || limit > max => max + 1,
+    _ => limit,
+  }
+}
+
+/// Drop rows past `max_rows` / `max_bytes` and flag the result. A `total`
+/// beyond this batch (a table page's row count) is kept.
+pub fn truncate(raw: &mut RawArrowData, limits: &QueryLimits) {
+  let rows = raw.batch.num_rows();
+  let mut budget = Budget::new(limits);
+  let kept = budget.admit_batch(&raw.batch);
+  if budget.truncated {
+    if raw.total == rows {
+      raw.total = kept.num_rows();
+    }
+    raw.batch = kept;
+    raw.cursor = None;
+    raw.truncated = true;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::dialect::sqlite::SqliteConnection;
+
+  #[tokio::test]
+  async fn caps_rows_and_bytes_without_failing() {
+    let conn = SqliteConnection {
+      path: ":memory:".into(),
+    };
+    let sql = "with recursive r(i) as (select 1 union all select i + 1 from r where i < 1000)
+      select i, printf('%0100d', i) as pad from r";
+
+    let raw = conn.query_limited(sql, &QueryLimits::default()).await.unwrap();
+    assert_eq!((raw.total, raw.truncated), (1000, false));
+
+    let rows = QueryLimits {
+      max_rows: Some(10),
+      ..Default::default()
+    };
+    let raw = conn.query_limited(sql, &rows).await.unwrap();
+    assert_eq!((raw.batch.num_rows(), raw.truncated), (10, true));
+    let fits = QueryLimits {
+      max_rows: Some(1000),
+      ..Default::default()
+    };
+    assert!(!conn.query_limited(sql, &fits).await.unwrap().truncated);
+
+    let bytes = QueryLimits {
+      max_bytes: Some(16 * 1024),
+      ..Default::default()
+    };
+    let raw = conn.query_limited(sql, &bytes).await.unwrap();
+    assert!(raw.truncated);
+    assert!(raw.batch.num_rows() > 0 && raw.batch.num_rows() < 1000);
+  }
+
+  #[tokio::test]
+  async fn capped_reads_keep_columns_that_turn_to_text() {
+    use arrow::array::AsArray;
+
+    let conn = SqliteConnection {
+      path: ":memory:".into(),
+    };
+    // Integers for the whole first page, text further down.
+    let sql = "with recursive r(i) as (select 1 union all select i + 1 from r where i < 1500)
+      select case when i > 1200 then 'n/a' else i end as v from r";
+    let limits = QueryLimits {
+      max_rows: Some(2000),
+      ..Default::default()
+    };
+    let raw = conn.query_limited(sql, &limits).await.unwrap();
+    assert_eq!((raw.batch.num_rows(), raw.truncated), (1500, false));
+    let v = raw.batch.column(0).as_string::<i32>();
+    assert_eq!((v.value(0), v.value(1499)), ("1", "n/a"));
+  }
+
+  #[test]
+  fn table_pages_keep_their_total() {
+    use arrow::array::{ArrayRef, Int64Array};
+
+    let limits = QueryLimits {
+      max_rows: Some(2),
+      ..Default::default()
+    };
+    assert_eq!(
+      (
+        page_limit(0, &limits),
+        page_limit(500, &limits),
+        page_limit(1, &limits)
+      ),
+      (3, 3, 1)
+    );
+
+    let ids: ArrayRef = std::sync::Arc::new(Int64Array::from(vec![1, 2, 3]));
+    let batch = RecordBatch::try_from_iter([("id", ids)]).unwrap();
+    let mut raw = RawArrowData {
+      total: 1000,
+      cursor: Some("next".into()),
+      ..RawArrowData::from_batch(batch)
+    };
+    truncate(&mut raw, &limits);
+    assert_eq!(
+      (raw.batch.num_rows(), raw.total, raw.truncated),
+      (2, 1000, true)
+    );
+    assert_eq!(raw.cursor, None);
+  }
+
+  #[tokio::test]
+  async fn timeout_stops_the_statement() {
+    let conn = SqliteConnection {
+      path: ":memory:".into(),
+    };
+    let limits = QueryLimits {
+      timeout_ms: Some(50),
+      ..Default::default()
+    };
+    let slow = "with recursive r(i) as (select 1 union all select i + 1 from r where i < 1e10)
+      select count(*) from r";
+    let err = conn.query_limited(slow, &limits).await.unwrap_err();
+    assert!(err.to_string().contains("timed out"), "got {err}");
+  }
+}
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use crate::dialect::ast::first_stmt;
 use crate::utils::{FunctionMeta, Metadata, RawArrowData};
 use crate::utils::{TreeNode, batch_write};
@@ -7,15 +9,41 @@ use itertools::Itertools;
 pub mod ast;
 pub mod capabilities;
 pub mod clickhouse;
+pub mod cursor;
+pub mod data_diff;
+pub mod ddl;
+pub mod detail;
 pub mod duckdb;
+pub mod edit;
+pub mod explain;
 pub mod file;
 pub mod folder;
+pub mod import;
+pub mod keyset;
+pub mod limits;
+pub mod mssql;
 pub mod mysql;
+pub mod params;
 pub mod postgres;
 pub mod quack;
+pub mod row_count;
+pub mod schema_diff;
+pub mod script;
 pub mod sqlite;
+pub mod transaction;
+pub mod transfer;
 
 pub use capabilities::{Caps, caps_for_dialect};
+pub use cursor::ResultCursor;
+pub use ddl::ObjectKind;
+pub use detail::TableDetail;
+pub use explain::{PlanNode, QueryPlan};
+pub use keyset::PlanCache;
+pub use limits::QueryLimits;
+pub use params::{ParamType, ParamValue, Params};
+pub use row_count::CountOptions;
+pub use script::{ScriptErrorMode, StatementOutcome};
+pub use transaction::Transaction;
 
 /// Run a synchronous connector operation off the async runtime.
 pub async fn run_blocking<T, F>(f: F) -> anyhow::Result<T>
@@ -32,6 +60,25 @@ fn unsupported(method: &'static str) -> anyhow::Error {
   anyhow::Error::new(crate::error::ConnectorError::unsupported(method))
 }
 
+/// One LIMIT/OFFSET page of `sql` (`stmt` is its parsed form, when it parses).
+pub(crate) fn page_sql(
+  dialect: &str,
+  stmt: Option<&sqlparser::ast::Statement>,
+  sql: &str,
+  page: usize,
+  offset: usize,
+) -> String {
+  if let Some(s) = stmt {
+    ast::limit_stmt(dialect, s, Some(page), Some(offset))
+      .unwrap_or_else(|| format!("select * from ({sql}) ____ limit {page} offset {offset}"))
+  } else if dialect == "mssql" {
+    ast::offset_fetch_wrapped(sql, Some(page), Some(offset))
+  } else {
+    // Unparsed SQL: wrap as subquery (works for most engines).
+    format!("select * from ({sql}) ____ export_page limit {page} offset {offset}")
+  }
+}
+
 #[async_trait]
 pub trait Connection: Sync + Send {
   /// Operations this connection supports (for UI gating / IPC).
@@ -50,6 +97,18 @@ pub trait Connection: Sync + Send {
     Err(unsupported("query"))
   }
 
+  /// [`query`](Self::query) under `limits`: timed out on the server, and cut
+  /// to the row and byte caps with `truncated` set rather than failing.
+  async fn query_limited(&self, sql: &str, limits: &QueryLimits) -> anyhow::Result<RawArrowData> {
+    limits::query(self, sql, limits).await
+  }
+
+  /// `sql` rewritten to carry a native per-statement timeout, when the dialect
+  /// has one; the statement is also cancelled client-side at the deadline.
+  fn statement_timeout_sql(&self, _sql: &str, _timeout: Duration) -> Option<String> {
+    None
+  }
+
   async fn query_count(&self, _sql: &str) -> anyhow::Result<usize> {
     Err(unsupported("query_count"))
   }
@@ -61,11 +120,13 @@ pub trait Connection: Sync + Send {
     "generic"
   }
 
+  /// Run `sql` limited to one page, with `total` counted per `count`.
   async fn paging_query(
     &self,
     sql: &str,
     limit: Option<usize>,
     offset: Option<usize>,
+    count: &CountOptions,
   ) -> anyhow::Result<RawArrowData> {
     let dialect = self.dialect();
     let stmt = first_stmt(dialect, sql);
@@ -82,8 +143,12 @@ pub trait Connection: Sync + Send {
       && let Some(count_sql) = ast::count_stmt(dialect, _stmt)
     {
       log::info!("count_sql: {count_sql}");
-      if let Ok(count) = self.query_count(&count_sql).await {
-        res.total = count;
+      let seen = offset.unwrap_or(0) + res.batch.num_rows();
+      let table = ast::scanned_table(_stmt);
+      let exact = self.query_count(&count_sql);
+      if let Some(rows) = row_count::count(self, table.as_deref(), count, seen, exact).await {
+        res.total = rows.total;
+        res.estimated = rows.estimated;
       };
     }
     res.sql = Some(sql.to_string());
@@ -94,44 +159,92 @@ pub trait Connection: Sync + Send {
     Err(unsupported("_sql_row_count"))
   }
 
+  /// One page of `table`. When the order is a unique key (or blank and the
+  /// table has a primary key) pages carry a `cursor`; passing it back as
+  /// `after` seeks straight past that row instead of skipping `offset` rows.
+  /// `total` is counted per `count`.
+  #[allow(clippy::too_many_arguments)]
   async fn query_table(
     &self,
     table: &str,
     limit: usize,
     offset: usize,
+    after: Option<&str>,
     where_: &str,
     order_by: &str,
     select_extras: &str,
+    count: &CountOptions,
   ) -> anyhow::Result<RawArrowData> {
-    let sql = self._table_query_sql(table, where_, order_by, select_extras);
-    let mut limit_sql = self._table_query_sql(table, where_, order_by, select_extras);
+    let keyset = match limit {
+      0 => None,
+      _ => keyset::for_table(self, table, order_by).await,
+    };
+    let order_by = match &keyset {
+      Some(k) if order_by.trim().is_empty() => keyset::order_by_sql(self, k),
+      _ => order_by.to_string(),
+    };
+    let sql = self._table_query_sql(table, where_, &order_by, select_extras);
+    let mut limit_sql = match (&keyset, after) {
+      (Some(k), Some(token)) => {
+        let after = keyset::parse_token(self.dialect(), k, token)?;
+        let seek = keyset::seek_predicate(self, k, &after);
+        let where_ = if where_.trim().is_empty() {
+          seek
+        } else {
+          format!("({where_}) and ({seek})")
+        };
+        self._table_query_sql(table, &where_, &order_by, select_extras)
+      }
+      (None, Some(_)) => anyhow::bail!("{table} cannot be paged by key in this order"),
+      (_, None) => sql.clone(),
+    };
 
-    if limit != 0 {
-      limit_sql = format!("{limit_sql} limit {limit}");
-    }
-    if offset != 0 {
-      limit_sql = format!("{limit_sql} offset {offset}");
-    }
+    let skip = if after.is_some() { 0 } else { offset };
+    limit_sql = self._limit_sql(&limit_sql, limit, skip);
     log::warn!(
       "query table {}, sql: {}, limit_sql: {}",
       &table,
       &sql,
       &limit_sql
     );
-    let res = self.query(&limit_sql, 0, 0).await;
+    let r = self.query(&limit_sql, 0, 0).await?;
 
-    let total = self
-      .table_row_count(table, where_)
-      .await
-      .unwrap_or_default();
+    let seen = offset + r.batch.num_rows();
+    let whole_table = where_.trim().is_empty().then_some(table);
+    let exact = self.table_row_count(table, where_);
+    let rows = row_count::count(self, whole_table, count, seen, exact).await;
 
-    res.map(|r| RawArrowData {
-      total,
+    let cursor = match &keyset {
+      Some(k) if r.batch.num_rows() == limit => keyset::token(self.dialect(), k, &r.batch)?,
+      _ => None,
+    };
+    Ok(RawArrowData {
+      total: rows.map_or(0, |c| c.total),
+      estimated: rows.is_some_and(|c| c.estimated),
       sql: Some(sql),
+      cursor,
       ..r
     })
   }
 
+  /// Where [`Connection::query_table`] keeps keyset plans between pages;
+  /// `None` looks the table's keys up again for every page.
+  fn keyset_plans(&self) -> Option<&PlanCache> {
+    None
+  }
+
+  /// `sql` cut to `limit` rows (0 = all) after skipping `offset` rows.
+  fn _limit_sql(&self, sql: &str, limit: usize, offset: usize) -> String {
+    let mut sql = sql.to_string();
+    if limit != 0 {
+      sql = format!("{sql} limit {limit}");
+    }
+    if offset != 0 {
+      sql = format!("{sql} offset {offset}");
+    }
+    sql
+  }
+
   async fn show_schema(&self, _schema: &str) -> anyhow::Result<RawArrowData> {
     Err(unsupported("show_schema"))
   }
@@ -154,6 +267,11 @@ pub trait Connection: Sync + Send {
     Err(unsupported("drop_table"))
   }
 
+  /// Row count of `table` from catalog statistics, without scanning it.
+  async fn estimate_row_count(&self, _table: &str) -> anyhow::Result<usize> {
+    Err(unsupported("estimate_row_count"))
+  }
+
   async fn table_row_count(&self, _table: &str, _where: &str) -> anyhow::Result<usize> {
     Err(unsupported("table_row_count"))
   }
@@ -249,15 +367,7 @@ pub trait Connection: Sync + Send {
       if let Some(t) = cancel {
         t.check()?;
       }
-      let page_sql = if let Some(ref s) = stmt {
-        ast::limit_stmt(dialect, s, Some(page), Some(offset)).unwrap_or_else(|| {
-          format!("select * from ({sql}) ____ limit {page} offset {offset}")
-        })
-      } else {
-        // Unparsed SQL: wrap as subquery (works for most engines).
-        format!("select * from ({sql}) ____ export_page limit {page} offset {offset}")
-      };
-
+      let page_sql = page_sql(dialect, stmt.as_ref(), sql, page, offset);
       let batch = self.query(&page_sql, 0, 0).await?.batch;
       let n = batch.num_rows();
       if n == 0 {
@@ -281,6 +391,94 @@ pub trait Connection: Sync + Send {
     Err(unsupported("execute"))
   }
 
+  /// [`query`](Self::query) with `:name` / `$1` placeholders bound to `params`
+  /// as a prepared statement; values never become part of the SQL text.
+  async fn query_params(&self, _sql: &str, _params: &Params) -> anyhow::Result<RawArrowData> {
+    Err(unsupported("query_params"))
+  }
+
+  /// [`execute`](Self::execute) with bound parameters, see [`query_params`](Self::query_params).
+  async fn execute_params(&self, _sql: &str, _params: &Params) -> anyhow::Result<usize> {
+    Err(unsupported("execute_params"))
+  }
+
+  /// Open an explicit transaction bound to a single physical connection.
+  async fn begin_transaction(&self) -> anyhow::Result<Box<dyn Transaction>> {
+    Err(unsupported("begin_transaction"))
+  }
+
+  /// Execute `sql` once and keep its result open for incremental [`ResultCursor::fetch`]es.
+  async fn open_cursor(&self, _sql: &str) -> anyhow::Result<Box<dyn ResultCursor>> {
+    Err(unsupported("open_cursor"))
+  }
+
+  /// Plan of `sql` as an operator tree. With `analyze` the query is executed
+  /// and actual rows and timings are filled in where the engine reports them.
+  async fn explain(&self, _sql: &str, _analyze: bool) -> anyhow::Result<QueryPlan> {
+    Err(unsupported("explain"))
+  }
+
+  /// Primary-key columns of a table in key order (empty when the table has none).
+  async fn primary_key(&self, _schema: Option<&str>, _table: &str) -> anyhow::Result<Vec<String>> {
+    Err(unsupported("primary_key"))
+  }
+
+  /// Columns, keys, indexes and foreign keys of one table (`schema` defaults to
+  /// the connection's current schema/database).
+  async fn table_detail(&self, _schema: Option<&str>, _table: &str) -> anyhow::Result<TableDetail> {
+    Err(unsupported("table_detail"))
+  }
+
+  /// CREATE statement(s) for a table, view or function. Tables include their
+  /// secondary indexes where the engine keeps them separate.
+  async fn object_ddl(
+    &self,
+    _schema: Option<&str>,
+    _name: &str,
+    _kind: ObjectKind,
+  ) -> anyhow::Result<String> {
+    Err(unsupported("object_ddl"))
+  }
+
+  /// Execute `stmts` in one transaction; rows affected per statement.
+  /// Rolls back and returns the first error if any statement fails.
+  async fn execute_atomic(&self, stmts: &[String]) -> anyhow::Result<Vec<usize>> {
+    let tx = self.begin_transaction().await?;
+    let mut affected = Vec::with_capacity(stmts.len());
+    for sql in stmts {
+      match tx.execute(sql).await {
+        Ok(n) => affected.push(n),
+        Err(err) => {
+          if let Err(e) = tx.rollback().await {
+            log::warn!("rollback failed: {e}");
+          }
+          return Err(err.context(format!("failed statement: {sql}")));
+        }
+      }
+    }
+    tx.commit().await?;
+    Ok(affected)
+  }
+
+  /// Run a multi-statement script in order, one outcome per statement.
+  async fn run_script(
+    &self,
+    sql: &str,
+    limit: Option<usize>,
+    mode: ScriptErrorMode,
+  ) -> anyhow::Result<Vec<StatementOutcome>> {
+    script::run_script(self, sql, limit, mode).await
+  }
+
+  /// A dedicated connection for [`run_script`](Self::run_script), so session
+  /// state (variables, `USE`, temp tables, `BEGIN … COMMIT`) carries from one
+  /// statement to the next. It is a [`Transaction`] handle with no transaction
+  /// open; `rollback` releases it and discards whatever the script left open.
+  /// `None` runs scripts on the connection itself.
+  async fn script_session(&self) -> anyhow::Result<Option<Box<dyn Transaction>>> {
+    Ok(None)
+  }
+
   /// check if the identifier is valid
   fn validator(&self, _id: &str) -> bool {
     true
@@ -0,0 +1,807 @@
+mod type_arrow;
+
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, anyhow};
+use arrow::datatypes::Schema;
+use arrow::record_batch::RecordBatch;
+use async_trait::async_trait;
+use tiberius::{AuthMethod, Client, Config, EncryptionLevel};
+use tokio::net::TcpStream;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio_util::compat::{Compat, TokioAsyncWriteCompatExt};
+
+use crate::cancel::CancelHook;
+use crate::dialect::detail::group_sorted;
+use crate::dialect::keyset::PlanCache;
+pub(crate) use crate::dialect::mssql::type_arrow::declared_arrow_type;
+use crate::dialect::{Connection, ast};
+use crate::ssh_tunnel::{DbSshConfig, SshTunnel};
+use crate::utils::{FunctionMeta, Metadata, RawArrowData, Table, TreeNode, build_tree};
+
+type TdsClient = Client<Compat<TcpStream>>;
+
+/// Sessions one connection keeps open at most; further statements wait for
+/// a free one.
+const MAX_SESSIONS: usize = 4;
+
+/// One TDS session. A session runs one request at a time.
+struct Session {
+  client: TdsClient,
+  /// `@@SPID`, the target of `KILL` on cancel.
+  spid: i16,
+}
+
+/// Idle sessions plus one permit per session that may be open.
+struct SessionPool {
+  idle: Mutex<Vec<Session>>,
+  permits: Arc<Semaphore>,
+}
+
+impl Default for SessionPool {
+  fn default() -> Self {
+    Self {
+      idle: Mutex::new(Vec::new()),
+      permits: Arc::new(Semaphore::new(MAX_SESSIONS)),
+    }
+  }
+}
+
+struct MssqlLive {
+  _tunnel: Option<SshTunnel>,
+  tunnel_port: Option<u16>,
+}
+
+/// SQL Server dialect connection (TDS with SQL authentication). Tunnel and
+/// session pool are opened on first use and shared across clones for
+/// SessionManager reuse.
+pub struct MssqlConnection {
+  pub host: String,
+  pub port: String,
+  pub username: String,
+  pub password: String,
+  pub database: Option<String>,
+  pub ssh: Option<DbSshConfig>,
+  /// Encrypt the session with TLS; off sends everything in clear text.
+  pub encrypt: bool,
+  /// Accept any server certificate, as `TrustServerCertificate=true` does.
+  /// Off verifies it against the system trust store.
+  pub trust_cert: bool,
+  live: Arc<Mutex<Option<MssqlLive>>>,
+  sessions: Arc<SessionPool>,
+  keyset_plans: Arc<PlanCache>,
+}
+
+impl Default for MssqlConnection {
+  fn default() -> Self {
+    Self::new(
+      String::new(),
+      String::new(),
+      String::new(),
+      String::new(),
+      None,
+      None,
+    )
+  }
+}
+
+impl MssqlConnection {
+  pub fn new(
+    host: String,
+    port: String,
+    username: String,
+    password: String,
+    database: Option<String>,
+    ssh: Option<DbSshConfig>,
+  ) -> Self {
+    Self {
+      host,
+      port,
+      username,
+      password,
+      database,
+      ssh,
+      encrypt: true,
+      trust_cert: false,
+      live: Arc::new(Mutex::new(None)),
+      sessions: Arc::default(),
+      keyset_plans: Arc::default(),
+    }
+  }
+}
+
+impl Clone for MssqlConnection {
+  fn clone(&self) -> Self {
+    Self {
+      host: self.host.clone(),
+      port: self.port.clone(),
+      username: self.username.clone(),
+      password: self.password.clone(),
+      database: self.database.clone(),
+      ssh: self.ssh.clone(),
+      encrypt: self.encrypt,
+      trust_cert: self.trust_cert,
+      live: Arc::clone(&self.live),
+      sessions: Arc::clone(&self.sessions),
+      keyset_plans: Arc::clone(&self.keyset_plans),
+    }
+  }
+}
+
+impl std::fmt::Debug for MssqlConnection {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("MssqlConnection")
+      .field("host", &self.host)
+      .field("port", &self.port)
+      .field("username", &self.username)
+      .field("database", &self.database)
+      .field("encrypt", &self.encrypt)
+      .field("trust_cert", &self.trust_cert)
+      .finish_non_exhaustive()
+  }
+}
+
+#[async_trait]
+impl Connection for MssqlConnection {
+  async fn get_db(&self) -> anyhow::Result<TreeNode> {
+    let tables = self.get_all_tables().await?;
+    Ok(TreeNode {
+      name: self.host.clone(),
+      path: self.host.clone(),
+      node_type: "root".to_string(),
+      schema: None,
+      children: Some(build_tree(tables)),
+      size: None,
+      comment: None,
+    })
+  }
+
+  async fn list_databases(&self) -> anyhow::Result<Vec<String>> {
+    self.databases().await
+  }
+
+  async fn query(&self, sql: &str, _limit: usize, _offset: usize) -> anyhow::Result<RawArrowData> {
+    let mut session = self.session().await?;
+    let _hook = self.kill_on_cancel(&session)?;
+    let raw = fetch_all(&mut session.client, sql).await;
+    session.finish(raw)
+  }
+
+  async fn query_count(&self, sql: &str) -> anyhow::Result<usize> {
+    let mut session = self.session().await?;
+    let _hook = self.kill_on_cancel(&session)?;
+    let total = fetch_count(&mut session.client, sql).await;
+    session.finish(total)
+  }
+
+  async fn query_all(&self, sql: &str) -> anyhow::Result<RawArrowData> {
+    self.query(sql, 0, 0).await
+  }
+
+  async fn execute(&self, sql: &str) -> anyhow::Result<usize> {
+    let mut session = self.session().await?;
+    let _hook = self.kill_on_cancel(&session)?;
+    let result = session.client.execute(sql, &[]).await;
+    let result = session.finish(result.map_err(anyhow::Error::from))?;
+    Ok(result.total() as usize)
+  }
+
+  fn dialect(&self) -> &'static str {
+    "mssql"
+  }
+
+  fn keyset_plans(&self) -> Option<&PlanCache> {
+    Some(&self.keyset_plans)
+  }
+
+  fn _limit_sql(&self, sql: &str, limit: usize, offset: usize) -> String {
+    let limit = (limit != 0).then_some(limit);
+    let offset = (offset != 0).then_some(offset);
+    if limit.is_none() && offset.is_none() {
+      return sql.to_string();
+    }
+    ast::first_stmt(self.dialect(), sql)
+      .and_then(|stmt| ast::limit_stmt(self.dialect(), &stmt, limit, offset))
+      .unwrap_or_else(|| ast::offset_fetch_wrapped(sql, limit, offset))
+  }
+
+  async fn show_schema(&self, schema: &str) -> anyhow::Result<RawArrowData> {
+    let sql = format!(
+      "select * from information_schema.tables where table_schema = {} order by table_type, table_name",
+      quote_literal(schema)
+    );
+    self.query(&sql, 0, 0).await
+  }
+
+  async fn show_column(&self, schema: Option<&str>, table: &str) -> anyhow::Result<RawArrowData> {
+    let unquote = |s: &str| {
+      s.trim_start_matches('[')
+        .trim_end_matches(']')
+        .replace("]]", "]")
+    };
+    let mut parts: Vec<String> = table.split('.').map(unquote).collect();
+    let tbl = parts.pop().unwrap_or_default();
+    let schema = schema.map(str::to_string).or_else(|| parts.pop());
+    let catalog = match parts.pop() {
+      Some(db) => format!("{}.", self.quote(&db)),
+      None => String::new(),
+    };
+    let mut sql = format!(
+      "select * from {catalog}information_schema.columns where table_name = {}",
+      quote_literal(&tbl)
+    );
+    if let Some(schema) = schema {
+      sql.push_str(&format!(" and table_schema = {}", quote_literal(&schema)));
+    }
+    sql.push_str(" order by ordinal_position");
+    log::info!("show columns: {}", &sql);
+    self.query(&sql, 0, 0).await
+  }
+
+  async fn all_columns(&self) -> anyhow::Result<Vec<Metadata>> {
+    let sql = "
+      select table_schema, table_name, column_name, data_type
+      from information_schema.columns
+      order by table_schema, table_name, ordinal_position
+    ";
+    let mut session = self.session().await?;
+    let rows = session
+      .client
+      .simple_query(sql)
+      .await?
+      .into_first_result()
+      .await?;
+    session.release();
+    Ok(
+      group_sorted(
+        rows
+          .iter()
+          .map(|row| ((text(row, 0), text(row, 1)), (text(row, 2), text(row, 3)))),
+      )
+      .into_iter()
+      .map(|((database, table), columns)| Metadata {
+        database,
+        table,
+        columns,
+      })
+      .collect(),
+    )
+  }
+
+  /// User-defined functions; T-SQL built-ins are not in `sys.objects`.
+  async fn functions(&self) -> anyhow::Result<Vec<FunctionMeta>> {
+    let sql = "
+      select o.name, o.type_desc
+      from sys.objects o
+      where o.type in ('FN', 'IF', 'TF', 'FS', 'FT', 'AF')
+      order by o.name
+    ";
+    let mut session = self.session().await?;
+    let rows = session
+      .client
+      .simple_query(sql)
+      .await?
+      .into_first_result()
+      .await?;
+    session.release();
+    let mut seen = std::collections::HashSet::new();
+    let mut out = Vec::new();
+    for row in &rows {
+      let name = text(row, 0);
+      if seen.insert(name.to_lowercase()) {
+        out.push(FunctionMeta {
+          name,
+          kind: Some(text(row, 1)),
+        });
+      }
+    }
+    Ok(out)
+  }
+
+  /// Rows of the heap or clustered index from `sys.partitions`.
+  async fn estimate_row_count(&self, table: &str) -> anyhow::Result<usize> {
+    let sql = "
+      select sum(p.rows)
+      from sys.partitions p
+      where p.object_id = object_id(@P1) and p.index_id in (0, 1)
+    ";
+    let table = self.quote_table_ref(table);
+    let mut session = self.session().await?;
+    let row = session
+      .client
+      .query(sql, &[&table.as_str()])
+      .await?
+      .into_row()
+      .await?;
+    session.release();
+    row
+      .and_then(|r| r.get::<i64, _>(0))
+      .map(|n| n as usize)
+      .ok_or_else(|| anyhow!("no statistics for {table}"))
+  }
+
+  async fn table_row_count(&self, table: &str, r#where: &str) -> anyhow::Result<usize> {
+    let sql = self._table_count_sql(table, r#where);
+    self.query_count(&sql).await
+  }
+
+  async fn export(
+    &self,
+    sql: &str,
+    file: &str,
+    format: &str,
+    options: &crate::utils::ExportOptions,
+    cancel: Option<&crate::cancel::CancelToken>,
+  ) -> anyhow::Result<()> {
+    // Batched OFFSET/FETCH export to bound peak memory for large result sets.
+    self
+      .export_batched(sql, file, format, options, cancel)
+      .await
+  }
+
+  fn start_quote(&self) -> &'static str {
+    "["
+  }
+
+  fn end_quote(&self) -> &'static str {
+    "]"
+  }
+
+  fn validator(&self, id: &str) -> bool {
+    // Regular identifiers: letter or `_` first, then letters, digits and `_`.
+    let mut chars = id.chars();
+    let Some(first) = chars.next() else {
+      return false;
+    };
+    (first.is_ascii_alphabetic() || first == '_')
+      && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+  }
+}
+
+impl MssqlConnection {
+  fn ensure_tunnel(&self) -> anyhow::Result<Option<u16>> {
+    let mut guard = self
+      .live
+      .lock()
+      .map_err(|_| anyhow!("mssql live lock poisoned"))?;
+    if let Some(live) = guard.as_ref() {
+      return Ok(live.tunnel_port);
+    }
+
+    let tunnel = if let Some(ssh_config) = self.ssh.as_ref().and_then(|s| s.to_tunnel_config()) {
+      Some(SshTunnel::open(&ssh_config, &self.host, self.port()?)?)
+    } else {
+      None
+    };
+    let tunnel_port = tunnel.as_ref().map(|t| t.local_port());
+    *guard = Some(MssqlLive {
+      _tunnel: tunnel,
+      tunnel_port,
+    });
+    Ok(tunnel_port)
+  }
+
+  fn port(&self) -> anyhow::Result<u16> {
+    if self.port.trim().is_empty() {
+      return Ok(1433);
+    }
+    self.port.trim().parse().context("invalid SQL Server port")
+  }
+
+  fn tds_config(&self, tunnel_port: Option<u16>) -> anyhow::Result<Config> {
+    let mut config = Config::new();
+    match tunnel_port {
+      Some(port) => {
+        config.host("127.0.0.1");
+        config.port(port);
+      }
+      None => {
+        config.host(&self.host);
+        config.port(self.port()?);
+      }
+    }
+    config.authentication(AuthMethod::sql_server(&self.username, &self.password));
+    if let Some(db) = self.database.as_deref().filter(|db| !db.is_empty()) {
+      config.database(db);
+    }
+    config.application_name("duckling");
+    if self.encrypt {
+      config.encryption(EncryptionLevel::Required);
+      if self.trust_cert {
+        config.trust_cert();
+      }
+    } else {
+      config.encryption(EncryptionLevel::NotSupported);
+    }
+    Ok(config)
+  }
+
+  /// Open a new TDS session (through the SSH tunnel when configured).
+  async fn connect(&self) -> anyhow::Result<Session> {
+    let tunnel_port = self.ensure_tunnel()?;
+    let config = self.tds_config(tunnel_port)?;
+    let addr = config.get_addr();
+    let tcp = TcpStream::connect(&addr)
+      .await
+      .with_context(|| format!("failed to connect to SQL Server at {addr}"))?;
+    tcp.set_nodelay(true)?;
+    let mut client = Client::connect(config, tcp.compat_write()).await?;
+    let spid = client
+      .simple_query("SELECT @@SPID")
+      .await?
+      .into_row()
+      .await?
+      .and_then(|row| row.get::<i16, _>(0))
+      .ok_or_else(|| anyhow!("SQL Server did not report a session id"))?;
+    Ok(Session { client, spid })
+  }
+
+  /// Check out a session: an idle one when there is one, else a new one
+  /// while fewer than `MAX_SESSIONS` are open.
+  async fn session(&self) -> anyhow::Result<PooledSession> {
+    let permit = Arc::clone(&self.sessions.permits).acquire_owned().await?;
+    let idle = self
+      .sessions
+      .idle
+      .lock()
+      .map_err(|_| anyhow!("mssql session pool lock poisoned"))?
+      .pop();
+    let session = match idle {
+      Some(session) => session,
+      None => self.connect().await?,
+    };
+    Ok(PooledSession {
+      session: Some(session),
+      pool: Arc::clone(&self.sessions),
+      kill: Arc::default(),
+      reusable: false,
+      _permit: permit,
+    })
+  }
+
+  /// `KILL <spid>` from a side session when the enclosing request is
+  /// cancelled. The killed session never returns to the pool and stays open
+  /// until the KILL is done, so its spid cannot be reused by someone else's
+  /// login in between.
+  fn kill_on_cancel(&self, session: &PooledSession) -> anyhow::Result<Option<CancelHook>> {
+    let Some(cancel) = crate::cancel::current() else {
+      return Ok(None);
+    };
+    let this = self.clone();
+    let state = Arc::clone(&session.kill);
+    let spid = session.spid;
+    let runtime = tokio::runtime::Handle::current();
+    let kill = move || {
+      match state.lock() {
+        Ok(mut s) if !s.released => s.killed = true,
+        _ => return,
+      }
+      runtime.spawn(async move {
+        let result = async {
+          let mut side = this.connect().await?;
+          side.client.execute(format!("KILL {spid}"), &[]).await?;
+          anyhow::Ok(())
+        };
+        if let Err(e) = result.await {
+          log::warn!("KILL {spid} failed: {e}");
+        }
+        drop(state);
+      });
+    };
+    Ok(crate::cancel::interrupt_on(Some(&cancel), kill)?)
+  }
+
+  pub async fn databases(&self) -> anyhow::Result<Vec<String>> {
+    // System databases (ids 1-4) only show up when connected to one.
+    let sql = "
+      select name from sys.databases
+      where (database_id > 4 or name = db_name())
+        and state_desc = 'ONLINE' and has_dbaccess(name) = 1
+      order by name
+    ";
+    let mut session = self.session().await?;
+    let rows = session
+      .client
+      .simple_query(sql)
+      .await?
+      .into_first_result()
+      .await?;
+    session.release();
+    Ok(rows.iter().map(|row| text(row, 0)).collect())
+  }
+
+  pub async fn get_tables(&self, db: &str) -> anyhow::Result<Vec<Table>> {
+    let catalog = self.quote(db);
+    let sql = format!(
+      "
+      select s.name, o.name, o.type_desc,
+        case when o.type = 'V' then 'view' else 'table' end
+      from {catalog}.sys.objects o
+      join {catalog}.sys.schemas s on s.schema_id = o.schema_id
+      where o.type in ('U', 'V') and o.is_ms_shipped = 0
+      order by s.name, o.name
+      "
+    );
+    let mut session = self.session().await?;
+    let rows = session
+      .client
+      .simple_query(sql)
+      .await?
+      .into_first_result()
+      .await?;
+    session.release();
+    Ok(
+      rows
+        .iter()
+        .map(|row| Table {
+          db_name: db.to_string(),
+          schema: Some(text(row, 0)),
+          table_name: text(row, 1),
+          table_type: text(row, 2),
+          r#type: text(row, 3),
+          size: None,
+        })
+        .collect(),
+    )
+  }
+
+  pub async fn get_all_tables(&self) -> anyhow::Result<Vec<Table>> {
+    let mut tables = vec![];
+    for db in self.databases().await? {
+      tables.extend(self.get_tables(&db).await?);
+    }
+    Ok(tables)
+  }
+}
+
+/// Shared by a checked-out session and its cancel hook; the lock orders a
+/// KILL against the session's return to the pool.
+#[derive(Default)]
+struct KillState {
+  /// `KILL` is on its way; the session must not be reused.
+  killed: bool,
+  /// The session was dropped or pooled; a late cancel must not KILL its spid.
+  released: bool,
+  /// A killed session, held open until the hook's task lets go of it.
+  parked: Option<Session>,
+}
+
+/// A session checked out of the pool. It goes back to the idle list only
+/// after `release` or `finish`, so one left mid-request by a broken stream or
+/// a dropped future is closed instead of handed to the next statement.
+struct PooledSession {
+  session: Option<Session>,
+  pool: Arc<SessionPool>,
+  kill: Arc<Mutex<KillState>>,
+  reusable: bool,
+  _permit: OwnedSemaphorePermit,
+}
+
+impl PooledSession {
+  /// Mark the request as complete so the session can be reused.
+  fn release(mut self) {
+    self.reusable = true;
+  }
+
+  /// Pass `result` through, keeping the session when the request finished
+  /// or failed with a server error; the client flushes what is left of the
+  /// response before its next request. I/O and protocol errors drop it.
+  fn finish<T>(mut self, result: anyhow::Result<T>) -> anyhow::Result<T> {
+    self.reusable = match &result {
+      Ok(_) => true,
+      Err(e) => matches!(
+        e.downcast_ref::<tiberius::error::Error>(),
+        Some(tiberius::error::Error::Server(_))
+      ),
+    };
+    result
+  }
+}
+
+impl std::ops::Deref for PooledSession {
+  type Target = Session;
+  fn deref(&self) -> &Session {
+    self.session.as_ref().expect("pooled session taken")
+  }
+}
+
+impl std::ops::DerefMut for PooledSession {
+  fn deref_mut(&mut self) -> &mut Session {
+    self.session.as_mut().expect("pooled session taken")
+  }
+}
+
+impl Drop for PooledSession {
+  fn drop(&mut self) {
+    let Some(session) = self.session.take() else {
+      return;
+    };
+    let Ok(mut state) = self.kill.lock() else {
+      return;
+    };
+    if state.killed {
+      state.parked = Some(session);
+      return;
+    }
+    state.released = true;
+    drop(state);
+    if self.reusable
+      && let Ok(mut idle) = self.pool.idle.lock()
+    {
+      idle.push(session);
+    }
+  }
+}
+
+/// First result set of `sql` as Arrow.
+async fn fetch_all(client: &mut TdsClient, sql: &str) -> anyhow::Result<RawArrowData> {
+  let mut stream = client.simple_query(sql).await?;
+  let columns = stream
+    .columns()
+    .await?
+    .map(<[_]>::to_vec)
+    .unwrap_or_default();
+  let rows = stream.into_first_result().await?;
+  if columns.is_empty() {
+    let mut raw = RawArrowData::from_batch(RecordBatch::new_empty(Arc::new(Schema::empty())));
+    raw.sql = Some(sql.to_string());
+    return Ok(raw);
+  }
+  let (titles, batch) = type_arrow::rows_to_arrow(&columns, &rows)?;
+  Ok(RawArrowData {
+    titles: Some(titles),
+    sql: Some(sql.to_string()),
+    ..RawArrowData::from_batch(batch)
+  })
+}
+
+async fn fetch_count(client: &mut TdsClient, sql: &str) -> anyhow::Result<usize> {
+  let row = client
+    .simple_query(sql)
+    .await?
+    .into_row()
+    .await?
+    .ok_or_else(|| anyhow!("count returned no rows"))?;
+  // count(*) is INT, count_big(*) BIGINT.
+  let total = match row.try_get::<i32, _>(0) {
+    Ok(n) => n.map(i64::from),
+    Err(_) => row.try_get::<i64, _>(0)?,
+  };
+  total
+    .map(|n| n as usize)
+    .ok_or_else(|| anyhow!("count returned null"))
+}
+
+/// Text cell of a catalog row (empty when NULL).
+fn text(row: &tiberius::Row, idx: usize) -> String {
+  row.get::<&str, _>(idx).unwrap_or_default().to_string()
+}
+
+fn quote_literal(s: &str) -> String {
+  format!("N'{}'", s.replace('\'', "''"))
+}
+
+#[test]
+fn quotes_with_brackets() {
+  let conn = MssqlConnection::default();
+  assert_eq!(conn.quote("orders"), "orders");
+  assert_eq!(conn.quote("order lines"), "[order lines]");
+  assert_eq!(conn.quote("my]table"), "[my]]table]");
+  assert_eq!(conn.quote("[dbo]"), "[dbo]");
+  assert_eq!(
+    conn.quote_table_ref("erp.dbo.Order Lines"),
+    "erp.dbo.[Order Lines]"
+  );
+  assert_eq!(quote_literal("O'Brien"), "N'O''Brien'");
+}
+
+#[test]
+fn pages_with_offset_fetch() {
+  let conn = MssqlConnection::default();
+  assert_eq!(conn._limit_sql("select * from t", 0, 0), "select * from t");
+  assert_eq!(
+    conn._limit_sql("select * from t order by id", 10, 20),
+    "SELECT * FROM t ORDER BY id OFFSET 20 ROWS FETCH NEXT 10 ROWS ONLY"
+  );
+  assert_eq!(
+    conn._limit_sql("select a from t", 10, 0),
+    "SELECT a FROM t ORDER BY (SELECT NULL) OFFSET 0 ROWS FETCH NEXT 10 ROWS ONLY"
+  );
+}
+
+#[test]
+fn tds_config_targets_tunnel_port() {
+  let conn = MssqlConnection::new(
+    "db.internal".into(),
+    "".into(),
+    "sa".into(),
+    "pw".into(),
+    Some("erp".into()),
+    None,
+  );
+  assert_eq!(
+    conn.tds_config(None).unwrap().get_addr(),
+    "db.internal:1433"
+  );
+  assert_eq!(
+    conn.tds_config(Some(40000)).unwrap().get_addr(),
+    "127.0.0.1:40000"
+  );
+}
+
+#[test]
+fn tds_config_verifies_certificates_unless_trusted() {
+  let mut conn = MssqlConnection::new(
+    "db.internal".into(),
+    "".into(),
+    "sa".into(),
+    "pw".into(),
+    None,
+    None,
+  );
+  let config = format!("{:?}", conn.tds_config(None).unwrap());
+  assert!(config.contains("trust: Default"), "{config}");
+  conn.trust_cert = true;
+  let config = format!("{:?}", conn.tds_config(None).unwrap());
+  assert!(config.contains("trust: TrustAll"), "{config}");
+}
+
+/// `docker run -e ACCEPT_EULA=Y -e MSSQL_SA_PASSWORD='Duckling!2024' -p 1433:1433
+/// mcr.microsoft.com/mssql/server:2022-latest`
+#[tokio::test]
+#[ignore = "requires a local SQL Server container"]
+async fn test_local_container() {
+  use crate::dialect::CountOptions;
+
+  let conn = MssqlConnection::new(
+    "127.0.0.1".into(),
+    "1433".into(),
+    "sa".into(),
+    "Duckling!2024".into(),
+    Some("master".into()),
+    None,
+  );
+  conn
+    .execute("if object_id('dbo.duckling_t') is not null drop table dbo.duckling_t")
+    .await
+    .unwrap();
+  conn
+    .execute(
+      "create table dbo.duckling_t (id int primary key, name nvarchar(20), price decimal(10, 2),
+        created datetime2, uid uniqueidentifier)",
+    )
+    .await
+    .unwrap();
+  let inserted = conn
+    .execute(
+      "insert into dbo.duckling_t values
+        (1, N'a', 1.50, '2024-01-15 10:30:00', newid()),
+        (2, N'b', null, null, null),
+        (3, N'c', 3.25, '2024-02-01', newid())",
+    )
+    .await
+    .unwrap();
+  assert_eq!(inserted, 3);
+
+  let page = conn
+    .paging_query(
+      "select * from dbo.duckling_t order by id",
+      Some(2),
+      Some(1),
+      &CountOptions::default(),
+    )
+    .await
+    .unwrap();
+  assert_eq!(page.batch.num_rows(), 2);
+  assert_eq!(page.total, 3);
+
+  let tree = conn.get_db().await.unwrap();
+  assert!(tree.children.is_some_and(|dbs| !dbs.is_empty()));
+  let cols = conn
+    .show_column(None, "master.dbo.duckling_t")
+    .await
+    .unwrap();
+  assert_eq!(cols.batch.num_rows(), 5);
+  conn.functions().await.unwrap();
+  conn.execute("drop table dbo.duckling_t").await.unwrap();
+}
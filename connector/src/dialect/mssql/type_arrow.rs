@@ -0,0 +1,329 @@
+//! SQL Server rows → Arrow: integers, floats and BIT natively, DECIMAL /
+//! NUMERIC as Decimal128, DATE / TIME / DATETIME* as Date32 / Time64 /
+//! Timestamp, binaries as Binary. UNIQUEIDENTIFIER, MONEY and everything else
+//! (strings, XML, sql_variant) become Utf8. A column whose cells do not all
+//! decode to its declared type falls back to Utf8 instead of losing values.
+
+use std::sync::Arc;
+
+use arrow::array::*;
+use arrow::datatypes::{DataType, Field, TimeUnit};
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Utc};
+use tiberius::{Column, ColumnData, ColumnType, FromSql, Row};
+
+use crate::utils::{
+  Title, finalize_preview_batch, record_batch_from_arrays, truncate_bytes_for_preview,
+  truncate_utf8_for_preview,
+};
+
+/// Widest precision of Decimal128 (and of SQL Server DECIMAL).
+const MAX_DECIMAL_PRECISION: u8 = 38;
+/// Days from 0001-01-01 (CE) to 1970-01-01.
+const EPOCH_DAYS_FROM_CE: i32 = 719_163;
+
+pub fn rows_to_arrow(
+  columns: &[Column],
+  rows: &[Row],
+) -> anyhow::Result<(Vec<Title>, arrow::record_batch::RecordBatch)> {
+  let mut cells: Vec<Vec<&ColumnData<'static>>> = columns
+    .iter()
+    .map(|_| Vec::with_capacity(rows.len()))
+    .collect();
+  for row in rows {
+    for (i, (_, data)) in row.cells().enumerate() {
+      if let Some(col) = cells.get_mut(i) {
+        col.push(data);
+      }
+    }
+  }
+
+  let mut titles = Vec::with_capacity(columns.len());
+  let mut fields = Vec::with_capacity(columns.len());
+  let mut arrays: Vec<ArrayRef> = Vec::with_capacity(columns.len());
+  for (col, cells) in columns.iter().zip(&cells) {
+    titles.push(Title {
+      name: col.name().to_string(),
+      r#type: type_name(col.column_type()).to_string(),
+    });
+    let array = column_to_array(col.column_type(), cells);
+    fields.push(Field::new(col.name(), array.data_type().clone(), true));
+    arrays.push(array);
+  }
+
+  let batch = record_batch_from_arrays(fields, arrays)?;
+  let batch = finalize_preview_batch(batch)?;
+  Ok((titles, batch))
+}
+
+fn column_to_array(ty: ColumnType, cells: &[&ColumnData<'static>]) -> ArrayRef {
+  typed_array(ty, cells).unwrap_or_else(|| Arc::new(string_array(cells)))
+}
+
+/// The column at its native Arrow type; `None` when some cell does not decode.
+fn typed_array(ty: ColumnType, cells: &[&ColumnData<'static>]) -> Option<ArrayRef> {
+  let array: ArrayRef = match ty {
+    ColumnType::Bit | ColumnType::Bitn => Arc::new(BooleanArray::from(decode::<bool>(cells)?)),
+    ColumnType::Int1 => Arc::new(UInt8Array::from(decode::<u8>(cells)?)),
+    ColumnType::Int2 => Arc::new(Int16Array::from(decode::<i16>(cells)?)),
+    ColumnType::Int4 => Arc::new(Int32Array::from(decode::<i32>(cells)?)),
+    ColumnType::Int8 | ColumnType::Intn => Arc::new(Int64Array::from(int_cells(cells)?)),
+    ColumnType::Float4 | ColumnType::Float8 | ColumnType::Floatn => {
+      let values = cells.iter().map(|c| match c {
+        ColumnData::F32(v) => Some(v.map(f64::from)),
+        ColumnData::F64(v) => Some(*v),
+        _ => None,
+      });
+      Arc::new(Float64Array::from(values.collect::<Option<Vec<_>>>()?))
+    }
+    ColumnType::Decimaln | ColumnType::Numericn => decimal_array(cells)?,
+    ColumnType::Daten => {
+      let days = decode::<NaiveDate>(cells)?
+        .into_iter()
+        .map(|d| d.map(|d| d.num_days_from_ce() - EPOCH_DAYS_FROM_CE));
+      Arc::new(Date32Array::from_iter(days))
+    }
+    ColumnType::Timen => {
+      let micros = decode::<NaiveTime>(cells)?.into_iter().map(|t| {
+        t.map(|t| t.num_seconds_from_midnight() as i64 * 1_000_000 + t.nanosecond() as i64 / 1_000)
+      });
+      Arc::new(Time64MicrosecondArray::from_iter(micros))
+    }
+    ColumnType::Datetime
+    | ColumnType::Datetime4
+    | ColumnType::Datetimen
+    | ColumnType::Datetime2 => {
+      let micros = decode::<NaiveDateTime>(cells)?
+        .into_iter()
+        .map(|t| t.map(|t| t.and_utc().timestamp_micros()));
+      Arc::new(TimestampMicrosecondArray::from_iter(micros))
+    }
+    ColumnType::DatetimeOffsetn => {
+      let micros = decode::<DateTime<Utc>>(cells)?
+        .into_iter()
+        .map(|t| t.map(|t| t.timestamp_micros()));
+      Arc::new(TimestampMicrosecondArray::from_iter(micros).with_timezone_utc())
+    }
+    ColumnType::BigVarBin | ColumnType::BigBinary | ColumnType::Image => {
+      let values = cells.iter().map(|c| match c {
+        ColumnData::Binary(b) => Some(b.as_deref().map(truncate_bytes_for_preview)),
+        _ => None,
+      });
+      Arc::new(BinaryArray::from_iter(values.collect::<Option<Vec<_>>>()?))
+    }
+    _ => return None,
+  };
+  Some(array)
+}
+
+/// Every cell as `T`, or `None` when any of them is a different TDS type.
+fn decode<'a, T: FromSql<'a>>(cells: &[&'a ColumnData<'static>]) -> Option<Vec<Option<T>>> {
+  cells.iter().map(|c| T::from_sql(c).ok()).collect()
+}
+
+/// `INTN` columns arrive at the width the server picked for the value.
+fn int_cells(cells: &[&ColumnData<'static>]) -> Option<Vec<Option<i64>>> {
+  cells
+    .iter()
+    .map(|c| match c {
+      ColumnData::U8(v) => Some(v.map(i64::from)),
+      ColumnData::I16(v) => Some(v.map(i64::from)),
+      ColumnData::I32(v) => Some(v.map(i64::from)),
+      ColumnData::I64(v) => Some(*v),
+      _ => None,
+    })
+    .collect()
+}
+
+/// Decimal128 at precision 38 and the column's scale (every value of a
+/// DECIMAL column carries the same one).
+fn decimal_array(cells: &[&ColumnData<'static>]) -> Option<ArrayRef> {
+  let mut scale = None;
+  let mut values = Vec::with_capacity(cells.len());
+  for cell in cells {
+    let ColumnData::Numeric(n) = cell else {
+      return None;
+    };
+    if let Some(n) = n
+      && *scale.get_or_insert(n.scale()) != n.scale()
+    {
+      return None;
+    }
+    values.push(n.map(|n| n.value()));
+  }
+  let scale = i8::try_from(scale.unwrap_or(0)).ok()?;
+  let array = Decimal128Array::from(values)
+    .with_precision_and_scale(MAX_DECIMAL_PRECISION, scale)
+    .ok()?;
+  Some(Arc::new(array))
+}
+
+fn string_array(cells: &[&ColumnData<'static>]) -> StringArray {
+  StringArray::from_iter(cells.iter().map(|c| cell_text(c)))
+}
+
+/// Display text of one cell, for columns without a native mapping.
+fn cell_text(cell: &ColumnData<'static>) -> Option<String> {
+  let text = match cell {
+    ColumnData::U8(v) => v.map(|v| v.to_string()),
+    ColumnData::I16(v) => v.map(|v| v.to_string()),
+    ColumnData::I32(v) => v.map(|v| v.to_string()),
+    ColumnData::I64(v) => v.map(|v| v.to_string()),
+    ColumnData::F32(v) => v.map(|v| v.to_string()),
+    ColumnData::F64(v) => v.map(|v| v.to_string()),
+    ColumnData::Bit(v) => v.map(|v| v.to_string()),
+    ColumnData::String(v) => v.as_deref().map(str::to_string),
+    ColumnData::Guid(v) => v.map(|v| v.to_string()),
+    ColumnData::Binary(v) => v
+      .as_deref()
+      .map(|b| String::from_utf8_lossy(b).into_owned()),
+    ColumnData::Numeric(v) => v.map(|v| v.to_string()),
+    ColumnData::Xml(v) => v.as_deref().map(|x| x.to_string()),
+    ColumnData::DateTime(_) | ColumnData::SmallDateTime(_) | ColumnData::DateTime2(_) => {
+      NaiveDateTime::from_sql(cell)
+        .ok()
+        .flatten()
+        .map(|t| t.to_string())
+    }
+    ColumnData::Date(_) => NaiveDate::from_sql(cell)
+      .ok()
+      .flatten()
+      .map(|d| d.to_string()),
+    ColumnData::Time(_) => NaiveTime::from_sql(cell)
+      .ok()
+      .flatten()
+      .map(|t| t.to_string()),
+    ColumnData::DateTimeOffset(_) => DateTime::<chrono::FixedOffset>::from_sql(cell)
+      .ok()
+      .flatten()
+      .map(|t| t.to_rfc3339()),
+  };
+  text.map(|s| truncate_utf8_for_preview(&s))
+}
+
+/// Arrow type of a declared column type for comparing schemas: `base` is the
+/// lower-cased name without its parameters, which are in `params`. MONEY is
+/// the DECIMAL it stores. `None` for types without an Arrow counterpart.
+pub fn declared_arrow_type(base: &str, params: Option<&str>) -> Option<DataType> {
+  Some(match base {
+    "bit" => DataType::Boolean,
+    "tinyint" => DataType::UInt8,
+    "smallint" => DataType::Int16,
+    "int" => DataType::Int32,
+    "bigint" => DataType::Int64,
+    "real" => DataType::Float32,
+    // float(n) is a real up to 24 bits of mantissa.
+    "float" if params.and_then(|p| p.parse::<u8>().ok()).is_some_and(|p| p <= 24) => {
+      DataType::Float32
+    }
+    "float" => DataType::Float64,
+    "money" => DataType::Decimal128(19, 4),
+    "smallmoney" => DataType::Decimal128(10, 4),
+    "date" => DataType::Date32,
+    "time" => DataType::Time64(TimeUnit::Microsecond),
+    "datetime" | "datetime2" | "smalldatetime" => DataType::Timestamp(TimeUnit::Microsecond, None),
+    "datetimeoffset" => DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+    "binary" | "varbinary" | "image" => DataType::Binary,
+    _ => return None,
+  })
+}
+
+/// T-SQL name of a wire type, for column titles.
+fn type_name(ty: ColumnType) -> &'static str {
+  match ty {
+    ColumnType::Null => "null",
+    ColumnType::Bit | ColumnType::Bitn => "bit",
+    ColumnType::Int1 => "tinyint",
+    ColumnType::Int2 => "smallint",
+    ColumnType::Int4 => "int",
+    ColumnType::Int8 | ColumnType::Intn => "bigint",
+    ColumnType::Float4 => "real",
+    ColumnType::Float8 | ColumnType::Floatn => "float",
+    ColumnType::Money => "money",
+    ColumnType::Money4 => "smallmoney",
+    ColumnType::Decimaln => "decimal",
+    ColumnType::Numericn => "numeric",
+    ColumnType::Datetime4 => "smalldatetime",
+    ColumnType::Datetime | ColumnType::Datetimen => "datetime",
+    ColumnType::Datetime2 => "datetime2",
+    ColumnType::DatetimeOffsetn => "datetimeoffset",
+    ColumnType::Daten => "date",
+    ColumnType::Timen => "time",
+    ColumnType::Guid => "uniqueidentifier",
+    ColumnType::BigVarBin => "varbinary",
+    ColumnType::BigBinary => "binary",
+    ColumnType::Image => "image",
+    ColumnType::BigVarChar => "varchar",
+    ColumnType::BigChar => "char",
+    ColumnType::NVarchar => "nvarchar",
+    ColumnType::NChar => "nchar",
+    ColumnType::Text => "text",
+    ColumnType::NText => "ntext",
+    ColumnType::Xml => "xml",
+    ColumnType::Udt => "udt",
+    ColumnType::SSVariant => "sql_variant",
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::borrow::Cow;
+
+  use arrow::datatypes::DataType;
+  use tiberius::numeric::Numeric;
+  use tiberius::time::{Date, DateTime2, Time};
+
+  use super::*;
+
+  #[test]
+  fn maps_native_types() {
+    let ints = [ColumnData::I32(Some(7)), ColumnData::I32(None)];
+    let array = column_to_array(ColumnType::Int4, &ints.iter().collect::<Vec<_>>());
+    assert_eq!(array.data_type(), &DataType::Int32);
+    assert!(array.is_null(1));
+
+    // INTN values narrower than the column still land in Int64.
+    let intn = [ColumnData::U8(Some(1)), ColumnData::I64(Some(1 << 40))];
+    let array = column_to_array(ColumnType::Intn, &intn.iter().collect::<Vec<_>>());
+    assert_eq!(array.data_type(), &DataType::Int64);
+
+    let dec = [
+      ColumnData::Numeric(Some(Numeric::new_with_scale(12345, 2))),
+      ColumnData::Numeric(None),
+    ];
+    let array = column_to_array(ColumnType::Decimaln, &dec.iter().collect::<Vec<_>>());
+    assert_eq!(array.data_type(), &DataType::Decimal128(38, 2));
+
+    // 2024-01-15 10:30:00: days since 0001-01-01 and 100ns ticks at scale 7.
+    let date = Date::new(738_899);
+    let time = Time::new(378_000_000_000, 7);
+    let dt = [ColumnData::DateTime2(Some(DateTime2::new(date, time)))];
+    let array = column_to_array(ColumnType::Datetime2, &dt.iter().collect::<Vec<_>>());
+    let ts = array
+      .as_any()
+      .downcast_ref::<TimestampMicrosecondArray>()
+      .unwrap();
+    assert_eq!(ts.value(0), 1_705_314_600_000_000);
+
+    let days = [ColumnData::Date(Some(date))];
+    let array = column_to_array(ColumnType::Daten, &days.iter().collect::<Vec<_>>());
+    let d = array.as_any().downcast_ref::<Date32Array>().unwrap();
+    assert_eq!(d.value(0), 19_737);
+  }
+
+  #[test]
+  fn guid_and_mismatched_cells_become_text() {
+    let id = tiberius::Uuid::from_u128(0x0123_4567_89ab_cdef_0123_4567_89ab_cdef);
+    let guid = [ColumnData::Guid(Some(id))];
+    let array = column_to_array(ColumnType::Guid, &guid.iter().collect::<Vec<_>>());
+    let text = array.as_any().downcast_ref::<StringArray>().unwrap();
+    assert_eq!(text.value(0), "01234567-89ab-cdef-0123-456789abcdef");
+
+    let mixed = [
+      ColumnData::I32(Some(1)),
+      ColumnData::String(Some(Cow::from("x"))),
+    ];
+    let array = column_to_array(ColumnType::Int4, &mixed.iter().collect::<Vec<_>>());
+    let text = array.as_any().downcast_ref::<StringArray>().unwrap();
+    assert_eq!((text.value(0), text.value(1)), ("1", "x"));
+  }
+}
@@ -1,19 +1,29 @@
-mod decode;
-#[allow(dead_code)]
 mod type_arrow;
 
+use crate::cancel::{CancelToken, StatementGuard};
 use crate::dialect::Connection;
-use crate::dialect::mysql::decode::columns_to_grid;
-use crate::preview::grid_to_raw_arrow_data;
+use crate::dialect::cursor::{ResultCursor, ThreadCursor};
+use crate::dialect::ddl::{self, ObjectKind, join_statements};
+use crate::dialect::detail::{
+  ColumnDetail, ForeignKey, IndexDetail, TableDetail, UniqueConstraint, group_sorted, non_empty,
+};
+use crate::dialect::explain::{self, QueryPlan};
+use crate::dialect::keyset::PlanCache;
+pub(crate) use crate::dialect::mysql::type_arrow::declared_arrow_type;
+use crate::dialect::mysql::type_arrow::{ColumnMeta, values_to_raw};
+use crate::dialect::params::{self, ParamValue};
+use crate::dialect::transaction::{self, Transaction};
 use crate::ssh_tunnel::{DbSshConfig, SshTunnel};
 use crate::utils::{FunctionMeta, Metadata, RawArrowData, Table, build_tree};
 use crate::utils::{Title, TreeNode};
 use anyhow::{Context, anyhow};
+use arrow::datatypes::DataType;
 use async_trait::async_trait;
 use mysql::prelude::*;
 use mysql::*;
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 pub type MySqlSshConfig = DbSshConfig;
 
@@ -32,6 +42,7 @@ pub struct MySqlConnection {
   pub database: Option<String>,
   pub ssh: Option<DbSshConfig>,
   live: Arc<Mutex<Option<MySqlLive>>>,
+  keyset_plans: Arc<PlanCache>,
 }
 
 impl Default for MySqlConnection {
@@ -64,6 +75,7 @@ impl MySqlConnection {
       database,
       ssh,
       live: Arc::new(Mutex::new(None)),
+      keyset_plans: Arc::default(),
     }
   }
 }
@@ -89,6 +101,7 @@ impl Clone for MySqlConnection {
       database: self.database.clone(),
       ssh: self.ssh.clone(),
       live: Arc::clone(&self.live),
+      keyset_plans: Arc::clone(&self.keyset_plans),
     }
   }
 }
@@ -123,14 +136,33 @@ impl Connection for MySqlConnection {
   async fn query(&self, sql: &str, _limit: usize, _offset: usize) -> anyhow::Result<RawArrowData> {
     let this = self.clone_config();
     let sql = sql.to_string();
-    crate::dialect::run_blocking(move || this._query(&sql)).await
+    let cancel = crate::cancel::current();
+    crate::dialect::run_blocking(move || this._query(&sql, cancel.as_ref())).await
+  }
+
+  async fn query_params(
+    &self,
+    sql: &str,
+    params: &crate::dialect::Params,
+  ) -> anyhow::Result<RawArrowData> {
+    let this = self.clone_config();
+    let bound = params::bind(self.dialect(), sql, params)?;
+    let cancel = crate::cancel::current();
+    crate::dialect::run_blocking(move || {
+      let mut conn = this.get_conn()?;
+      let _hook = this.kill_on_cancel(&conn, cancel.as_ref())?;
+      exec_conn(&mut conn, &bound.sql, &bound.values)
+    })
+    .await
   }
 
   async fn query_count(&self, sql: &str) -> anyhow::Result<usize> {
     let this = self.clone_config();
     let sql = sql.to_string();
+    let cancel = crate::cancel::current();
     crate::dialect::run_blocking(move || {
       let mut conn = this.get_conn()?;
+      let _hook = this.kill_on_cancel(&conn, cancel.as_ref())?;
       if let Some(total) = conn.query_first::<usize, _>(sql)? {
         Ok(total)
       } else {
@@ -144,10 +176,156 @@ impl Connection for MySqlConnection {
     self.query(sql, 0, 0).await
   }
 
+  async fn execute(&self, sql: &str) -> anyhow::Result<usize> {
+    let this = self.clone_config();
+    let sql = sql.to_string();
+    let cancel = crate::cancel::current();
+    crate::dialect::run_blocking(move || this._execute(&sql, cancel.as_ref())).await
+  }
+
+  async fn execute_params(
+    &self,
+    sql: &str,
+    params: &crate::dialect::Params,
+  ) -> anyhow::Result<usize> {
+    let this = self.clone_config();
+    let bound = params::bind(self.dialect(), sql, params)?;
+    let cancel = crate::cancel::current();
+    crate::dialect::run_blocking(move || {
+      let mut conn = this.get_conn()?;
+      let _hook = this.kill_on_cancel(&conn, cancel.as_ref())?;
+      conn.exec_drop(&bound.sql, bound_params(&bound.values))?;
+      Ok(conn.affected_rows() as usize)
+    })
+    .await
+  }
+
   fn dialect(&self) -> &'static str {
     "mysql"
   }
 
+  fn keyset_plans(&self) -> Option<&PlanCache> {
+    Some(&self.keyset_plans)
+  }
+
+  /// `MAX_EXECUTION_TIME` optimizer hint; MySQL honours it on `SELECT` only.
+  fn statement_timeout_sql(&self, sql: &str, timeout: Duration) -> Option<String> {
+    let sql = sql.trim_start();
+    let rest = sql
+      .get(..6)
+      .filter(|kw| kw.eq_ignore_ascii_case("select"))
+      .map(|_| &sql[6..])
+      .filter(|rest| rest.starts_with(char::is_whitespace))?;
+    Some(format!("SELECT /*+ MAX_EXECUTION_TIME({}) */{rest}", timeout.as_millis()))
+  }
+
+  async fn primary_key(&self, schema: Option<&str>, table: &str) -> anyhow::Result<Vec<String>> {
+    let this = self.clone_config();
+    let schema = schema.map(str::to_string);
+    let table = table.to_string();
+    crate::dialect::run_blocking(move || {
+      let mut conn = this.get_conn()?;
+      let sql = "
+      SELECT COLUMN_NAME
+      FROM information_schema.KEY_COLUMN_USAGE
+      WHERE CONSTRAINT_NAME = 'PRIMARY'
+        AND TABLE_SCHEMA = COALESCE(?, DATABASE())
+        AND TABLE_NAME = ?
+      ORDER BY ORDINAL_POSITION
+      ";
+      let cols: Vec<String> = conn.exec(sql, (schema, table))?;
+      Ok(cols)
+    })
+    .await
+  }
+
+  async fn table_detail(&self, schema: Option<&str>, table: &str) -> anyhow::Result<TableDetail> {
+    let this = self.clone_config();
+    let schema = schema.map(str::to_string);
+    let table = table.to_string();
+    crate::dialect::run_blocking(move || this._table_detail(schema, table)).await
+  }
+
+  async fn object_ddl(
+    &self,
+    schema: Option<&str>,
+    name: &str,
+    kind: ObjectKind,
+  ) -> anyhow::Result<String> {
+    let this = self.clone_config();
+    let schema = schema.map(str::to_string);
+    let name = name.to_string();
+    crate::dialect::run_blocking(move || this._object_ddl(schema, name, kind)).await
+  }
+
+  async fn begin_transaction(&self) -> anyhow::Result<Box<dyn Transaction>> {
+    let this = self.clone_config();
+    let conn = crate::dialect::run_blocking(move || {
+      let mut conn = this.get_conn()?;
+      conn.query_drop("START TRANSACTION")?;
+      Ok(conn)
+    })
+    .await?;
+    Ok(Box::new(MySqlTransaction {
+      _owner: self.clone(),
+      conn: Arc::new(Mutex::new(Some(conn))),
+    }))
+  }
+
+  /// One connection checked out of the pool for the whole script.
+  async fn script_session(&self) -> anyhow::Result<Option<Box<dyn Transaction>>> {
+    let this = self.clone_config();
+    let conn = crate::dialect::run_blocking(move || this.get_conn()).await?;
+    Ok(Some(Box::new(MySqlTransaction {
+      _owner: self.clone(),
+      conn: Arc::new(Mutex::new(Some(conn))),
+    })))
+  }
+
+  /// Streams the (first) result set on a worker thread; rows are read off the
+  /// socket only as pages are requested.
+  async fn open_cursor(&self, sql: &str) -> anyhow::Result<Box<dyn ResultCursor>> {
+    let this = self.clone_config();
+    let sql = sql.to_string();
+    let cursor = ThreadCursor::spawn("mysql", move |requests| {
+      let mut conn = this.get_conn()?;
+      let mut result = conn.query_iter(&sql)?;
+      let metas: Vec<ColumnMeta> = result
+        .columns()
+        .as_ref()
+        .iter()
+        .map(ColumnMeta::from_column)
+        .collect();
+      let mut set = result.iter();
+      let mut types: Option<Vec<DataType>> = None;
+      while let Some(req) = requests.next_fetch() {
+        let mut read = || -> anyhow::Result<RawArrowData> {
+          let mut tables: Vec<Vec<Value>> = (0..metas.len()).map(|_| vec![]).collect();
+          if let Some(rows) = set.as_mut() {
+            for row in rows.by_ref().take(req.rows) {
+              let row = row?;
+              for (i, values) in tables.iter_mut().enumerate() {
+                values.push(row.get::<Value, _>(i).unwrap_or(Value::NULL));
+              }
+            }
+          }
+          // The first page fixes the column types so every page shares a schema.
+          let raw = values_to_raw(&metas, types.as_deref(), tables, &sql)?;
+          if types.is_none() {
+            let schema = raw.batch.schema();
+            types = Some(schema.fields().iter().map(|f| f.data_type().clone()).collect());
+          }
+          Ok(raw)
+        };
+        let page = read();
+        req.reply(page);
+      }
+      Ok(())
+    })
+    .await?;
+    Ok(Box::new(cursor))
+  }
+
   async fn show_schema(&self, schema: &str) -> anyhow::Result<RawArrowData> {
     let sql = format!(
       "select * from information_schema.tables where TABLE_SCHEMA='{schema}' order by TABLE_TYPE, TABLE_NAME"
@@ -179,11 +357,60 @@ impl Connection for MySqlConnection {
     crate::dialect::run_blocking(move || this._functions()).await
   }
 
+  /// `EXPLAIN FORMAT=JSON`, or the `EXPLAIN ANALYZE` tree (MySQL 8.0.18+).
+  async fn explain(&self, sql: &str, analyze: bool) -> anyhow::Result<QueryPlan> {
+    let explain_sql = if analyze {
+      explain::check_analyze("mysql", sql)?;
+      format!("EXPLAIN ANALYZE {sql}")
+    } else {
+      format!("EXPLAIN FORMAT=JSON {sql}")
+    };
+    let this = self.clone_config();
+    let raw = crate::dialect::run_blocking(move || {
+      let mut conn = this.get_conn()?;
+      let raw: Option<String> = conn.query_first(explain_sql)?;
+      raw.ok_or_else(|| anyhow!("EXPLAIN returned no rows"))
+    })
+    .await?;
+    if analyze {
+      explain::parse_mysql_tree(&raw)
+    } else {
+      explain::parse_mysql_json(&raw)
+    }
+  }
+
+  async fn estimate_row_count(&self, table: &str) -> anyhow::Result<usize> {
+    let this = self.clone_config();
+    let unquote = |s: &str| s.trim_matches('`').to_string();
+    let (schema, table) = match table.rsplit_once('.') {
+      Some((schema, table)) => (Some(unquote(schema)), unquote(table)),
+      None => (None, unquote(table)),
+    };
+    crate::dialect::run_blocking(move || {
+      let mut conn = this.get_conn()?;
+      // InnoDB's TABLE_ROWS is a sampled estimate; NULL for views.
+      let sql = "
+      SELECT TABLE_ROWS
+      FROM information_schema.TABLES
+      WHERE TABLE_SCHEMA = COALESCE(?, DATABASE())
+        AND TABLE_NAME = ?
+      ";
+      let rows: Option<Option<u64>> = conn.exec_first(sql, (schema, table))?;
+      rows
+        .flatten()
+        .map(|n| n as usize)
+        .ok_or_else(|| anyhow!("no statistics for table"))
+    })
+    .await
+  }
+
   async fn table_row_count(&self, table: &str, r#where: &str) -> anyhow::Result<usize> {
     let this = self.clone_config();
     let table = table.to_string();
     let where_ = r#where.to_string();
-    crate::dialect::run_blocking(move || this._table_row_count(&table, &where_)).await
+    let cancel = crate::cancel::current();
+    crate::dialect::run_blocking(move || this._table_row_count(&table, &where_, cancel.as_ref()))
+      .await
   }
 
   async fn export(
@@ -245,6 +472,10 @@ impl MySqlConnection {
         .tcp_port(self.port.parse().unwrap_or(3306));
     }
 
+    // Report matched rather than changed rows, so a grid edit that writes an
+    // unchanged value still counts as hitting its row.
+    builder = builder.additional_capabilities(consts::CapabilityFlags::CLIENT_FOUND_ROWS);
+
     Ok(builder.into())
   }
 
@@ -354,6 +585,171 @@ impl MySqlConnection {
     Ok(metadata_list)
   }
 
+  fn _table_detail(&self, schema: Option<String>, table: String) -> anyhow::Result<TableDetail> {
+    let mut conn = self.get_conn()?;
+    let args = || (schema.clone(), table.clone());
+
+    let sql = "
+    SELECT TABLE_SCHEMA, TABLE_COMMENT
+    FROM information_schema.TABLES
+    WHERE TABLE_SCHEMA = COALESCE(?, DATABASE()) AND TABLE_NAME = ?
+    ";
+    let found: Option<(String, Option<String>)> = conn.exec_first(sql, args())?;
+    let (schema_name, comment) = found.ok_or_else(|| anyhow!("table not found: {table}"))?;
+
+    let sql = "
+    SELECT COLUMN_NAME, COLUMN_TYPE, IS_NULLABLE, COLUMN_DEFAULT, COLUMN_COMMENT
+    FROM information_schema.COLUMNS
+    WHERE TABLE_SCHEMA = COALESCE(?, DATABASE()) AND TABLE_NAME = ?
+    ORDER BY ORDINAL_POSITION
+    ";
+    let rows: Vec<(String, String, String, Option<String>, Option<String>)> =
+      conn.exec(sql, args())?;
+    let columns = rows
+      .into_iter()
+      .map(|(name, r#type, nullable, default, comment)| ColumnDetail {
+        name,
+        r#type,
+        nullable: nullable == "YES",
+        default,
+        comment: non_empty(comment),
+      })
+      .collect();
+
+    // Functional index parts (8.0.13+) have no COLUMN_NAME.
+    let sql = "
+    SELECT INDEX_NAME, COALESCE(COLUMN_NAME, '<expression>'), NON_UNIQUE, INDEX_TYPE
+    FROM information_schema.STATISTICS
+    WHERE TABLE_SCHEMA = COALESCE(?, DATABASE()) AND TABLE_NAME = ?
+    ORDER BY INDEX_NAME, SEQ_IN_INDEX
+    ";
+    let rows: Vec<(String, String, i64, String)> = conn.exec(sql, args())?;
+    let mut primary_key = vec![];
+    let mut indexes = vec![];
+    for ((name, non_unique, method), parts) in group_sorted(
+      rows
+        .into_iter()
+        .map(|(name, col, non_unique, method)| ((name, non_unique, method), col)),
+    ) {
+      if name == "PRIMARY" {
+        primary_key = parts;
+        continue;
+      }
+      indexes.push(IndexDetail {
+        name,
+        columns: parts,
+        unique: non_unique == 0,
+        method: Some(method),
+      });
+    }
+
+    let sql = "
+    SELECT tc.CONSTRAINT_NAME, k.COLUMN_NAME
+    FROM information_schema.TABLE_CONSTRAINTS tc
+    JOIN information_schema.KEY_COLUMN_USAGE k
+      ON k.CONSTRAINT_SCHEMA = tc.CONSTRAINT_SCHEMA
+      AND k.CONSTRAINT_NAME = tc.CONSTRAINT_NAME
+      AND k.TABLE_NAME = tc.TABLE_NAME
+    WHERE tc.CONSTRAINT_TYPE = 'UNIQUE'
+      AND tc.TABLE_SCHEMA = COALESCE(?, DATABASE()) AND tc.TABLE_NAME = ?
+    ORDER BY tc.CONSTRAINT_NAME, k.ORDINAL_POSITION
+    ";
+    let rows: Vec<(String, String)> = conn.exec(sql, args())?;
+    let unique_constraints = group_sorted(rows)
+      .into_iter()
+      .map(|(name, columns)| UniqueConstraint {
+        name: Some(name),
+        columns,
+      })
+      .collect();
+
+    let sql = "
+    SELECT k.CONSTRAINT_NAME, k.COLUMN_NAME, k.REFERENCED_TABLE_SCHEMA,
+      k.REFERENCED_TABLE_NAME, k.REFERENCED_COLUMN_NAME, r.UPDATE_RULE, r.DELETE_RULE
+    FROM information_schema.KEY_COLUMN_USAGE k
+    JOIN information_schema.REFERENTIAL_CONSTRAINTS r
+      ON r.CONSTRAINT_SCHEMA = k.CONSTRAINT_SCHEMA
+      AND r.CONSTRAINT_NAME = k.CONSTRAINT_NAME
+      AND r.TABLE_NAME = k.TABLE_NAME
+    WHERE k.TABLE_SCHEMA = COALESCE(?, DATABASE()) AND k.TABLE_NAME = ?
+      AND k.REFERENCED_TABLE_NAME IS NOT NULL
+    ORDER BY k.CONSTRAINT_NAME, k.ORDINAL_POSITION
+    ";
+    let rows: Vec<(String, String, String, String, String, String, String)> =
+      conn.exec(sql, args())?;
+    let foreign_keys = group_sorted(rows.into_iter().map(
+      |(name, col, ref_schema, ref_table, ref_col, on_update, on_delete)| {
+        ((name, ref_schema, ref_table, on_update, on_delete), (col, ref_col))
+      },
+    ))
+    .into_iter()
+    .map(|((name, ref_schema, ref_table, on_update, on_delete), pairs)| {
+      let (columns, ref_columns) = pairs.into_iter().unzip();
+      ForeignKey {
+        name: Some(name),
+        columns,
+        ref_schema: Some(ref_schema),
+        ref_table,
+        ref_columns,
+        on_update: Some(on_update),
+        on_delete: Some(on_delete),
+      }
+    })
+    .collect();
+
+    Ok(TableDetail {
+      schema: Some(schema_name),
+      table,
+      comment: non_empty(comment),
+      columns,
+      primary_key,
+      unique_constraints,
+      indexes,
+      foreign_keys,
+      ..Default::default()
+    })
+  }
+
+  fn _object_ddl(
+    &self,
+    schema: Option<String>,
+    name: String,
+    kind: ObjectKind,
+  ) -> anyhow::Result<String> {
+    let mut conn = self.get_conn()?;
+    let target = match &schema {
+      Some(s) => format!("{}.{}", self.quote(s), self.quote(&name)),
+      None => self.quote(&name),
+    };
+    // (object type, result column holding the statement)
+    let attempts: &[(&str, usize)] = match kind {
+      ObjectKind::Table => &[("TABLE", 1)],
+      ObjectKind::View => &[("VIEW", 1)],
+      // Stored routines share one namespace in the UI; try both kinds.
+      ObjectKind::Function => &[("FUNCTION", 2), ("PROCEDURE", 2)],
+    };
+    let mut last_err = None;
+    for (what, col) in attempts {
+      let sql = format!("SHOW CREATE {what} {target}");
+      match conn.query_first::<Row, _>(&sql) {
+        Ok(Some(row)) => {
+          // NULL when the user lacks privileges to see the routine body.
+          let ddl = row
+            .get::<Option<String>, _>(*col)
+            .flatten()
+            .ok_or_else(|| anyhow!("no permission to view definition of {name}"))?;
+          return Ok(join_statements([ddl]));
+        }
+        Ok(None) => {}
+        Err(e) => last_err = Some(e),
+      }
+    }
+    Err(match last_err {
+      Some(e) => e.into(),
+      None => ddl::not_found(kind, &name),
+    })
+  }
+
   /// MySQL catalogs only expose stored routines (built-in functions are not
   /// enumerable) — the frontend unions curated built-ins on top of these.
   fn _functions(&self) -> anyhow::Result<Vec<FunctionMeta>> {
@@ -378,30 +774,37 @@ impl MySqlConnection {
     Ok(out)
   }
 
-  fn _query(&self, sql: &str) -> anyhow::Result<RawArrowData> {
+  fn _query(&self, sql: &str, cancel: Option<&CancelToken>) -> anyhow::Result<RawArrowData> {
     let mut conn = self.get_conn()?;
+    let _hook = self.kill_on_cancel(&conn, cancel)?;
+    query_conn(&mut conn, sql)
+  }
 
-    let mut result = conn.query_iter(sql)?;
-    let metas: Vec<decode::ColumnMeta> = result
-      .columns()
-      .as_ref()
-      .iter()
-      .map(decode::ColumnMeta::from_column)
-      .collect();
-    let k = metas.len();
-
-    let mut tables: Vec<Vec<Value>> = (0..k).map(|_| vec![]).collect();
-    while let Some(result_set) = result.iter() {
-      for row in result_set.flatten() {
-        for (i, _col) in row.columns_ref().iter().enumerate() {
-          let val = row.get::<Value, _>(i).unwrap_or(Value::NULL);
-          tables[i].push(val);
-        }
-      }
-    }
+  fn _execute(&self, sql: &str, cancel: Option<&CancelToken>) -> anyhow::Result<usize> {
+    let mut conn = self.get_conn()?;
+    let _hook = self.kill_on_cancel(&conn, cancel)?;
+    execute_conn(&mut conn, sql)
+  }
 
-    let grid = columns_to_grid(&metas, tables, sql);
-    grid_to_raw_arrow_data(grid)
+  /// `KILL QUERY` for the statement running on `conn` when `cancel` fires,
+  /// sent from another pooled connection on a separate thread. Keep the guard
+  /// until the statement is done and drop it before `conn`.
+  fn kill_on_cancel(
+    &self,
+    conn: &PooledConn,
+    cancel: Option<&CancelToken>,
+  ) -> anyhow::Result<StatementGuard> {
+    let this = self.clone_config();
+    let id = conn.connection_id();
+    let kill = move || {
+      let killed = this
+        .get_conn()
+        .and_then(|mut side| Ok(side.query_drop(format!("KILL QUERY {id}"))?));
+      if let Err(e) = killed {
+        log::warn!("KILL QUERY {id} failed: {e}");
+      }
+    };
+    Ok(crate::cancel::interrupt_statement(cancel, kill)?)
   }
 
   #[allow(dead_code)]
@@ -419,8 +822,14 @@ impl MySqlConnection {
       .collect()
   }
 
-  fn _table_row_count(&self, table: &str, cond: &str) -> anyhow::Result<usize> {
+  fn _table_row_count(
+    &self,
+    table: &str,
+    cond: &str,
+    cancel: Option<&CancelToken>,
+  ) -> anyhow::Result<usize> {
     let mut conn = self.get_conn()?;
+    let _hook = self.kill_on_cancel(&conn, cancel)?;
     let mut sql = format!("select count(*) from {table}");
     if !cond.is_empty() {
       sql = format!("{sql} where {cond}");
@@ -431,5 +840,136 @@ impl MySqlConnection {
   }
 }
 
+fn query_conn(conn: &mut PooledConn, sql: &str) -> anyhow::Result<RawArrowData> {
+  let result = conn.query_iter(sql)?;
+  result_to_raw(result, sql)
+}
+
+/// Run `sql` as a prepared statement with `?` placeholders bound to `values`.
+fn exec_conn(
+  conn: &mut PooledConn,
+  sql: &str,
+  values: &[ParamValue],
+) -> anyhow::Result<RawArrowData> {
+  let result = conn.exec_iter(sql, bound_params(values))?;
+  result_to_raw(result, sql)
+}
+
+fn bound_params(values: &[ParamValue]) -> Params {
+  if values.is_empty() {
+    return Params::Empty;
+  }
+  let values = values
+    .iter()
+    .map(|v| match v {
+      ParamValue::Null => Value::NULL,
+      ParamValue::Bool(b) => Value::Int(*b as i64),
+      ParamValue::Int(i) => Value::Int(*i),
+      ParamValue::Float(f) => Value::Double(*f),
+      ParamValue::Text(s) => Value::Bytes(s.as_bytes().to_vec()),
+    })
+    .collect();
+  Params::Positional(values)
+}
+
+fn result_to_raw<P: Protocol>(
+  mut result: QueryResult<'_, '_, '_, P>,
+  sql: &str,
+) -> anyhow::Result<RawArrowData> {
+  let metas: Vec<ColumnMeta> = result
+    .columns()
+    .as_ref()
+    .iter()
+    .map(ColumnMeta::from_column)
+    .collect();
+  let k = metas.len();
+
+  let mut tables: Vec<Vec<Value>> = (0..k).map(|_| vec![]).collect();
+  while let Some(result_set) = result.iter() {
+    for row in result_set.flatten() {
+      for (i, _col) in row.columns_ref().iter().enumerate() {
+        let val = row.get::<Value, _>(i).unwrap_or(Value::NULL);
+        tables[i].push(val);
+      }
+    }
+  }
+
+  values_to_raw(&metas, None, tables, sql)
+}
+
+fn execute_conn(conn: &mut PooledConn, sql: &str) -> anyhow::Result<usize> {
+  conn.query_drop(sql)?;
+  Ok(conn.affected_rows() as usize)
+}
+
+/// Transaction on a connection checked out of the pool until commit/rollback.
+pub struct MySqlTransaction {
+  /// Keeps the pool and SSH tunnel alive while the transaction is open.
+  _owner: MySqlConnection,
+  conn: Arc<Mutex<Option<PooledConn>>>,
+}
+
+impl MySqlTransaction {
+  async fn with_conn<T, F>(&self, f: F) -> anyhow::Result<T>
+  where
+    T: Send + 'static,
+    F: FnOnce(&mut PooledConn) -> anyhow::Result<T> + Send + 'static,
+  {
+    let conn = Arc::clone(&self.conn);
+    crate::dialect::run_blocking(move || {
+      let mut guard = conn
+        .lock()
+        .map_err(|_| anyhow!("mysql transaction lock poisoned"))?;
+      f(guard.as_mut().ok_or_else(transaction::finished)?)
+    })
+    .await
+  }
+
+  async fn finish(&self, sql: &'static str) -> anyhow::Result<()> {
+    let conn = Arc::clone(&self.conn);
+    crate::dialect::run_blocking(move || {
+      let mut guard = conn
+        .lock()
+        .map_err(|_| anyhow!("mysql transaction lock poisoned"))?;
+      let mut conn = guard.take().ok_or_else(transaction::finished)?;
+      conn.query_drop(sql)?;
+      Ok(())
+    })
+    .await
+  }
+}
+
+#[async_trait]
+impl Transaction for MySqlTransaction {
+  fn dialect(&self) -> &'static str {
+    "mysql"
+  }
+
+  async fn query(&self, sql: &str) -> anyhow::Result<RawArrowData> {
+    let sql = sql.to_string();
+    self.with_conn(move |conn| query_conn(conn, &sql)).await
+  }
+
+  async fn execute(&self, sql: &str) -> anyhow::Result<usize> {
+    let sql = sql.to_string();
+    self.with_conn(move |conn| execute_conn(conn, &sql)).await
+  }
+
+  async fn commit(&self) -> anyhow::Result<()> {
+    self.finish("COMMIT").await
+  }
+
+  async fn rollback(&self) -> anyhow::Result<()> {
+    self.finish("ROLLBACK").await
+  }
+}
+
 #[tokio::test]
 async fn test_query() {}
+
+#[test]
+fn opts_report_matched_rows() {
+  let opts = MySqlConnection::default().get_opts(None).unwrap();
+  let caps = opts.get_additional_capabilities();
+  assert!(caps.contains(consts::CapabilityFlags::CLIENT_FOUND_ROWS));
+}
@@ -1,15 +1,78 @@
-use arrow::array::{ArrayRef, Float64Array, Int64Array, StringArray};
-use arrow::datatypes::{DataType, Field};
-use mysql::consts::ColumnType;
+//! MySQL values → Arrow with native column types: DATETIME / TIMESTAMP as
+//! Timestamp(us), DATE as Date32, TIME as Time64(us), DECIMAL as Decimal128
+//! and unsigned BIGINT as UInt64. JSON stays Utf8, tagged with the `arrow.json`
+//! extension name so the UI can pretty-print it. Other types fall back to Utf8,
+//! as does a column holding a value its native type cannot (zero dates, TIMEs
+//! outside a day).
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arrow::array::{
+  ArrayRef, Date32Array, Decimal128Array, Float64Array, Int64Array, StringArray,
+  Time64MicrosecondArray, TimestampMicrosecondArray, UInt64Array,
+};
+use anyhow::anyhow;
+use arrow::datatypes::{DataType, Field, TimeUnit};
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime};
 use mysql::consts::ColumnType::*;
+use mysql::consts::{ColumnFlags, ColumnType};
 use mysql::{Column, Value};
-use std::sync::Arc;
 
 use crate::utils::{
-  finalize_preview_batch, record_batch_from_arrays, truncate_bytes_for_preview,
-  truncate_utf8_for_preview,
+  RawArrowData, Title, finalize_preview_batch, record_batch_from_arrays,
+  truncate_bytes_for_preview, truncate_utf8_for_preview,
 };
 
+/// Widest precision of Decimal128.
+const MAX_DECIMAL_PRECISION: u32 = 38;
+/// Days from 0001-01-01 (CE) to 1970-01-01.
+const EPOCH_DAYS_FROM_CE: i32 = 719_163;
+const MICROS_PER_DAY: i64 = 86_400_000_000;
+/// Field metadata key naming an Arrow extension type.
+const EXTENSION_NAME_KEY: &str = "ARROW:extension:name";
+/// Canonical extension name of JSON text columns.
+const JSON_EXTENSION: &str = "arrow.json";
+
+/// Column meta owned independently of the live result set (avoids borrow issues).
+#[derive(Debug, Clone)]
+pub struct ColumnMeta {
+  pub name: String,
+  pub column_type: ColumnType,
+  pub unsigned: bool,
+  /// Display width; for DECIMAL it counts the sign and the decimal point.
+  pub length: u32,
+  pub decimals: u8,
+}
+
+impl ColumnMeta {
+  pub fn from_column(c: &Column) -> Self {
+    Self {
+      name: c.name_str().to_string(),
+      column_type: c.column_type(),
+      unsigned: c.flags().contains(ColumnFlags::UNSIGNED_FLAG),
+      length: c.column_length(),
+      decimals: c.decimals(),
+    }
+  }
+
+  /// Declared DECIMAL precision, recovered from the display width.
+  fn decimal_precision(&self) -> u32 {
+    let point = u32::from(self.decimals > 0);
+    let sign = u32::from(!self.unsigned);
+    self.length.saturating_sub(point + sign)
+  }
+}
+
+/// Title type of a column: `LONGLONG`, `VAR_STRING`, ...
+pub fn native_type_name(col_type: ColumnType) -> String {
+  let type_ = format!("{col_type:?}");
+  type_
+    .strip_prefix("MYSQL_TYPE_")
+    .unwrap_or(type_.as_str())
+    .to_string()
+}
+
 /// Safe cell decode: never panics; unreadable values become None (L1).
 fn convert_to_str(unknown_val: &Value) -> Option<String> {
   match unknown_val {
@@ -26,9 +89,9 @@ fn convert_to_str(unknown_val: &Value) -> Option<String> {
     Value::UInt(u) => Some(u.to_string()),
     Value::Float(f) => Some(f.to_string()),
     Value::Double(f) => Some(f.to_string()),
-    Value::Date(y, m, d, h, mi, s, us) => {
-      Some(format!("{y:04}-{m:02}-{d:02} {h:02}:{mi:02}:{s:02}.{us:06}"))
-    }
+    Value::Date(y, m, d, h, mi, s, us) => Some(format!(
+      "{y:04}-{m:02}-{d:02} {h:02}:{mi:02}:{s:02}.{us:06}"
+    )),
     Value::Time(neg, d, h, mi, s, us) => {
       let sign = if *neg { "-" } else { "" };
       Some(format!("{sign}{d} {h:02}:{mi:02}:{s:02}.{us:06}"))
@@ -36,10 +99,6 @@ fn convert_to_str(unknown_val: &Value) -> Option<String> {
   }
 }
 
-fn convert_to_str_arr(values: &[Value]) -> Vec<Option<String>> {
-  values.iter().map(convert_to_str).collect()
-}
-
 fn convert_to_i64(unknown_val: &Value) -> Option<i64> {
   match unknown_val {
     Value::NULL => None,
@@ -52,8 +111,14 @@ fn convert_to_i64(unknown_val: &Value) -> Option<i64> {
   }
 }
 
-fn convert_to_i64_arr(values: &[Value]) -> Vec<Option<i64>> {
-  values.iter().map(convert_to_i64).collect()
+fn convert_to_u64(unknown_val: &Value) -> Option<u64> {
+  match unknown_val {
+    Value::NULL => None,
+    Value::UInt(u) => Some(*u),
+    Value::Int(i) => u64::try_from(*i).ok(),
+    Value::Bytes(bytes) => std::str::from_utf8(bytes).ok()?.parse().ok(),
+    _ => None,
+  }
 }
 
 fn convert_to_f64(unknown_val: &Value) -> Option<f64> {
@@ -68,92 +133,257 @@ fn convert_to_f64(unknown_val: &Value) -> Option<f64> {
   }
 }
 
-fn convert_to_f64_arr(values: &[Value]) -> Vec<Option<f64>> {
-  values.iter().map(convert_to_f64).collect()
-}
-
-/// Map MySQL wire type → Arrow field type.
-/// Must stay in sync with [`convert_arrow`]: schema Binary + array Utf8 causes
-/// `column types must match schema types` when building RecordBatch.
-fn mysql_to_arrow_type(col_type: ColumnType) -> DataType {
-  match col_type {
-    MYSQL_TYPE_TINY | MYSQL_TYPE_INT24 | MYSQL_TYPE_SHORT | MYSQL_TYPE_LONG
-    | MYSQL_TYPE_LONGLONG => DataType::Int64,
-    MYSQL_TYPE_DECIMAL
-    | MYSQL_TYPE_NEWDECIMAL
-    | MYSQL_TYPE_FLOAT
-    | MYSQL_TYPE_YEAR
-    | MYSQL_TYPE_DOUBLE => DataType::Float64,
-    // Text/temporal/json/enum/blob variants → Utf8 (convert_arrow builds StringArray).
-    MYSQL_TYPE_STRING
-    | MYSQL_TYPE_VAR_STRING
-    | MYSQL_TYPE_VARCHAR
-    | MYSQL_TYPE_BLOB
-    | MYSQL_TYPE_TINY_BLOB
-    | MYSQL_TYPE_MEDIUM_BLOB
-    | MYSQL_TYPE_LONG_BLOB
-    | MYSQL_TYPE_JSON
-    | MYSQL_TYPE_ENUM
-    | MYSQL_TYPE_SET
-    | MYSQL_TYPE_BIT
-    | MYSQL_TYPE_GEOMETRY
-    | MYSQL_TYPE_VECTOR
-    | MYSQL_TYPE_DATE
-    | MYSQL_TYPE_NEWDATE
-    | MYSQL_TYPE_TIME
-    | MYSQL_TYPE_TIME2
-    | MYSQL_TYPE_DATETIME
-    | MYSQL_TYPE_DATETIME2
-    | MYSQL_TYPE_TIMESTAMP
-    | MYSQL_TYPE_TIMESTAMP2
-    | MYSQL_TYPE_NULL
-    | MYSQL_TYPE_TYPED_ARRAY
-    | MYSQL_TYPE_UNKNOWN => DataType::Utf8,
-  }
-}
-
-pub fn get_fields(columns: &[Column]) -> (Vec<Field>, Vec<ColumnType>) {
-  let mut fields = vec![];
-  let mut types = vec![];
-  for col in columns {
-    let col_type = col.column_type();
-    types.push(col_type);
-    fields.push(Field::new(
-      col.name_str(),
-      mysql_to_arrow_type(col_type),
-      true,
-    ));
-  }
-  (fields, types)
-}
-
-pub fn convert_arrow(types: Vec<ColumnType>, tables: Vec<Vec<Value>>) -> Vec<ArrayRef> {
-  let mut arrs = vec![];
-  for (type_, col) in types.iter().zip(tables) {
-    let arr: ArrayRef = match mysql_to_arrow_type(*type_) {
-      DataType::Int64 => Arc::new(Int64Array::from(convert_to_i64_arr(&col))),
-      DataType::Float64 => Arc::new(Float64Array::from(convert_to_f64_arr(&col))),
-      _ => Arc::new(StringArray::from(convert_to_str_arr(&col))),
-    };
-    arrs.push(arr);
+/// DATE / DATETIME / TIMESTAMP from the binary (`Value::Date`) or text
+/// protocol. Zero dates (`0000-00-00`) have no calendar value and become None.
+fn convert_to_datetime(unknown_val: &Value) -> Option<NaiveDateTime> {
+  match unknown_val {
+    Value::Date(y, m, d, h, mi, s, us) => NaiveDate::from_ymd_opt(
+      i32::from(*y),
+      u32::from(*m),
+      u32::from(*d),
+    )?
+    .and_hms_micro_opt(u32::from(*h), u32::from(*mi), u32::from(*s), *us),
+    Value::Bytes(bytes) => {
+      let s = std::str::from_utf8(bytes).ok()?;
+      NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f")
+        .or_else(|_| NaiveDate::parse_from_str(s, "%Y-%m-%d").map(|d| d.and_time(NaiveTime::MIN)))
+        .ok()
+    }
+    _ => None,
+  }
+}
+
+/// TIME as signed microseconds; MySQL allows negative values and values past
+/// 24 hours (`-838:59:59` to `838:59:59`).
+fn convert_to_time_micros(unknown_val: &Value) -> Option<i64> {
+  match unknown_val {
+    Value::Time(neg, d, h, mi, s, us) => {
+      let secs =
+        i64::from(*d) * 86_400 + i64::from(*h) * 3_600 + i64::from(*mi) * 60 + i64::from(*s);
+      let micros = secs * 1_000_000 + i64::from(*us);
+      Some(if *neg { -micros } else { micros })
+    }
+    Value::Bytes(bytes) => parse_time(std::str::from_utf8(bytes).ok()?),
+    _ => None,
+  }
+}
+
+/// `[-]HHH:MM:SS[.ffffff]` → signed microseconds.
+fn parse_time(s: &str) -> Option<i64> {
+  let (neg, s) = match s.strip_prefix('-') {
+    Some(rest) => (true, rest),
+    None => (false, s),
+  };
+  let (hms, frac) = s.split_once('.').unwrap_or((s, ""));
+  let mut parts = hms.split(':').map(|p| p.parse::<i64>().ok());
+  let (h, m, sec) = (parts.next()??, parts.next()??, parts.next()??);
+  if frac.len() > 6 || !frac.bytes().all(|b| b.is_ascii_digit()) {
+    return None;
   }
-  arrs
+  let frac: i64 = format!("{frac:0<6}").parse().ok()?;
+  let micros = (h * 3_600 + m * 60 + sec) * 1_000_000 + frac;
+  Some(if neg { -micros } else { micros })
 }
 
-/// Build a finalized preview batch (shared schema guards + cell truncation).
-pub fn build_preview_batch(
-  fields: Vec<Field>,
-  types: Vec<ColumnType>,
+/// DECIMAL text → unscaled integer at `scale`; None when it has more fraction
+/// digits than `scale` or does not fit an i128.
+fn parse_decimal(s: &str, scale: u8) -> Option<i128> {
+  let (neg, digits) = match s.strip_prefix('-') {
+    Some(rest) => (true, rest),
+    None => (false, s),
+  };
+  let (int, frac) = digits.split_once('.').unwrap_or((digits, ""));
+  let scale = usize::from(scale);
+  let valid = !(int.is_empty() && frac.is_empty())
+    && frac.len() <= scale
+    && int.bytes().chain(frac.bytes()).all(|b| b.is_ascii_digit());
+  if !valid {
+    return None;
+  }
+  let n: i128 = format!("{int}{frac:0<scale$}").parse().ok()?;
+  Some(if neg { -n } else { n })
+}
+
+fn convert_to_decimal(unknown_val: &Value, scale: u8) -> Option<i128> {
+  let factor = 10i128.checked_pow(u32::from(scale))?;
+  match unknown_val {
+    Value::Bytes(bytes) => parse_decimal(std::str::from_utf8(bytes).ok()?, scale),
+    Value::Int(i) => i128::from(*i).checked_mul(factor),
+    Value::UInt(u) => i128::from(*u).checked_mul(factor),
+    _ => None,
+  }
+}
+
+fn string_array(values: &[Value]) -> ArrayRef {
+  Arc::new(StringArray::from(
+    values.iter().map(convert_to_str).collect::<Vec<_>>(),
+  ))
+}
+
+/// Every value through `convert`, or `None` when a non-NULL one fails.
+fn convert_all<T>(
+  values: &[Value],
+  convert: impl Fn(&Value) -> Option<T>,
+) -> Option<Vec<Option<T>>> {
+  values
+    .iter()
+    .map(|v| match v {
+      Value::NULL => Some(None),
+      v => convert(v).map(Some),
+    })
+    .collect()
+}
+
+/// Arrow type of a column's declared type. DECIMAL keeps the declared scale
+/// with the precision capped at 38.
+fn native_type(meta: &ColumnMeta) -> DataType {
+  match meta.column_type {
+    MYSQL_TYPE_LONGLONG if meta.unsigned => DataType::UInt64,
+    MYSQL_TYPE_TINY | MYSQL_TYPE_SHORT | MYSQL_TYPE_INT24 | MYSQL_TYPE_LONG
+    | MYSQL_TYPE_LONGLONG | MYSQL_TYPE_YEAR => DataType::Int64,
+    MYSQL_TYPE_FLOAT | MYSQL_TYPE_DOUBLE => DataType::Float64,
+    MYSQL_TYPE_DECIMAL | MYSQL_TYPE_NEWDECIMAL => {
+      let precision = meta.decimal_precision().min(MAX_DECIMAL_PRECISION);
+      if precision == 0 || u32::from(meta.decimals) > precision {
+        return DataType::Utf8;
+      }
+      DataType::Decimal128(precision as u8, meta.decimals as i8)
+    }
+    MYSQL_TYPE_DATE | MYSQL_TYPE_NEWDATE => DataType::Date32,
+    MYSQL_TYPE_DATETIME | MYSQL_TYPE_DATETIME2 | MYSQL_TYPE_TIMESTAMP | MYSQL_TYPE_TIMESTAMP2 => {
+      DataType::Timestamp(TimeUnit::Microsecond, None)
+    }
+    MYSQL_TYPE_TIME | MYSQL_TYPE_TIME2 => DataType::Time64(TimeUnit::Microsecond),
+    _ => DataType::Utf8,
+  }
+}
+
+/// Arrow type of a declared column type for comparing schemas: `base` is the
+/// lower-cased name without its parameters (`int unsigned`, `tinyint`), which
+/// are in `params` (`1`). Unlike [`native_type`], integers keep their width.
+/// `None` for types without an Arrow counterpart.
+pub fn declared_arrow_type(base: &str, params: Option<&str>) -> Option<DataType> {
+  let unsigned = base.contains("unsigned");
+  let int = |signed: DataType, unsigned_type: DataType| {
+    if unsigned { unsigned_type } else { signed }
+  };
+  let name = base.replace("unsigned", "").replace("zerofill", "");
+  Some(match name.trim() {
+    "tinyint" if params == Some("1") => DataType::Boolean,
+    "bool" | "boolean" => DataType::Boolean,
+    "tinyint" => int(DataType::Int8, DataType::UInt8),
+    "smallint" => int(DataType::Int16, DataType::UInt16),
+    "mediumint" | "int" | "integer" => int(DataType::Int32, DataType::UInt32),
+    "bigint" => int(DataType::Int64, DataType::UInt64),
+    // FLOAT(p) with p > 24 is a DOUBLE.
+    "float" if params.and_then(|p| p.parse::<u8>().ok()).is_some_and(|p| p > 24) => {
+      DataType::Float64
+    }
+    "float" => DataType::Float32,
+    "double" | "double precision" | "real" => DataType::Float64,
+    "date" => DataType::Date32,
+    "datetime" | "timestamp" => DataType::Timestamp(TimeUnit::Microsecond, None),
+    "time" => DataType::Time64(TimeUnit::Microsecond),
+    "binary" | "varbinary" | "tinyblob" | "blob" | "mediumblob" | "longblob" => DataType::Binary,
+    _ => return None,
+  })
+}
+
+/// `values` as an array of `data_type`; `None` when one has no form in it: a
+/// zero date, a TIME outside a day (negative or 24h+), a DECIMAL without an
+/// exact Decimal128 value.
+fn typed_array(data_type: &DataType, values: &[Value]) -> Option<ArrayRef> {
+  Some(match data_type {
+    DataType::UInt64 => Arc::new(UInt64Array::from(convert_all(values, convert_to_u64)?)),
+    DataType::Int64 => Arc::new(Int64Array::from(convert_all(values, convert_to_i64)?)),
+    DataType::Float64 => Arc::new(Float64Array::from(convert_all(values, convert_to_f64)?)),
+    DataType::Decimal128(precision, scale) => {
+      let limit = 10i128.pow(u32::from(*precision));
+      let scaled = convert_all(values, |v| {
+        convert_to_decimal(v, *scale as u8).filter(|n| n.abs() < limit)
+      })?;
+      let array = Decimal128Array::from(scaled).with_precision_and_scale(*precision, *scale);
+      Arc::new(array.ok()?)
+    }
+    DataType::Date32 => Arc::new(Date32Array::from(convert_all(values, |v| {
+      Some(convert_to_datetime(v)?.date().num_days_from_ce() - EPOCH_DAYS_FROM_CE)
+    })?)),
+    DataType::Timestamp(..) => Arc::new(TimestampMicrosecondArray::from(convert_all(
+      values,
+      |v| Some(convert_to_datetime(v)?.and_utc().timestamp_micros()),
+    )?)),
+    DataType::Time64(_) => Arc::new(Time64MicrosecondArray::from(convert_all(values, |v| {
+      convert_to_time_micros(v).filter(|m| (0..MICROS_PER_DAY).contains(m))
+    })?)),
+    _ => string_array(values),
+  })
+}
+
+/// The column at its native type, or Utf8 when a value does not fit it.
+fn column_to_array(meta: &ColumnMeta, values: &[Value]) -> ArrayRef {
+  typed_array(&native_type(meta), values).unwrap_or_else(|| string_array(values))
+}
+
+/// Build a finalized preview batch from column-oriented values. Without
+/// `types` each column takes its native type or falls back to Utf8; with them
+/// (a cursor's later pages) every column keeps its type or the page fails.
+pub fn values_to_raw(
+  metas: &[ColumnMeta],
+  types: Option<&[DataType]>,
   tables: Vec<Vec<Value>>,
-) -> anyhow::Result<arrow::record_batch::RecordBatch> {
-  let arrays = convert_arrow(types, tables);
-  let batch = record_batch_from_arrays(fields, arrays)?;
-  finalize_preview_batch(batch)
+  sql: &str,
+) -> anyhow::Result<RawArrowData> {
+  let mut titles = Vec::with_capacity(metas.len());
+  let mut fields = Vec::with_capacity(metas.len());
+  let mut arrays = Vec::with_capacity(metas.len());
+  for (i, (meta, values)) in metas.iter().zip(tables).enumerate() {
+    titles.push(Title {
+      name: meta.name.clone(),
+      r#type: native_type_name(meta.column_type),
+    });
+    let array = match types.and_then(|t| t.get(i)) {
+      None => column_to_array(meta, &values),
+      Some(ty) => typed_array(ty, &values).ok_or_else(|| {
+        let name = &meta.name;
+        anyhow!("column {name} has a value that does not fit its type {ty} from earlier rows")
+      })?,
+    };
+    let mut field = Field::new(&meta.name, array.data_type().clone(), true);
+    if meta.column_type == MYSQL_TYPE_JSON {
+      field = field.with_metadata(HashMap::from([(
+        EXTENSION_NAME_KEY.to_string(),
+        JSON_EXTENSION.to_string(),
+      )]));
+    }
+    fields.push(field);
+    arrays.push(array);
+  }
+  let batch = finalize_preview_batch(record_batch_from_arrays(fields, arrays)?)?;
+  Ok(RawArrowData {
+    titles: Some(titles),
+    sql: Some(sql.to_string()),
+    ..RawArrowData::from_batch(batch)
+  })
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
+  use arrow::array::Array;
+
+  fn meta(column_type: ColumnType) -> ColumnMeta {
+    ColumnMeta {
+      name: "c".to_string(),
+      column_type,
+      unsigned: false,
+      length: 0,
+      decimals: 0,
+    }
+  }
+
+  fn text(s: &str) -> Value {
+    Value::Bytes(s.as_bytes().to_vec())
+  }
 
   #[test]
   fn schema_and_array_types_agree_for_text_like_columns() {
@@ -164,40 +394,108 @@ mod tests {
       MYSQL_TYPE_TINY_BLOB,
       MYSQL_TYPE_MEDIUM_BLOB,
       MYSQL_TYPE_LONG_BLOB,
-      MYSQL_TYPE_TIMESTAMP,
-      MYSQL_TYPE_TIME,
       MYSQL_TYPE_BIT,
       MYSQL_TYPE_GEOMETRY,
     ] {
-      assert_eq!(
-        mysql_to_arrow_type(t),
-        DataType::Utf8,
-        "{t:?} must be Utf8 to match StringArray"
-      );
+      let array = column_to_array(&meta(t), &[text("x"), Value::NULL]);
+      assert_eq!(array.data_type(), &DataType::Utf8, "{t:?} must be Utf8");
     }
   }
 
-  #[test]
-  fn numeric_types_stay_numeric() {
-    assert_eq!(mysql_to_arrow_type(MYSQL_TYPE_LONG), DataType::Int64);
-    assert_eq!(mysql_to_arrow_type(MYSQL_TYPE_DOUBLE), DataType::Float64);
-  }
-
   #[test]
   fn unreadable_numeric_bytes_become_null() {
-    let bad = Value::Bytes(b"not-a-number".to_vec());
+    let bad = text("not-a-number");
     assert_eq!(convert_to_i64(&bad), None);
     assert_eq!(convert_to_f64(&bad), None);
     assert!(convert_to_str(&bad).is_some());
   }
 
   #[test]
-  fn build_preview_batch_handles_json_like_column() {
-    let fields = vec![Field::new("j", DataType::Utf8, true)];
-    let types = vec![MYSQL_TYPE_JSON];
-    let tables = vec![vec![Value::Bytes(br#"{"a":1}"#.to_vec()), Value::NULL]];
-    let batch = build_preview_batch(fields, types, tables).unwrap();
-    assert_eq!(batch.num_rows(), 2);
-    assert_eq!(batch.schema().field(0).data_type(), &DataType::Utf8);
+  fn temporal_columns_are_typed() {
+    let date = column_to_array(&meta(MYSQL_TYPE_DATE), &[text("1970-01-02"), Value::NULL]);
+    let date = date.as_any().downcast_ref::<Date32Array>().unwrap();
+    assert_eq!(date.value(0), 1);
+    assert!(date.is_null(1));
+    // Zero dates have no calendar value; the column keeps them as text.
+    let zero = column_to_array(&meta(MYSQL_TYPE_DATE), &[text("1970-01-02"), text("0000-00-00")]);
+    let zero = zero.as_any().downcast_ref::<StringArray>().unwrap();
+    assert_eq!(zero.value(1), "0000-00-00");
+
+    let ts = column_to_array(
+      &meta(MYSQL_TYPE_DATETIME),
+      &[
+        text("1970-01-01 00:00:01.5"),
+        Value::Date(1970, 1, 1, 0, 1, 0, 0),
+      ],
+    );
+    assert_eq!(
+      ts.data_type(),
+      &DataType::Timestamp(TimeUnit::Microsecond, None)
+    );
+    let ts = ts
+      .as_any()
+      .downcast_ref::<TimestampMicrosecondArray>()
+      .unwrap();
+    assert_eq!((ts.value(0), ts.value(1)), (1_500_000, 60_000_000));
+
+    let time = column_to_array(&meta(MYSQL_TYPE_TIME), &[text("01:02:03.25")]);
+    let time = time
+      .as_any()
+      .downcast_ref::<Time64MicrosecondArray>()
+      .unwrap();
+    assert_eq!(time.value(0), 3_723_250_000);
+    let long = column_to_array(&meta(MYSQL_TYPE_TIME), &[text("-838:59:59")]);
+    assert_eq!(long.data_type(), &DataType::Utf8);
+  }
+
+  #[test]
+  fn decimals_and_unsigned_bigint_keep_precision() {
+    let dec = ColumnMeta {
+      length: 12,
+      decimals: 2,
+      ..meta(MYSQL_TYPE_NEWDECIMAL)
+    };
+    let array = column_to_array(&dec, &[text("-12.5"), text("12345678.9"), Value::NULL]);
+    assert_eq!(array.data_type(), &DataType::Decimal128(10, 2));
+    let array = array.as_any().downcast_ref::<Decimal128Array>().unwrap();
+    assert_eq!((array.value(0), array.value(1)), (-1250, 1_234_567_890));
+    assert!(array.is_null(2));
+
+    let big = ColumnMeta {
+      unsigned: true,
+      ..meta(MYSQL_TYPE_LONGLONG)
+    };
+    let array = column_to_array(&big, &[Value::UInt(u64::MAX), text("18446744073709551615")]);
+    let array = array.as_any().downcast_ref::<UInt64Array>().unwrap();
+    assert_eq!((array.value(0), array.value(1)), (u64::MAX, u64::MAX));
+  }
+
+  #[test]
+  fn json_columns_carry_extension_metadata() {
+    let metas = vec![ColumnMeta {
+      name: "j".to_string(),
+      ..meta(MYSQL_TYPE_JSON)
+    }];
+    let tables = vec![vec![text(r#"{"a":1}"#), Value::NULL]];
+    let raw = values_to_raw(&metas, None, tables, "select j").unwrap();
+    assert_eq!(raw.batch.num_rows(), 2);
+    let schema = raw.batch.schema();
+    let field = schema.field(0);
+    assert_eq!(field.data_type(), &DataType::Utf8);
+    assert_eq!(
+      field.metadata().get(EXTENSION_NAME_KEY).map(String::as_str),
+      Some(JSON_EXTENSION)
+    );
+  }
+
+  #[test]
+  fn later_pages_keep_the_first_page_types() {
+    let metas = vec![meta(MYSQL_TYPE_DATETIME)];
+    let first = values_to_raw(&metas, None, vec![vec![text("2024-01-15 10:00:00")]], "").unwrap();
+    let types = [first.batch.schema().field(0).data_type().clone()];
+    let next = values_to_raw(&metas, Some(&types), vec![vec![Value::NULL]], "").unwrap();
+    assert_eq!(next.batch.schema().field(0).data_type(), &types[0]);
+    let zero = vec![vec![Value::Date(0, 0, 0, 0, 0, 0, 0)]];
+    assert!(values_to_raw(&metas, Some(&types), zero, "").is_err());
   }
 }
@@ -0,0 +1,337 @@
+//! Bind parameters: `:name` or `$1` placeholders in SQL, rewritten to each
+//! driver's native style and executed as a prepared statement, so values are
+//! never spliced into the SQL text.
+
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, bail};
+use serde::{Deserialize, Serialize};
+
+/// One bound value; JSON `null`, booleans, numbers and strings map directly.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ParamValue {
+  Null,
+  Bool(bool),
+  Int(i64),
+  Float(f64),
+  Text(String),
+}
+
+/// Declared type of a parameter, used to coerce values entered as text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ParamType {
+  #[default]
+  Text,
+  Int,
+  Float,
+  Bool,
+}
+
+impl ParamType {
+  /// `raw` as a value of this type; `null` (any case) is SQL NULL.
+  pub fn parse(self, raw: &str) -> anyhow::Result<ParamValue> {
+    let trimmed = raw.trim();
+    if trimmed.eq_ignore_ascii_case("null") {
+      return Ok(ParamValue::Null);
+    }
+    let invalid = || anyhow!("invalid {} value: {raw:?}", self.name());
+    Ok(match self {
+      Self::Text => ParamValue::Text(raw.to_string()),
+      Self::Int => ParamValue::Int(trimmed.parse().map_err(|_| invalid())?),
+      Self::Float => ParamValue::Float(trimmed.parse().map_err(|_| invalid())?),
+      Self::Bool => match trimmed.to_ascii_lowercase().as_str() {
+        "true" | "t" | "1" | "yes" => ParamValue::Bool(true),
+        "false" | "f" | "0" | "no" => ParamValue::Bool(false),
+        _ => return Err(invalid()),
+      },
+    })
+  }
+
+  fn name(self) -> &'static str {
+    match self {
+      Self::Text => "text",
+      Self::Int => "int",
+      Self::Float => "float",
+      Self::Bool => "bool",
+    }
+  }
+}
+
+/// Values for `$1`-style placeholders (a JSON array) or `:name` ones (an object).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Params {
+  Positional(Vec<ParamValue>),
+  Named(BTreeMap<String, ParamValue>),
+}
+
+impl Default for Params {
+  fn default() -> Self {
+    Self::Positional(vec![])
+  }
+}
+
+/// Native placeholder syntax of a driver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Placeholder {
+  /// `?`, one value per occurrence (MySQL, SQLite, DuckDB).
+  Question,
+  /// `$1`, one value per distinct parameter (Postgres).
+  Dollar,
+  /// `{p1:Type}` server-side parameters sent as `param_p1` (ClickHouse).
+  ClickHouse,
+}
+
+/// SQL rewritten for a driver with the values in placeholder order.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Bound {
+  pub sql: String,
+  pub values: Vec<ParamValue>,
+}
+
+/// A placeholder found in SQL: `$n` (1-based) or `:name`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ParamRef {
+  Index(usize),
+  Name(String),
+}
+
+impl std::fmt::Display for ParamRef {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Index(i) => write!(f, "${i}"),
+      Self::Name(name) => write!(f, ":{name}"),
+    }
+  }
+}
+
+/// Distinct placeholders in `sql`, in order of first use.
+pub fn placeholders(sql: &str) -> Vec<ParamRef> {
+  let mut out: Vec<ParamRef> = vec![];
+  for (_, _, param) in scan(sql, false) {
+    if !out.contains(&param) {
+      out.push(param);
+    }
+  }
+  out
+}
+
+/// Rewrite the placeholders in `sql` to the native style of `dialect` and
+/// collect their values.
+pub(crate) fn bind(dialect: &str, sql: &str, params: &Params) -> anyhow::Result<Bound> {
+  let style = match dialect {
+    "postgres" => Placeholder::Dollar,
+    "clickhouse" => Placeholder::ClickHouse,
+    _ => Placeholder::Question,
+  };
+  // MySQL and ClickHouse treat a backslash in a string literal as an escape.
+  let found = scan(sql, matches!(dialect, "mysql" | "clickhouse"));
+  let mut out = String::with_capacity(sql.len());
+  let mut values: Vec<ParamValue> = vec![];
+  let mut distinct: Vec<ParamRef> = vec![];
+  let mut last = 0;
+  for (start, end, param) in found {
+    let value = lookup(params, &param)?;
+    out.push_str(&sql[last..start]);
+    last = end;
+    if style == Placeholder::Question {
+      out.push('?');
+      values.push(value.clone());
+      continue;
+    }
+    let n = match distinct.iter().position(|p| *p == param) {
+      Some(i) => i + 1,
+      None => {
+        distinct.push(param);
+        values.push(value.clone());
+        values.len()
+      }
+    };
+    match style {
+      Placeholder::Dollar => out.push_str(&format!("${n}")),
+      _ => out.push_str(&format!("{{p{n}:{}}}", clickhouse_type(value))),
+    }
+  }
+  out.push_str(&sql[last..]);
+  Ok(Bound { sql: out, values })
+}
+
+fn lookup<'a>(params: &'a Params, param: &ParamRef) -> anyhow::Result<&'a ParamValue> {
+  match (params, param) {
+    (Params::Positional(values), ParamRef::Index(i)) => values
+      .get(i - 1)
+      .ok_or_else(|| anyhow!("missing value for parameter ${i} ({} given)", values.len())),
+    (Params::Named(values), ParamRef::Name(name)) => values
+      .get(name)
+      .ok_or_else(|| anyhow!("missing value for parameter :{name}")),
+    (Params::Positional(_), ParamRef::Name(name)) => {
+      bail!("parameter :{name} needs named values, got a list")
+    }
+    (Params::Named(_), ParamRef::Index(i)) => {
+      bail!("parameter ${i} needs positional values, got named ones")
+    }
+  }
+}
+
+fn clickhouse_type(value: &ParamValue) -> &'static str {
+  match value {
+    ParamValue::Null => "Nullable(String)",
+    ParamValue::Bool(_) => "Bool",
+    ParamValue::Int(_) => "Int64",
+    ParamValue::Float(_) => "Float64",
+    ParamValue::Text(_) => "String",
+  }
+}
+
+/// Byte ranges of placeholders outside strings, quoted identifiers, comments
+/// and Postgres dollar-quoted bodies; `::` casts are not placeholders.
+fn scan(sql: &str, backslash_escapes: bool) -> Vec<(usize, usize, ParamRef)> {
+  let bytes = sql.as_bytes();
+  let ident = |b: u8| b.is_ascii_alphanumeric() || b == b'_';
+  let mut out = vec![];
+  let mut i = 0;
+  while i < bytes.len() {
+    match bytes[i] {
+      quote @ (b'\'' | b'"' | b'`') => {
+        i += 1;
+        while i < bytes.len() {
+          if backslash_escapes && bytes[i] == b'\\' && quote == b'\'' {
+            i += 2;
+            continue;
+          }
+          if bytes[i] == quote {
+            // A doubled quote is an escaped one; keep scanning.
+            if bytes.get(i + 1) == Some(&quote) {
+              i += 2;
+              continue;
+            }
+            break;
+          }
+          i += 1;
+        }
+        i += 1;
+      }
+      b'-' if bytes.get(i + 1) == Some(&b'-') => {
+        while i < bytes.len() && bytes[i] != b'\n' {
+          i += 1;
+        }
+      }
+      b'/' if bytes.get(i + 1) == Some(&b'*') => {
+        i = sql[i + 2..].find("*/").map_or(bytes.len(), |end| i + 2 + end + 2);
+      }
+      b':' if bytes.get(i + 1) == Some(&b':') => i += 2,
+      b':' if (i == 0 || !ident(bytes[i - 1]))
+        && bytes.get(i + 1).is_some_and(|b| b.is_ascii_alphabetic() || *b == b'_') =>
+      {
+        let start = i;
+        i += 1;
+        while i < bytes.len() && ident(bytes[i]) {
+          i += 1;
+        }
+        out.push((start, i, ParamRef::Name(sql[start + 1..i].to_string())));
+      }
+      b'$'
+        if (i == 0 || !ident(bytes[i - 1])) && bytes.get(i + 1).is_some_and(u8::is_ascii_digit) =>
+      {
+        let start = i;
+        i += 1;
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+          i += 1;
+        }
+        match sql[start + 1..i].parse::<usize>() {
+          Ok(n) if n > 0 => out.push((start, i, ParamRef::Index(n))),
+          _ => {}
+        }
+      }
+      b'$' if i == 0 || !ident(bytes[i - 1]) => {
+        // `$tag$ ... $tag$` (tag may be empty) is a Postgres string literal.
+        let tag_end = sql[i + 1..]
+          .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+          .map(|n| i + 1 + n);
+        match tag_end.filter(|&end| bytes[end] == b'$') {
+          Some(end) => {
+            let tag = &sql[i..=end];
+            i = sql[end + 1..]
+              .find(tag)
+              .map_or(bytes.len(), |close| end + 1 + close + tag.len());
+          }
+          None => i += 1,
+        }
+      }
+      _ => i += 1,
+    }
+  }
+  out
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::dialect::Connection;
+  use crate::dialect::sqlite::SqliteConnection;
+
+  fn named(pairs: &[(&str, ParamValue)]) -> Params {
+    Params::Named(pairs.iter().map(|(k, v)| (k.to_string(), v.clone())).collect())
+  }
+
+  #[test]
+  fn rewrites_placeholders_outside_literals() {
+    let sql = "select ':skip', $$ :no $$, x::int, /* :c */ a from t -- :d
+      where a = :id and b = :name and c = :id";
+    let params = named(&[
+      ("id", ParamValue::Int(7)),
+      ("name", ParamValue::Text("o'neil".into())),
+    ]);
+    let names = placeholders(sql);
+    assert_eq!(names, [ParamRef::Name("id".into()), ParamRef::Name("name".into())]);
+
+    let q = bind("sqlite", sql, &params).unwrap();
+    assert!(q.sql.ends_with("where a = ? and b = ? and c = ?"), "{}", q.sql);
+    assert_eq!(q.values.len(), 3);
+    let d = bind("postgres", sql, &params).unwrap();
+    assert!(d.sql.ends_with("where a = $1 and b = $2 and c = $1"), "{}", d.sql);
+    assert_eq!(d.values.len(), 2);
+    let ch = bind("clickhouse", sql, &params).unwrap();
+    assert!(ch.sql.ends_with("a = {p1:Int64} and b = {p2:String} and c = {p1:Int64}"));
+
+    let positional = Params::Positional(vec![ParamValue::Int(1)]);
+    let bound = bind("duckdb", "select $1, $1", &positional).unwrap();
+    assert_eq!(bound.values, [ParamValue::Int(1), ParamValue::Int(1)]);
+    assert!(bind("postgres", "select $2", &positional).is_err());
+    assert!(bind("postgres", "select :id", &positional).is_err());
+    let escaped = bind("mysql", r"select 'it\'s :x', :id", &named(&[("id", ParamValue::Null)]));
+    assert_eq!(escaped.unwrap().sql, r"select 'it\'s :x', ?");
+  }
+
+  #[test]
+  fn typed_values_parse_from_text() {
+    assert_eq!(ParamType::Int.parse(" 42 ").unwrap(), ParamValue::Int(42));
+    assert_eq!(ParamType::Bool.parse("yes").unwrap(), ParamValue::Bool(true));
+    assert_eq!(ParamType::Float.parse("NULL").unwrap(), ParamValue::Null);
+    assert!(ParamType::Int.parse("4x").is_err());
+    let params: Params = serde_json::from_str(r#"{"a": 1, "b": "x", "c": null}"#).unwrap();
+    assert_eq!(
+      params,
+      named(&[
+        ("a", ParamValue::Int(1)),
+        ("b", ParamValue::Text("x".into())),
+        ("c", ParamValue::Null),
+      ])
+    );
+  }
+
+  #[tokio::test]
+  async fn sqlite_binds_values_that_would_break_templating() {
+    let conn = SqliteConnection {
+      path: ":memory:".into(),
+    };
+    let params = named(&[("name", ParamValue::Text("o'neil; drop table t".into()))]);
+    let raw = conn.query_params("select :name as name, :name = :name as same", &params);
+    let raw = raw.await.unwrap();
+    assert_eq!(raw.batch.num_rows(), 1);
+    let name = arrow::util::display::array_value_to_string(raw.batch.column(0), 0).unwrap();
+    assert_eq!(name, "o'neil; drop table t");
+  }
+}
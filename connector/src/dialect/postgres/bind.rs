@@ -0,0 +1,117 @@
+//! [`ParamValue`] as a tokio-postgres parameter. The server infers each
+//! parameter's type from the statement, so values are converted to that type
+//! here rather than sent as whatever JSON type the caller had.
+
+use std::error::Error;
+use std::str::FromStr;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use rust_decimal::Decimal;
+use tokio_postgres::types::private::BytesMut;
+use tokio_postgres::types::{IsNull, ToSql, Type, to_sql_checked};
+
+use crate::dialect::params::ParamValue;
+
+type BoxError = Box<dyn Error + Sync + Send>;
+
+impl ToSql for ParamValue {
+  fn to_sql(&self, ty: &Type, out: &mut BytesMut) -> Result<IsNull, BoxError> {
+    match self {
+      ParamValue::Null => Ok(IsNull::Yes),
+      ParamValue::Bool(v) if *ty == Type::BOOL => v.to_sql(ty, out),
+      ParamValue::Int(v) => match *ty {
+        Type::INT2 => i16::try_from(*v)?.to_sql(ty, out),
+        Type::INT4 => i32::try_from(*v)?.to_sql(ty, out),
+        Type::INT8 => v.to_sql(ty, out),
+        Type::OID => u32::try_from(*v)?.to_sql(ty, out),
+        Type::FLOAT4 => (*v as f32).to_sql(ty, out),
+        Type::FLOAT8 => (*v as f64).to_sql(ty, out),
+        Type::NUMERIC => Decimal::from(*v).to_sql(ty, out),
+        _ => text_to_sql(&v.to_string(), ty, out),
+      },
+      ParamValue::Float(v) => match *ty {
+        Type::FLOAT4 => (*v as f32).to_sql(ty, out),
+        Type::FLOAT8 => v.to_sql(ty, out),
+        Type::NUMERIC => Decimal::try_from(*v)?.to_sql(ty, out),
+        _ => text_to_sql(&v.to_string(), ty, out),
+      },
+      ParamValue::Bool(v) => text_to_sql(&v.to_string(), ty, out),
+      ParamValue::Text(v) => text_to_sql(v, ty, out),
+    }
+  }
+
+  fn accepts(_ty: &Type) -> bool {
+    true
+  }
+
+  to_sql_checked!();
+}
+
+/// Text parsed into the parameter's type; types without a conversion need an
+/// explicit cast in SQL (`$1::text`).
+fn text_to_sql(v: &str, ty: &Type, out: &mut BytesMut) -> Result<IsNull, BoxError> {
+  if <&str as ToSql>::accepts(ty) {
+    return v.to_sql(ty, out);
+  }
+  let v = v.trim();
+  match *ty {
+    Type::BOOL => match v.to_ascii_lowercase().as_str() {
+      "true" | "t" | "1" | "yes" => true.to_sql(ty, out),
+      "false" | "f" | "0" | "no" => false.to_sql(ty, out),
+      _ => Err(format!("invalid boolean: {v:?}").into()),
+    },
+    Type::INT2 => v.parse::<i16>()?.to_sql(ty, out),
+    Type::INT4 => v.parse::<i32>()?.to_sql(ty, out),
+    Type::INT8 => v.parse::<i64>()?.to_sql(ty, out),
+    Type::OID => v.parse::<u32>()?.to_sql(ty, out),
+    Type::FLOAT4 => v.parse::<f32>()?.to_sql(ty, out),
+    Type::FLOAT8 => v.parse::<f64>()?.to_sql(ty, out),
+    Type::NUMERIC => Decimal::from_str(v)?.to_sql(ty, out),
+    Type::DATE => NaiveDate::from_str(v)?.to_sql(ty, out),
+    Type::TIME => NaiveTime::from_str(v)?.to_sql(ty, out),
+    Type::TIMESTAMP => naive_datetime(v)?.to_sql(ty, out),
+    Type::TIMESTAMPTZ => match DateTime::parse_from_rfc3339(v) {
+      Ok(at) => at.with_timezone(&Utc).to_sql(ty, out),
+      Err(_) => naive_datetime(v)?.and_utc().to_sql(ty, out),
+    },
+    Type::JSON | Type::JSONB => serde_json::from_str::<serde_json::Value>(v)?.to_sql(ty, out),
+    _ => {
+      let msg = format!("cannot bind {v:?} to a {ty} parameter; cast it in SQL, e.g. $1::text");
+      Err(msg.into())
+    }
+  }
+}
+
+fn naive_datetime(v: &str) -> Result<NaiveDateTime, BoxError> {
+  let formats = ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M"];
+  for format in formats {
+    if let Ok(at) = NaiveDateTime::parse_from_str(v, format) {
+      return Ok(at);
+    }
+  }
+  Ok(NaiveDate::from_str(v)?.and_time(NaiveTime::MIN))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn encode(value: ParamValue, ty: Type) -> Result<Vec<u8>, BoxError> {
+    let mut out = BytesMut::new();
+    value.to_sql_checked(&ty, &mut out)?;
+    Ok(out.to_vec())
+  }
+
+  #[test]
+  fn converts_to_the_inferred_parameter_type() {
+    assert_eq!(encode(ParamValue::Int(7), Type::INT4).unwrap(), 7i32.to_be_bytes());
+    assert_eq!(encode(ParamValue::Text("7".into()), Type::INT2).unwrap(), 7i16.to_be_bytes());
+    assert_eq!(encode(ParamValue::Int(7), Type::TEXT).unwrap(), b"7");
+    assert_eq!(encode(ParamValue::Text("t".into()), Type::BOOL).unwrap(), [1]);
+    assert!(encode(ParamValue::Text("2024-02-30".into()), Type::DATE).is_err());
+    assert!(encode(ParamValue::Text("2024-02-03 04:05".into()), Type::TIMESTAMP).is_ok());
+    assert!(encode(ParamValue::Text("2024-02-03T04:05:06Z".into()), Type::TIMESTAMPTZ).is_ok());
+    assert!(encode(ParamValue::Int(70_000), Type::INT2).is_err());
+    assert!(encode(ParamValue::Text("x".into()), Type::UUID).is_err());
+  }
+}
@@ -1,5 +1,4 @@
-mod decode;
-#[allow(dead_code)]
+mod bind;
 mod type_arrow;
 #[allow(dead_code)]
 mod type_json;
@@ -7,15 +6,28 @@ mod type_json;
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
-use arrow::datatypes::ArrowNativeType;
 use async_trait::async_trait;
 use futures_util::FutureExt;
+use sqlparser::ast::Statement;
 use tokio_postgres::{Client, NoTls};
 
+use crate::cancel::StatementGuard;
 use crate::dialect::Connection;
+use crate::dialect::ast::first_stmt;
+use crate::dialect::cursor::{self, ResultCursor};
+use crate::dialect::ddl::{self, ObjectKind, join_statements};
+use crate::dialect::detail::{
+  ColumnDetail, ForeignKey, IndexDetail, TableDetail, UniqueConstraint, group_sorted,
+};
+use crate::dialect::explain::{self, QueryPlan};
+use crate::dialect::keyset::PlanCache;
+use crate::dialect::limits::{self, QueryLimits};
+use crate::dialect::params::{self, ParamValue, Params};
+pub(crate) use crate::dialect::postgres::type_arrow::{declared_arrow_type, is_uuid_field};
+use crate::dialect::transaction::{self, Transaction};
 use crate::ssh_tunnel::{DbSshConfig, SshTunnel};
-use crate::utils::{FunctionMeta, RawArrowData, Table, TreeNode, build_tree};
-use anyhow::{Context, anyhow};
+use crate::utils::{FunctionMeta, Metadata, RawArrowData, Table, TreeNode, build_tree};
+use anyhow::{Context, anyhow, bail};
 
 /// TLS mode for Postgres connections.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -47,8 +59,16 @@ struct PostgresLive {
   _tunnel: Option<SshTunnel>,
   tunnel_port: Option<u16>,
   clients: HashMap<String, Arc<Client>>,
+  /// Dedicated clients for [`Connection::query_limited`], one statement at a time.
+  limited: HashMap<String, Arc<tokio::sync::Mutex<Client>>>,
+  /// Idle clients for cancellable counts, so each count can be cancelled on
+  /// the server without waiting for a shared session.
+  count_clients: HashMap<String, Vec<Client>>,
 }
 
+/// Idle count clients kept per database; more are opened while counts overlap.
+const IDLE_COUNT_CLIENTS: usize = 4;
+
 /// Postgres dialect connection. Tunnel + clients are cached per database name
 /// and shared across clones for SessionManager reuse.
 pub struct PostgresConnection {
@@ -60,6 +80,7 @@ pub struct PostgresConnection {
   pub ssh: Option<DbSshConfig>,
   pub ssl_mode: SslMode,
   live: Arc<Mutex<Option<PostgresLive>>>,
+  keyset_plans: Arc<PlanCache>,
 }
 
 impl Default for PostgresConnection {
@@ -105,6 +126,7 @@ impl PostgresConnection {
       ssh,
       ssl_mode,
       live: Arc::new(Mutex::new(None)),
+      keyset_plans: Arc::default(),
     }
   }
 }
@@ -120,6 +142,7 @@ impl Clone for PostgresConnection {
       ssh: self.ssh.clone(),
       ssl_mode: self.ssl_mode,
       live: Arc::clone(&self.live),
+      keyset_plans: Arc::clone(&self.keyset_plans),
     }
   }
 }
@@ -163,8 +186,351 @@ impl Connection for PostgresConnection {
     "postgres"
   }
 
+  fn keyset_plans(&self) -> Option<&PlanCache> {
+    Some(&self.keyset_plans)
+  }
+
+  /// Runs on a dedicated client per database, reused across calls, so
+  /// `statement_timeout` never reaches the shared one. A query is read through
+  /// a portal in chunks; rows past the caps are never produced.
+  async fn query_limited(&self, sql: &str, limits: &QueryLimits) -> anyhow::Result<RawArrowData> {
+    if *limits == QueryLimits::default() {
+      return self.query(sql, 0, 0).await;
+    }
+    let session = self.limited_client(&self.database()).await?;
+    let mut client = session.lock().await;
+    let timeout = limits.timeout().map_or(0, |t| t.as_millis());
+    client
+      .batch_execute(&format!("SET statement_timeout = {timeout}"))
+      .await?;
+    let pg_cancel = client.cancel_token();
+    let read = async {
+      let _hook = cancel_on(pg_cancel, self.ssl_mode, crate::cancel::current().as_ref())?;
+      read_limited(&mut client, sql, limits).await
+    };
+    match limits.timeout() {
+      Some(timeout) => crate::cancel::with_timeout(timeout, read).await,
+      None => read.await,
+    }
+  }
+
   async fn query_count(&self, sql: &str) -> anyhow::Result<usize> {
-    self.query_count_cancellable(sql, None).await
+    self.count_one(sql).await
+  }
+
+  async fn execute(&self, sql: &str) -> anyhow::Result<usize> {
+    let client = self.get_client(&self.database()).await?;
+    let affected = client.execute(sql, &[]).await?;
+    Ok(affected as usize)
+  }
+
+  async fn query_params(&self, sql: &str, params: &Params) -> anyhow::Result<RawArrowData> {
+    let bound = params::bind(self.dialect(), sql, params)?;
+    let client = self.get_client(&self.database()).await?;
+    let cancel = crate::cancel::current();
+    let mut raw = query_client(&client, &bound.sql, &bound.values, cancel.as_ref()).await?;
+    raw.sql = Some(sql.to_string());
+    Ok(raw)
+  }
+
+  async fn execute_params(&self, sql: &str, params: &Params) -> anyhow::Result<usize> {
+    let bound = params::bind(self.dialect(), sql, params)?;
+    let client = self.get_client(&self.database()).await?;
+    let affected = client.execute(&bound.sql, &sql_params(&bound.values)).await?;
+    Ok(affected as usize)
+  }
+
+  async fn primary_key(&self, schema: Option<&str>, table: &str) -> anyhow::Result<Vec<String>> {
+    let client = self.get_client(&self.database()).await?;
+    let sql = "
+      select kcu.column_name::text
+      from information_schema.table_constraints tc
+      join information_schema.key_column_usage kcu
+        on kcu.constraint_name = tc.constraint_name
+        and kcu.table_schema = tc.table_schema
+        and kcu.table_name = tc.table_name
+      where tc.constraint_type = 'PRIMARY KEY'
+        and tc.table_schema = coalesce($1::text, current_schema())
+        and tc.table_name = $2::text
+      order by kcu.ordinal_position
+    ";
+    let rows = client.query(sql, &[&schema, &table]).await?;
+    Ok(rows.iter().map(|row| row.get::<_, String>(0)).collect())
+  }
+
+  async fn all_columns(&self) -> anyhow::Result<Vec<Metadata>> {
+    let client = self.get_client(&self.database()).await?;
+    let sql = "
+      select n.nspname::text, c.relname::text, a.attname::text,
+        format_type(a.atttypid, a.atttypmod)
+      from pg_attribute a
+      join pg_class c on c.oid = a.attrelid
+      join pg_namespace n on n.oid = c.relnamespace
+      where a.attnum > 0 and not a.attisdropped
+        and c.relkind in ('r', 'p', 'v', 'm', 'f')
+        and n.nspname not in ('pg_catalog', 'information_schema')
+        and n.nspname not like 'pg_toast%'
+      order by n.nspname, c.relname, a.attnum
+    ";
+    let rows = client.query(sql, &[]).await?;
+    Ok(
+      group_sorted(rows.iter().map(|row| {
+        (
+          (row.get::<_, String>(0), row.get::<_, String>(1)),
+          (row.get::<_, String>(2), row.get::<_, String>(3)),
+        )
+      }))
+      .into_iter()
+      .map(|((database, table), columns)| Metadata {
+        database,
+        table,
+        columns,
+      })
+      .collect(),
+    )
+  }
+
+  async fn table_detail(&self, schema: Option<&str>, table: &str) -> anyhow::Result<TableDetail> {
+    let client = self.get_client(&self.database()).await?;
+    let sql = "
+      select c.oid, n.nspname::text, obj_description(c.oid, 'pg_class')
+      from pg_class c
+      join pg_namespace n on n.oid = c.relnamespace
+      where n.nspname = coalesce($1::text, current_schema())
+        and c.relname = $2::text
+        and c.relkind in ('r', 'p', 'v', 'm', 'f')
+    ";
+    let row = client
+      .query_opt(sql, &[&schema, &table])
+      .await?
+      .ok_or_else(|| anyhow!("table not found: {table}"))?;
+    let oid: u32 = row.get(0);
+    let schema_name: String = row.get(1);
+    let comment: Option<String> = row.get(2);
+
+    let sql = "
+      select a.attname::text, format_type(a.atttypid, a.atttypmod), not a.attnotnull,
+        pg_get_expr(d.adbin, d.adrelid), col_description(a.attrelid, a.attnum)
+      from pg_attribute a
+      left join pg_attrdef d on d.adrelid = a.attrelid and d.adnum = a.attnum
+      where a.attrelid = $1::oid and a.attnum > 0 and not a.attisdropped
+      order by a.attnum
+    ";
+    let columns = client
+      .query(sql, &[&oid])
+      .await?
+      .iter()
+      .map(|row| ColumnDetail {
+        name: row.get(0),
+        r#type: row.get(1),
+        nullable: row.get(2),
+        default: row.get(3),
+        comment: row.get(4),
+      })
+      .collect();
+
+    // Key columns are resolved in constraint order, not attribute order.
+    let sql = "
+      select con.conname::text, con.contype::text,
+        array(
+          select att.attname::text
+          from unnest(con.conkey) with ordinality k(attnum, ord)
+          join pg_attribute att on att.attrelid = con.conrelid and att.attnum = k.attnum
+          order by k.ord
+        ),
+        fn.nspname::text, fc.relname::text,
+        array(
+          select att.attname::text
+          from unnest(con.confkey) with ordinality k(attnum, ord)
+          join pg_attribute att on att.attrelid = con.confrelid and att.attnum = k.attnum
+          order by k.ord
+        ),
+        con.confupdtype::text, con.confdeltype::text
+      from pg_constraint con
+      left join pg_class fc on fc.oid = con.confrelid
+      left join pg_namespace fn on fn.oid = fc.relnamespace
+      where con.conrelid = $1::oid and con.contype in ('p', 'u', 'f')
+      order by con.conname
+    ";
+    let mut primary_key = vec![];
+    let mut unique_constraints = vec![];
+    let mut foreign_keys = vec![];
+    for row in client.query(sql, &[&oid]).await? {
+      let name: String = row.get(0);
+      let kind: String = row.get(1);
+      let cols: Vec<String> = row.get(2);
+      match kind.as_str() {
+        "p" => primary_key = cols,
+        "u" => unique_constraints.push(UniqueConstraint {
+          name: Some(name),
+          columns: cols,
+        }),
+        _ => foreign_keys.push(ForeignKey {
+          name: Some(name),
+          columns: cols,
+          ref_schema: row.get(3),
+          ref_table: row.get::<_, Option<String>>(4).unwrap_or_default(),
+          ref_columns: row.get(5),
+          on_update: fk_action(&row.get::<_, String>(6)),
+          on_delete: fk_action(&row.get::<_, String>(7)),
+        }),
+      }
+    }
+
+    let sql = "
+      select ic.relname::text, i.indisunique, am.amname::text,
+        array(
+          select pg_get_indexdef(i.indexrelid, k, true)
+          from generate_series(1, i.indnkeyatts::int) k
+        )
+      from pg_index i
+      join pg_class ic on ic.oid = i.indexrelid
+      join pg_am am on am.oid = ic.relam
+      where i.indrelid = $1::oid and not i.indisprimary
+      order by ic.relname
+    ";
+    let indexes = client
+      .query(sql, &[&oid])
+      .await?
+      .iter()
+      .map(|row| IndexDetail {
+        name: row.get(0),
+        unique: row.get(1),
+        method: row.get(2),
+        columns: row.get(3),
+      })
+      .collect();
+
+    Ok(TableDetail {
+      schema: Some(schema_name),
+      table: table.to_string(),
+      comment,
+      columns,
+      primary_key,
+      unique_constraints,
+      indexes,
+      foreign_keys,
+      ..Default::default()
+    })
+  }
+
+  /// Postgres has no SHOW CREATE; tables are rebuilt from the catalog (columns,
+  /// constraints via `pg_get_constraintdef`, standalone indexes, comments).
+  async fn object_ddl(
+    &self,
+    schema: Option<&str>,
+    name: &str,
+    kind: ObjectKind,
+  ) -> anyhow::Result<String> {
+    let client = self.get_client(&self.database()).await?;
+    if kind == ObjectKind::Function {
+      let sql = "
+        select pg_get_functiondef(p.oid)
+        from pg_proc p
+        join pg_namespace n on n.oid = p.pronamespace
+        where n.nspname = coalesce($1::text, current_schema())
+          and p.proname = $2::text
+          and p.prokind in ('f', 'p')
+        order by p.oid
+      ";
+      let defs: Vec<String> = client
+        .query(sql, &[&schema, &name])
+        .await?
+        .iter()
+        .map(|row| row.get(0))
+        .collect();
+      if defs.is_empty() {
+        return Err(ddl::not_found(kind, name));
+      }
+      return Ok(join_statements(defs));
+    }
+
+    let sql = "
+      select c.oid, n.nspname::text, c.relkind::text
+      from pg_class c
+      join pg_namespace n on n.oid = c.relnamespace
+      where n.nspname = coalesce($1::text, current_schema())
+        and c.relname = $2::text
+        and c.relkind in ('r', 'p', 'f', 'v', 'm')
+    ";
+    let row = client
+      .query_opt(sql, &[&schema, &name])
+      .await?
+      .ok_or_else(|| ddl::not_found(kind, name))?;
+    let oid: u32 = row.get(0);
+    let nsp: String = row.get(1);
+    let relkind: String = row.get(2);
+    let target = format!("{}.{}", self.quote(&nsp), self.quote(name));
+
+    if relkind == "v" || relkind == "m" {
+      let def: String = client
+        .query_one("select pg_get_viewdef($1::oid, true)", &[&oid])
+        .await?
+        .get(0);
+      let create = if relkind == "m" {
+        "CREATE MATERIALIZED VIEW"
+      } else {
+        "CREATE OR REPLACE VIEW"
+      };
+      return Ok(join_statements([format!("{create} {target} AS\n{def}")]));
+    }
+    self.table_ddl(&client, oid, &relkind, &target).await
+  }
+
+  /// Opens a dedicated client so the shared per-database client stays autocommit.
+  async fn begin_transaction(&self) -> anyhow::Result<Box<dyn Transaction>> {
+    let tunnel_port = self.ensure_tunnel().await?;
+    let config = self.build_conn_string(&self.database(), tunnel_port);
+    let client = connect_with_ssl(&config, self.ssl_mode).await?;
+    client.batch_execute("BEGIN").await?;
+    Ok(Box::new(PostgresTransaction {
+      _owner: self.clone(),
+      client: Mutex::new(Some(Arc::new(client))),
+    }))
+  }
+
+  /// A dedicated client, so a script's `SET` / `BEGIN` never leaks into the
+  /// shared one.
+  async fn script_session(&self) -> anyhow::Result<Option<Box<dyn Transaction>>> {
+    let tunnel_port = self.ensure_tunnel().await?;
+    let config = self.build_conn_string(&self.database(), tunnel_port);
+    let client = connect_with_ssl(&config, self.ssl_mode).await?;
+    Ok(Some(Box::new(PostgresTransaction {
+      _owner: self.clone(),
+      client: Mutex::new(Some(Arc::new(client))),
+    })))
+  }
+
+  /// `DECLARE ... NO SCROLL CURSOR` inside a transaction on a dedicated client;
+  /// each page is one `FETCH FORWARD`.
+  async fn open_cursor(&self, sql: &str) -> anyhow::Result<Box<dyn ResultCursor>> {
+    let tunnel_port = self.ensure_tunnel().await?;
+    let config = self.build_conn_string(&self.database(), tunnel_port);
+    let client = connect_with_ssl(&config, self.ssl_mode).await?;
+    let sql = sql.trim().trim_end_matches(';').to_string();
+    client
+      .batch_execute(&format!("BEGIN; DECLARE {CURSOR_NAME} NO SCROLL CURSOR FOR {sql}"))
+      .await?;
+    Ok(Box::new(PostgresCursor {
+      _owner: self.clone(),
+      client: Mutex::new(Some(Arc::new(client))),
+      sql,
+    }))
+  }
+
+  async fn explain(&self, sql: &str, analyze: bool) -> anyhow::Result<QueryPlan> {
+    let options = if analyze {
+      explain::check_analyze("postgres", sql)?;
+      "FORMAT JSON, ANALYZE"
+    } else {
+      "FORMAT JSON"
+    };
+    let client = self.get_client(&self.database()).await?;
+    let row = client
+      .query_one(&format!("EXPLAIN ({options}) {sql}"), &[])
+      .await?;
+    let raw = row.get::<_, serde_json::Value>(0).to_string();
+    explain::parse_postgres_json(&raw, analyze)
   }
 
   async fn show_schema(&self, schema: &str) -> anyhow::Result<RawArrowData> {
@@ -191,6 +557,34 @@ impl Connection for PostgresConnection {
     self.query(&sql, 0, 0).await
   }
 
+  async fn estimate_row_count(&self, table: &str) -> anyhow::Result<usize> {
+    let client = self.get_client(&self.database()).await?;
+    // The text cast lets the name bind as a plain parameter (to_regclass takes
+    // cstring before PG 14). reltuples is -1 until the table is first vacuumed
+    // or analyzed, and a partitioned parent holds no rows of its own: sum its
+    // leaf partitions.
+    let sql = "
+      with recursive tree(oid) as (
+        select $1::text::regclass::oid
+        union all
+        select i.inhrelid from pg_inherits i join tree t on i.inhparent = t.oid
+      )
+      select case when bool_and(c.relkind = 'p' or c.reltuples >= 0)
+        then coalesce(sum(c.reltuples) filter (where c.relkind <> 'p'), 0)
+        else -1 end::float8
+      from tree t join pg_class c on c.oid = t.oid
+      having count(*) > 0";
+    let row = client
+      .query_opt(sql, &[&self.quote_table_ref(table)])
+      .await?
+      .ok_or_else(|| anyhow!("table not found: {table}"))?;
+    let tuples: f64 = row.get(0);
+    if tuples < 0.0 {
+      bail!("no statistics for {table}");
+    }
+    Ok(tuples as usize)
+  }
+
   async fn table_row_count(&self, table: &str, r#where: &str) -> anyhow::Result<usize> {
     self._table_row_count(table, r#where).await
   }
@@ -239,9 +633,154 @@ impl Connection for PostgresConnection {
   fn end_quote(&self) -> &'static str {
     "\""
   }
+
+  fn validator(&self, id: &str) -> bool {
+    // Unquoted identifiers fold to lower case, so anything else must be quoted.
+    let mut chars = id.chars();
+    let Some(first) = chars.next() else {
+      return false;
+    };
+    (first.is_ascii_lowercase() || first == '_')
+      && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '$')
+  }
 }
 
 impl PostgresConnection {
+  /// `relkind` is `r` (table), `p` (partitioned table) or `f` (foreign table).
+  async fn table_ddl(
+    &self,
+    client: &Client,
+    oid: u32,
+    relkind: &str,
+    target: &str,
+  ) -> anyhow::Result<String> {
+    // Identity columns arrived in 10, generated columns in 12.
+    let version: i32 = client
+      .query_one("select current_setting('server_version_num')::int", &[])
+      .await?
+      .get(0);
+    let identity = if version >= 100000 { "a.attidentity::text" } else { "''::text" };
+    let generated = if version >= 120000 { "a.attgenerated::text" } else { "''::text" };
+    let sql = format!(
+      "
+      select a.attname::text, format_type(a.atttypid, a.atttypmod), a.attnotnull,
+        pg_get_expr(d.adbin, d.adrelid), {identity}, {generated},
+        col_description(a.attrelid, a.attnum), a.attfdwoptions::text[]
+      from pg_attribute a
+      left join pg_attrdef d on d.adrelid = a.attrelid and d.adnum = a.attnum
+      where a.attrelid = $1::oid and a.attnum > 0 and not a.attisdropped
+      order by a.attnum
+    "
+    );
+    let mut lines = vec![];
+    let mut comments = vec![];
+    for row in client.query(&sql, &[&oid]).await? {
+      let col = self.quote(row.get::<_, &str>(0));
+      let mut line = format!("  {col} {}", row.get::<_, String>(1));
+      if let Some(options) = row.get::<_, Option<Vec<String>>>(7) {
+        line.push_str(&self.fdw_options(&options));
+      }
+      let default: Option<String> = row.get(3);
+      match (row.get::<_, &str>(4), row.get::<_, &str>(5), default) {
+        ("a", _, _) => line.push_str(" GENERATED ALWAYS AS IDENTITY"),
+        ("d", _, _) => line.push_str(" GENERATED BY DEFAULT AS IDENTITY"),
+        (_, "s", Some(expr)) => line.push_str(&format!(" GENERATED ALWAYS AS ({expr}) STORED")),
+        (_, _, Some(expr)) => line.push_str(&format!(" DEFAULT {expr}")),
+        _ => {}
+      }
+      if row.get::<_, bool>(2) {
+        line.push_str(" NOT NULL");
+      }
+      lines.push(line);
+      if let Some(comment) = row.get::<_, Option<String>>(6) {
+        comments.push(format!(
+          "COMMENT ON COLUMN {target}.{col} IS {}",
+          quote_literal(&comment)
+        ));
+      }
+    }
+
+    let sql = "
+      select conname::text, pg_get_constraintdef(oid, true)
+      from pg_constraint
+      where conrelid = $1::oid and contype in ('p', 'u', 'c', 'f', 'x')
+      order by position(contype::text in 'pucfx'), conname
+    ";
+    for row in client.query(sql, &[&oid]).await? {
+      lines.push(format!(
+        "  CONSTRAINT {} {}",
+        self.quote(row.get::<_, &str>(0)),
+        row.get::<_, String>(1)
+      ));
+    }
+    let mut create = match relkind {
+      "f" => format!("CREATE FOREIGN TABLE {target} (\n{}\n)", lines.join(",\n")),
+      _ => format!("CREATE TABLE {target} (\n{}\n)", lines.join(",\n")),
+    };
+    if relkind == "p" {
+      let key: String = client
+        .query_one("select pg_get_partkeydef($1::oid)", &[&oid])
+        .await?
+        .get(0);
+      create.push_str(&format!("\nPARTITION BY {key}"));
+    }
+    if relkind == "f" {
+      let sql = "
+        select s.srvname::text, ft.ftoptions::text[]
+        from pg_foreign_table ft
+        join pg_foreign_server s on s.oid = ft.ftserver
+        where ft.ftrelid = $1::oid
+      ";
+      let row = client.query_one(sql, &[&oid]).await?;
+      create.push_str(&format!("\nSERVER {}", self.quote(row.get::<_, &str>(0))));
+      if let Some(options) = row.get::<_, Option<Vec<String>>>(1) {
+        create.push_str(&self.fdw_options(&options));
+      }
+    }
+    let mut stmts = vec![create];
+
+    // Indexes backing constraints are already covered by the constraint clauses.
+    let sql = "
+      select pg_get_indexdef(i.indexrelid)
+      from pg_index i
+      join pg_class ic on ic.oid = i.indexrelid
+      where i.indrelid = $1::oid
+        and not exists (
+          select 1 from pg_constraint c
+          where c.conindid = i.indexrelid and c.conrelid = i.indrelid
+        )
+      order by ic.relname
+    ";
+    for row in client.query(sql, &[&oid]).await? {
+      stmts.push(row.get(0));
+    }
+
+    let comment: Option<String> = client
+      .query_one("select obj_description($1::oid, 'pg_class')", &[&oid])
+      .await?
+      .get(0);
+    if let Some(comment) = comment {
+      stmts.push(format!("COMMENT ON TABLE {target} IS {}", quote_literal(&comment)));
+    }
+    stmts.extend(comments);
+    Ok(join_statements(stmts))
+  }
+
+  /// ` OPTIONS (key 'value', ...)` from a `{key=value}` catalog array; empty if none.
+  fn fdw_options(&self, options: &[String]) -> String {
+    if options.is_empty() {
+      return String::new();
+    }
+    let items = options
+      .iter()
+      .map(|o| {
+        let (key, value) = o.split_once('=').unwrap_or((o, ""));
+        format!("{} {}", self.quote(key), quote_literal(value))
+      })
+      .collect::<Vec<_>>();
+    format!(" OPTIONS ({})", items.join(", "))
+  }
+
   fn build_conn_string(&self, db: &str, tunnel_port: Option<u16>) -> String {
     let (host, port) = if let Some(local_port) = tunnel_port {
       ("127.0.0.1".to_string(), local_port.to_string())
@@ -287,6 +826,8 @@ impl PostgresConnection {
         _tunnel: tunnel,
         tunnel_port,
         clients: HashMap::new(),
+        limited: HashMap::new(),
+        count_clients: HashMap::new(),
       });
     }
     Ok(guard.as_ref().and_then(|l| l.tunnel_port))
@@ -330,6 +871,72 @@ impl PostgresConnection {
     ))
   }
 
+  /// The cached client for [`Connection::query_limited`] on `db`, reconnected
+  /// once its session has closed.
+  async fn limited_client(&self, db: &str) -> anyhow::Result<Arc<tokio::sync::Mutex<Client>>> {
+    let tunnel_port = self.ensure_tunnel().await?;
+    {
+      let guard = self
+        .live
+        .lock()
+        .map_err(|_| anyhow!("postgres live lock poisoned"))?;
+      if let Some(live) = guard.as_ref()
+        && let Some(client) = live.limited.get(db)
+        && !matches!(client.try_lock(), Ok(c) if c.is_closed())
+      {
+        return Ok(Arc::clone(client));
+      }
+    }
+
+    let config = self.build_conn_string(db, tunnel_port);
+    let client = connect_with_ssl(&config, self.ssl_mode).await?;
+    let client = Arc::new(tokio::sync::Mutex::new(client));
+
+    let mut guard = self
+      .live
+      .lock()
+      .map_err(|_| anyhow!("postgres live lock poisoned"))?;
+    let live = guard
+      .as_mut()
+      .ok_or_else(|| anyhow!("postgres live missing after ensure_tunnel"))?;
+    live.limited.insert(db.to_string(), Arc::clone(&client));
+    Ok(client)
+  }
+
+  /// An idle count client for `db`, or a new one when all are busy.
+  async fn count_client(&self, db: &str) -> anyhow::Result<Client> {
+    let tunnel_port = self.ensure_tunnel().await?;
+    {
+      let mut guard = self
+        .live
+        .lock()
+        .map_err(|_| anyhow!("postgres live lock poisoned"))?;
+      if let Some(idle) = guard.as_mut().and_then(|l| l.count_clients.get_mut(db)) {
+        idle.retain(|c| !c.is_closed());
+        if let Some(client) = idle.pop() {
+          return Ok(client);
+        }
+      }
+    }
+    let config = self.build_conn_string(db, tunnel_port);
+    connect_with_ssl(&config, self.ssl_mode).await
+  }
+
+  /// Return a count client that finished its statement to the idle list.
+  fn release_count_client(&self, db: &str, client: Client) {
+    let Ok(mut guard) = self.live.lock() else {
+      return;
+    };
+    if let Some(live) = guard.as_mut()
+      && !client.is_closed()
+    {
+      let idle = live.count_clients.entry(db.to_string()).or_default();
+      if idle.len() < IDLE_COUNT_CLIENTS {
+        idle.push(client);
+      }
+    }
+  }
+
   pub async fn databases(&self) -> anyhow::Result<Vec<String>> {
     let client = self.get_client("postgres").await?;
     let sql = "SELECT datname FROM pg_database WHERE datistemplate = false";
@@ -387,16 +994,29 @@ impl PostgresConnection {
     sql: &str,
     cancel: Option<&crate::cancel::CancelToken>,
   ) -> anyhow::Result<usize> {
-    let client = self.get_client(&self.database()).await?;
-    let row = crate::cancel::with_cancel(cancel, async {
-      client
-        .query_one(sql, &[])
-        .await
-        .map_err(|e| anyhow::anyhow!(e))
-    })
-    .await?;
-    let total: u32 = row.get(0);
-    Ok(total as usize)
+    crate::cancel::with_cancel(cancel, self.count_one(sql)).await
+  }
+
+  /// The `bigint` that `sql` returns. With a cancel token in scope it runs on
+  /// a count client of its own, so the cancel request reaches this statement
+  /// alone; a cancelled count drops its client rather than returning it.
+  async fn count_one(&self, sql: &str) -> anyhow::Result<usize> {
+    let Some(cancel) = crate::cancel::current() else {
+      let client = self.get_client(&self.database()).await?;
+      let row = client.query_one(sql, &[]).await?;
+      return Ok(row.get::<_, i64>(0) as usize);
+    };
+    let db = self.database();
+    let client = self.count_client(&db).await?;
+    let row = {
+      let _hook = cancel_on(client.cancel_token(), self.ssl_mode, Some(&cancel))?;
+      client.query_one(sql, &[]).await?
+    };
+    // A cancel request sent as the count finished could still hit the next one.
+    if !cancel.is_cancelled() {
+      self.release_count_client(&db, client);
+    }
+    Ok(row.get::<_, i64>(0) as usize)
   }
 
   /// Run a query with optional cooperative cancellation (select races cancel token).
@@ -413,24 +1033,7 @@ impl PostgresConnection {
       t.check()?;
     }
 
-    let stmt = crate::cancel::with_cancel(cancel, async {
-      client.prepare(sql).await.map_err(|e| anyhow::anyhow!(e))
-    })
-    .await?;
-
-    let rows = crate::cancel::with_cancel(cancel, async {
-      client
-        .query(&stmt, &[])
-        .await
-        .map_err(|e| anyhow::anyhow!(e))
-    })
-    .await?;
-
-    if let Some(t) = cancel {
-      t.check()?;
-    }
-    let grid = decode::rows_to_grid(stmt.columns(), &rows, sql);
-    crate::preview::grid_to_raw_arrow_data(grid)
+    query_client(&client, sql, &[], cancel).await
   }
 
   fn database(&self) -> String {
@@ -438,12 +1041,228 @@ impl PostgresConnection {
   }
 
   async fn _table_row_count(&self, table: &str, cond: &str) -> anyhow::Result<usize> {
-    let client = self.get_client(&self.database()).await?;
     let sql = self._table_count_sql(table, cond);
-    let row = client.query_one(&sql, &[]).await?;
-    let total: u32 = row.get::<_, u32>(0);
-    Ok(total.as_usize())
+    self.count_one(&sql).await
+  }
+}
+
+async fn query_client(
+  client: &Client,
+  sql: &str,
+  params: &[ParamValue],
+  cancel: Option<&crate::cancel::CancelToken>,
+) -> anyhow::Result<RawArrowData> {
+  let stmt = crate::cancel::with_cancel(cancel, async {
+    client.prepare(sql).await.map_err(|e| anyhow::anyhow!(e))
+  })
+  .await?;
+
+  let rows = crate::cancel::with_cancel(cancel, async {
+    client
+      .query(&stmt, &sql_params(params))
+      .await
+      .map_err(|e| anyhow::anyhow!(e))
+  })
+  .await?;
+
+  if let Some(t) = cancel {
+    t.check()?;
+  }
+  let (titles, batch) = type_arrow::rows_to_arrow(stmt.columns(), &rows)?;
+  Ok(RawArrowData {
+    titles: Some(titles),
+    sql: Some(sql.to_string()),
+    ..RawArrowData::from_batch(batch)
+  })
+}
+
+/// Read `sql` within the row and byte caps of `limits`. A query runs through
+/// a portal fetched in chunks; anything else runs whole and is trimmed.
+async fn read_limited(
+  client: &mut Client,
+  sql: &str,
+  limits: &QueryLimits,
+) -> anyhow::Result<RawArrowData> {
+  if !matches!(first_stmt("postgres", sql), Some(Statement::Query(_))) {
+    let mut raw = query_client(client, sql, &[], None).await?;
+    limits::truncate(&mut raw, limits);
+    return Ok(raw);
+  }
+  let tx = client.transaction().await?;
+  let stmt = tx.prepare(sql).await?;
+  let portal = tx.bind(&stmt, &[]).await?;
+  let mut budget = limits::Budget::new(limits);
+  let mut rows = vec![];
+  loop {
+    let wanted = budget.wanted(limits::FETCH_ROWS);
+    let chunk = tx.query_portal(&portal, wanted as i32).await?;
+    let fetched = chunk.len();
+    // Decoding is only needed up front to cost the chunk in bytes.
+    let bytes = match limits.max_bytes {
+      Some(_) => type_arrow::rows_to_arrow(stmt.columns(), &chunk)?.1.get_array_memory_size(),
+      None => 0,
+    };
+    let keep = budget.admit(fetched, bytes);
+    rows.extend(chunk.into_iter().take(keep));
+    if fetched < wanted || budget.truncated {
+      break;
+    }
+  }
+  // Ends the read-only transaction and closes the portal.
+  tx.commit().await?;
+  let (titles, batch) = type_arrow::rows_to_arrow(stmt.columns(), &rows)?;
+  Ok(RawArrowData {
+    titles: Some(titles),
+    sql: Some(sql.to_string()),
+    truncated: budget.truncated,
+    ..RawArrowData::from_batch(batch)
+  })
+}
+
+/// Send an out-of-band cancel request for the statement running on the
+/// client behind `pg_cancel` when `cancel` fires.
+fn cancel_on(
+  pg_cancel: tokio_postgres::CancelToken,
+  ssl_mode: SslMode,
+  cancel: Option<&crate::cancel::CancelToken>,
+) -> anyhow::Result<StatementGuard> {
+  let runtime = tokio::runtime::Handle::current();
+  let interrupt = move || {
+    let sent = runtime.block_on(async move {
+      match ssl_mode {
+        SslMode::Disable => pg_cancel.cancel_query(NoTls).await.map_err(anyhow::Error::from),
+        SslMode::Require => {
+          let connector = native_tls::TlsConnector::builder().build()?;
+          let tls = postgres_native_tls::MakeTlsConnector::new(connector);
+          pg_cancel.cancel_query(tls).await.map_err(anyhow::Error::from)
+        }
+      }
+    });
+    if let Err(e) = sent {
+      log::warn!("postgres cancel request failed: {e}");
+    }
+  };
+  Ok(crate::cancel::interrupt_statement(cancel, interrupt)?)
+}
+
+fn sql_params(values: &[ParamValue]) -> Vec<&(dyn tokio_postgres::types::ToSql + Sync)> {
+  values.iter().map(|v| v as _).collect()
+}
+
+/// Transaction on its own client; dropping the client closes the session.
+pub struct PostgresTransaction {
+  /// Keeps the SSH tunnel alive while the transaction is open.
+  _owner: PostgresConnection,
+  client: Mutex<Option<Arc<Client>>>,
+}
+
+impl PostgresTransaction {
+  fn client(&self) -> anyhow::Result<Arc<Client>> {
+    let guard = self
+      .client
+      .lock()
+      .map_err(|_| anyhow!("postgres transaction lock poisoned"))?;
+    guard.clone().ok_or_else(transaction::finished)
+  }
+
+  async fn finish(&self, sql: &str) -> anyhow::Result<()> {
+    let client = self
+      .client
+      .lock()
+      .map_err(|_| anyhow!("postgres transaction lock poisoned"))?
+      .take()
+      .ok_or_else(transaction::finished)?;
+    client.batch_execute(sql).await?;
+    Ok(())
+  }
+}
+
+#[async_trait]
+impl Transaction for PostgresTransaction {
+  fn dialect(&self) -> &'static str {
+    "postgres"
+  }
+
+  async fn query(&self, sql: &str) -> anyhow::Result<RawArrowData> {
+    let client = self.client()?;
+    query_client(&client, sql, &[], None).await
+  }
+
+  async fn execute(&self, sql: &str) -> anyhow::Result<usize> {
+    let affected = self.client()?.execute(sql, &[]).await?;
+    Ok(affected as usize)
+  }
+
+  async fn commit(&self) -> anyhow::Result<()> {
+    self.finish("COMMIT").await
   }
+
+  async fn rollback(&self) -> anyhow::Result<()> {
+    self.finish("ROLLBACK").await
+  }
+}
+
+const CURSOR_NAME: &str = "duckling_cursor";
+
+/// Named cursor on its own client; dropping the client ends the transaction.
+pub struct PostgresCursor {
+  /// Keeps the SSH tunnel alive while the cursor is open.
+  _owner: PostgresConnection,
+  client: Mutex<Option<Arc<Client>>>,
+  sql: String,
+}
+
+impl PostgresCursor {
+  fn client(&self) -> anyhow::Result<Arc<Client>> {
+    let guard = self
+      .client
+      .lock()
+      .map_err(|_| anyhow!("postgres cursor lock poisoned"))?;
+    guard.clone().ok_or_else(cursor::closed)
+  }
+}
+
+#[async_trait]
+impl ResultCursor for PostgresCursor {
+  fn dialect(&self) -> &'static str {
+    "postgres"
+  }
+
+  async fn fetch(&self, rows: usize) -> anyhow::Result<RawArrowData> {
+    let fetch = format!("FETCH FORWARD {rows} FROM {CURSOR_NAME}");
+    let client = self.client()?;
+    let mut page = query_client(&client, &fetch, &[], None).await?;
+    page.sql = Some(self.sql.clone());
+    Ok(page)
+  }
+
+  async fn close(&self) -> anyhow::Result<()> {
+    let client = self
+      .client
+      .lock()
+      .map_err(|_| anyhow!("postgres cursor lock poisoned"))?
+      .take()
+      .ok_or_else(cursor::closed)?;
+    client.batch_execute(&format!("CLOSE {CURSOR_NAME}; COMMIT")).await?;
+    Ok(())
+  }
+}
+
+fn quote_literal(s: &str) -> String {
+  format!("'{}'", s.replace('\'', "''"))
+}
+
+/// `pg_constraint.confupdtype` / `confdeltype` code to its SQL spelling.
+fn fk_action(code: &str) -> Option<String> {
+  let action = match code {
+    "a" => "NO ACTION",
+    "r" => "RESTRICT",
+    "c" => "CASCADE",
+    "n" => "SET NULL",
+    "d" => "SET DEFAULT",
+    _ => return None,
+  };
+  Some(action.to_string())
 }
 
 async fn connect_with_ssl(s: &str, ssl_mode: SslMode) -> anyhow::Result<Client> {
@@ -480,6 +1299,17 @@ fn ssl_mode_parse() {
   assert_eq!(SslMode::Require.as_str(), "require");
 }
 
+#[test]
+fn fdw_options_render() {
+  let conn = PostgresConnection::default();
+  assert_eq!(conn.fdw_options(&[]), "");
+  let options = vec!["schema_name=public".to_string(), "table_name=o'neil".to_string()];
+  assert_eq!(
+    conn.fdw_options(&options),
+    " OPTIONS (schema_name 'public', table_name 'o''neil')"
+  );
+}
+
 #[tokio::test]
 async fn query_cancellable_respects_precheck() {
   use crate::cancel::CancelToken;
@@ -1,10 +1,20 @@
+//! Postgres rows → Arrow with native column types: NUMERIC as Decimal128,
+//! DATE / TIME / TIMESTAMP(TZ) as Date32 / Time64 / Timestamp, UUID as
+//! FixedSizeBinary(16), INTERVAL as Interval(MonthDayNano) and arrays as
+//! `List<T>`. Types without a mapping fall back to Utf8, as does a NUMERIC
+//! or date/time column holding a value its Arrow type cannot (`NaN`,
+//! `infinity`).
+
+use std::collections::HashMap;
+use std::error::Error;
 use std::sync::Arc;
 
 use arrow::array::*;
-use arrow::datatypes::{DataType, Field};
-use chrono::NaiveDate;
-use rust_decimal::Decimal;
-use tokio_postgres::types::Type;
+use arrow::buffer::{NullBuffer, OffsetBuffer};
+use arrow::datatypes::{DataType, Field, IntervalMonthDayNano, TimeUnit};
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Utc};
+use rust_decimal::{Decimal, RoundingStrategy};
+use tokio_postgres::types::{FromSql, Kind, Type};
 use tokio_postgres::{Column, Row};
 
 use crate::utils::{
@@ -12,59 +22,163 @@ use crate::utils::{
   truncate_utf8_for_preview, Title,
 };
 
-pub fn col_to_arrow_type(column: &Column) -> DataType {
-  match *column.type_() {
-    Type::BOOL => DataType::Boolean,
-    Type::INT2 | Type::INT4 | Type::INT8 => DataType::Int64,
-    Type::FLOAT4 | Type::FLOAT8 => DataType::Float64,
-    Type::BYTEA => DataType::Binary,
-    Type::TEXT
-    | Type::VARCHAR
-    | Type::NAME
-    | Type::BPCHAR
-    | Type::JSON
-    | Type::JSONB
-    | Type::NUMERIC
-    | Type::DATE
-    | Type::TIME
-    | Type::TIMESTAMP
-    | Type::TIMESTAMPTZ
-    | Type::UUID
-    | Type::TS_VECTOR
-    | Type::BOOL_ARRAY
-    | Type::INT2_ARRAY
-    | Type::INT4_ARRAY
-    | Type::INT8_ARRAY
-    | Type::FLOAT4_ARRAY
-    | Type::FLOAT8_ARRAY
-    | Type::TEXT_ARRAY
-    | Type::VARCHAR_ARRAY
-    | Type::JSON_ARRAY
-    | Type::JSONB_ARRAY
-    | Type::ANYENUM => DataType::Utf8,
-    _ => DataType::Utf8,
+/// Widest precision of Decimal128.
+const MAX_DECIMAL_PRECISION: u8 = 38;
+/// Largest scale `rust_decimal` can hold.
+const MAX_DECIMAL_SCALE: u32 = 28;
+/// Days from 0001-01-01 (CE) to 1970-01-01.
+const EPOCH_DAYS_FROM_CE: i32 = 719_163;
+/// Field metadata key naming an Arrow extension type.
+const EXTENSION_NAME_KEY: &str = "ARROW:extension:name";
+/// Canonical extension name of UUID columns.
+const UUID_EXTENSION: &str = "arrow.uuid";
+
+type BoxError = Box<dyn Error + Sync + Send>;
+
+/// Whether `field` holds UUIDs (FixedSizeBinary(16) tagged `arrow.uuid`).
+pub(crate) fn is_uuid_field(field: &Field) -> bool {
+  field.data_type() == &DataType::FixedSizeBinary(16)
+    && field.metadata().get(EXTENSION_NAME_KEY).map(String::as_str) == Some(UUID_EXTENSION)
+}
+
+/// INTERVAL in the binary wire format: microseconds, days, months.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct PgInterval(IntervalMonthDayNano);
+
+impl<'a> FromSql<'a> for PgInterval {
+  fn from_sql(_: &Type, raw: &'a [u8]) -> Result<Self, BoxError> {
+    let raw: &[u8; 16] = raw.try_into()?;
+    let micros = i64::from_be_bytes(raw[..8].try_into()?);
+    let days = i32::from_be_bytes(raw[8..12].try_into()?);
+    let months = i32::from_be_bytes(raw[12..].try_into()?);
+    let nanos = micros.saturating_mul(1_000);
+    Ok(Self(IntervalMonthDayNano::new(months, days, nanos)))
+  }
+
+  fn accepts(ty: &Type) -> bool {
+    *ty == Type::INTERVAL
   }
 }
 
+/// UUID as its 16 raw bytes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct PgUuid([u8; 16]);
+
+impl<'a> FromSql<'a> for PgUuid {
+  fn from_sql(_: &Type, raw: &'a [u8]) -> Result<Self, BoxError> {
+    Ok(Self(raw.try_into()?))
+  }
+
+  fn accepts(ty: &Type) -> bool {
+    *ty == Type::UUID
+  }
+}
+
+/// A cell of `T`, or the text of a value `T` cannot hold: NUMERIC `NaN`,
+/// `±Infinity` or too many digits, and date/time `±infinity`.
+enum Lenient<T> {
+  Value(T),
+  Text(String),
+}
+
+impl<'a, T: FromSql<'a>> FromSql<'a> for Lenient<T> {
+  fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, BoxError> {
+    match T::from_sql(ty, raw) {
+      Ok(v) => Ok(Self::Value(v)),
+      Err(e) => special_text(ty, raw).map(Self::Text).ok_or(e),
+    }
+  }
+
+  fn accepts(ty: &Type) -> bool {
+    T::accepts(ty)
+  }
+}
+
+/// Text of a NUMERIC, DATE or TIMESTAMP(TZ) that failed to decode.
+fn special_text(ty: &Type, raw: &[u8]) -> Option<String> {
+  let infinity = |positive: bool| Some(if positive { "infinity" } else { "-infinity" }.into());
+  match *ty {
+    Type::NUMERIC => numeric_text(raw),
+    Type::DATE => match i32::from_be_bytes(raw.try_into().ok()?) {
+      i32::MAX => infinity(true),
+      i32::MIN => infinity(false),
+      _ => None,
+    },
+    Type::TIMESTAMP | Type::TIMESTAMPTZ => match i64::from_be_bytes(raw.try_into().ok()?) {
+      i64::MAX => infinity(true),
+      i64::MIN => infinity(false),
+      _ => None,
+    },
+    _ => None,
+  }
+}
+
+/// Text of a NUMERIC in the binary wire format: digit count, weight of the
+/// first digit, sign and display scale, then base-10000 digits.
+fn numeric_text(raw: &[u8]) -> Option<String> {
+  let word = |i: usize| Some(u16::from_be_bytes(raw.get(i * 2..i * 2 + 2)?.try_into().ok()?));
+  let (ndigits, weight, sign, dscale) = (word(0)?, word(1)? as i16, word(2)?, word(3)?);
+  match sign {
+    0xC000 => return Some("NaN".into()),
+    0xD000 => return Some("Infinity".into()),
+    0xF000 => return Some("-Infinity".into()),
+    _ => {}
+  }
+  let digits = (0..usize::from(ndigits))
+    .map(|i| word(4 + i))
+    .collect::<Option<Vec<_>>>()?;
+  // Digit `i` is worth 10000^(weight - i); positions past either end are 0.
+  let digit = |i: i32| usize::try_from(i).ok().and_then(|i| digits.get(i).copied()).unwrap_or(0);
+  let mut text = String::new();
+  if sign == 0x4000 {
+    text.push('-');
+  }
+  let whole: Vec<u16> = (0..=i32::from(weight)).map(digit).collect();
+  match whole.split_first() {
+    Some((first, rest)) => {
+      text.push_str(&first.to_string());
+      rest.iter().for_each(|d| text.push_str(&format!("{d:04}")));
+    }
+    None => text.push('0'),
+  }
+  if dscale > 0 {
+    let mut fraction = String::new();
+    let mut i = i32::from(weight) + 1;
+    while fraction.len() < usize::from(dscale) {
+      fraction.push_str(&format!("{:04}", digit(i)));
+      i += 1;
+    }
+    fraction.truncate(usize::from(dscale));
+    text.push('.');
+    text.push_str(&fraction);
+  }
+  Some(text)
+}
+
 /// Convert prepared-statement rows directly into a RecordBatch (no JSON hop).
+/// Field types follow the arrays built, so NUMERIC can adapt to its data.
 pub fn rows_to_arrow(
   columns: &[Column],
   rows: &[Row],
 ) -> anyhow::Result<(Vec<Title>, arrow::record_batch::RecordBatch)> {
   let mut titles = Vec::with_capacity(columns.len());
   let mut fields = Vec::with_capacity(columns.len());
-  for col in columns {
+  let mut arrays: Vec<ArrayRef> = Vec::with_capacity(columns.len());
+  for (col_i, col) in columns.iter().enumerate() {
     titles.push(Title {
       name: col.name().to_string(),
       r#type: col.type_().name().to_string(),
     });
-    fields.push(Field::new(col.name(), col_to_arrow_type(col), true));
-  }
-
-  let mut arrays: Vec<ArrayRef> = Vec::with_capacity(columns.len());
-  for (col_i, col) in columns.iter().enumerate() {
-    // Build array from the same DataType used for the field (single source of truth).
-    arrays.push(column_to_array(col_to_arrow_type(col), col, rows, col_i)?);
+    let array = column_to_array(col, rows, col_i)?;
+    let mut field = Field::new(col.name(), array.data_type().clone(), true);
+    if *col.type_() == Type::UUID {
+      field = field.with_metadata(HashMap::from([(
+        EXTENSION_NAME_KEY.to_string(),
+        UUID_EXTENSION.to_string(),
+      )]));
+    }
+    fields.push(field);
+    arrays.push(array);
   }
 
   let batch = record_batch_from_arrays(fields, arrays)?;
@@ -72,146 +186,306 @@ pub fn rows_to_arrow(
   Ok((titles, batch))
 }
 
-fn column_to_array(
-  data_type: DataType,
-  col: &Column,
-  rows: &[Row],
+fn column_to_array(col: &Column, rows: &[Row], col_i: usize) -> anyhow::Result<ArrayRef> {
+  // An array column's typmod is its element's (`numeric(10,2)[]`).
+  let typmod = col.type_modifier();
+  let cells = Cells { rows, col_i, list: false };
+  match col.type_().kind() {
+    Kind::Array(elem) => element_array(elem, typmod, Cells { list: true, ..cells }),
+    _ => element_array(col.type_(), typmod, cells),
+  }
+}
+
+/// Cells of one column: scalars, or PG arrays flattened into a `List`.
+#[derive(Clone, Copy)]
+struct Cells<'a> {
+  rows: &'a [Row],
   col_i: usize,
-) -> anyhow::Result<ArrayRef> {
-  // Prefer DataType dispatch so schema and array stay aligned.
-  // For INT/FLOAT/BOOL/BYTEA still use PG wire type for correct try_get width.
-  Ok(match data_type {
-    DataType::Boolean => {
-      let values: Vec<Option<bool>> = rows
+  list: bool,
+}
+
+impl Cells<'_> {
+  /// Decode every cell as `T` (undecodable → null) and build the array with
+  /// `to_array`; for list columns that builds the child of the `List`.
+  fn build<T>(self, to_array: impl FnOnce(Vec<Option<T>>) -> ArrayRef) -> anyhow::Result<ArrayRef>
+  where
+    T: for<'b> FromSql<'b>,
+  {
+    if !self.list {
+      let values = self
+        .rows
         .iter()
-        .map(|r| r.try_get::<_, Option<bool>>(col_i).ok().flatten())
+        .map(|r| r.try_get::<_, Option<T>>(self.col_i).ok().flatten())
         .collect();
-      Arc::new(BooleanArray::from(values))
+      return Ok(to_array(values));
     }
-    DataType::Int64 => match *col.type_() {
-      Type::INT2 => {
-        let values: Vec<Option<i64>> = rows
-          .iter()
-          .map(|r| {
-            r.try_get::<_, Option<i16>>(col_i)
-              .ok()
-              .flatten()
-              .map(|v| v as i64)
-          })
-          .collect();
-        Arc::new(Int64Array::from(values))
-      }
-      Type::INT4 => {
-        let values: Vec<Option<i64>> = rows
-          .iter()
-          .map(|r| {
-            r.try_get::<_, Option<i32>>(col_i)
-              .ok()
-              .flatten()
-              .map(|v| v as i64)
-          })
-          .collect();
-        Arc::new(Int64Array::from(values))
-      }
-      _ => {
-        let values: Vec<Option<i64>> = rows
-          .iter()
-          .map(|r| r.try_get::<_, Option<i64>>(col_i).ok().flatten())
-          .collect();
-        Arc::new(Int64Array::from(values))
-      }
-    },
-    DataType::Float64 => match *col.type_() {
-      Type::FLOAT4 => {
-        let values: Vec<Option<f64>> = rows
-          .iter()
-          .map(|r| {
-            r.try_get::<_, Option<f32>>(col_i)
-              .ok()
-              .flatten()
-              .map(|v| v as f64)
-          })
-          .collect();
-        Arc::new(Float64Array::from(values))
-      }
-      _ => {
-        let values: Vec<Option<f64>> = rows
-          .iter()
-          .map(|r| r.try_get::<_, Option<f64>>(col_i).ok().flatten())
-          .collect();
-        Arc::new(Float64Array::from(values))
-      }
-    },
-    DataType::Binary => {
-      let values: Vec<Option<Vec<u8>>> = rows
-        .iter()
-        .map(|r| {
-          r.try_get::<_, Option<Vec<u8>>>(col_i)
-            .ok()
-            .flatten()
-            .map(|b| truncate_bytes_for_preview(&b))
-        })
-        .collect();
-      let mut builder = BinaryBuilder::new();
-      for v in values {
-        match v {
-          Some(bytes) => builder.append_value(bytes),
-          None => builder.append_null(),
+    let mut values = vec![];
+    let mut offsets = vec![0i32];
+    let mut valid = Vec::with_capacity(self.rows.len());
+    for r in self.rows {
+      // Multi-dimensional arrays do not decode into a Vec and become null.
+      match r.try_get::<_, Option<Vec<Option<T>>>>(self.col_i).ok().flatten() {
+        Some(items) => {
+          values.extend(items);
+          valid.push(true);
         }
+        None => valid.push(false),
       }
-      Arc::new(builder.finish())
+      offsets.push(i32::try_from(values.len())?);
     }
+    let child = to_array(values);
+    let field = Arc::new(Field::new_list_field(child.data_type().clone(), true));
+    let offsets = OffsetBuffer::new(offsets.into());
+    let list = ListArray::try_new(field, offsets, child, Some(NullBuffer::from(valid)))?;
+    Ok(Arc::new(list))
+  }
+
+  /// [`build`](Self::build) for types with values `T` cannot hold: when any
+  /// cell has one, the column (or list child) is Utf8 instead.
+  fn build_lenient<T>(
+    self,
+    to_array: impl FnOnce(Vec<Option<T>>) -> ArrayRef,
+  ) -> anyhow::Result<ArrayRef>
+  where
+    T: for<'b> FromSql<'b> + ToString,
+  {
+    self.build(|v: Vec<Option<Lenient<T>>>| {
+      if !v.iter().flatten().any(|c| matches!(c, Lenient::Text(_))) {
+        let values = v.into_iter().map(|c| match c {
+          Some(Lenient::Value(v)) => Some(v),
+          _ => None,
+        });
+        return to_array(values.collect());
+      }
+      let text = v.into_iter().map(|c| match c? {
+        Lenient::Value(v) => Some(v.to_string()),
+        Lenient::Text(t) => Some(t),
+      });
+      Arc::new(StringArray::from_iter(text))
+    })
+  }
+}
+
+fn element_array(ty: &Type, typmod: i32, cells: Cells) -> anyhow::Result<ArrayRef> {
+  match *ty {
+    Type::BOOL => cells.build(|v: Vec<Option<bool>>| Arc::new(BooleanArray::from(v))),
+    Type::CHAR => cells.build(|v: Vec<Option<i8>>| Arc::new(Int8Array::from(v))),
+    Type::INT2 => cells.build(|v: Vec<Option<i16>>| Arc::new(Int16Array::from(v))),
+    Type::INT4 => cells.build(|v: Vec<Option<i32>>| Arc::new(Int32Array::from(v))),
+    Type::INT8 => cells.build(|v: Vec<Option<i64>>| Arc::new(Int64Array::from(v))),
+    Type::OID => cells.build(|v: Vec<Option<u32>>| Arc::new(UInt32Array::from(v))),
+    Type::FLOAT4 => cells.build(|v: Vec<Option<f32>>| Arc::new(Float32Array::from(v))),
+    Type::FLOAT8 => cells.build(|v: Vec<Option<f64>>| Arc::new(Float64Array::from(v))),
+    Type::NUMERIC => cells.build_lenient(|v: Vec<Option<Decimal>>| decimal_array(typmod, &v)),
+    Type::DATE => cells.build_lenient(|v: Vec<Option<NaiveDate>>| {
+      let days = v.into_iter().map(|d| d.map(|d| d.num_days_from_ce() - EPOCH_DAYS_FROM_CE));
+      Arc::new(Date32Array::from_iter(days))
+    }),
+    Type::TIME => cells.build(|v: Vec<Option<NaiveTime>>| {
+      let micros = v.into_iter().map(|t| {
+        t.map(|t| t.num_seconds_from_midnight() as i64 * 1_000_000 + t.nanosecond() as i64 / 1_000)
+      });
+      Arc::new(Time64MicrosecondArray::from_iter(micros))
+    }),
+    Type::TIMESTAMP => cells.build_lenient(|v: Vec<Option<NaiveDateTime>>| {
+      let micros = v.into_iter().map(|t| t.map(|t| t.and_utc().timestamp_micros()));
+      Arc::new(TimestampMicrosecondArray::from_iter(micros))
+    }),
+    Type::TIMESTAMPTZ => cells.build_lenient(|v: Vec<Option<DateTime<Utc>>>| {
+      let micros = v.into_iter().map(|t| t.map(|t| t.timestamp_micros()));
+      Arc::new(TimestampMicrosecondArray::from_iter(micros).with_timezone_utc())
+    }),
+    Type::INTERVAL => cells.build(|v: Vec<Option<PgInterval>>| {
+      Arc::new(IntervalMonthDayNanoArray::from_iter(v.into_iter().map(|i| i.map(|i| i.0))))
+    }),
+    Type::UUID => cells.build(|v: Vec<Option<PgUuid>>| {
+      let nulls = NullBuffer::from_iter(v.iter().map(Option::is_some));
+      let bytes: Vec<u8> = v.iter().flat_map(|u| u.map_or([0; 16], |u| u.0)).collect();
+      Arc::new(FixedSizeBinaryArray::new(16, bytes.into(), Some(nulls)))
+    }),
+    Type::BYTEA => cells.build(|v: Vec<Option<Vec<u8>>>| {
+      let bytes = v.iter().map(|b| b.as_deref().map(truncate_bytes_for_preview));
+      Arc::new(BinaryArray::from_iter(bytes))
+    }),
+    Type::JSON | Type::JSONB => cells.build(|v: Vec<Option<serde_json::Value>>| {
+      let text = v.iter().map(|j| j.as_ref().map(|j| truncate_utf8_for_preview(&j.to_string())));
+      Arc::new(StringArray::from_iter(text))
+    }),
+    _ if cells.list => cells.build(|v: Vec<Option<String>>| {
+      let text = v.iter().map(|s| s.as_deref().map(truncate_utf8_for_preview));
+      Arc::new(StringArray::from_iter(text))
+    }),
     // Utf8 and everything else: multi-step string fallback (L1 → null).
-    _ => Arc::new(StringArray::from(string_column(col, rows, col_i))),
+    _ => Ok(Arc::new(StringArray::from(string_column(cells.rows, cells.col_i)))),
+  }
+}
+
+/// Arrow type of a declared column type for comparing schemas, as
+/// [`element_array`] maps its values: `base` is the lower-cased name without
+/// its parameters (`timestamp with time zone`), which are in `params`. `None`
+/// for types without an Arrow counterpart.
+pub fn declared_arrow_type(base: &str, params: Option<&str>) -> Option<DataType> {
+  Some(match base {
+    "bool" | "boolean" => DataType::Boolean,
+    "smallint" | "int2" | "smallserial" | "serial2" => DataType::Int16,
+    "integer" | "int" | "int4" | "serial" | "serial4" => DataType::Int32,
+    "bigint" | "int8" | "bigserial" | "serial8" => DataType::Int64,
+    "oid" => DataType::UInt32,
+    // float(p) is a real up to 24 bits of precision.
+    "float" if params.and_then(|p| p.parse::<u8>().ok()).is_some_and(|p| p <= 24) => {
+      DataType::Float32
+    }
+    "real" | "float4" => DataType::Float32,
+    "double precision" | "float8" | "float" => DataType::Float64,
+    "date" => DataType::Date32,
+    "time" | "time without time zone" => DataType::Time64(TimeUnit::Microsecond),
+    "timestamp" | "timestamp without time zone" => {
+      DataType::Timestamp(TimeUnit::Microsecond, None)
+    }
+    "timestamptz" | "timestamp with time zone" => {
+      DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into()))
+    }
+    "bytea" => DataType::Binary,
+    _ => return None,
   })
 }
 
-fn string_column(col: &Column, rows: &[Row], col_i: usize) -> Vec<Option<String>> {
-  match *col.type_() {
-    Type::NUMERIC => rows
-      .iter()
-      .map(|r| {
-        r.try_get::<_, Option<Decimal>>(col_i)
-          .ok()
-          .flatten()
-          .map(|d| d.to_string())
-      })
-      .collect(),
-    Type::DATE => rows
-      .iter()
-      .map(|r| {
-        r.try_get::<_, Option<NaiveDate>>(col_i)
-          .ok()
-          .flatten()
-          .map(|d| d.to_string())
-      })
-      .collect(),
-    Type::JSON | Type::JSONB => rows
-      .iter()
-      .map(|r| {
-        r.try_get::<_, Option<serde_json::Value>>(col_i)
-          .ok()
-          .flatten()
-          .map(|v| truncate_utf8_for_preview(&v.to_string()))
-      })
-      .collect(),
-    _ => rows
-      .iter()
-      .map(|r| {
-        if let Ok(v) = r.try_get::<_, Option<String>>(col_i) {
-          return v.map(|s| truncate_utf8_for_preview(&s));
-        }
-        if let Ok(v) = r.try_get::<_, Option<serde_json::Value>>(col_i) {
-          return v.map(|j| truncate_utf8_for_preview(&j.to_string()));
-        }
-        if let Ok(v) = r.try_get::<_, Option<Vec<u8>>>(col_i) {
-          return v.map(|b| {
-            let s = String::from_utf8_lossy(&truncate_bytes_for_preview(&b)).into_owned();
-            truncate_utf8_for_preview(&s)
-          });
-        }
-        None
-      })
-      .collect(),
+/// `numeric(p, s)` from a column typmod; `None` when unconstrained or outside
+/// what Decimal128 / `rust_decimal` can hold (including negative scales).
+fn numeric_typmod(typmod: i32) -> Option<(u8, u32)> {
+  // typmod = ((precision << 16) | scale) + VARHDRSZ
+  let typmod = typmod.checked_sub(4).filter(|t| *t >= 0)?;
+  let precision = u8::try_from(typmod >> 16).ok()?;
+  let scale = (typmod & 0xffff) as u32;
+  let fits = (1..=MAX_DECIMAL_PRECISION).contains(&precision)
+    && scale <= u32::from(precision)
+    && scale <= MAX_DECIMAL_SCALE;
+  fits.then_some((precision, scale))
+}
+
+/// Decimal128 at the column's precision/scale; unconstrained NUMERIC uses the
+/// largest scale in the data at precision 38. Text when a value does not fit.
+fn decimal_array(typmod: i32, values: &[Option<Decimal>]) -> ArrayRef {
+  let (precision, scale) = numeric_typmod(typmod).unwrap_or_else(|| {
+    let scale = values.iter().flatten().map(Decimal::scale).max().unwrap_or(0);
+    (MAX_DECIMAL_PRECISION, scale)
+  });
+  let limit = 10i128.pow(u32::from(precision));
+  let scaled: Option<Vec<Option<i128>>> = values
+    .iter()
+    .map(|v| match v {
+      None => Some(None),
+      Some(d) => rescale(*d, scale).filter(|m| m.abs() < limit).map(Some),
+    })
+    .collect();
+  let array = scaled.and_then(|scaled| {
+    Decimal128Array::from(scaled)
+      .with_precision_and_scale(precision, scale as i8)
+      .ok()
+  });
+  match array {
+    Some(array) => Arc::new(array),
+    None => Arc::new(StringArray::from_iter(values.iter().map(|d| d.map(|d| d.to_string())))),
+  }
+}
+
+/// Mantissa of `d` with exactly `scale` fractional digits (extra digits
+/// rounded half away from zero, as Postgres does).
+fn rescale(d: Decimal, scale: u32) -> Option<i128> {
+  let d = d.round_dp_with_strategy(scale, RoundingStrategy::MidpointAwayFromZero);
+  10i128.checked_pow(scale - d.scale())?.checked_mul(d.mantissa())
+}
+
+fn string_column(rows: &[Row], col_i: usize) -> Vec<Option<String>> {
+  rows
+    .iter()
+    .map(|r| {
+      if let Ok(v) = r.try_get::<_, Option<String>>(col_i) {
+        return v.map(|s| truncate_utf8_for_preview(&s));
+      }
+      if let Ok(v) = r.try_get::<_, Option<serde_json::Value>>(col_i) {
+        return v.map(|j| truncate_utf8_for_preview(&j.to_string()));
+      }
+      if let Ok(v) = r.try_get::<_, Option<Vec<u8>>>(col_i) {
+        return v.map(|b| {
+          let s = String::from_utf8_lossy(&truncate_bytes_for_preview(&b)).into_owned();
+          truncate_utf8_for_preview(&s)
+        });
+      }
+      None
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use std::str::FromStr;
+
+  use arrow::datatypes::DataType;
+
+  use super::*;
+
+  #[test]
+  fn numeric_uses_typmod_or_data_scale() {
+    // numeric(10, 2), unconstrained, numeric(1000, 0) and a negative scale.
+    assert_eq!(numeric_typmod(((10 << 16) | 2) + 4), Some((10, 2)));
+    assert_eq!(numeric_typmod(-1), None);
+    assert_eq!(numeric_typmod((1000 << 16) + 4), None);
+    assert_eq!(numeric_typmod(((5 << 16) | 0x7fe) + 4), None);
+
+    let d = |s: &str| Some(Decimal::from_str(s).unwrap());
+    let fixed = decimal_array(((10 << 16) | 2) + 4, &[d("1.5"), None, d("-12.345")]);
+    assert_eq!(fixed.data_type(), &DataType::Decimal128(10, 2));
+    let fixed = fixed.as_any().downcast_ref::<Decimal128Array>().unwrap();
+    assert_eq!(fixed.value(0), 150);
+    assert!(fixed.is_null(1));
+    assert_eq!(fixed.value(2), -1235);
+
+    let free = decimal_array(-1, &[d("1.25"), d("100.123456")]);
+    assert_eq!(free.data_type(), &DataType::Decimal128(38, 6));
+    let max = "79228162514264337593543950335";
+    let wide = decimal_array(-1, &[d(max), d("0.0000000000000000000000000001")]);
+    assert_eq!(wide.data_type(), &DataType::Utf8);
+  }
+
+  #[test]
+  fn decodes_interval_and_uuid_wire_format() {
+    let mut raw = vec![];
+    raw.extend(90_000_000i64.to_be_bytes());
+    raw.extend(3i32.to_be_bytes());
+    raw.extend(14i32.to_be_bytes());
+    let interval = PgInterval::from_sql(&Type::INTERVAL, &raw).unwrap();
+    assert_eq!(interval.0, IntervalMonthDayNano::new(14, 3, 90_000_000_000));
+    assert!(PgInterval::from_sql(&Type::INTERVAL, &raw[..8]).is_err());
+
+    let uuid = PgUuid::from_sql(&Type::UUID, &[7; 16]).unwrap();
+    assert_eq!(uuid.0, [7; 16]);
+    assert!(PgUuid::accepts(&Type::UUID) && !PgUuid::accepts(&Type::TEXT));
+  }
+
+  #[test]
+  fn special_values_render_as_text() {
+    let numeric = |weight: i16, sign: u16, dscale: u16, digits: &[u16]| {
+      let mut raw = vec![];
+      for word in [digits.len() as u16, weight as u16, sign, dscale].iter().chain(digits) {
+        raw.extend(word.to_be_bytes());
+      }
+      numeric_text(&raw).unwrap()
+    };
+    assert_eq!(numeric(0, 0xC000, 0, &[]), "NaN");
+    assert_eq!(numeric(0, 0xF000, 0, &[]), "-Infinity");
+    assert_eq!(numeric(1, 0x4000, 3, &[12, 3456, 7800]), "-123456.780");
+    assert_eq!(numeric(-2, 0, 6, &[1200]), "0.000012");
+    assert_eq!(numeric(0, 0, 0, &[]), "0");
+
+    let date = |days: i32| special_text(&Type::DATE, &days.to_be_bytes());
+    assert_eq!(date(i32::MAX).as_deref(), Some("infinity"));
+    assert_eq!(date(i32::MIN).as_deref(), Some("-infinity"));
+    assert_eq!(date(0), None);
+    let ts = special_text(&Type::TIMESTAMPTZ, &i64::MIN.to_be_bytes());
+    assert_eq!(ts.as_deref(), Some("-infinity"));
+
+    let date = Lenient::<NaiveDate>::from_sql(&Type::DATE, &i32::MAX.to_be_bytes()).unwrap();
+    assert!(matches!(date, Lenient::Text(t) if t == "infinity"));
   }
 }
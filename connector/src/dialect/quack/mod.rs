@@ -70,6 +70,9 @@ impl Connection for QuackConnection {
         batch,
         titles: Some(titles),
         sql: Some(sql),
+        estimated: false,
+        cursor: None,
+        truncated: false,
       })
     })
     .await
@@ -0,0 +1,116 @@
+//! Row totals for paged results: exact `count(*)`, catalog estimates, or
+//! whatever is known when an exact count runs past its time budget.
+
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::dialect::Connection;
+
+/// How `query_table` / `paging_query` compute `total`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CountOptions {
+  /// Use catalog statistics instead of `count(*)` when the result is a whole
+  /// table (no filter). Such totals are flagged `estimated`.
+  pub estimate: bool,
+  /// Abandon an exact count after this many milliseconds and fall back to an
+  /// estimate, or to the rows seen so far.
+  pub timeout_ms: Option<u64>,
+}
+
+impl CountOptions {
+  fn timeout(&self) -> Option<Duration> {
+    self.timeout_ms.filter(|ms| *ms > 0).map(Duration::from_millis)
+  }
+}
+
+/// A `total` and whether it came from an estimate rather than an exact count.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct RowCount {
+  pub total: usize,
+  pub estimated: bool,
+}
+
+impl RowCount {
+  fn exact(total: usize) -> Self {
+    Self {
+      total,
+      estimated: false,
+    }
+  }
+
+  fn estimated(total: usize) -> Self {
+    Self {
+      total,
+      estimated: true,
+    }
+  }
+}
+
+/// Total rows of a result. `table` is set when the result is that whole table,
+/// unfiltered, so catalog estimates apply; `exact` is its `count(*)`; `seen`
+/// (offset plus rows fetched) is the floor reported when neither an exact count
+/// nor an estimate arrives in time. `None` when counting failed outright.
+pub(crate) async fn count<C, F>(
+  conn: &C,
+  table: Option<&str>,
+  options: &CountOptions,
+  seen: usize,
+  exact: F,
+) -> Option<RowCount>
+where
+  C: Connection + ?Sized,
+  F: Future<Output = anyhow::Result<usize>> + Send,
+{
+  if options.estimate
+    && let Some(t) = table
+    && let Ok(n) = conn.estimate_row_count(t).await
+  {
+    return Some(RowCount::estimated(n.max(seen)));
+  }
+  let Some(timeout) = options.timeout() else {
+    return exact.await.ok().map(RowCount::exact);
+  };
+  // The deadline cancels the count's token, so the server stops counting too.
+  let started = Instant::now();
+  match crate::cancel::with_timeout(timeout, exact).await {
+    Ok(n) => Some(RowCount::exact(n)),
+    Err(_) if started.elapsed() < timeout => None,
+    Err(_) => {
+      log::info!("row count abandoned after {timeout:?}");
+      let fallback = match table {
+        Some(t) if !options.estimate => conn.estimate_row_count(t).await.ok(),
+        _ => None,
+      };
+      Some(RowCount::estimated(fallback.unwrap_or(0).max(seen)))
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::dialect::sqlite::SqliteConnection;
+
+  #[tokio::test]
+  async fn slow_exact_counts_fall_back_to_rows_seen() {
+    let conn = SqliteConnection {
+      path: ":memory:".into(),
+    };
+    let options = CountOptions {
+      estimate: false,
+      timeout_ms: Some(10),
+    };
+    let slow = async {
+      tokio::time::sleep(Duration::from_secs(5)).await;
+      Ok(1_000_000)
+    };
+    let total = count(&conn, Some("t"), &options, 42, slow).await;
+    assert_eq!(total, Some(RowCount::estimated(42)));
+
+    let fast = async { Ok(7) };
+    let total = count(&conn, Some("t"), &options, 5, fast).await;
+    assert_eq!(total, Some(RowCount::exact(7)));
+  }
+}
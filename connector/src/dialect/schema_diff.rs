@@ -0,0 +1,588 @@
+//! Schema comparison between two connections, possibly of different dialects.
+//!
+//! Column types are reduced to a canonical family (`int32`, `varchar(20)`,
+//! `timestamp`, ...) before comparing, so spelling differences such as MySQL
+//! `int` vs Postgres `integer`/`int4` are not reported. Each dialect maps its
+//! declared types to Arrow next to its `type_arrow` value mapping.
+
+use std::collections::HashMap;
+
+use anyhow::bail;
+use arrow::datatypes::DataType;
+use serde::{Deserialize, Serialize};
+
+use super::{Connection, clickhouse, duckdb, mssql, mysql, postgres, sqlite};
+use crate::utils::Metadata;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColumnDef {
+  pub name: String,
+  /// Type as reported by the connection.
+  pub r#type: String,
+  /// Canonical type used for comparison.
+  pub normalized: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableDef {
+  pub table: String,
+  pub columns: Vec<ColumnDef>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColumnChange {
+  pub name: String,
+  pub source: ColumnDef,
+  pub target: ColumnDef,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TableChange {
+  pub table: String,
+  /// Columns only in the source.
+  pub added_columns: Vec<ColumnDef>,
+  /// Columns only in the target.
+  pub removed_columns: Vec<ColumnDef>,
+  pub changed_columns: Vec<ColumnChange>,
+}
+
+/// Differences that would have to be applied to the target to match the source.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SchemaDiff {
+  pub source_dialect: String,
+  pub target_dialect: String,
+  /// Tables only in the source.
+  pub added_tables: Vec<TableDef>,
+  /// Tables only in the target.
+  pub removed_tables: Vec<TableDef>,
+  pub changed_tables: Vec<TableChange>,
+  pub unchanged_tables: usize,
+}
+
+impl SchemaDiff {
+  pub fn is_empty(&self) -> bool {
+    self.added_tables.is_empty() && self.removed_tables.is_empty() && self.changed_tables.is_empty()
+  }
+}
+
+/// Compare the tables of `source` and `target`. `*_schema` restricts each side to
+/// one database/schema (matched against [`Metadata::database`]); tables and
+/// columns are matched by case-insensitive name.
+pub async fn diff_schemas(
+  source: &dyn Connection,
+  source_schema: Option<&str>,
+  target: &dyn Connection,
+  target_schema: Option<&str>,
+) -> anyhow::Result<SchemaDiff> {
+  let src = source.all_columns().await?;
+  let dst = target.all_columns().await?;
+  diff_metadata(
+    (source.dialect(), &src, source_schema),
+    (target.dialect(), &dst, target_schema),
+  )
+}
+
+/// `(dialect, all_columns output, schema filter)` for one side of a diff.
+pub type SchemaSide<'a> = (&'a str, &'a [Metadata], Option<&'a str>);
+
+pub fn diff_metadata(source: SchemaSide<'_>, target: SchemaSide<'_>) -> anyhow::Result<SchemaDiff> {
+  let (src_dialect, src_tables) = (source.0, collect_tables(source)?);
+  let (dst_dialect, dst_tables) = (target.0, collect_tables(target)?);
+  let dst_index: HashMap<String, &TableDef> = dst_tables
+    .iter()
+    .map(|t| (t.table.to_lowercase(), t))
+    .collect();
+
+  let mut diff = SchemaDiff {
+    source_dialect: src_dialect.to_string(),
+    target_dialect: dst_dialect.to_string(),
+    ..Default::default()
+  };
+  for table in &src_tables {
+    match dst_index.get(&table.table.to_lowercase()) {
+      None => diff.added_tables.push(table.clone()),
+      Some(other) => {
+        let change = diff_columns(table, other);
+        if change.added_columns.is_empty()
+          && change.removed_columns.is_empty()
+          && change.changed_columns.is_empty()
+        {
+          diff.unchanged_tables += 1;
+        } else {
+          diff.changed_tables.push(change);
+        }
+      }
+    }
+  }
+  let src_names: std::collections::HashSet<String> =
+    src_tables.iter().map(|t| t.table.to_lowercase()).collect();
+  diff.removed_tables = dst_tables
+    .into_iter()
+    .filter(|t| !src_names.contains(&t.table.to_lowercase()))
+    .collect();
+  Ok(diff)
+}
+
+fn collect_tables((dialect, metadata, schema): SchemaSide<'_>) -> anyhow::Result<Vec<TableDef>> {
+  let mut seen: HashMap<String, &str> = HashMap::new();
+  let mut out = vec![];
+  for meta in metadata {
+    if schema.is_some_and(|s| s != meta.database) {
+      continue;
+    }
+    if let Some(db) = seen.insert(meta.table.to_lowercase(), &meta.database) {
+      bail!(
+        "table {} exists in both {db} and {}; pick a schema to compare",
+        meta.table,
+        meta.database
+      );
+    }
+    out.push(TableDef {
+      table: meta.table.clone(),
+      columns: meta
+        .columns
+        .iter()
+        .map(|(name, r#type)| ColumnDef {
+          name: name.clone(),
+          r#type: r#type.clone(),
+          normalized: normalize_type(dialect, r#type),
+        })
+        .collect(),
+    });
+  }
+  out.sort_by(|a, b| a.table.cmp(&b.table));
+  Ok(out)
+}
+
+fn diff_columns(source: &TableDef, target: &TableDef) -> TableChange {
+  let dst: HashMap<String, &ColumnDef> = target
+    .columns
+    .iter()
+    .map(|c| (c.name.to_lowercase(), c))
+    .collect();
+  let mut change = TableChange {
+    table: source.table.clone(),
+    ..Default::default()
+  };
+  for col in &source.columns {
+    match dst.get(&col.name.to_lowercase()) {
+      None => change.added_columns.push(col.clone()),
+      Some(other) if other.normalized != col.normalized => {
+        change.changed_columns.push(ColumnChange {
+          name: col.name.clone(),
+          source: col.clone(),
+          target: (*other).clone(),
+        })
+      }
+      Some(_) => {}
+    }
+  }
+  let src: std::collections::HashSet<String> =
+    source.columns.iter().map(|c| c.name.to_lowercase()).collect();
+  change.removed_columns = target
+    .columns
+    .iter()
+    .filter(|c| !src.contains(&c.name.to_lowercase()))
+    .cloned()
+    .collect();
+  change
+}
+
+/// Reduce a dialect-specific type name to a canonical, comparable spelling.
+/// Character, decimal, JSON and UUID types get shared SQL spellings; the rest
+/// go through the dialect's declared-type mapping in its `type_arrow` module
+/// and [`normalize_arrow_type`]. Unknown types come back lower-cased with
+/// whitespace collapsed.
+pub fn normalize_type(dialect: &str, raw: &str) -> String {
+  let mut t = raw.trim();
+  if dialect == "clickhouse" {
+    // Wrappers do not change the value type.
+    t = clickhouse::base_type(t);
+    if let Some(inner) = t.strip_prefix("Array(").and_then(|s| s.strip_suffix(')')) {
+      return format!("{}[]", normalize_type(dialect, inner));
+    }
+  }
+  if let Some(inner) = t.strip_suffix("[]") {
+    return format!("{}[]", normalize_type(dialect, inner));
+  }
+  if dialect == "sqlite" {
+    // Declared types only select an affinity or a date convention.
+    return normalize_arrow_type(&sqlite::declared_arrow_type(t));
+  }
+
+  let (base, params) = split_params(&t.to_ascii_lowercase());
+  let params = params.map(|p| p.replace(' ', ""));
+  if let Some(shared) = shared_type(dialect, &base, params.as_deref()) {
+    return shared;
+  }
+  let declared = match dialect {
+    "mysql" => mysql::declared_arrow_type(&base, params.as_deref()),
+    "postgres" => postgres::declared_arrow_type(&base, params.as_deref()),
+    "mssql" => mssql::declared_arrow_type(&base, params.as_deref()),
+    "clickhouse" => clickhouse::declared_arrow_type(t),
+    _ if is_duckdb(dialect) => duckdb::declared_arrow_type(&base),
+    _ => None,
+  };
+  match (declared, params) {
+    (Some(data_type), _) => normalize_arrow_type(&data_type),
+    (None, Some(p)) => format!("{base}({p})"),
+    (None, None) => base,
+  }
+}
+
+/// DuckDB and the file, folder and remote connections it backs.
+fn is_duckdb(dialect: &str) -> bool {
+  matches!(dialect, "duckdb" | "file" | "folder" | "quack")
+}
+
+/// Types Arrow cannot tell apart or size: character types keep their length,
+/// decimals their precision, JSON and UUID their name.
+fn shared_type(dialect: &str, base: &str, params: Option<&str>) -> Option<String> {
+  Some(match base {
+    // DuckDB ignores VARCHAR(n); Postgres varchar without a length is text.
+    "varchar" | "character varying" | "nvarchar" | "varchar2" => match params {
+      Some(p) if !is_duckdb(dialect) => format!("varchar({p})"),
+      _ => "text".into(),
+    },
+    "char" | "character" | "bpchar" | "nchar" | "fixedstring" => {
+      format!("char({})", params.unwrap_or("1"))
+    }
+    "text" | "tinytext" | "mediumtext" | "longtext" | "ntext" | "string" | "clob" => "text".into(),
+    "decimal" | "numeric" | "dec" | "fixed" => match params {
+      Some(p) if !p.contains(',') => format!("decimal({p},0)"),
+      Some(p) => format!("decimal({p})"),
+      None => "decimal".into(),
+    },
+    "json" | "jsonb" => "json".into(),
+    "uuid" | "uniqueidentifier" => "uuid".into(),
+    "timetz" | "time with time zone" => "timetz".into(),
+    _ => return None,
+  })
+}
+
+/// `character varying(20)` -> (`character varying`, `20`);
+/// `timestamp(3) with time zone` -> (`timestamp with time zone`, `3`).
+fn split_params(t: &str) -> (String, Option<String>) {
+  let (Some(open), Some(close)) = (t.find('('), t.rfind(')')) else {
+    return (collapse_ws(t), None);
+  };
+  if close < open {
+    return (collapse_ws(t), None);
+  }
+  let base = format!("{} {}", &t[..open], &t[close + 1..]);
+  (collapse_ws(&base), Some(t[open + 1..close].trim().to_string()))
+}
+
+fn collapse_ws(s: &str) -> String {
+  s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Canonical type (see [`normalize_type`]) for an Arrow result column.
+pub fn normalize_arrow_type(dt: &DataType) -> String {
+  match dt {
+    DataType::Int8 => "int8".into(),
+    DataType::Int16 => "int16".into(),
+    DataType::Int32 => "int32".into(),
+    DataType::Int64 => "int64".into(),
+    DataType::UInt8 => "uint8".into(),
+    DataType::UInt16 => "uint16".into(),
+    DataType::UInt32 => "uint32".into(),
+    DataType::UInt64 => "uint64".into(),
+    DataType::Float16 | DataType::Float32 => "float32".into(),
+    DataType::Float64 => "float64".into(),
+    DataType::Decimal32(p, s)
+    | DataType::Decimal64(p, s)
+    | DataType::Decimal128(p, s)
+    | DataType::Decimal256(p, s) => format!("decimal({p},{s})"),
+    DataType::Boolean => "bool".into(),
+    DataType::Binary
+    | DataType::LargeBinary
+    | DataType::BinaryView
+    | DataType::FixedSizeBinary(_) => "binary".into(),
+    DataType::Date32 | DataType::Date64 => "date".into(),
+    DataType::Time32(_) | DataType::Time64(_) => "time".into(),
+    DataType::Timestamp(_, None) => "timestamp".into(),
+    DataType::Timestamp(_, Some(_)) => "timestamptz".into(),
+    DataType::Interval(_) => "interval".into(),
+    DataType::List(f) | DataType::LargeList(f) | DataType::FixedSizeList(f, _) => {
+      format!("{}[]", normalize_arrow_type(f.data_type()))
+    }
+    DataType::Struct(_) | DataType::Map(_, _) => "json".into(),
+    DataType::Dictionary(_, value) => normalize_arrow_type(value),
+    _ => "text".into(),
+  }
+}
+
+/// Spell a canonical type (see [`normalize_type`]) for `dialect`.
+pub fn render_type(dialect: &str, normalized: &str) -> String {
+  if let Some(inner) = normalized.strip_suffix("[]") {
+    let inner = render_type(dialect, inner);
+    return match dialect {
+      "clickhouse" => format!("Array({inner})"),
+      "mysql" | "sqlite" => "json".to_string(),
+      "mssql" => "nvarchar(max)".to_string(),
+      _ => format!("{inner}[]"),
+    };
+  }
+  let (base, params) = split_params(normalized);
+  let p = params.unwrap_or_default();
+  let (mysql, postgres, sqlite, duckdb, clickhouse, mssql) = match base.as_str() {
+    "int8" => ("tinyint", "smallint", "integer", "tinyint", "Int8", "smallint"),
+    "int16" => ("smallint", "smallint", "integer", "smallint", "Int16", "smallint"),
+    "int32" => ("int", "integer", "integer", "integer", "Int32", "int"),
+    "int64" => ("bigint", "bigint", "integer", "bigint", "Int64", "bigint"),
+    "int128" => ("decimal(39,0)", "numeric(39,0)", "integer", "hugeint", "Int128", "decimal(38,0)"),
+    "uint8" => ("tinyint unsigned", "smallint", "integer", "utinyint", "UInt8", "tinyint"),
+    "uint16" => ("smallint unsigned", "integer", "integer", "usmallint", "UInt16", "int"),
+    "uint32" => ("int unsigned", "bigint", "integer", "uinteger", "UInt32", "bigint"),
+    "uint64" => (
+      "bigint unsigned",
+      "numeric(20,0)",
+      "integer",
+      "ubigint",
+      "UInt64",
+      "decimal(20,0)",
+    ),
+    "float32" => ("float", "real", "real", "float", "Float32", "real"),
+    "float64" => ("double", "double precision", "real", "double", "Float64", "float"),
+    "bool" => ("boolean", "boolean", "integer", "boolean", "Bool", "bit"),
+    "text" => ("text", "text", "text", "varchar", "String", "nvarchar(max)"),
+    "binary" => ("longblob", "bytea", "blob", "blob", "String", "varbinary(max)"),
+    "date" => ("date", "date", "text", "date", "Date32", "date"),
+    "time" => ("time", "time", "text", "time", "String", "time"),
+    "timetz" => ("time", "timetz", "text", "timetz", "String", "time"),
+    "timestamp" => ("datetime", "timestamp", "text", "timestamp", "DateTime64(6)", "datetime2"),
+    "timestamptz" => (
+      "timestamp",
+      "timestamptz",
+      "text",
+      "timestamptz",
+      "DateTime64(6)",
+      "datetimeoffset",
+    ),
+    // Only Postgres and DuckDB have a month/day/time interval; the rest keep
+    // ISO 8601 duration text.
+    "interval" => ("varchar(64)", "interval", "text", "interval", "String", "nvarchar(64)"),
+    "json" => ("json", "jsonb", "text", "json", "String", "nvarchar(max)"),
+    "uuid" => ("char(36)", "uuid", "text", "uuid", "UUID", "uniqueidentifier"),
+    "decimal" => {
+      let d = if p.is_empty() {
+        "decimal".to_string()
+      } else {
+        format!("decimal({p})")
+      };
+      return match dialect {
+        "sqlite" => "numeric".to_string(),
+        "postgres" => d.replace("decimal", "numeric"),
+        "clickhouse" => d.replace("decimal", "Decimal"),
+        _ => d,
+      };
+    }
+    "varchar" | "char" => {
+      return match dialect {
+        "sqlite" | "duckdb" => if base == "char" { "text" } else { "varchar" }.to_string(),
+        "clickhouse" => "String".to_string(),
+        // nvarchar / nchar hold at most 4000 characters inline.
+        "mssql" => match p.parse::<usize>() {
+          Ok(n) if n <= 4000 => format!("n{base}({n})"),
+          _ => "nvarchar(max)".to_string(),
+        },
+        _ => format!("{base}({p})"),
+      };
+    }
+    // Unrecognized: keep the source spelling.
+    _ => return normalized.to_string(),
+  };
+  match dialect {
+    "mysql" => mysql,
+    "postgres" => postgres,
+    "sqlite" => sqlite,
+    "clickhouse" => clickhouse,
+    "mssql" => mssql,
+    _ => duckdb,
+  }
+  .to_string()
+}
+
+/// SQL that makes the target of `diff` match its source. Statements the target
+/// dialect cannot express are emitted as `--` comments.
+pub fn migration_sql<C: Connection + ?Sized>(
+  target: &C,
+  schema: Option<&str>,
+  diff: &SchemaDiff,
+) -> Vec<String> {
+  let dialect = target.dialect();
+  let table_ref = |table: &str| match schema.filter(|s| !s.is_empty()) {
+    Some(s) => format!("{}.{}", target.quote(s), target.quote(table)),
+    None => target.quote(table),
+  };
+  let column_def =
+    |c: &ColumnDef| format!("{} {}", target.quote(&c.name), render_type(dialect, &c.normalized));
+  let mut out = vec![];
+
+  for table in &diff.added_tables {
+    let cols = table
+      .columns
+      .iter()
+      .map(|c| format!("  {}", column_def(c)))
+      .collect::<Vec<_>>()
+      .join(",\n");
+    // ClickHouse tables need an engine; keep the migration runnable.
+    let engine = if dialect == "clickhouse" {
+      " ENGINE = MergeTree ORDER BY tuple()"
+    } else {
+      ""
+    };
+    out.push(format!("CREATE TABLE {} (\n{cols}\n){engine}", table_ref(&table.table)));
+  }
+
+  for change in &diff.changed_tables {
+    let t = table_ref(&change.table);
+    for col in &change.added_columns {
+      out.push(match dialect {
+        "mssql" => format!("ALTER TABLE {t} ADD {}", column_def(col)),
+        _ => format!("ALTER TABLE {t} ADD COLUMN {}", column_def(col)),
+      });
+    }
+    for col in &change.changed_columns {
+      let name = target.quote(&col.target.name);
+      let ty = render_type(dialect, &col.source.normalized);
+      out.push(match dialect {
+        "mysql" | "clickhouse" => format!("ALTER TABLE {t} MODIFY COLUMN {name} {ty}"),
+        "mssql" => format!("ALTER TABLE {t} ALTER COLUMN {name} {ty}"),
+        "sqlite" => format!(
+          "-- sqlite cannot change the type of {t}.{name} ({} -> {ty}); rebuild the table",
+          col.target.r#type
+        ),
+        _ => format!("ALTER TABLE {t} ALTER COLUMN {name} TYPE {ty}"),
+      });
+    }
+    for col in &change.removed_columns {
+      out.push(format!("ALTER TABLE {t} DROP COLUMN {}", target.quote(&col.name)));
+    }
+  }
+
+  for table in &diff.removed_tables {
+    out.push(format!("DROP TABLE {}", table_ref(&table.table)));
+  }
+  out
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::dialect::mssql::MssqlConnection;
+  use crate::dialect::postgres::PostgresConnection;
+
+  fn meta(db: &str, table: &str, cols: &[(&str, &str)]) -> Metadata {
+    Metadata {
+      database: db.into(),
+      table: table.into(),
+      columns: cols
+        .iter()
+        .map(|(n, t)| (n.to_string(), t.to_string()))
+        .collect(),
+    }
+  }
+
+  #[test]
+  fn equivalent_spellings_normalize_together() {
+    let same = |a: (&str, &str), b: (&str, &str)| {
+      assert_eq!(normalize_type(a.0, a.1), normalize_type(b.0, b.1), "{a:?} vs {b:?}");
+    };
+    same(("mysql", "int(11)"), ("postgres", "int4"));
+    same(("mysql", "int"), ("postgres", "integer"));
+    same(("mysql", "bigint(20)"), ("clickhouse", "Nullable(Int64)"));
+    same(("mysql", "varchar(20)"), ("postgres", "character varying(20)"));
+    same(("mysql", "decimal(10, 2)"), ("postgres", "numeric(10,2)"));
+    same(("mysql", "tinyint(1)"), ("duckdb", "BOOLEAN"));
+    same(("postgres", "timestamp(3) without time zone"), ("mysql", "datetime(3)"));
+    same(("postgres", "integer[]"), ("clickhouse", "Array(Int32)"));
+    same(("sqlite", "INTEGER"), ("duckdb", "BIGINT"));
+    same(("sqlite", "DATE"), ("mysql", "date"));
+    same(("mssql", "money"), ("mysql", "decimal(19,4)"));
+    same(("mssql", "float(24)"), ("postgres", "real"));
+    same(("clickhouse", "DateTime('UTC')"), ("postgres", "timestamptz"));
+    same(("duckdb", "HUGEINT"), ("postgres", "numeric(38)"));
+    assert_ne!(normalize_type("postgres", "int8"), normalize_type("clickhouse", "Int8"));
+    assert_ne!(normalize_type("mysql", "int"), normalize_type("mysql", "int unsigned"));
+  }
+
+  #[test]
+  fn reports_table_and_column_changes() {
+    let src = vec![
+      meta("app", "users", &[("id", "int"), ("email", "varchar(255)"), ("age", "int")]),
+      meta("app", "orders", &[("id", "bigint")]),
+      meta("other", "ignored", &[("id", "int")]),
+    ];
+    let dst = vec![
+      meta("public", "users", &[("id", "integer"), ("email", "text"), ("legacy", "text")]),
+      meta("public", "audit", &[("id", "bigint")]),
+    ];
+    let diff = diff_metadata(
+      ("mysql", &src, Some("app")),
+      ("postgres", &dst, Some("public")),
+    )
+    .unwrap();
+    assert_eq!(diff.added_tables.len(), 1);
+    assert_eq!(diff.added_tables[0].table, "orders");
+    assert_eq!(diff.removed_tables[0].table, "audit");
+    let users = &diff.changed_tables[0];
+    assert_eq!(users.added_columns[0].name, "age");
+    assert_eq!(users.removed_columns[0].name, "legacy");
+    assert_eq!(users.changed_columns.len(), 1);
+    assert_eq!(users.changed_columns[0].name, "email");
+
+    let sql = migration_sql(&PostgresConnection::default(), Some("public"), &diff);
+    assert_eq!(
+      sql,
+      vec![
+        "CREATE TABLE public.orders (\n  id bigint\n)",
+        "ALTER TABLE public.users ADD COLUMN age integer",
+        "ALTER TABLE public.users ALTER COLUMN email TYPE varchar(255)",
+        "ALTER TABLE public.users DROP COLUMN legacy",
+        "DROP TABLE public.audit",
+      ]
+    );
+  }
+
+  #[test]
+  fn mssql_targets_get_tsql_types_and_ddl() {
+    let spell = |t: &str| render_type("mssql", t);
+    assert_eq!(spell("bool"), "bit");
+    assert_eq!(spell("binary"), "varbinary(max)");
+    assert_eq!(spell("float64"), "float");
+    assert_eq!(spell("int128"), "decimal(38,0)");
+    assert_eq!(spell("timestamp"), "datetime2");
+    assert_eq!(spell("timestamptz"), "datetimeoffset");
+    assert_eq!(spell("text"), "nvarchar(max)");
+    assert_eq!(spell("uuid"), "uniqueidentifier");
+    assert_eq!(spell("decimal(10,2)"), "decimal(10,2)");
+    assert_eq!(spell("varchar(20)"), "nvarchar(20)");
+    assert_eq!(spell("varchar(10000)"), "nvarchar(max)");
+    assert_eq!(spell("int32[]"), "nvarchar(max)");
+
+    let src = vec![meta(
+      "app",
+      "users",
+      &[("id", "int"), ("email", "varchar(255)"), ("ok", "bool")],
+    )];
+    let dst = vec![meta("dbo", "users", &[("id", "int"), ("email", "nvarchar(50)")])];
+    let diff = diff_metadata(("mysql", &src, Some("app")), ("mssql", &dst, Some("dbo"))).unwrap();
+    let sql = migration_sql(&MssqlConnection::default(), Some("dbo"), &diff);
+    assert_eq!(
+      sql,
+      vec![
+        "ALTER TABLE dbo.users ADD ok bit",
+        "ALTER TABLE dbo.users ALTER COLUMN email nvarchar(255)",
+      ]
+    );
+  }
+
+  #[test]
+  fn ambiguous_table_names_need_a_schema() {
+    let src = vec![meta("a", "t", &[]), meta("b", "t", &[])];
+    assert!(diff_metadata(("mysql", &src, None), ("mysql", &[], None)).is_err());
+    assert!(diff_metadata(("mysql", &src, Some("a")), ("mysql", &[], None)).is_ok());
+  }
+}
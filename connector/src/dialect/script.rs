@@ -0,0 +1,272 @@
+//! Multi-statement script execution shared by all dialects.
+
+use std::time::Instant;
+
+use arrow::array::{Array, AsArray};
+use arrow::compute::cast;
+use arrow::datatypes::{DataType, Int64Type};
+use arrow::record_batch::RecordBatch;
+use sqlparser::ast::Statement;
+
+use super::ast::{self, SqlParseLocation};
+use super::{Caps, Connection, CountOptions, Transaction};
+use crate::utils::RawArrowData;
+
+/// What [`Connection::run_script`] does after a statement fails.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ScriptErrorMode {
+  /// Stop at the first failing statement (later statements are not run).
+  #[default]
+  Stop,
+  /// Record the failure and keep going.
+  Continue,
+}
+
+/// Outcome of one statement in a script.
+#[derive(Debug)]
+pub struct StatementOutcome {
+  /// 0-based position of the statement in the script.
+  pub index: usize,
+  pub sql: String,
+  /// 1-based start position of the statement within the script.
+  pub line: u64,
+  pub column: u64,
+  /// Result set for row-returning statements.
+  pub result: Option<RawArrowData>,
+  /// Rows affected for DDL/DML run through [`Connection::execute`].
+  pub rows_affected: Option<usize>,
+  /// Milliseconds spent on this statement.
+  pub elapsed: u128,
+  pub error: Option<anyhow::Error>,
+  /// sqlparser location, only attached when the server also rejected the statement.
+  pub parse_location: Option<SqlParseLocation>,
+}
+
+/// Whether a parsed statement produces a result set.
+fn returns_rows(stmt: &Statement) -> bool {
+  match stmt {
+    Statement::Insert(insert) => insert.returning.is_some(),
+    Statement::Update { returning, .. } => returning.is_some(),
+    Statement::Delete(delete) => delete.returning.is_some(),
+    _ => matches!(
+      stmt,
+      Statement::Query(_)
+        | Statement::Explain { .. }
+        | Statement::ExplainTable { .. }
+        | Statement::Pragma { .. }
+        | Statement::ShowFunctions { .. }
+        | Statement::ShowVariable { .. }
+        | Statement::ShowStatus { .. }
+        | Statement::ShowVariables { .. }
+        | Statement::ShowCreate { .. }
+        | Statement::ShowColumns { .. }
+        | Statement::ShowDatabases { .. }
+        | Statement::ShowSchemas { .. }
+        | Statement::ShowCharset(_)
+        | Statement::ShowObjects(_)
+        | Statement::ShowTables { .. }
+        | Statement::ShowViews { .. }
+        | Statement::ShowCollation { .. }
+    ),
+  }
+}
+
+/// Split `sql` for the connection's dialect and run each statement in order.
+///
+/// The whole script runs on one [`Connection::script_session`] when the dialect
+/// hands one out, so variables, `USE`, temp tables and transactions carry over;
+/// whatever transaction the script leaves open is rolled back at the end.
+///
+/// Row-returning statements return the first `limit` rows plus a total (all rows
+/// when `limit` is `None`). Other statements are executed for rows affected when
+/// the dialect advertises [`Caps::EXECUTE`]. Statements sqlparser cannot parse are
+/// still sent as queries; their parse location is reported only if the server
+/// rejects them too.
+pub async fn run_script<C: Connection + ?Sized>(
+  conn: &C,
+  sql: &str,
+  limit: Option<usize>,
+  mode: ScriptErrorMode,
+) -> anyhow::Result<Vec<StatementOutcome>> {
+  let can_execute = conn.capabilities().contains(Caps::EXECUTE);
+  let session = conn.script_session().await?;
+  let mut outcomes = vec![];
+  for (index, item) in ast::split_statements(conn.dialect(), sql)
+    .into_iter()
+    .enumerate()
+  {
+    let start = Instant::now();
+    let wants_rows = item.stmt.as_ref().is_none_or(returns_rows);
+    let (result, rows_affected, error) = if wants_rows || !can_execute {
+      let res = match &session {
+        Some(session) => query_session(session.as_ref(), &item.sql, limit).await,
+        None => query_conn(conn, &item.sql, limit).await,
+      };
+      match res {
+        Ok(raw) => (Some(raw), None, None),
+        Err(err) => (None, None, Some(err)),
+      }
+    } else {
+      let res = match &session {
+        Some(session) => session.execute(&item.sql).await,
+        None => conn.execute(&item.sql).await,
+      };
+      match res {
+        Ok(n) => (None, Some(n), None),
+        Err(err) => (None, None, Some(err)),
+      }
+    };
+    let failed = error.is_some();
+    outcomes.push(StatementOutcome {
+      index,
+      sql: item.sql,
+      line: item.line,
+      column: item.column,
+      result,
+      rows_affected,
+      elapsed: start.elapsed().as_millis(),
+      error,
+      parse_location: if failed { item.parse_error } else { None },
+    });
+    if failed && mode == ScriptErrorMode::Stop {
+      break;
+    }
+  }
+  if let Some(session) = session
+    && let Err(e) = session.rollback().await
+  {
+    // No transaction left open (SQLite reports that as an error).
+    log::debug!("script session release: {e}");
+  }
+  Ok(outcomes)
+}
+
+async fn query_conn<C: Connection + ?Sized>(
+  conn: &C,
+  sql: &str,
+  limit: Option<usize>,
+) -> anyhow::Result<RawArrowData> {
+  match limit {
+    Some(limit) => {
+      let count = CountOptions::default();
+      conn.paging_query(sql, Some(limit), Some(0), &count).await
+    }
+    None => conn.query(sql, 0, 0).await,
+  }
+}
+
+/// [`Connection::paging_query`] on a script session: the first `limit` rows,
+/// counted on the same session when the page is full.
+async fn query_session(
+  session: &dyn Transaction,
+  sql: &str,
+  limit: Option<usize>,
+) -> anyhow::Result<RawArrowData> {
+  let dialect = session.dialect();
+  let stmt = ast::first_stmt(dialect, sql);
+  let paged = match (limit, &stmt) {
+    (Some(limit), Some(stmt)) => ast::limit_stmt(dialect, stmt, Some(limit), Some(0)),
+    _ => None,
+  };
+  let mut raw = session.query(paged.as_deref().unwrap_or(sql)).await?;
+  raw.total = raw.batch.num_rows();
+  if paged.is_some()
+    && limit == Some(raw.total)
+    && let Some(count_sql) = stmt.as_ref().and_then(|s| ast::count_stmt(dialect, s))
+  {
+    let counted = session.query(&count_sql).await?;
+    raw.total = first_count(&counted.batch).unwrap_or(raw.total);
+  }
+  raw.sql = Some(sql.to_string());
+  Ok(raw)
+}
+
+/// First cell of a `count(*)` result, whatever integer type the engine returns.
+fn first_count(batch: &RecordBatch) -> Option<usize> {
+  let column = cast(batch.columns().first()?, &DataType::Int64).ok()?;
+  let column = column.as_primitive_opt::<Int64Type>()?;
+  (!column.is_empty() && column.is_valid(0)).then(|| column.value(0) as usize)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::dialect::sqlite::SqliteConnection;
+
+  fn temp_db() -> (SqliteConnection, std::path::PathBuf) {
+    let path = std::env::temp_dir().join(format!("duckling_script_{}.db", nanoid::nanoid!(8)));
+    let conn = SqliteConnection {
+      path: path.to_string_lossy().to_string(),
+    };
+    (conn, path)
+  }
+
+  const SCRIPT: &str = "create table t (id integer primary key, name text);
+insert into t (id, name) values (1, 'a'), (2, 'b');
+select * from nope;
+update t set name = 'c';
+select id, name from t order by id";
+
+  #[tokio::test]
+  async fn stop_mode_halts_at_first_error() {
+    let (db, path) = temp_db();
+    let out = db.run_script(SCRIPT, Some(100), ScriptErrorMode::Stop).await.unwrap();
+    assert_eq!(out.len(), 3);
+    assert_eq!(out[0].rows_affected, Some(0));
+    assert_eq!(out[1].rows_affected, Some(2));
+    assert!(out[2].error.is_some());
+    assert_eq!(out[2].line, 3);
+    let _ = std::fs::remove_file(path);
+  }
+
+  #[tokio::test]
+  async fn continue_mode_runs_every_statement() {
+    let (db, path) = temp_db();
+    let out = db
+      .run_script(SCRIPT, Some(100), ScriptErrorMode::Continue)
+      .await
+      .unwrap();
+    assert_eq!(out.len(), 5);
+    assert!(out[2].error.is_some());
+    // Semantic error: sqlparser accepted the statement, so no parse location.
+    assert!(out[2].parse_location.is_none());
+    assert_eq!(out[3].rows_affected, Some(2));
+    let last = out[4].result.as_ref().expect("result set");
+    assert_eq!(last.batch.num_rows(), 2);
+    assert_eq!(last.total, 2);
+    let _ = std::fs::remove_file(path);
+  }
+
+  #[tokio::test]
+  async fn statements_share_one_session() {
+    let (db, path) = temp_db();
+    let temp = "create temp table tmp (id integer);
+insert into tmp values (1), (2);
+select id from tmp";
+    let out = db.run_script(temp, Some(100), ScriptErrorMode::Stop).await.unwrap();
+    assert!(out.iter().all(|o| o.error.is_none()));
+    assert_eq!(out[2].result.as_ref().unwrap().batch.num_rows(), 2);
+
+    db.execute("create table t (id integer)").await.unwrap();
+    let open = "begin;\ninsert into t values (1);";
+    let out = db.run_script(open, None, ScriptErrorMode::Stop).await.unwrap();
+    assert_eq!(out[1].rows_affected, Some(1));
+    // The transaction the script left open was rolled back, not leaked.
+    assert_eq!(db.query_count("select count(*) from t").await.unwrap(), 0);
+    let _ = std::fs::remove_file(path);
+  }
+
+  #[tokio::test]
+  async fn syntax_error_reports_script_location() {
+    let (db, path) = temp_db();
+    let out = db
+      .run_script("select 1;\nselect from where;", None, ScriptErrorMode::Continue)
+      .await
+      .unwrap();
+    assert_eq!(out.len(), 2);
+    assert!(out[0].error.is_none());
+    let loc = out[1].parse_location.as_ref().expect("parse location");
+    assert_eq!(loc.line, 2);
+    let _ = std::fs::remove_file(path);
+  }
+}
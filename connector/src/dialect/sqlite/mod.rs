@@ -1,17 +1,24 @@
-mod decode;
-#[allow(dead_code)]
 mod type_arrow;
 
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use arrow::datatypes::{Field, Schema};
 use async_trait::async_trait;
 use rusqlite::Statement;
+use rusqlite::types::{ToSql, ToSqlOutput};
 
+use crate::cancel::{CancelHook, CancelToken};
 use crate::dialect::Connection;
-use crate::dialect::sqlite::decode::statement_to_grid;
-use crate::dialect::sqlite::type_arrow::db_to_arrow_type;
-use crate::preview::grid_to_raw_arrow_data;
+use crate::dialect::cursor::{ResultCursor, ThreadCursor};
+use crate::dialect::ddl::{self, ObjectKind, join_statements};
+use crate::dialect::detail::{
+  ColumnDetail, ForeignKey, IndexDetail, TableDetail, UniqueConstraint, group_sorted,
+};
+use crate::dialect::explain::{self, QueryPlan};
+use crate::dialect::params::{self, ParamValue, Params};
+use crate::dialect::transaction::{self, Transaction};
+pub(crate) use crate::dialect::sqlite::type_arrow::{db_to_arrow_type, declared_arrow_type};
+use crate::dialect::sqlite::type_arrow::{infer_types, read_rows, statement_columns, values_to_raw};
 use crate::utils::{FunctionMeta, Metadata, RawArrowData};
 use crate::utils::{Table, Title, TreeNode, build_tree, get_file_name};
 
@@ -59,12 +66,29 @@ impl Connection for SqliteConnection {
     Ok(vec![name])
   }
 
-  async fn query(&self, sql: &str, limit: usize, offset: usize) -> anyhow::Result<RawArrowData> {
+  async fn query(&self, sql: &str, _limit: usize, _offset: usize) -> anyhow::Result<RawArrowData> {
     let path = self.path.clone();
     let sql = sql.to_string();
+    let cancel = crate::cancel::current();
     crate::dialect::run_blocking(move || {
-      let conn = SqliteConnection { path };
-      conn._query(&sql, limit, offset)
+      let conn = SqliteConnection { path }.connect()?;
+      let _hook = interrupt_on_cancel(&conn, cancel.as_ref())?;
+      query_conn(&conn, &sql, &[])
+    })
+    .await
+  }
+
+  async fn query_params(&self, sql: &str, params: &Params) -> anyhow::Result<RawArrowData> {
+    let path = self.path.clone();
+    let bound = params::bind(self.dialect(), sql, params)?;
+    let sql = sql.to_string();
+    let cancel = crate::cancel::current();
+    crate::dialect::run_blocking(move || {
+      let conn = SqliteConnection { path }.connect()?;
+      let _hook = interrupt_on_cancel(&conn, cancel.as_ref())?;
+      let mut raw = query_conn(&conn, &bound.sql, &bound.values)?;
+      raw.sql = Some(sql);
+      Ok(raw)
     })
     .await
   }
@@ -84,6 +108,142 @@ impl Connection for SqliteConnection {
     "sqlite"
   }
 
+  async fn execute(&self, sql: &str) -> anyhow::Result<usize> {
+    let path = self.path.clone();
+    let sql = sql.to_string();
+    let cancel = crate::cancel::current();
+    crate::dialect::run_blocking(move || {
+      let conn = SqliteConnection { path }.connect()?;
+      let _hook = interrupt_on_cancel(&conn, cancel.as_ref())?;
+      Ok(conn.execute(&sql, [])?)
+    })
+    .await
+  }
+
+  async fn execute_params(&self, sql: &str, params: &Params) -> anyhow::Result<usize> {
+    let path = self.path.clone();
+    let bound = params::bind(self.dialect(), sql, params)?;
+    let cancel = crate::cancel::current();
+    crate::dialect::run_blocking(move || {
+      let conn = SqliteConnection { path }.connect()?;
+      let _hook = interrupt_on_cancel(&conn, cancel.as_ref())?;
+      let args = rusqlite::params_from_iter(&bound.values);
+      Ok(conn.execute(&bound.sql, args)?)
+    })
+    .await
+  }
+
+  async fn primary_key(&self, _schema: Option<&str>, table: &str) -> anyhow::Result<Vec<String>> {
+    let path = self.path.clone();
+    let table = table.to_string();
+    crate::dialect::run_blocking(move || {
+      let conn = SqliteConnection { path }.connect()?;
+      let mut stmt =
+        conn.prepare("SELECT name FROM pragma_table_info(?1) WHERE pk > 0 ORDER BY pk")?;
+      let cols = stmt
+        .query_map([&table], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+      Ok(cols)
+    })
+    .await
+  }
+
+  async fn table_detail(&self, _schema: Option<&str>, table: &str) -> anyhow::Result<TableDetail> {
+    let path = self.path.clone();
+    let table = table.to_string();
+    crate::dialect::run_blocking(move || {
+      let conn = SqliteConnection { path }.connect()?;
+      table_detail(&conn, &table)
+    })
+    .await
+  }
+
+  async fn object_ddl(
+    &self,
+    _schema: Option<&str>,
+    name: &str,
+    kind: ObjectKind,
+  ) -> anyhow::Result<String> {
+    let path = self.path.clone();
+    let name = name.to_string();
+    crate::dialect::run_blocking(move || {
+      let conn = SqliteConnection { path }.connect()?;
+      object_ddl(&conn, &name, kind)
+    })
+    .await
+  }
+
+  async fn begin_transaction(&self) -> anyhow::Result<Box<dyn Transaction>> {
+    let path = self.path.clone();
+    let conn = crate::dialect::run_blocking(move || {
+      let conn = SqliteConnection { path }.connect()?;
+      conn.execute_batch("BEGIN")?;
+      Ok(conn)
+    })
+    .await?;
+    Ok(Box::new(SqliteTransaction {
+      conn: Arc::new(Mutex::new(Some(conn))),
+    }))
+  }
+
+  /// One `rusqlite::Connection` for the whole script.
+  async fn script_session(&self) -> anyhow::Result<Option<Box<dyn Transaction>>> {
+    let path = self.path.clone();
+    let conn = crate::dialect::run_blocking(move || SqliteConnection { path }.connect()).await?;
+    Ok(Some(Box::new(SqliteTransaction {
+      conn: Arc::new(Mutex::new(Some(conn))),
+    })))
+  }
+
+  /// Steps one prepared statement on a worker thread that owns the connection.
+  async fn open_cursor(&self, sql: &str) -> anyhow::Result<Box<dyn ResultCursor>> {
+    let path = self.path.clone();
+    let sql = sql.to_string();
+    let cursor = ThreadCursor::spawn("sqlite", move |requests| {
+      let conn = SqliteConnection { path }.connect()?;
+      let mut stmt = conn.prepare(&sql)?;
+      let metas = statement_columns(&stmt);
+      let mut rows = stmt.query([])?;
+      let mut types = None;
+      while let Some(req) = requests.next_fetch() {
+        let page = read_rows(&mut rows, metas.len(), req.rows).and_then(|columns| {
+          // The first page fixes the column types; a column only changes
+          // later by falling back to text (see `values_to_raw`).
+          let types = types.get_or_insert_with(|| infer_types(&metas, &columns));
+          values_to_raw(&metas, types, columns, &sql)
+        });
+        req.reply(page);
+      }
+      Ok(())
+    })
+    .await?;
+    Ok(Box::new(cursor))
+  }
+
+  /// `EXPLAIN QUERY PLAN`; SQLite has no EXPLAIN ANALYZE, so the plan is
+  /// never executed.
+  async fn explain(&self, sql: &str, analyze: bool) -> anyhow::Result<QueryPlan> {
+    if analyze {
+      explain::check_analyze("sqlite", sql)?;
+    }
+    let path = self.path.clone();
+    let sql = format!("EXPLAIN QUERY PLAN {sql}");
+    crate::dialect::run_blocking(move || {
+      let conn = SqliteConnection { path }.connect()?;
+      let mut stmt = conn.prepare(&sql)?;
+      let rows = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(3)?)))?
+        .collect::<rusqlite::Result<Vec<(i64, i64, String)>>>()?;
+      let raw = rows
+        .iter()
+        .map(|(id, parent, detail)| format!("{id}|{parent}|{detail}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+      Ok(explain::sqlite_tree(&rows, &raw))
+    })
+    .await
+  }
+
   async fn show_schema(&self, _schema: &str) -> anyhow::Result<RawArrowData> {
     let sql = "
       SELECT * FROM sqlite_master
@@ -162,17 +322,20 @@ impl Connection for SqliteConnection {
   }
 }
 
+/// `sqlite3_interrupt` on `conn` when `cancel` fires.
+fn interrupt_on_cancel(
+  conn: &rusqlite::Connection,
+  cancel: Option<&CancelToken>,
+) -> anyhow::Result<Option<CancelHook>> {
+  let handle = conn.get_interrupt_handle();
+  Ok(crate::cancel::interrupt_on(cancel, move || handle.interrupt())?)
+}
+
 impl SqliteConnection {
   fn connect(&self) -> anyhow::Result<rusqlite::Connection> {
     Ok(rusqlite::Connection::open(&self.path)?)
   }
 
-  fn _query(&self, sql: &str, _limit: usize, _offset: usize) -> anyhow::Result<RawArrowData> {
-    let conn = self.connect()?;
-    let mut stmt = conn.prepare(sql)?;
-    let grid = statement_to_grid(&mut stmt, sql)?;
-    grid_to_raw_arrow_data(grid)
-  }
   fn _all_columns(&self) -> anyhow::Result<Vec<Metadata>> {
     let table_names = self._all_table_names()?;
     let conn = self.connect()?;
@@ -253,6 +416,239 @@ impl SqliteConnection {
   }
 }
 
+fn query_conn(
+  conn: &rusqlite::Connection,
+  sql: &str,
+  params: &[ParamValue],
+) -> anyhow::Result<RawArrowData> {
+  let mut stmt = conn.prepare(sql)?;
+  let metas = statement_columns(&stmt);
+  let mut rows = stmt.query(rusqlite::params_from_iter(params))?;
+  let columns = read_rows(&mut rows, metas.len(), usize::MAX)?;
+  let mut types = infer_types(&metas, &columns);
+  values_to_raw(&metas, &mut types, columns, sql)
+}
+
+impl ToSql for ParamValue {
+  fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+    Ok(match self {
+      ParamValue::Null => ToSqlOutput::from(rusqlite::types::Null),
+      ParamValue::Bool(v) => ToSqlOutput::from(*v),
+      ParamValue::Int(v) => ToSqlOutput::from(*v),
+      ParamValue::Float(v) => ToSqlOutput::from(*v),
+      ParamValue::Text(v) => ToSqlOutput::from(v.as_str()),
+    })
+  }
+}
+
+/// DDL as stored in `sqlite_master`; tables also get their indexes and triggers.
+fn object_ddl(conn: &rusqlite::Connection, name: &str, kind: ObjectKind) -> anyhow::Result<String> {
+  if kind == ObjectKind::Function {
+    anyhow::bail!("sqlite has no stored functions");
+  }
+  let sql = "SELECT sql FROM sqlite_master WHERE type = ?1 AND name = ?2 AND sql IS NOT NULL";
+  let mut stmt = conn.prepare(sql)?;
+  let ddl = stmt
+    .query_map([kind.as_str(), name], |row| row.get::<_, String>(0))?
+    .next()
+    .transpose()?
+    .ok_or_else(|| ddl::not_found(kind, name))?;
+  let mut stmts = vec![ddl];
+  if kind == ObjectKind::Table {
+    // Auto-indexes for UNIQUE/PRIMARY KEY have no SQL and are part of the table DDL.
+    let sql = "
+    SELECT sql FROM sqlite_master
+    WHERE type IN ('index', 'trigger') AND tbl_name = ?1 AND sql IS NOT NULL
+    ORDER BY type, name
+    ";
+    let mut stmt = conn.prepare(sql)?;
+    let extra = stmt
+      .query_map([name], |row| row.get::<_, String>(0))?
+      .collect::<Result<Vec<_>, _>>()?;
+    stmts.extend(extra);
+  }
+  Ok(join_statements(stmts))
+}
+
+fn table_detail(conn: &rusqlite::Connection, table: &str) -> anyhow::Result<TableDetail> {
+  let mut stmt = conn.prepare(
+    "SELECT name, type, \"notnull\", dflt_value, pk FROM pragma_table_info(?1) ORDER BY cid",
+  )?;
+  let rows = stmt
+    .query_map([table], |row| {
+      Ok((
+        row.get::<_, String>(0)?,
+        row.get::<_, String>(1)?,
+        row.get::<_, bool>(2)?,
+        row.get::<_, Option<String>>(3)?,
+        row.get::<_, i64>(4)?,
+      ))
+    })?
+    .collect::<Result<Vec<_>, _>>()?;
+  if rows.is_empty() {
+    anyhow::bail!("table not found: {table}");
+  }
+  let mut pk = vec![];
+  let mut columns = vec![];
+  for (name, r#type, not_null, default, pk_pos) in rows {
+    if pk_pos > 0 {
+      pk.push((pk_pos, name.clone()));
+    }
+    columns.push(ColumnDetail {
+      name,
+      r#type,
+      nullable: !not_null,
+      default,
+      comment: None,
+    });
+  }
+  pk.sort();
+
+  // origin: 'pk' backs the primary key, 'u' a UNIQUE constraint, 'c' CREATE INDEX.
+  let mut stmt =
+    conn.prepare("SELECT name, \"unique\", origin FROM pragma_index_list(?1) ORDER BY seq")?;
+  let index_list = stmt
+    .query_map([table], |row| {
+      Ok((
+        row.get::<_, String>(0)?,
+        row.get::<_, bool>(1)?,
+        row.get::<_, String>(2)?,
+      ))
+    })?
+    .collect::<Result<Vec<_>, _>>()?;
+  let mut part_stmt =
+    conn.prepare("SELECT coalesce(name, '<expression>') FROM pragma_index_info(?1) ORDER BY seqno")?;
+  let mut indexes = vec![];
+  let mut unique_constraints = vec![];
+  for (name, unique, origin) in index_list {
+    if origin == "pk" {
+      continue;
+    }
+    let parts = part_stmt
+      .query_map([&name], |row| row.get::<_, String>(0))?
+      .collect::<Result<Vec<_>, _>>()?;
+    if origin == "u" {
+      unique_constraints.push(UniqueConstraint {
+        name: None,
+        columns: parts.clone(),
+      });
+    }
+    indexes.push(IndexDetail {
+      name,
+      columns: parts,
+      unique,
+      method: None,
+    });
+  }
+
+  let mut stmt = conn.prepare(
+    "SELECT id, \"table\", \"from\", \"to\", on_update, on_delete
+     FROM pragma_foreign_key_list(?1) ORDER BY id, seq",
+  )?;
+  let rows = stmt
+    .query_map([table], |row| {
+      Ok((
+        (
+          row.get::<_, i64>(0)?,
+          row.get::<_, String>(1)?,
+          row.get::<_, String>(4)?,
+          row.get::<_, String>(5)?,
+        ),
+        (row.get::<_, String>(2)?, row.get::<_, Option<String>>(3)?),
+      ))
+    })?
+    .collect::<Result<Vec<_>, _>>()?;
+  let foreign_keys = group_sorted(rows)
+    .into_iter()
+    .map(|((_, ref_table, on_update, on_delete), pairs)| {
+      let (columns, ref_columns): (Vec<_>, Vec<_>) = pairs.into_iter().unzip();
+      ForeignKey {
+        name: None,
+        columns,
+        ref_schema: None,
+        ref_table,
+        // `to` is NULL when the key references the parent's primary key implicitly.
+        ref_columns: ref_columns.into_iter().flatten().collect(),
+        on_update: Some(on_update),
+        on_delete: Some(on_delete),
+      }
+    })
+    .collect();
+
+  Ok(TableDetail {
+    table: table.to_string(),
+    columns,
+    primary_key: pk.into_iter().map(|(_, name)| name).collect(),
+    unique_constraints,
+    indexes,
+    foreign_keys,
+    ..Default::default()
+  })
+}
+
+/// Transaction on a dedicated file handle; other handles see changes after commit.
+pub struct SqliteTransaction {
+  conn: Arc<Mutex<Option<rusqlite::Connection>>>,
+}
+
+impl SqliteTransaction {
+  async fn with_conn<T, F>(&self, f: F) -> anyhow::Result<T>
+  where
+    T: Send + 'static,
+    F: FnOnce(&rusqlite::Connection) -> anyhow::Result<T> + Send + 'static,
+  {
+    let conn = Arc::clone(&self.conn);
+    crate::dialect::run_blocking(move || {
+      let guard = conn
+        .lock()
+        .map_err(|_| anyhow::anyhow!("sqlite transaction lock poisoned"))?;
+      f(guard.as_ref().ok_or_else(transaction::finished)?)
+    })
+    .await
+  }
+
+  async fn finish(&self, sql: &'static str) -> anyhow::Result<()> {
+    let conn = Arc::clone(&self.conn);
+    crate::dialect::run_blocking(move || {
+      let conn = conn
+        .lock()
+        .map_err(|_| anyhow::anyhow!("sqlite transaction lock poisoned"))?
+        .take()
+        .ok_or_else(transaction::finished)?;
+      conn.execute_batch(sql)?;
+      Ok(())
+    })
+    .await
+  }
+}
+
+#[async_trait]
+impl Transaction for SqliteTransaction {
+  fn dialect(&self) -> &'static str {
+    "sqlite"
+  }
+
+  async fn query(&self, sql: &str) -> anyhow::Result<RawArrowData> {
+    let sql = sql.to_string();
+    self.with_conn(move |conn| query_conn(conn, &sql, &[])).await
+  }
+
+  async fn execute(&self, sql: &str) -> anyhow::Result<usize> {
+    let sql = sql.to_string();
+    self
+      .with_conn(move |conn| Ok(conn.execute(&sql, [])?))
+      .await
+  }
+
+  async fn commit(&self) -> anyhow::Result<()> {
+    self.finish("COMMIT").await
+  }
+
+  async fn rollback(&self) -> anyhow::Result<()> {
+    self.finish("ROLLBACK").await
+  }
+}
+
 #[cfg(test)]
 mod contract_tests {
   use super::*;
@@ -300,7 +696,12 @@ mod contract_tests {
   async fn paging_and_count() {
     let (db, path) = temp_db();
     let page = db
-      .paging_query("SELECT id, name FROM items ORDER BY id", Some(1), Some(0))
+      .paging_query(
+        "SELECT id, name FROM items ORDER BY id",
+        Some(1),
+        Some(0),
+        &Default::default(),
+      )
       .await
       .unwrap();
     assert_eq!(page.batch.num_rows(), 1);
@@ -338,6 +739,145 @@ mod contract_tests {
     let _ = std::fs::remove_file(path);
   }
 
+  #[tokio::test]
+  async fn execute_reports_rows_affected() {
+    let (db, path) = temp_db();
+    assert!(db.capabilities().contains(Caps::EXECUTE));
+    let n = db
+      .execute("UPDATE items SET score = score + 1")
+      .await
+      .unwrap();
+    assert_eq!(n, 2);
+    let n = db.execute("DELETE FROM items WHERE id = 1").await.unwrap();
+    assert_eq!(n, 1);
+    assert_eq!(db.table_row_count("items", "").await.unwrap(), 1);
+    let _ = std::fs::remove_file(path);
+  }
+
+  #[tokio::test]
+  async fn transaction_commit_and_rollback() {
+    let (db, path) = temp_db();
+    let tx = db.begin_transaction().await.unwrap();
+    assert_eq!(tx.execute("DELETE FROM items").await.unwrap(), 2);
+    let inside = tx.query("SELECT count(*) AS n FROM items").await.unwrap();
+    assert_eq!(inside.batch.num_rows(), 1);
+    tx.rollback().await.unwrap();
+    assert!(tx.execute("DELETE FROM items").await.is_err());
+    assert_eq!(db.table_row_count("items", "").await.unwrap(), 2);
+
+    let tx = db.begin_transaction().await.unwrap();
+    tx.execute("DELETE FROM items WHERE id = 1").await.unwrap();
+    tx.commit().await.unwrap();
+    assert_eq!(db.table_row_count("items", "").await.unwrap(), 1);
+
+    // Dropping an unfinished handle rolls back.
+    let tx = db.begin_transaction().await.unwrap();
+    tx.execute("DELETE FROM items").await.unwrap();
+    drop(tx);
+    assert_eq!(db.table_row_count("items", "").await.unwrap(), 1);
+    let _ = std::fs::remove_file(path);
+  }
+
+  #[tokio::test]
+  async fn table_detail_reports_keys_indexes_and_foreign_keys() {
+    let (db, path) = temp_db();
+    db.execute(
+      "CREATE TABLE tags (
+        item_id INTEGER NOT NULL REFERENCES items(id) ON DELETE CASCADE,
+        tag TEXT DEFAULT 'x',
+        PRIMARY KEY (tag, item_id),
+        UNIQUE (item_id, tag)
+      )",
+    )
+    .await
+    .unwrap();
+    db.execute("CREATE INDEX tags_tag ON tags (tag)").await.unwrap();
+
+    let detail = db.table_detail(None, "tags").await.unwrap();
+    assert_eq!(detail.primary_key, vec!["tag", "item_id"]);
+    let names: Vec<_> = detail.columns.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, vec!["item_id", "tag"]);
+    assert!(!detail.columns[0].nullable);
+    assert_eq!(detail.columns[1].default.as_deref(), Some("'x'"));
+    assert_eq!(detail.unique_constraints.len(), 1);
+    assert_eq!(detail.unique_constraints[0].columns, vec!["item_id", "tag"]);
+    let idx = detail.indexes.iter().find(|i| i.name == "tags_tag").unwrap();
+    assert_eq!(idx.columns, vec!["tag"]);
+    assert!(!idx.unique);
+    assert_eq!(detail.foreign_keys.len(), 1);
+    let fk = &detail.foreign_keys[0];
+    assert_eq!(fk.ref_table, "items");
+    assert_eq!(fk.columns, vec!["item_id"]);
+    assert_eq!(fk.ref_columns, vec!["id"]);
+    assert_eq!(fk.on_delete.as_deref(), Some("CASCADE"));
+
+    assert!(db.table_detail(None, "missing").await.is_err());
+    let _ = std::fs::remove_file(path);
+  }
+
+  #[tokio::test]
+  async fn object_ddl_includes_indexes() {
+    let (db, path) = temp_db();
+    db.execute("CREATE INDEX items_name ON items (name)").await.unwrap();
+    db.execute("CREATE VIEW cheap AS SELECT * FROM items WHERE score < 2").await.unwrap();
+
+    let ddl = db.object_ddl(None, "items", ObjectKind::Table).await.unwrap();
+    assert!(ddl.starts_with("CREATE TABLE items"), "{ddl}");
+    assert!(ddl.ends_with("CREATE INDEX items_name ON items (name);"), "{ddl}");
+    let ddl = db.object_ddl(None, "cheap", ObjectKind::View).await.unwrap();
+    assert!(ddl.starts_with("CREATE VIEW cheap"), "{ddl}");
+    assert!(db.object_ddl(None, "cheap", ObjectKind::Table).await.is_err());
+    assert!(db.object_ddl(None, "f", ObjectKind::Function).await.is_err());
+    let _ = std::fs::remove_file(path);
+  }
+
+  #[tokio::test]
+  async fn primary_key_and_apply_grid_changes() {
+    use crate::dialect::edit::{ChangeSet, RowUpdate, build_statements};
+    let (db, path) = temp_db();
+    let pk = db.primary_key(None, "items").await.unwrap();
+    assert_eq!(pk, vec!["id"]);
+
+    let row = |v: serde_json::Value| serde_json::from_value(v).unwrap();
+    let changes = ChangeSet {
+      table: "items".into(),
+      updates: vec![RowUpdate {
+        key: row(serde_json::json!({"id": 1})),
+        changes: row(serde_json::json!({"name": "it's"})),
+      }],
+      inserts: vec![row(serde_json::json!({"id": 3, "name": "c"}))],
+      deletes: vec![row(serde_json::json!({"id": 2}))],
+      ..Default::default()
+    };
+    let stmts = build_statements(&db, &changes, &pk).unwrap();
+    assert_eq!(db.execute_atomic(&stmts).await.unwrap(), vec![1, 1, 1]);
+    let n = db.table_row_count("items", "name = 'it''s' or id = 3").await.unwrap();
+    assert_eq!(n, 2);
+
+    // A failing statement rolls back the whole change set.
+    let bad = vec![
+      "DELETE FROM items".to_string(),
+      "INSERT INTO missing VALUES (1)".to_string(),
+    ];
+    assert!(db.execute_atomic(&bad).await.is_err());
+    assert_eq!(db.table_row_count("items", "").await.unwrap(), 2);
+    let _ = std::fs::remove_file(path);
+  }
+
+  #[tokio::test]
+  async fn cursor_fetches_in_pages() {
+    let (db, path) = temp_db();
+    let cursor = db.open_cursor("SELECT id, name FROM items ORDER BY id").await.unwrap();
+    let first = cursor.fetch(1).await.unwrap();
+    assert_eq!(first.batch.num_rows(), 1);
+    assert_eq!(first.batch.num_columns(), 2);
+    assert_eq!(cursor.fetch(5).await.unwrap().batch.num_rows(), 1);
+    assert_eq!(cursor.fetch(5).await.unwrap().batch.num_rows(), 0);
+    cursor.close().await.unwrap();
+    assert!(db.open_cursor("SELECT * FROM missing").await.is_err());
+    let _ = std::fs::remove_file(path);
+  }
+
   #[tokio::test]
   async fn batched_export_csv() {
     let (db, path) = temp_db();
@@ -1,16 +1,32 @@
+//! SQLite rows → Arrow. Column types come from the storage classes of the
+//! values read, not only the declared type, so expression columns
+//! (`count(*)`, `avg(x)`) and untyped columns are still typed; see
+//! [`infer_arrow_type`] for the rules. A cursor types its columns from the
+//! first page; see [`values_to_raw`] for values that do not fit later on.
+
 use arrow::array::{
-  ArrayBuilder, ArrayRef, BooleanBuilder, Float64Builder, Int64Builder, LargeBinaryBuilder,
-  StringBuilder,
+  ArrayBuilder, ArrayRef, BooleanBuilder, Date32Builder, Float64Builder, Int64Builder,
+  LargeBinaryBuilder, StringBuilder, TimestampMicrosecondBuilder,
 };
-use arrow::datatypes::{DataType, Field};
-use itertools::izip;
-use rusqlite::{types::Value, Statement};
+use arrow::datatypes::{DataType, Field, TimeUnit};
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, NaiveTime};
+use rusqlite::{Rows, Statement, types::Value};
 
 use crate::utils::{
-  finalize_preview_batch, record_batch_from_arrays, truncate_bytes_for_preview,
-  truncate_utf8_for_preview,
+  RawArrowData, Title, finalize_preview_batch, record_batch_from_arrays,
+  truncate_bytes_for_preview, truncate_utf8_for_preview,
 };
 
+/// Julian day number of 1970-01-01T00:00:00Z.
+const UNIX_EPOCH_JULIAN_DAY: f64 = 2_440_587.5;
+const MICROS_PER_DAY: f64 = 86_400_000_000.0;
+/// Days from 0001-01-01 (CE) to 1970-01-01.
+const EPOCH_DAYS_FROM_CE: i32 = 719_163;
+/// Julian days of 0001-01-01 and 10000-01-01: numbers outside are not dates.
+const JULIAN_DAY_RANGE: std::ops::Range<f64> = 1_721_425.5..5_373_484.5;
+/// Unix seconds of 10000-01-01.
+const MAX_EPOCH_SECS: i64 = 253_402_300_800;
+
 pub fn db_to_arrow_type(decl_type: Option<&str>) -> DataType {
   // https://sqlite.org/datatype3.html#determination_of_column_affinity
   if let Some(decl_type) = decl_type {
@@ -29,6 +45,67 @@ pub fn db_to_arrow_type(decl_type: Option<&str>) -> DataType {
   }
 }
 
+/// Declared DATE → Date32, DATETIME / TIMESTAMP → Timestamp(us). TIME alone
+/// has no date part and stays text.
+fn declared_temporal(decl_type: &str) -> Option<DataType> {
+  if decl_type.contains("DATETIME") || decl_type.contains("TIMESTAMP") {
+    Some(DataType::Timestamp(TimeUnit::Microsecond, None))
+  } else if decl_type.contains("DATE") {
+    Some(DataType::Date32)
+  } else {
+    None
+  }
+}
+
+/// Arrow type a declared type stands for before any value is read: its date
+/// convention or its affinity. Used to compare schemas.
+pub fn declared_arrow_type(decl_type: &str) -> DataType {
+  let decl_type = decl_type.to_ascii_uppercase();
+  declared_temporal(&decl_type).unwrap_or_else(|| db_to_arrow_type(Some(&decl_type)))
+}
+
+/// Choose a column's Arrow type from its declared type and the values read:
+///
+/// - DATE / DATETIME / TIMESTAMP columns become Date32 / Timestamp when every
+///   value follows one of SQLite's date conventions (see
+///   [`convert_to_datetime`]).
+/// - BOOLEAN columns become Boolean when every value reads as one.
+/// - Otherwise the storage classes decide: only INTEGER → Int64, INTEGER and
+///   REAL → Float64, only TEXT → Utf8, only BLOB → LargeBinary.
+/// - Any other mix (TEXT next to INTEGER, BLOB next to TEXT, ...) falls back
+///   to Utf8, with numbers rendered as text and blobs decoded lossily, so no
+///   value turns into a null.
+/// - A column without any non-null value keeps its declared affinity.
+pub fn infer_arrow_type(decl_type: Option<&str>, values: &[Value]) -> DataType {
+  let decl_type = decl_type.map(str::to_ascii_uppercase);
+  let decl_type = decl_type.as_deref();
+  let present: Vec<&Value> = values.iter().filter(|v| **v != Value::Null).collect();
+  let temporal = decl_type.and_then(declared_temporal);
+  if present.is_empty() {
+    return temporal.unwrap_or_else(|| db_to_arrow_type(decl_type));
+  }
+  if let Some(temporal) = temporal
+    && present.iter().all(|v| convert_to_datetime(v).is_some())
+  {
+    return temporal;
+  }
+  if decl_type.is_some_and(|t| t.contains("BOOL"))
+    && present.iter().all(|v| convert_to_bool(v).is_some())
+  {
+    return DataType::Boolean;
+  }
+  let all = |class: fn(&Value) -> bool| present.iter().all(|v| class(v));
+  if all(|v| matches!(v, Value::Integer(_))) {
+    DataType::Int64
+  } else if all(|v| matches!(v, Value::Integer(_) | Value::Real(_))) {
+    DataType::Float64
+  } else if all(|v| matches!(v, Value::Blob(_))) {
+    DataType::LargeBinary
+  } else {
+    DataType::Utf8
+  }
+}
+
 fn make_builder(data_type: &DataType) -> Box<dyn ArrayBuilder> {
   match data_type {
     DataType::Int64 => Box::new(Int64Builder::new()),
@@ -36,49 +113,127 @@ fn make_builder(data_type: &DataType) -> Box<dyn ArrayBuilder> {
     DataType::Utf8 => Box::new(StringBuilder::new()),
     DataType::Float64 => Box::new(Float64Builder::new()),
     DataType::Boolean => Box::new(BooleanBuilder::new()),
+    DataType::Date32 => Box::new(Date32Builder::new()),
+    DataType::Timestamp(TimeUnit::Microsecond, None) => {
+      Box::new(TimestampMicrosecondBuilder::new())
+    }
     // Unknown → Utf8 builder so every cell can still be appended.
     _ => Box::new(StringBuilder::new()),
   }
 }
 
-/// Every row/column must append exactly once (value or null).
-pub fn db_result_to_arrow(stmt: &mut Statement) -> anyhow::Result<arrow::record_batch::RecordBatch> {
-  let data_types: Vec<DataType> = stmt
+/// Result column as declared by the statement.
+#[derive(Debug, Clone)]
+pub struct ColumnMeta {
+  pub name: String,
+  pub decl_type: Option<String>,
+}
+
+pub fn statement_columns(stmt: &Statement) -> Vec<ColumnMeta> {
+  stmt
     .columns()
     .iter()
-    .map(|col| db_to_arrow_type(col.decl_type()))
-    .collect();
+    .map(|c| ColumnMeta {
+      name: c.name().to_string(),
+      decl_type: c.decl_type().map(str::to_string),
+    })
+    .collect()
+}
 
-  let fields: Vec<Field> = izip!(stmt.column_names(), data_types.clone())
-    .map(|(name, data_type)| Field::new(name, data_type.clone(), true))
-    .collect();
+/// Read up to `limit` rows, column by column.
+pub fn read_rows(
+  rows: &mut Rows<'_>,
+  width: usize,
+  limit: usize,
+) -> anyhow::Result<Vec<Vec<Value>>> {
+  let mut columns: Vec<Vec<Value>> = vec![Vec::new(); width];
+  let mut n = 0;
+  while n < limit {
+    let Some(row) = rows.next()? else {
+      break;
+    };
+    for (idx, column) in columns.iter_mut().enumerate() {
+      column.push(row.get::<_, Value>(idx).unwrap_or(Value::Null));
+    }
+    n += 1;
+  }
+  Ok(columns)
+}
 
-  let mut builders: Vec<Box<dyn ArrayBuilder>> = data_types.iter().map(make_builder).collect();
+/// [`infer_arrow_type`] for every column.
+pub fn infer_types(metas: &[ColumnMeta], columns: &[Vec<Value>]) -> Vec<DataType> {
+  metas
+    .iter()
+    .zip(columns)
+    .map(|(meta, values)| infer_arrow_type(meta.decl_type.as_deref(), values))
+    .collect()
+}
 
-  let mut rows = stmt.query([])?;
-  while let Some(row) = rows.next()? {
-    for (idx, data_type) in data_types.iter().enumerate() {
-      let val = row.get::<_, Value>(idx).unwrap_or(Value::Null);
-      append_cell(builders[idx].as_mut(), data_type, &val);
+/// Build a finalized preview batch with the given column types. Titles carry
+/// the declared type, or the storage class for expression columns.
+///
+/// A column holding a value that does not fit its type (text below integers
+/// on a cursor's later page) falls back to Utf8, as a mixed column does in
+/// [`infer_arrow_type`]; `types` is updated so later pages stay Utf8 too.
+pub fn values_to_raw(
+  metas: &[ColumnMeta],
+  types: &mut [DataType],
+  columns: Vec<Vec<Value>>,
+  sql: &str,
+) -> anyhow::Result<RawArrowData> {
+  let mut titles = Vec::with_capacity(metas.len());
+  let mut fields = Vec::with_capacity(metas.len());
+  let mut arrays: Vec<ArrayRef> = Vec::with_capacity(metas.len());
+  for ((meta, data_type), values) in metas.iter().zip(types.iter_mut()).zip(columns) {
+    let mut builder = make_builder(data_type);
+    let fits = values
+      .iter()
+      .fold(true, |fits, value| append_cell(builder.as_mut(), data_type, value) && fits);
+    if !fits {
+      log::debug!("sqlite column {} no longer fits {data_type}; reading it as text", meta.name);
+      *data_type = DataType::Utf8;
+      builder = make_builder(data_type);
+      for value in &values {
+        append_cell(builder.as_mut(), data_type, value);
+      }
     }
+    let r#type = match &meta.decl_type {
+      Some(decl) => decl.clone(),
+      None => storage_class_name(data_type).to_string(),
+    };
+    titles.push(Title {
+      name: meta.name.clone(),
+      r#type,
+    });
+    fields.push(Field::new(&meta.name, data_type.clone(), true));
+    arrays.push(builder.finish());
   }
+  let batch = finalize_preview_batch(record_batch_from_arrays(fields, arrays)?)?;
+  Ok(RawArrowData {
+    titles: Some(titles),
+    sql: Some(sql.to_string()),
+    ..RawArrowData::from_batch(batch)
+  })
+}
 
-  let mut columns: Vec<ArrayRef> = Vec::with_capacity(builders.len());
-  for mut builder in builders {
-    columns.push(builder.finish());
+fn storage_class_name(data_type: &DataType) -> &'static str {
+  match data_type {
+    DataType::Int64 | DataType::Boolean => "INTEGER",
+    DataType::Float64 => "REAL",
+    DataType::LargeBinary => "BLOB",
+    _ => "TEXT",
   }
-
-  let batch = record_batch_from_arrays(fields, columns)?;
-  finalize_preview_batch(batch)
 }
 
-fn append_cell(builder: &mut dyn ArrayBuilder, data_type: &DataType, val: &Value) {
-  match data_type {
+/// Every call appends exactly one value or null. Returns false when a present
+/// value does not fit `data_type` (possible on a cursor's later pages, whose
+/// types were fixed by the first); a null is appended in its place.
+fn append_cell(builder: &mut dyn ArrayBuilder, data_type: &DataType, val: &Value) -> bool {
+  let present = *val != Value::Null;
+  let fits = match data_type {
     DataType::Int64 => {
       let value = match val {
         Value::Integer(i) => Some(*i),
-        Value::Real(f) => Some(*f as i64),
-        Value::Text(s) => s.parse::<i64>().ok(),
         _ => None,
       };
       builder
@@ -86,12 +241,12 @@ fn append_cell(builder: &mut dyn ArrayBuilder, data_type: &DataType, val: &Value
         .downcast_mut::<Int64Builder>()
         .expect("Int64Builder")
         .append_option(value);
+      value.is_some()
     }
     DataType::Float64 => {
       let value = match val {
         Value::Real(f) => Some(*f),
         Value::Integer(i) => Some(*i as f64),
-        Value::Text(s) => s.parse::<f64>().ok(),
         _ => None,
       };
       builder
@@ -99,35 +254,47 @@ fn append_cell(builder: &mut dyn ArrayBuilder, data_type: &DataType, val: &Value
         .downcast_mut::<Float64Builder>()
         .expect("Float64Builder")
         .append_option(value);
+      value.is_some()
     }
     DataType::Boolean => {
-      let value = match val {
-        Value::Integer(i) => Some(*i != 0),
-        Value::Text(s) => match s.to_ascii_lowercase().as_str() {
-          "1" | "true" | "t" | "yes" => Some(true),
-          "0" | "false" | "f" | "no" => Some(false),
-          _ => None,
-        },
-        _ => None,
-      };
+      let value = convert_to_bool(val);
       builder
         .as_any_mut()
         .downcast_mut::<BooleanBuilder>()
         .expect("BooleanBuilder")
         .append_option(value);
+      value.is_some()
+    }
+    DataType::Date32 => {
+      let value =
+        convert_to_datetime(val).map(|dt| dt.date().num_days_from_ce() - EPOCH_DAYS_FROM_CE);
+      builder
+        .as_any_mut()
+        .downcast_mut::<Date32Builder>()
+        .expect("Date32Builder")
+        .append_option(value);
+      value.is_some()
+    }
+    DataType::Timestamp(TimeUnit::Microsecond, None) => {
+      let value = convert_to_datetime(val).map(|dt| dt.and_utc().timestamp_micros());
+      builder
+        .as_any_mut()
+        .downcast_mut::<TimestampMicrosecondBuilder>()
+        .expect("TimestampMicrosecondBuilder")
+        .append_option(value);
+      value.is_some()
     }
     DataType::LargeBinary => {
       let value = match val {
         Value::Blob(b) => Some(truncate_bytes_for_preview(b)),
-        Value::Text(s) => Some(truncate_bytes_for_preview(s.as_bytes())),
-        Value::Null => None,
-        other => convert_to_string(other).map(|s| truncate_bytes_for_preview(s.as_bytes())),
+        _ => None,
       };
       builder
         .as_any_mut()
         .downcast_mut::<LargeBinaryBuilder>()
         .expect("LargeBinaryBuilder")
-        .append_option(value);
+        .append_option(value.as_deref());
+      value.is_some()
     }
     // Utf8 and any unexpected DataType (builder is StringBuilder).
     _ => {
@@ -138,8 +305,10 @@ fn append_cell(builder: &mut dyn ArrayBuilder, data_type: &DataType, val: &Value
         // Should not happen if make_builder stays in sync; still avoid skip-append.
         log::warn!("sqlite preview: unexpected builder for Utf8 column");
       }
+      true
     }
-  }
+  };
+  fits || !present
 }
 
 pub fn convert_to_string(value: &Value) -> Option<String> {
@@ -152,30 +321,73 @@ pub fn convert_to_string(value: &Value) -> Option<String> {
   }
 }
 
-#[allow(dead_code)]
-pub fn convert_to_i64(value: &Value) -> Option<i64> {
+fn convert_to_bool(value: &Value) -> Option<bool> {
   match value {
-    Value::Integer(i) => Some(*i),
-    Value::Real(f) => Some(*f as i64),
-    Value::Text(s) => s.parse::<i64>().ok(),
+    Value::Integer(i) => Some(*i != 0),
+    Value::Text(s) => match s.to_ascii_lowercase().as_str() {
+      "1" | "true" | "t" | "yes" => Some(true),
+      "0" | "false" | "f" | "no" => Some(false),
+      _ => None,
+    },
     _ => None,
   }
 }
 
-#[allow(dead_code)]
-pub fn convert_to_f64(value: &Value) -> Option<f64> {
+/// A date/time in one of SQLite's conventions
+/// (<https://sqlite.org/lang_datefunc.html>): ISO-8601 TEXT, unix-epoch
+/// seconds as INTEGER, or a Julian day number as REAL. Text with a UTC offset
+/// is normalized to UTC; text without one is taken as is. Numbers are only
+/// taken when plausible: an INTEGER that reads as `YYYYMMDD` (`20240115`) is a
+/// compact date rather than 1970-08-23, so it is not converted.
+fn convert_to_datetime(value: &Value) -> Option<NaiveDateTime> {
   match value {
-    Value::Integer(i) => Some(*i as f64),
-    Value::Real(f) => Some(*f),
-    Value::Text(s) => s.parse::<f64>().ok(),
+    Value::Text(s) => parse_iso8601(s.trim()),
+    Value::Integer(secs) => {
+      if !(-MAX_EPOCH_SECS..MAX_EPOCH_SECS).contains(secs) || looks_like_yyyymmdd(*secs) {
+        return None;
+      }
+      DateTime::from_timestamp(*secs, 0).map(|dt| dt.naive_utc())
+    }
+    Value::Real(day) if JULIAN_DAY_RANGE.contains(day) => {
+      let micros = ((day - UNIX_EPOCH_JULIAN_DAY) * MICROS_PER_DAY).round();
+      DateTime::from_timestamp_micros(micros as i64).map(|dt| dt.naive_utc())
+    }
     _ => None,
   }
 }
 
+fn looks_like_yyyymmdd(n: i64) -> bool {
+  (10_000_101..=99_991_231).contains(&n)
+    && NaiveDate::parse_from_str(&n.to_string(), "%Y%m%d").is_ok()
+}
+
+/// `YYYY-MM-DD`, optionally followed by ` ` or `T`, `HH:MM[:SS[.SSS]]` and a
+/// `Z` / `±HH:MM` suffix.
+fn parse_iso8601(s: &str) -> Option<NaiveDateTime> {
+  if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+    return Some(dt.naive_utc());
+  }
+  const FORMATS: [&str; 4] = [
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%dT%H:%M",
+  ];
+  let naive = s.strip_suffix('Z').unwrap_or(s);
+  FORMATS
+    .iter()
+    .find_map(|f| NaiveDateTime::parse_from_str(naive, f).ok())
+    .or_else(|| {
+      NaiveDate::parse_from_str(naive, "%Y-%m-%d")
+        .ok()
+        .map(|d| d.and_time(NaiveTime::MIN))
+    })
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
-  use arrow::array::Array;
+  use arrow::array::{Array, AsArray};
 
   #[test]
   fn affinity_int_and_blob() {
@@ -187,13 +399,117 @@ mod tests {
   #[test]
   fn append_cell_always_extends_int_builder() {
     let mut builder = Int64Builder::new();
-    append_cell(&mut builder, &DataType::Int64, &Value::Integer(1));
-    append_cell(&mut builder, &DataType::Int64, &Value::Text("x".into())); // → null
-    append_cell(&mut builder, &DataType::Int64, &Value::Null);
+    assert!(append_cell(
+      &mut builder,
+      &DataType::Int64,
+      &Value::Integer(1)
+    ));
+    // A misfit is reported and leaves a null in its place.
+    assert!(!append_cell(
+      &mut builder,
+      &DataType::Int64,
+      &Value::Text("x".into())
+    ));
+    assert!(append_cell(&mut builder, &DataType::Int64, &Value::Null));
     let arr = builder.finish();
     assert_eq!(arr.len(), 3);
     assert_eq!(arr.value(0), 1);
     assert!(arr.is_null(1));
     assert!(arr.is_null(2));
   }
+
+  #[test]
+  fn storage_classes_decide_undeclared_columns() {
+    let int = [Value::Integer(3), Value::Null];
+    assert_eq!(infer_arrow_type(None, &int), DataType::Int64);
+    let num = [Value::Integer(3), Value::Real(0.5)];
+    assert_eq!(infer_arrow_type(None, &num), DataType::Float64);
+    let blob = [Value::Blob(vec![0xff])];
+    assert_eq!(infer_arrow_type(None, &blob), DataType::LargeBinary);
+    // Mixed storage classes fall back to text, even under a numeric affinity.
+    let mixed = [Value::Integer(1), Value::Text("n/a".into())];
+    assert_eq!(infer_arrow_type(Some("integer"), &mixed), DataType::Utf8);
+    assert_eq!(
+      infer_arrow_type(Some("integer"), &[Value::Null]),
+      DataType::Int64
+    );
+  }
+
+  #[test]
+  fn declared_dates_follow_sqlite_conventions() {
+    let ts = DataType::Timestamp(TimeUnit::Microsecond, None);
+    let values = [
+      Value::Text("2024-01-02 03:04:05.5".into()),
+      Value::Text("2024-01-02T03:04:05+02:00".into()),
+      Value::Integer(0),
+      Value::Real(2_440_588.0),
+    ];
+    assert_eq!(infer_arrow_type(Some("datetime"), &values), ts);
+    let micros: Vec<_> = values
+      .iter()
+      .map(|v| convert_to_datetime(v).unwrap().and_utc().timestamp_micros())
+      .collect();
+    assert_eq!(
+      micros,
+      [
+        1_704_164_645_500_000,
+        1_704_157_445_000_000,
+        0,
+        43_200_000_000
+      ]
+    );
+
+    let dates = [Value::Text("1970-01-02".into())];
+    assert_eq!(infer_arrow_type(Some("DATE"), &dates), DataType::Date32);
+    // Undeclared or unparseable date text stays text.
+    assert_eq!(infer_arrow_type(None, &dates), DataType::Utf8);
+    let bad = [Value::Text("someday".into())];
+    assert_eq!(infer_arrow_type(Some("DATE"), &bad), DataType::Utf8);
+    // Compact dates and out-of-range numbers are not epochs or Julian days.
+    let compact = [Value::Integer(20_240_115)];
+    assert_eq!(infer_arrow_type(Some("DATE"), &compact), DataType::Int64);
+    let small = [Value::Real(3.5)];
+    assert_eq!(
+      infer_arrow_type(Some("DATETIME"), &small),
+      DataType::Float64
+    );
+  }
+
+  #[test]
+  fn values_to_raw_builds_inferred_columns() {
+    let metas = vec![
+      ColumnMeta {
+        name: "n".into(),
+        decl_type: None,
+      },
+      ColumnMeta {
+        name: "d".into(),
+        decl_type: Some("DATE".into()),
+      },
+    ];
+    let columns = vec![
+      vec![Value::Integer(7), Value::Null],
+      vec![Value::Text("1970-01-02".into()), Value::Null],
+    ];
+    let mut types = infer_types(&metas, &columns);
+    let raw = values_to_raw(&metas, &mut types, columns, "select").unwrap();
+    assert_eq!(raw.batch.column(0).data_type(), &DataType::Int64);
+    assert_eq!(raw.batch.column(1).data_type(), &DataType::Date32);
+    let titles = raw.titles.unwrap();
+    assert_eq!(
+      (titles[0].r#type.as_str(), titles[1].r#type.as_str()),
+      ("INTEGER", "DATE")
+    );
+    // A later page whose values do not fit the first page's types turns the
+    // misfit column to text, for that page and the ones after it.
+    let later = vec![vec![Value::Integer(8), Value::Text("n/a".into())], vec![Value::Null; 2]];
+    let raw = values_to_raw(&metas, &mut types, later, "select").unwrap();
+    assert_eq!(types, [DataType::Utf8, DataType::Date32]);
+    let text = raw.batch.column(0).as_string::<i32>();
+    assert_eq!((text.value(0), text.value(1)), ("8", "n/a"));
+    assert_eq!(raw.titles.unwrap()[0].r#type, "TEXT");
+    let after = vec![vec![Value::Integer(9)], vec![Value::Null]];
+    let raw = values_to_raw(&metas, &mut types, after, "select").unwrap();
+    assert_eq!(raw.batch.column(0).data_type(), &DataType::Utf8);
+  }
 }
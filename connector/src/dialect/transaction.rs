@@ -0,0 +1,28 @@
+//! Explicit transactions pinned to one physical connection.
+
+use async_trait::async_trait;
+
+use crate::utils::RawArrowData;
+
+/// An open transaction on a dedicated connection (not the shared pool/client).
+///
+/// `commit` / `rollback` finish the transaction; later calls fail. Dropping an
+/// unfinished handle rolls back: the connection is closed (Postgres, SQLite,
+/// DuckDB) or reset when returned to the pool (MySQL).
+#[async_trait]
+pub trait Transaction: Send + Sync {
+  fn dialect(&self) -> &'static str;
+
+  async fn query(&self, sql: &str) -> anyhow::Result<RawArrowData>;
+
+  /// Run DDL/DML inside the transaction and return rows affected.
+  async fn execute(&self, sql: &str) -> anyhow::Result<usize>;
+
+  async fn commit(&self) -> anyhow::Result<()>;
+
+  async fn rollback(&self) -> anyhow::Result<()>;
+}
+
+pub(crate) fn finished() -> anyhow::Error {
+  anyhow::anyhow!("transaction already finished")
+}
@@ -0,0 +1,697 @@
+//! Copy a query result from one connection into a table on another.
+//!
+//! The source is streamed through one [`Connection::open_cursor`] scan, or
+//! paged like [`Connection::export_batched`] where the source has no cursors
+//! or both ends are SQLite; each page becomes multi-row `INSERT` statements on the target.
+//! The target table is created from the Arrow schema of the first page (see
+//! [`render_type`]).
+
+use std::ops::Range;
+
+use arrow::array::{Array, ArrayRef, AsArray, RecordBatch};
+use arrow::datatypes::{
+  DataType, IntervalDayTimeType, IntervalMonthDayNanoType, IntervalUnit, IntervalYearMonthType,
+  Schema,
+};
+use arrow::util::display::array_value_to_string;
+use serde::{Deserialize, Serialize};
+use sqlparser::ast::Statement;
+
+use super::ast::first_stmt;
+use super::edit::quote_string;
+use super::limits::is_unsupported;
+use super::postgres::is_uuid_field;
+use super::schema_diff::{normalize_arrow_type, render_type};
+use super::{Connection, ResultCursor, page_sql};
+use crate::cancel::CancelToken;
+use crate::utils::EXPORT_BATCH_ROWS;
+
+/// Rows per generated `INSERT` statement.
+pub(crate) const INSERT_CHUNK_ROWS: usize = 500;
+
+/// What to do with the target table before loading.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransferMode {
+  /// Create the table; fails when it already exists.
+  #[default]
+  Create,
+  /// Insert into an existing table.
+  Append,
+  /// Drop the table if it exists, then create it.
+  Replace,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferOptions {
+  #[serde(default)]
+  pub schema: Option<String>,
+  pub table: String,
+  #[serde(default)]
+  pub mode: TransferMode,
+  /// Source rows fetched per page (default [`EXPORT_BATCH_ROWS`]).
+  #[serde(default)]
+  pub batch_rows: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferProgress {
+  /// Rows written to the target so far.
+  pub rows: usize,
+  /// Source pages processed so far.
+  pub batches: usize,
+  pub done: bool,
+}
+
+/// Run `sql` on `source` and load the result into `options.table` on `target`.
+/// `progress` is called after every full page and once more when done.
+pub async fn transfer(
+  source: &dyn Connection,
+  sql: &str,
+  target: &dyn Connection,
+  options: &TransferOptions,
+  cancel: Option<&CancelToken>,
+  progress: &(dyn Fn(&TransferProgress) + Send + Sync),
+) -> anyhow::Result<TransferProgress> {
+  // A SQLite scan holds its shared lock until it ends, so writes to the
+  // same file could not commit while it is open.
+  let stream = !(source.dialect() == "sqlite" && target.dialect() == "sqlite");
+  let pages = Pages::open(source, sql, stream).await?;
+  let copied = copy_pages(&pages, target, options, cancel, progress).await;
+  if let Err(e) = pages.close().await {
+    log::debug!("closing transfer source: {e}");
+  }
+  copied
+}
+
+/// Source pages: one streamed cursor scan, or LIMIT/OFFSET re-queries.
+enum Pages<'a> {
+  Cursor(Box<dyn ResultCursor>),
+  Offset {
+    source: &'a dyn Connection,
+    sql: &'a str,
+    stmt: Option<Statement>,
+  },
+}
+
+impl<'a> Pages<'a> {
+  async fn open(source: &'a dyn Connection, sql: &'a str, stream: bool) -> anyhow::Result<Self> {
+    if stream {
+      match source.open_cursor(sql).await {
+        Ok(cursor) => return Ok(Self::Cursor(cursor)),
+        Err(e) if !is_unsupported(&e) => return Err(e),
+        Err(_) => {}
+      }
+    }
+    Ok(Self::Offset {
+      source,
+      sql,
+      stmt: first_stmt(source.dialect(), sql),
+    })
+  }
+
+  /// The next `rows` rows; `offset` rows were fetched before.
+  async fn fetch(&self, rows: usize, offset: usize) -> anyhow::Result<RecordBatch> {
+    match self {
+      Self::Cursor(cursor) => Ok(cursor.fetch(rows).await?.batch),
+      Self::Offset { source, sql, stmt } => {
+        let page_sql = page_sql(source.dialect(), stmt.as_ref(), sql, rows, offset);
+        Ok(source.query(&page_sql, 0, 0).await?.batch)
+      }
+    }
+  }
+
+  async fn close(&self) -> anyhow::Result<()> {
+    match self {
+      Self::Cursor(cursor) => cursor.close().await,
+      Self::Offset { .. } => Ok(()),
+    }
+  }
+}
+
+async fn copy_pages(
+  pages: &Pages<'_>,
+  target: &dyn Connection,
+  options: &TransferOptions,
+  cancel: Option<&CancelToken>,
+  progress: &(dyn Fn(&TransferProgress) + Send + Sync),
+) -> anyhow::Result<TransferProgress> {
+  let page = options.batch_rows.unwrap_or(EXPORT_BATCH_ROWS).max(1);
+  let table = table_ref(target, options.schema.as_deref(), &options.table);
+  let mut state = TransferProgress::default();
+
+  loop {
+    if let Some(t) = cancel {
+      t.check()?;
+    }
+    let batch = pages.fetch(page, state.rows).await?;
+    if state.batches == 0 {
+      prepare_target(target, &table, options.mode, &batch.schema()).await?;
+    }
+    let n = batch.num_rows();
+    for stmt in insert_statements(target, &table, &batch)? {
+      if let Some(t) = cancel {
+        t.check()?;
+      }
+      target
+        .execute(&stmt)
+        .await
+        .map_err(|e| e.context(format!("insert into {table} failed after {} rows", state.rows)))?;
+    }
+    state.rows += n;
+    state.batches += 1;
+    if n < page {
+      break;
+    }
+    progress(&state);
+  }
+
+  state.done = true;
+  progress(&state);
+  Ok(state)
+}
+
+/// `table`, qualified by `schema` when one is given, quoted for `target`.
+pub(crate) fn table_ref(target: &dyn Connection, schema: Option<&str>, table: &str) -> String {
+  match schema.filter(|s| !s.is_empty()) {
+    Some(s) => format!("{}.{}", target.quote(s), target.quote(table)),
+    None => target.quote(table),
+  }
+}
+
+async fn prepare_target(
+  target: &dyn Connection,
+  table: &str,
+  mode: TransferMode,
+  schema: &Schema,
+) -> anyhow::Result<()> {
+  if mode == TransferMode::Append {
+    return Ok(());
+  }
+  if mode == TransferMode::Replace {
+    target.execute(&format!("DROP TABLE IF EXISTS {table}")).await?;
+  }
+  target.execute(&create_table_sql(target, table, schema)).await?;
+  Ok(())
+}
+
+/// `CREATE TABLE` for `schema`, with types mapped to the target dialect.
+pub fn create_table_sql(target: &dyn Connection, table: &str, schema: &Schema) -> String {
+  let dialect = target.dialect();
+  let columns = schema
+    .fields()
+    .iter()
+    .map(|f| {
+      let normalized = match is_uuid_field(f) {
+        true => "uuid".to_string(),
+        false => normalize_arrow_type(f.data_type()),
+      };
+      let ty = render_type(dialect, &normalized);
+      let ty = if f.is_nullable() { nullable(dialect, ty) } else { ty };
+      (f.name().clone(), ty)
+    })
+    .collect::<Vec<_>>();
+  create_columns_sql(target, table, &columns)
+}
+
+/// ClickHouse columns are NOT NULL unless wrapped; other engines default to NULL.
+pub(crate) fn nullable(dialect: &str, ty: String) -> String {
+  if dialect == "clickhouse" && !ty.starts_with("Array(") && !ty.starts_with("Nullable(") {
+    format!("Nullable({ty})")
+  } else {
+    ty
+  }
+}
+
+/// `CREATE TABLE` from `(name, type)` pairs already spelled for the target.
+pub(crate) fn create_columns_sql(
+  target: &dyn Connection,
+  table: &str,
+  columns: &[(String, String)],
+) -> String {
+  let cols = columns
+    .iter()
+    .map(|(name, ty)| format!("  {} {ty}", target.quote(name)))
+    .collect::<Vec<_>>()
+    .join(",\n");
+  let engine = if target.dialect() == "clickhouse" {
+    " ENGINE = MergeTree ORDER BY tuple()"
+  } else {
+    ""
+  };
+  format!("CREATE TABLE {table} (\n{cols}\n){engine}")
+}
+
+/// Multi-row `INSERT ... VALUES` statements for `batch`.
+pub fn insert_statements(
+  target: &dyn Connection,
+  table: &str,
+  batch: &RecordBatch,
+) -> anyhow::Result<Vec<String>> {
+  let mut out = vec![];
+  let mut start = 0;
+  while start < batch.num_rows() {
+    let end = (start + INSERT_CHUNK_ROWS).min(batch.num_rows());
+    out.push(insert_sql(target, table, batch, start..end)?);
+    start = end;
+  }
+  Ok(out)
+}
+
+/// One `INSERT ... VALUES` for `rows` of `batch`.
+pub(crate) fn insert_sql(
+  target: &dyn Connection,
+  table: &str,
+  batch: &RecordBatch,
+  rows: Range<usize>,
+) -> anyhow::Result<String> {
+  let dialect = target.dialect();
+  let schema = batch.schema();
+  let columns = schema
+    .fields()
+    .iter()
+    .map(|f| target.quote(f.name()))
+    .collect::<Vec<_>>()
+    .join(", ");
+  let mut values = Vec::with_capacity(rows.len());
+  for row in rows {
+    let cells = schema
+      .fields()
+      .iter()
+      .zip(batch.columns())
+      .map(|(f, col)| match is_uuid_field(f) && !col.is_null(row) {
+        true => uuid_literal(dialect, col, row),
+        false => cell_literal(dialect, col, row),
+      })
+      .collect::<anyhow::Result<Vec<_>>>()?;
+    values.push(format!("({})", cells.join(", ")));
+  }
+  Ok(format!("INSERT INTO {table} ({columns}) VALUES {}", values.join(", ")))
+}
+
+/// Render one Arrow cell as a SQL literal for `dialect`.
+pub(crate) fn cell_literal(dialect: &str, col: &ArrayRef, row: usize) -> anyhow::Result<String> {
+  if col.is_null(row) {
+    return Ok("NULL".into());
+  }
+  let dt = col.data_type();
+  if matches!(
+    dt,
+    DataType::Binary | DataType::LargeBinary | DataType::FixedSizeBinary(_)
+  ) {
+    let bytes = match dt {
+      DataType::Binary => col.as_binary::<i32>().value(row),
+      DataType::LargeBinary => col.as_binary::<i64>().value(row),
+      _ => col.as_fixed_size_binary().value(row),
+    };
+    return Ok(binary_literal(dialect, bytes));
+  }
+  match dt {
+    DataType::List(_) => return list_literal(dialect, &col.as_list::<i32>().value(row)),
+    DataType::LargeList(_) => return list_literal(dialect, &col.as_list::<i64>().value(row)),
+    DataType::Interval(unit) => {
+      return Ok(quote_string(dialect, &interval_text(dialect, col, *unit, row)));
+    }
+    _ => {}
+  }
+  let text = array_value_to_string(col, row)?;
+  Ok(match dt {
+    DataType::Boolean if matches!(dialect, "sqlite" | "mssql") => {
+      (if text == "true" { "1" } else { "0" }).into()
+    }
+    DataType::Boolean => text.to_uppercase(),
+    dt if dt.is_numeric() => match text.as_str() {
+      // No portable literal for these; let the engine parse the text.
+      "NaN" | "inf" | "-inf" => quote_string(dialect, &text),
+      _ => text,
+    },
+    DataType::Timestamp(_, tz) => {
+      let text = text.replacen('T', " ", 1);
+      match (dialect, tz) {
+        ("clickhouse", Some(_)) => {
+          format!("parseDateTime64BestEffort({}, 6)", quote_string(dialect, &text))
+        }
+        _ => quote_string(dialect, &text),
+      }
+    }
+    _ => quote_string(dialect, &text),
+  })
+}
+
+/// A UUID cell in its dashed text form, which every target's `uuid` /
+/// `char(36)` / text column takes.
+fn uuid_literal(dialect: &str, col: &ArrayRef, row: usize) -> anyhow::Result<String> {
+  let uuid = uuid::Uuid::from_slice(col.as_fixed_size_binary().value(row))?;
+  Ok(quote_string(dialect, &uuid.hyphenated().to_string()))
+}
+
+/// An interval cell as text its target column reads: unit text for the
+/// `interval` columns of Postgres and DuckDB (both keep microseconds), an ISO
+/// 8601 duration for the text columns elsewhere (see [`render_type`]).
+fn interval_text(dialect: &str, col: &ArrayRef, unit: IntervalUnit, row: usize) -> String {
+  let (months, days, nanos) = match unit {
+    IntervalUnit::YearMonth => (col.as_primitive::<IntervalYearMonthType>().value(row), 0, 0),
+    IntervalUnit::DayTime => {
+      let v = col.as_primitive::<IntervalDayTimeType>().value(row);
+      (0, v.days, i64::from(v.milliseconds) * 1_000_000)
+    }
+    IntervalUnit::MonthDayNano => {
+      let v = col.as_primitive::<IntervalMonthDayNanoType>().value(row);
+      (v.months, v.days, v.nanoseconds)
+    }
+  };
+  match dialect {
+    "mysql" | "sqlite" | "clickhouse" | "mssql" => iso_duration(months, days, nanos),
+    _ => format!("{months} months {days} days {} microseconds", nanos / 1_000),
+  }
+}
+
+/// `P1Y2M3DT4H5M6.5S`, leaving out zero parts (`PT0S` when all are).
+fn iso_duration(months: i32, days: i32, nanos: i64) -> String {
+  let mut out = "P".to_string();
+  for (n, unit) in [(months / 12, 'Y'), (months % 12, 'M'), (days, 'D')] {
+    if n != 0 {
+      out.push_str(&format!("{n}{unit}"));
+    }
+  }
+  if nanos != 0 {
+    let sign = if nanos < 0 { "-" } else { "" };
+    let (secs, frac) = (nanos.unsigned_abs() / 1_000_000_000, nanos.unsigned_abs() % 1_000_000_000);
+    out.push('T');
+    for (n, unit) in [(secs / 3600, 'H'), (secs / 60 % 60, 'M')] {
+      if n != 0 {
+        out.push_str(&format!("{sign}{n}{unit}"));
+      }
+    }
+    let s = secs % 60;
+    if frac != 0 {
+      let frac = format!("{frac:09}");
+      out.push_str(&format!("{sign}{s}.{}S", frac.trim_end_matches('0')));
+    } else if s != 0 {
+      out.push_str(&format!("{sign}{s}S"));
+    }
+  }
+  if out == "P" {
+    out.push_str("T0S");
+  }
+  out
+}
+
+/// A list cell: Postgres array text, a `[...]` literal for DuckDB and
+/// ClickHouse, and JSON text where the column is `json` (see [`render_type`]).
+fn list_literal(dialect: &str, items: &ArrayRef) -> anyhow::Result<String> {
+  match dialect {
+    "postgres" => Ok(quote_string(dialect, &pg_array_text(items)?)),
+    "duckdb" | "clickhouse" => {
+      let items = (0..items.len())
+        .map(|i| cell_literal(dialect, items, i))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+      Ok(format!("[{}]", items.join(", ")))
+    }
+    _ => Ok(quote_string(dialect, &json_list(items)?.to_string())),
+  }
+}
+
+/// `{"a","b",NULL}`. Quoted elements take the column's element type on
+/// insert, where an `ARRAY['2024-01-15']` would stay `text[]`.
+fn pg_array_text(items: &ArrayRef) -> anyhow::Result<String> {
+  let mut parts = Vec::with_capacity(items.len());
+  for i in 0..items.len() {
+    let part = match items.data_type() {
+      _ if items.is_null(i) => "NULL".to_string(),
+      DataType::List(_) => pg_array_text(&items.as_list::<i32>().value(i))?,
+      DataType::LargeList(_) => pg_array_text(&items.as_list::<i64>().value(i))?,
+      dt => {
+        let text = match dt {
+          DataType::Binary => format!("\\x{}", hex(items.as_binary::<i32>().value(i))),
+          DataType::Timestamp(..) => array_value_to_string(items, i)?.replacen('T', " ", 1),
+          DataType::Interval(unit) => interval_text("postgres", items, *unit, i),
+          _ => array_value_to_string(items, i)?,
+        };
+        format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+      }
+    };
+    parts.push(part);
+  }
+  Ok(format!("{{{}}}", parts.join(",")))
+}
+
+fn json_list(items: &ArrayRef) -> anyhow::Result<serde_json::Value> {
+  let values = (0..items.len())
+    .map(|i| json_value(items, i))
+    .collect::<anyhow::Result<Vec<_>>>()?;
+  Ok(serde_json::Value::Array(values))
+}
+
+fn json_value(items: &ArrayRef, i: usize) -> anyhow::Result<serde_json::Value> {
+  use serde_json::Value;
+  if items.is_null(i) {
+    return Ok(Value::Null);
+  }
+  Ok(match items.data_type() {
+    DataType::List(_) => json_list(&items.as_list::<i32>().value(i))?,
+    DataType::LargeList(_) => json_list(&items.as_list::<i64>().value(i))?,
+    DataType::Boolean => Value::Bool(items.as_boolean().value(i)),
+    dt => {
+      let text = array_value_to_string(items, i)?;
+      match dt.is_numeric() {
+        true => serde_json::from_str(&text).unwrap_or(Value::String(text)),
+        false => Value::String(text),
+      }
+    }
+  })
+}
+
+fn hex(bytes: &[u8]) -> String {
+  bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn binary_literal(dialect: &str, bytes: &[u8]) -> String {
+  let hex = hex(bytes);
+  match dialect {
+    "postgres" => format!("'\\x{hex}'::bytea"),
+    "clickhouse" => format!("unhex('{hex}')"),
+    "duckdb" => format!("from_hex('{hex}')"),
+    "mssql" => format!("0x{hex}"),
+    _ => format!("X'{hex}'"),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Arc;
+  use std::sync::atomic::{AtomicUsize, Ordering};
+
+  use arrow::array::{
+    BinaryArray, BooleanArray, FixedSizeBinaryArray, Int32Array, IntervalMonthDayNanoArray,
+    ListArray, StringArray,
+  };
+  use arrow::datatypes::{Int32Type, IntervalMonthDayNano};
+  use arrow::datatypes::Field;
+
+  use super::*;
+  use crate::dialect::duckdb::DuckDbConnection;
+  use crate::dialect::mysql::MySqlConnection;
+  use crate::dialect::sqlite::SqliteConnection;
+
+  fn temp_db(sql: &str) -> (SqliteConnection, std::path::PathBuf) {
+    let path = std::env::temp_dir().join(format!("duckling_transfer_{}.db", nanoid::nanoid!(8)));
+    rusqlite::Connection::open(&path)
+      .unwrap()
+      .execute_batch(sql)
+      .unwrap();
+    let conn = SqliteConnection {
+      path: path.to_string_lossy().to_string(),
+    };
+    (conn, path)
+  }
+
+  #[test]
+  fn literals_follow_the_target_dialect() {
+    let batch = RecordBatch::try_new(
+      Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int32, false),
+        Field::new("ok", DataType::Boolean, true),
+        Field::new("name", DataType::Utf8, true),
+        Field::new("raw", DataType::Binary, true),
+      ])),
+      vec![
+        Arc::new(Int32Array::from(vec![1, 2])),
+        Arc::new(BooleanArray::from(vec![Some(true), None])),
+        Arc::new(StringArray::from(vec![Some("it's"), Some("a\\b")])),
+        Arc::new(BinaryArray::from(vec![Some(&[0xab_u8][..]), None])),
+      ],
+    )
+    .unwrap();
+    let mysql = MySqlConnection::default();
+    let stmts = insert_statements(&mysql, "t", &batch).unwrap();
+    assert_eq!(
+      stmts,
+      vec![
+        "INSERT INTO t (id, ok, name, raw) VALUES (1, TRUE, 'it''s', X'ab'), (2, NULL, 'a\\\\b', NULL)"
+      ]
+    );
+  }
+
+  #[test]
+  fn lists_become_array_literals() {
+    let ints: ArrayRef = Arc::new(ListArray::from_iter_primitive::<Int32Type, _, _>(vec![
+      Some(vec![Some(1), None]),
+      Some(vec![]),
+    ]));
+    let words = StringArray::from(vec![Some("a\"b"), Some("it's")]);
+    let offsets = arrow::buffer::OffsetBuffer::new(vec![0, 2].into());
+    let field = Arc::new(Field::new_list_field(DataType::Utf8, true));
+    let words: ArrayRef = Arc::new(ListArray::new(field, offsets, Arc::new(words), None));
+
+    assert_eq!(cell_literal("postgres", &ints, 0).unwrap(), r#"'{"1",NULL}'"#);
+    assert_eq!(cell_literal("postgres", &ints, 1).unwrap(), "'{}'");
+    assert_eq!(cell_literal("postgres", &words, 0).unwrap(), r#"'{"a\"b","it''s"}'"#);
+    assert_eq!(cell_literal("duckdb", &ints, 0).unwrap(), "[1, NULL]");
+    assert_eq!(cell_literal("clickhouse", &words, 0).unwrap(), r#"['a"b', 'it''s']"#);
+    assert_eq!(cell_literal("mysql", &ints, 0).unwrap(), "'[1,null]'");
+  }
+
+  #[test]
+  fn intervals_are_written_per_target() {
+    let v = |months, days, nanos| IntervalMonthDayNano::new(months, days, nanos);
+    let col: ArrayRef = Arc::new(IntervalMonthDayNanoArray::from(vec![
+      v(14, 3, 90_500_000_000),
+      v(0, 0, 0),
+      v(0, -1, -500_000_000),
+    ]));
+    let lit = |dialect, row| cell_literal(dialect, &col, row).unwrap();
+    assert_eq!(lit("postgres", 0), "'14 months 3 days 90500000 microseconds'");
+    assert_eq!(lit("duckdb", 2), "'0 months -1 days -500000 microseconds'");
+    assert_eq!(lit("sqlite", 0), "'P1Y2M3DT1M30.5S'");
+    assert_eq!(lit("mysql", 1), "'PT0S'");
+    assert_eq!(lit("mssql", 2), "N'P-1DT-0.5S'");
+
+    let schema = Schema::new(vec![Field::new("wait", col.data_type().clone(), false)]);
+    let sql = |target: &dyn Connection| create_table_sql(target, "t", &schema);
+    assert!(sql(&DuckDbConnection::default()).contains("wait interval"));
+    assert!(sql(&MySqlConnection::default()).contains("wait varchar(64)"));
+  }
+
+  #[test]
+  fn uuids_and_fixed_binary_round_trip() {
+    let id = uuid::Uuid::parse_str("a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11").unwrap();
+    let uuid_field = Field::new("id", DataType::FixedSizeBinary(16), true).with_metadata(
+      std::collections::HashMap::from([(
+        "ARROW:extension:name".to_string(),
+        "arrow.uuid".to_string(),
+      )]),
+    );
+    let batch = RecordBatch::try_new(
+      Arc::new(Schema::new(vec![
+        uuid_field,
+        Field::new("raw", DataType::FixedSizeBinary(2), true),
+      ])),
+      vec![
+        Arc::new(FixedSizeBinaryArray::try_from_iter([id.as_bytes()].into_iter()).unwrap()),
+        Arc::new(FixedSizeBinaryArray::try_from_iter([[0xab_u8, 0x01]].into_iter()).unwrap()),
+      ],
+    )
+    .unwrap();
+    let mysql = MySqlConnection::default();
+    assert!(create_table_sql(&mysql, "t", &batch.schema()).contains("id char(36)"));
+
+    let (dst, dst_path) = temp_db("");
+    let mut sql = vec![create_table_sql(&dst, "t", &batch.schema())];
+    sql.extend(insert_statements(&dst, "t", &batch).unwrap());
+    let check = rusqlite::Connection::open(&dst_path).unwrap();
+    check.execute_batch(&sql.join(";\n")).unwrap();
+    let (text, raw): (String, Vec<u8>) = check
+      .query_row("select id, raw from t", [], |r| Ok((r.get(0)?, r.get(1)?)))
+      .unwrap();
+    assert_eq!(text, id.hyphenated().to_string());
+    assert_eq!(raw, vec![0xab, 0x01]);
+    let _ = std::fs::remove_file(dst_path);
+  }
+
+  #[tokio::test]
+  async fn copies_pages_into_a_new_table() {
+    let (src, src_path) = temp_db(
+      "create table items (id integer primary key, name text, score real);
+       insert into items values (1, 'a', 1.5), (2, 'b', null), (3, 'c', 3.0), (4, 'd', 4.0), (5, 'e', 5.0);",
+    );
+    let (dst, dst_path) = temp_db("create table copied (x integer);");
+    let calls = AtomicUsize::new(0);
+    let options = TransferOptions {
+      table: "copied".into(),
+      mode: TransferMode::Replace,
+      batch_rows: Some(2),
+      ..Default::default()
+    };
+    let done = transfer(
+      &src,
+      "select * from items order by id",
+      &dst,
+      &options,
+      None,
+      &|_| {
+        calls.fetch_add(1, Ordering::SeqCst);
+      },
+    )
+    .await
+    .unwrap();
+    assert_eq!((done.rows, done.batches, done.done), (5, 3, true));
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+    let check = rusqlite::Connection::open(&dst_path).unwrap();
+    let (n, missing): (i64, i64) = check
+      .query_row(
+        "select count(*), sum(score is null) from copied",
+        [],
+        |r| Ok((r.get(0)?, r.get(1)?)),
+      )
+      .unwrap();
+    assert_eq!((n, missing), (5, 1));
+
+    let again = TransferOptions {
+      mode: TransferMode::Create,
+      ..options
+    };
+    assert!(transfer(&src, "select * from items", &dst, &again, None, &|_| {}).await.is_err());
+
+    let token = CancelToken::new();
+    token.cancel();
+    let append = TransferOptions {
+      mode: TransferMode::Append,
+      ..again
+    };
+    let err = transfer(&src, "select * from items", &dst, &append, Some(&token), &|_| {})
+      .await
+      .unwrap_err();
+    assert!(err.to_string().contains("cancel"));
+    let _ = std::fs::remove_file(src_path);
+    let _ = std::fs::remove_file(dst_path);
+  }
+
+  #[tokio::test]
+  async fn streams_pages_from_a_cursor() {
+    let src = DuckDbConnection {
+      path: ":memory:".into(),
+      cwd: None,
+    };
+    let (dst, dst_path) = temp_db("");
+    let options = TransferOptions {
+      table: "numbers".into(),
+      batch_rows: Some(2),
+      ..Default::default()
+    };
+    let done = transfer(&src, "select range as id from range(5)", &dst, &options, None, &|_| {})
+      .await
+      .unwrap();
+    assert_eq!((done.rows, done.batches), (5, 3));
+
+    let check = rusqlite::Connection::open(&dst_path).unwrap();
+    let ids: i64 = check
+      .query_row("select sum(id) from numbers", [], |r| r.get(0))
+      .unwrap();
+    assert_eq!(ids, 10);
+    let _ = std::fs::remove_file(dst_path);
+  }
+}
@@ -7,6 +7,8 @@ pub enum ConnectorError {
   Auth(String),
   Network(String),
   Sql(String),
+  /// The target row changed or vanished since it was read.
+  Conflict(String),
   Cancelled,
   Config(String),
   Internal(String),
@@ -28,6 +30,7 @@ impl ConnectorError {
       Self::Auth(_) => "auth",
       Self::Network(_) => "network",
       Self::Sql(_) => "sql",
+      Self::Conflict(_) => "conflict",
       Self::Cancelled => "cancelled",
       Self::Config(_) => "config",
       Self::Internal(_) => "internal",
@@ -41,6 +44,7 @@ impl ConnectorError {
       Self::Auth(_) => 401,
       Self::Network(_) => 502,
       Self::Sql(_) => 400,
+      Self::Conflict(_) => 409,
       Self::Cancelled => 499,
       Self::Config(_) => 422,
       Self::Internal(_) => 500,
@@ -56,6 +60,9 @@ impl ConnectorError {
     if lower.contains("cancel") {
       return Self::Cancelled;
     }
+    if lower.contains("edit conflict") {
+      return Self::Conflict(message.to_string());
+    }
     if lower.contains("password")
       || lower.contains("auth")
       || lower.contains("permission denied")
@@ -90,6 +97,7 @@ impl fmt::Display for ConnectorError {
       Self::Auth(msg) => write!(f, "authentication failed: {msg}"),
       Self::Network(msg) => write!(f, "network error: {msg}"),
       Self::Sql(msg) => write!(f, "sql error: {msg}"),
+      Self::Conflict(msg) => write!(f, "edit conflict: {msg}"),
       Self::Cancelled => write!(f, "operation cancelled"),
       Self::Config(msg) => write!(f, "invalid config: {msg}"),
       Self::Internal(msg) => write!(f, "{msg}"),
@@ -113,6 +121,58 @@ pub fn arrow_error_parts(err: &anyhow::Error) -> (i32, String) {
   (classified.http_like_code(), classified.to_string())
 }
 
+/// [`ConnectorError::code`] for any error: exact when it is a `ConnectorError`,
+/// otherwise classified from the message.
+pub fn error_code(err: &anyhow::Error) -> &'static str {
+  match err.downcast_ref::<ConnectorError>() {
+    Some(e) => e.code(),
+    None => ConnectorError::classify(&err.to_string()).code(),
+  }
+}
+
+/// SQLSTATE of a driver error: the server's own where the protocol carries
+/// one (Postgres, MySQL), else the class its error code belongs to (`22000`
+/// data, `23000` integrity, `42000` missing object or syntax).
+pub fn sql_state(err: &anyhow::Error) -> Option<String> {
+  if let Some(e) = err.downcast_ref::<tokio_postgres::Error>() {
+    return e.code().map(|c| c.code().to_string());
+  }
+  if let Some(mysql::Error::MySqlError(e)) = err.downcast_ref::<mysql::Error>() {
+    return Some(e.state.clone());
+  }
+  let class = if let Some(rusqlite::Error::SqliteFailure(e, msg)) = err.downcast_ref() {
+    use rusqlite::ffi::ErrorCode;
+    match (e.code, msg.as_deref().unwrap_or_default()) {
+      (ErrorCode::ConstraintViolation, _) => "23",
+      (ErrorCode::TooBig | ErrorCode::TypeMismatch, _) => "22",
+      (_, m) if m.starts_with("no such") || m.contains("has no column named") => "42",
+      _ => return None,
+    }
+  } else if let Some(duckdb::Error::DuckDBFailure(_, Some(msg))) = err.downcast_ref() {
+    // DuckDB prefixes messages with the error kind.
+    let kind = msg.split(" Error:").next().unwrap_or_default();
+    match kind {
+      "Constraint" => "23",
+      "Conversion" | "Out of Range" | "Invalid Input" => "22",
+      "Catalog" | "Binder" | "Parser" => "42",
+      _ => return None,
+    }
+  } else if let Some(tiberius::error::Error::Server(e)) = err.downcast_ref() {
+    match e.code() {
+      // Duplicate key, NULL into NOT NULL, foreign key / check.
+      515 | 547 | 2601 | 2627 => "23",
+      // Truncation, conversion, overflow.
+      220 | 241 | 245 | 2628 | 8114 | 8115 | 8152 => "22",
+      // Invalid object / column name.
+      207 | 208 => "42",
+      _ => return None,
+    }
+  } else {
+    return None;
+  };
+  Some(format!("{class}000"))
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -123,6 +183,10 @@ mod tests {
     assert_eq!(ConnectorError::cancelled().code(), "cancelled");
     assert_eq!(ConnectorError::Sql("x".into()).http_like_code(), 400);
     assert_eq!(ConnectorError::Cancelled.http_like_code(), 499);
+    assert_eq!(error_code(&ConnectorError::cancelled().into()), "cancelled");
+    assert_eq!(error_code(&anyhow::anyhow!("syntax error at end")), "sql");
+    let conflict = ConnectorError::Conflict("row not found".into());
+    assert_eq!(ConnectorError::classify(&conflict.to_string()).http_like_code(), 409);
   }
 
   #[test]
@@ -154,6 +218,23 @@ mod tests {
     assert_eq!(back.code(), "unsupported");
   }
 
+  #[test]
+  fn sql_states_come_from_the_driver() {
+    let mysql = mysql::Error::MySqlError(mysql::MySqlError {
+      state: "22001".into(),
+      message: "Data too long for column 'name' at row 1".into(),
+      code: 1406,
+    });
+    assert_eq!(sql_state(&mysql.into()).as_deref(), Some("22001"));
+    let sqlite = rusqlite::Error::SqliteFailure(
+      rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_ERROR),
+      Some("no such table: t".into()),
+    );
+    let err = anyhow::Error::from(sqlite).context("insert failed");
+    assert_eq!(sql_state(&err).as_deref(), Some("42000"));
+    assert_eq!(sql_state(&anyhow::anyhow!("no such table: t")), None);
+  }
+
   #[test]
   fn arrow_error_parts_numeric() {
     let err = anyhow::anyhow!("operation not supported: drop_table");
@@ -0,0 +1,284 @@
+//! Persistent query history in a local SQLite file: every statement run from
+//! the app, searchable by text (FTS5), connection and date, with pinning.
+
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::anyhow;
+use rusqlite::types::Value;
+use rusqlite::{Row, params, params_from_iter};
+use serde::{Deserialize, Serialize};
+
+/// Unpinned entries kept; older ones are pruned on insert.
+const MAX_UNPINNED: usize = 10_000;
+
+const SCHEMA: &str = "
+  CREATE TABLE IF NOT EXISTS history (
+    id INTEGER PRIMARY KEY,
+    connection_id TEXT,
+    kind TEXT NOT NULL,
+    sql TEXT NOT NULL,
+    executed_at INTEGER NOT NULL,
+    elapsed_ms INTEGER NOT NULL,
+    rows INTEGER,
+    error_code TEXT,
+    message TEXT,
+    pinned INTEGER NOT NULL DEFAULT 0
+  );
+  CREATE INDEX IF NOT EXISTS history_connection ON history (connection_id, executed_at);
+  CREATE VIRTUAL TABLE IF NOT EXISTS history_fts
+    USING fts5(sql, content = 'history', content_rowid = 'id');
+  CREATE TRIGGER IF NOT EXISTS history_ai AFTER INSERT ON history BEGIN
+    INSERT INTO history_fts (rowid, sql) VALUES (new.id, new.sql);
+  END;
+  CREATE TRIGGER IF NOT EXISTS history_ad AFTER DELETE ON history BEGIN
+    INSERT INTO history_fts (history_fts, rowid, sql) VALUES ('delete', old.id, old.sql);
+  END;
+";
+
+/// One statement to record.
+#[derive(Debug, Clone, Default)]
+pub struct HistoryRecord {
+  pub connection_id: Option<String>,
+  /// `query`, `paging_query`, `export`, ...
+  pub kind: String,
+  pub sql: String,
+  pub elapsed_ms: u64,
+  pub rows: Option<usize>,
+  /// [`ConnectorError::code`](crate::ConnectorError::code) when it failed.
+  pub error_code: Option<String>,
+  pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+  pub id: i64,
+  pub connection_id: Option<String>,
+  pub kind: String,
+  pub sql: String,
+  /// Unix time in milliseconds.
+  pub executed_at: i64,
+  pub elapsed_ms: u64,
+  pub rows: Option<u64>,
+  pub error_code: Option<String>,
+  pub message: Option<String>,
+  pub pinned: bool,
+}
+
+/// Search criteria; all set fields must match. Newest entries come first.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HistoryFilter {
+  /// Words that must all appear in the SQL (prefix match).
+  pub text: Option<String>,
+  pub connection_id: Option<String>,
+  /// Unix milliseconds, inclusive.
+  pub from: Option<i64>,
+  /// Unix milliseconds, exclusive.
+  pub to: Option<i64>,
+  pub pinned_only: bool,
+  /// Defaults to 100.
+  pub limit: Option<usize>,
+  pub offset: usize,
+}
+
+pub struct HistoryStore {
+  conn: Mutex<rusqlite::Connection>,
+}
+
+impl HistoryStore {
+  pub fn open(path: &Path) -> anyhow::Result<Self> {
+    Self::init(rusqlite::Connection::open(path)?)
+  }
+
+  pub fn open_in_memory() -> anyhow::Result<Self> {
+    Self::init(rusqlite::Connection::open_in_memory()?)
+  }
+
+  fn init(conn: rusqlite::Connection) -> anyhow::Result<Self> {
+    conn.execute_batch(SCHEMA)?;
+    Ok(Self {
+      conn: Mutex::new(conn),
+    })
+  }
+
+  fn conn(&self) -> anyhow::Result<std::sync::MutexGuard<'_, rusqlite::Connection>> {
+    self.conn.lock().map_err(|_| anyhow!("history lock poisoned"))
+  }
+
+  pub fn record(&self, record: &HistoryRecord) -> anyhow::Result<i64> {
+    let executed_at = SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .map_or(0, |d| d.as_millis() as i64);
+    let conn = self.conn()?;
+    conn.execute(
+      "INSERT INTO history
+        (connection_id, kind, sql, executed_at, elapsed_ms, rows, error_code, message)
+      VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+      params![
+        record.connection_id,
+        record.kind,
+        record.sql,
+        executed_at,
+        record.elapsed_ms as i64,
+        record.rows.map(|r| r as i64),
+        record.error_code,
+        record.message,
+      ],
+    )?;
+    let id = conn.last_insert_rowid();
+    conn.execute(
+      "DELETE FROM history WHERE pinned = 0 AND id <= (
+        SELECT id FROM history WHERE pinned = 0 ORDER BY id DESC LIMIT 1 OFFSET ?1
+      )",
+      [MAX_UNPINNED as i64],
+    )?;
+    Ok(id)
+  }
+
+  pub fn search(&self, filter: &HistoryFilter) -> anyhow::Result<Vec<HistoryEntry>> {
+    let mut sql = "SELECT h.id, h.connection_id, h.kind, h.sql, h.executed_at, h.elapsed_ms,
+        h.rows, h.error_code, h.message, h.pinned
+      FROM history h WHERE 1 = 1"
+      .to_string();
+    let mut args: Vec<Value> = vec![];
+    if let Some(text) = filter.text.as_deref().and_then(fts_query) {
+      sql.push_str(" AND h.id IN (SELECT rowid FROM history_fts WHERE history_fts MATCH ?)");
+      args.push(Value::Text(text));
+    }
+    if let Some(id) = &filter.connection_id {
+      sql.push_str(" AND h.connection_id = ?");
+      args.push(Value::Text(id.clone()));
+    }
+    if let Some(from) = filter.from {
+      sql.push_str(" AND h.executed_at >= ?");
+      args.push(Value::Integer(from));
+    }
+    if let Some(to) = filter.to {
+      sql.push_str(" AND h.executed_at < ?");
+      args.push(Value::Integer(to));
+    }
+    if filter.pinned_only {
+      sql.push_str(" AND h.pinned = 1");
+    }
+    sql.push_str(" ORDER BY h.id DESC LIMIT ? OFFSET ?");
+    args.push(Value::Integer(filter.limit.unwrap_or(100) as i64));
+    args.push(Value::Integer(filter.offset as i64));
+
+    let conn = self.conn()?;
+    let mut stmt = conn.prepare(&sql)?;
+    let entries = stmt
+      .query_map(params_from_iter(args), entry)?
+      .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(entries)
+  }
+
+  /// Pinned entries survive pruning and [`clear`](Self::clear).
+  pub fn set_pinned(&self, id: i64, pinned: bool) -> anyhow::Result<bool> {
+    let n = self
+      .conn()?
+      .execute("UPDATE history SET pinned = ?2 WHERE id = ?1", params![id, pinned])?;
+    Ok(n > 0)
+  }
+
+  pub fn delete(&self, id: i64) -> anyhow::Result<bool> {
+    let n = self.conn()?.execute("DELETE FROM history WHERE id = ?1", [id])?;
+    Ok(n > 0)
+  }
+
+  /// Remove unpinned entries (optionally only those of one connection).
+  pub fn clear(&self, connection_id: Option<&str>) -> anyhow::Result<usize> {
+    let n = self.conn()?.execute(
+      "DELETE FROM history WHERE pinned = 0 AND (?1 IS NULL OR connection_id = ?1)",
+      [connection_id],
+    )?;
+    Ok(n)
+  }
+}
+
+fn entry(row: &Row) -> rusqlite::Result<HistoryEntry> {
+  Ok(HistoryEntry {
+    id: row.get(0)?,
+    connection_id: row.get(1)?,
+    kind: row.get(2)?,
+    sql: row.get(3)?,
+    executed_at: row.get(4)?,
+    elapsed_ms: row.get::<_, i64>(5)? as u64,
+    rows: row.get::<_, Option<i64>>(6)?.map(|r| r as u64),
+    error_code: row.get(7)?,
+    message: row.get(8)?,
+    pinned: row.get(9)?,
+  })
+}
+
+/// Free text as an FTS5 query: every word quoted (so operators and
+/// punctuation are literal) and matched as a prefix.
+fn fts_query(text: &str) -> Option<String> {
+  let terms: Vec<String> = text
+    .split_whitespace()
+    .map(|w| format!("\"{}\"*", w.replace('"', "\"\"")))
+    .collect();
+  (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn record(store: &HistoryStore, conn: &str, sql: &str, error: Option<&str>) -> i64 {
+    store
+      .record(&HistoryRecord {
+        connection_id: Some(conn.into()),
+        kind: "query".into(),
+        sql: sql.into(),
+        elapsed_ms: 3,
+        rows: error.is_none().then_some(1),
+        error_code: error.map(str::to_string),
+        ..Default::default()
+      })
+      .unwrap()
+  }
+
+  #[test]
+  fn search_filters_pins_and_clears() {
+    let store = HistoryStore::open_in_memory().unwrap();
+    let a = record(&store, "pg", "select * from orders where id = 1", None);
+    record(&store, "pg", "select count(*) from customers", None);
+    record(&store, "my", "select * from ordersx", Some("sql"));
+
+    let search = |filter: HistoryFilter| {
+      let found = store.search(&filter).unwrap();
+      found.into_iter().map(|e| e.sql).collect::<Vec<_>>()
+    };
+    let text = |t: &str| HistoryFilter {
+      text: Some(t.into()),
+      ..Default::default()
+    };
+    assert_eq!(search(text("order")).len(), 2);
+    assert_eq!(search(text("orders id")), ["select * from orders where id = 1"]);
+    assert_eq!(search(text("count(*)")), ["select count(*) from customers"]);
+    let mine = HistoryFilter {
+      connection_id: Some("my".into()),
+      ..Default::default()
+    };
+    let found = store.search(&mine).unwrap();
+    assert_eq!(found[0].error_code.as_deref(), Some("sql"));
+    let future = HistoryFilter {
+      from: Some(i64::MAX),
+      ..Default::default()
+    };
+    assert!(search(future).is_empty());
+
+    assert!(store.set_pinned(a, true).unwrap());
+    assert_eq!(store.clear(None).unwrap(), 2);
+    let pinned = HistoryFilter {
+      pinned_only: true,
+      ..Default::default()
+    };
+    assert_eq!(search(pinned).len(), 1);
+    assert!(store.delete(a).unwrap());
+    assert!(search(text("orders")).is_empty());
+  }
+}
@@ -2,6 +2,7 @@ pub mod cancel;
 pub mod config;
 pub mod dialect;
 pub mod error;
+pub mod history;
 pub mod preview;
 pub mod ssh_config;
 pub mod ssh_tunnel;
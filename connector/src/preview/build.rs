@@ -236,6 +236,9 @@ pub fn grid_to_raw_arrow_data(grid: PreviewGrid) -> anyhow::Result<RawArrowData>
     batch,
     titles: Some(titles),
     sql,
+    estimated: false,
+    cursor: None,
+    truncated: false,
   })
 }
 
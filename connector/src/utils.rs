@@ -96,12 +96,19 @@ pub struct Table {
   pub size: Option<u64>,
 }
 
+#[derive(Debug)]
 pub struct RawArrowData {
   /// The total number of rows that were selected.
   pub total: usize,
   pub batch: RecordBatch,
   pub titles: Option<Vec<Title>>,
   pub sql: Option<String>,
+  /// `total` comes from catalog statistics or an abandoned count, not `count(*)`.
+  pub estimated: bool,
+  /// Token that seeks to the page after this one (keyset `query_table` paging).
+  pub cursor: Option<String>,
+  /// Rows were dropped to stay within [`QueryLimits`](crate::dialect::QueryLimits).
+  pub truncated: bool,
 }
 
 impl RawArrowData {
@@ -110,6 +117,9 @@ impl RawArrowData {
       total: batch.num_rows(),
       titles: None,
       sql: None,
+      estimated: false,
+      cursor: None,
+      truncated: false,
       batch,
     }
   }
@@ -59,6 +59,18 @@ pub struct ArrowResponse {
   /// Row objects for `format=rows`.
   #[serde(default, skip_serializing_if = "Option::is_none")]
   pub rows: Option<Vec<serde_json::Map<String, serde_json::Value>>>,
+
+  /// `total` is an estimate (catalog statistics or an abandoned exact count).
+  #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+  pub estimated: bool,
+
+  /// Keyset token for the next `query_table` page (pass back as `after`).
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub cursor: Option<String>,
+
+  /// Rows were dropped to stay within the row / byte limits.
+  #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+  pub truncated: bool,
 }
 
 impl ArrowResponse {
@@ -93,6 +105,9 @@ impl ArrowResponse {
             data,
             elapsed,
             titles,
+            estimated: raw.estimated,
+            cursor: raw.cursor.clone(),
+            truncated: raw.truncated,
             format: PreviewFormat::Arrow,
             ..Self::default()
           },
@@ -124,6 +139,9 @@ impl ArrowResponse {
                   code: 0,
                   data: Vec::new(),
                   parse_location: None,
+                  estimated: raw.estimated,
+                  cursor: raw.cursor.clone(),
+                  truncated: raw.truncated,
                 }
               }
               Err(rows_err) => ArrowResponse {
@@ -188,6 +206,9 @@ impl ArrowResponse {
       message: String::new(),
       data: Vec::new(),
       parse_location: None,
+      estimated: false,
+      cursor: None,
+      truncated: false,
     }
   }
 }
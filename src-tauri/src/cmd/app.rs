@@ -216,7 +216,7 @@ fn sanitize_scratch_id(id: &str) -> String {
     .collect()
 }
 
-fn scratch_dir(app: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
+pub(crate) fn scratch_dir(app: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
   let dir = app
     .path()
     .app_data_dir()
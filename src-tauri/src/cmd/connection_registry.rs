@@ -63,6 +63,9 @@ fn apply_overrides(base: DialectPayload, overlay: &DialectPayload) -> DialectPay
     uri: overlay.uri.clone().or(base.uri),
     token: overlay.token.clone().or(base.token),
     disable_ssl: overlay.disable_ssl.or(base.disable_ssl),
+    trust_server_certificate: overlay
+      .trust_server_certificate
+      .or(base.trust_server_certificate),
     glob: overlay.glob.clone().or(base.glob),
     ssl_mode: overlay.ssl_mode.clone().or(base.ssl_mode),
     ssh_enabled: overlay.ssh_enabled.or(base.ssh_enabled),
@@ -79,6 +82,7 @@ fn apply_overrides(base: DialectPayload, overlay: &DialectPayload) -> DialectPay
       .ssh_host_key_policy
       .clone()
       .or(base.ssh_host_key_policy),
+    limits: overlay.limits.clone().or(base.limits),
   }
 }
 
@@ -1,16 +1,27 @@
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use std::time::Instant;
 
 use serde::Deserialize;
 use serde::Serialize;
-use tauri::State;
+use tauri::{AppHandle, Emitter, State};
 
-use crate::api::ArrowResponse;
+use crate::api::{ArrowResponse, SqlParseLocationDto};
 use super::connection_registry::{self, ConnectionRegistry};
+use super::history::{QueryHistory, result_rows};
 use super::inflight::{InflightGuard, InflightQueries};
 use super::session_manager::SessionManager;
+use super::sql_template;
 use connector::ConnectionConfig;
-use connector::dialect::Connection;
+use connector::dialect::{
+  Connection, CountOptions, ObjectKind, QueryLimits, QueryPlan, ResultCursor, TableDetail,
+};
+use connector::dialect::data_diff::{self, DataDiffOptions};
+use connector::dialect::edit::{self, ChangeSet, build_statements};
+use connector::dialect::import::{self, FileColumn, ImportOptions, ImportResult};
+use connector::dialect::limits;
+use connector::dialect::schema_diff::{self, SchemaDiff};
+use connector::dialect::transfer::{self, TransferOptions, TransferProgress};
 use connector::utils::{FunctionMeta, Metadata, TreeNode};
 
 #[allow(dead_code)]
@@ -81,6 +92,9 @@ pub struct DialectPayload {
   /// Frontend DialectConfig uses snake_case (`disable_ssl`); also accept camelCase.
   #[serde(default, alias = "disableSsl")]
   pub disable_ssl: Option<bool>,
+  /// SQL Server: skip certificate verification (`TrustServerCertificate=true`).
+  #[serde(default, alias = "trustServerCertificate")]
+  pub trust_server_certificate: Option<bool>,
   /// Quack: glob pattern for file discovery.
   #[serde(default)]
   pub glob: Option<String>,
@@ -104,6 +118,9 @@ pub struct DialectPayload {
   /// `insecure` | `accept_new` | `strict`
   #[serde(default, alias = "sshHostKeyPolicy")]
   pub ssh_host_key_policy: Option<String>,
+  /// Connection-wide defaults for statement timeout and result size.
+  #[serde(default)]
+  pub limits: Option<QueryLimits>,
 }
 
 #[allow(dead_code)]
@@ -144,6 +161,7 @@ fn payload_to_config(payload: DialectPayload) -> ConnectionConfig {
     uri: payload.uri,
     token: payload.token,
     disable_ssl: payload.disable_ssl,
+    trust_server_certificate: payload.trust_server_certificate,
     glob: payload.glob,
     ssl_mode: payload.ssl_mode,
     ssh: None,
@@ -231,34 +249,53 @@ pub async fn get_session_idle_ttl(
   Ok(sessions.idle_ttl_secs())
 }
 
+/// Request limits with unset fields falling back to the connection's own.
+fn request_limits(request: Option<QueryLimits>, payload: &DialectPayload) -> QueryLimits {
+  let connection = payload.limits.clone().unwrap_or_default();
+  request.unwrap_or_default().or(&connection)
+}
+
 #[tauri::command]
 pub async fn query(
   registry: State<'_, ConnectionRegistry>,
   sessions: State<'_, SessionManager>,
   inflight: State<'_, InflightQueries>,
+  history: State<'_, QueryHistory>,
   sql: String,
   limit: usize,
   offset: usize,
+  limits: Option<QueryLimits>,
   dialect: DialectPayload,
   #[allow(non_snake_case)]
   requestId: Option<String>,
 ) -> Result<ArrowResponse, String> {
+  let connection_id = dialect.connection_id.clone();
   let resolved = connection_registry::resolve_payload(&registry, dialect)?;
   let dname = if resolved.dialect.is_empty() {
     "generic".to_string()
   } else {
     resolved.dialect.clone()
   };
+  let limits = request_limits(limits, &resolved);
   let d = resolve_connection(&registry, &sessions, resolved).await?;
+  let run = async {
+    if limits == QueryLimits::default() {
+      d.query(&sql, limit, offset).await
+    } else {
+      d.query_limited(&sql, &limits).await
+    }
+  };
   let start = Instant::now();
   let res = if let Some(ref rid) = requestId.filter(|s| !s.trim().is_empty()) {
     let (_guard, token) = InflightGuard::register(&inflight, rid)?;
-    // Race the dialect query against cancel (cooperative; best-effort for sync drivers).
-    connector::cancel::with_cancel(Some(&token), d.query(&sql, limit, offset)).await
+    // Race the dialect query against cancel; drivers also stop it server-side.
+    connector::cancel::with_cancel(Some(&token), run).await
   } else {
-    d.query(&sql, limit, offset).await
+    run.await
   };
-  let duration = start.elapsed().as_millis();
+  let elapsed = start.elapsed();
+  history.record(connection_id.as_deref(), "query", &sql, elapsed, &res, result_rows);
+  let duration = elapsed.as_millis();
   Ok(ArrowResponse::from_raw_data_with_dialect(
     res,
     Some(duration),
@@ -272,32 +309,45 @@ pub async fn paging_query(
   registry: State<'_, ConnectionRegistry>,
   sessions: State<'_, SessionManager>,
   inflight: State<'_, InflightQueries>,
+  history: State<'_, QueryHistory>,
   sql: String,
   limit: usize,
   offset: usize,
+  count: Option<CountOptions>,
+  limits: Option<QueryLimits>,
   dialect: DialectPayload,
   #[allow(non_snake_case)]
   requestId: Option<String>,
 ) -> Result<ArrowResponse, String> {
+  let connection_id = dialect.connection_id.clone();
   let resolved = connection_registry::resolve_payload(&registry, dialect)?;
   let dname = if resolved.dialect.is_empty() {
     "generic".to_string()
   } else {
     resolved.dialect.clone()
   };
+  // Pages are already bounded in rows; only the timeout applies.
+  let timeout = request_limits(limits, &resolved).timeout();
   let d = resolve_connection(&registry, &sessions, resolved).await?;
+  let count = count.unwrap_or_default();
+  let run = async {
+    let page = d.paging_query(&sql, Some(limit), Some(offset), &count);
+    match timeout {
+      Some(timeout) => connector::cancel::with_timeout(timeout, page).await,
+      None => page.await,
+    }
+  };
   let start = Instant::now();
   let res = if let Some(ref rid) = requestId.filter(|s| !s.trim().is_empty()) {
     let (_guard, token) = InflightGuard::register(&inflight, rid)?;
-    connector::cancel::with_cancel(
-      Some(&token),
-      d.paging_query(&sql, Some(limit), Some(offset)),
-    )
-    .await
+    connector::cancel::with_cancel(Some(&token), run).await
   } else {
-    d.paging_query(&sql, Some(limit), Some(offset)).await
+    run.await
   };
-  let duration = start.elapsed().as_millis();
+  let elapsed = start.elapsed();
+  let kind = "paging_query";
+  history.record(connection_id.as_deref(), kind, &sql, elapsed, &res, result_rows);
+  let duration = elapsed.as_millis();
   Ok(ArrowResponse::from_raw_data_with_dialect(
     res,
     Some(duration),
@@ -306,6 +356,718 @@ pub async fn paging_query(
   ))
 }
 
+/// One statement's outcome from [`run_script`]; `code != 0` marks a failure.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScriptStatementResponse {
+  pub index: usize,
+  pub sql: String,
+  pub line: u64,
+  pub column: u64,
+  pub result: Option<ArrowResponse>,
+  pub rows_affected: Option<usize>,
+  pub elapsed: u128,
+  pub code: i32,
+  pub message: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub parse_location: Option<SqlParseLocationDto>,
+}
+
+/// Split a multi-statement script and run statements in order.
+/// `continueOnError` keeps going past failures (default: stop at the first one).
+#[tauri::command]
+pub async fn run_script(
+  registry: State<'_, ConnectionRegistry>,
+  sessions: State<'_, SessionManager>,
+  inflight: State<'_, InflightQueries>,
+  sql: String,
+  limit: Option<usize>,
+  #[allow(non_snake_case)]
+  continueOnError: Option<bool>,
+  dialect: DialectPayload,
+  #[allow(non_snake_case)]
+  requestId: Option<String>,
+) -> Result<Vec<ScriptStatementResponse>, String> {
+  let d = resolve_connection(&registry, &sessions, dialect).await?;
+  let mode = if continueOnError.unwrap_or(false) {
+    connector::dialect::ScriptErrorMode::Continue
+  } else {
+    connector::dialect::ScriptErrorMode::Stop
+  };
+  let outcomes = if let Some(ref rid) = requestId.filter(|s| !s.trim().is_empty()) {
+    let (_guard, token) = InflightGuard::register(&inflight, rid)?;
+    connector::cancel::with_cancel(Some(&token), d.run_script(&sql, limit, mode)).await
+  } else {
+    d.run_script(&sql, limit, mode).await
+  }
+  .map_err(|e| e.to_string())?;
+
+  Ok(
+    outcomes
+      .into_iter()
+      .map(|o| {
+        let (code, message) = o
+          .error
+          .as_ref()
+          .map(connector::error::arrow_error_parts)
+          .unwrap_or_default();
+        ScriptStatementResponse {
+          index: o.index,
+          result: o.result.map(|raw| {
+            ArrowResponse::from_raw_data(Ok(raw), Some(o.elapsed), Some(o.sql.clone()))
+          }),
+          sql: o.sql,
+          line: o.line,
+          column: o.column,
+          rows_affected: o.rows_affected,
+          elapsed: o.elapsed,
+          code,
+          message,
+          parse_location: o.parse_location.map(|loc| SqlParseLocationDto {
+            line: loc.line,
+            column: loc.column,
+            message: Some(loc.message),
+          }),
+        }
+      })
+      .collect(),
+  )
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecuteResponse {
+  pub rows_affected: usize,
+  pub elapsed: u128,
+}
+
+/// Run a non-query statement (DDL/DML) and report rows affected.
+#[tauri::command]
+pub async fn execute(
+  registry: State<'_, ConnectionRegistry>,
+  sessions: State<'_, SessionManager>,
+  inflight: State<'_, InflightQueries>,
+  sql: String,
+  dialect: DialectPayload,
+  #[allow(non_snake_case)]
+  requestId: Option<String>,
+) -> Result<ExecuteResponse, String> {
+  let d = resolve_connection(&registry, &sessions, dialect).await?;
+  let start = Instant::now();
+  let res = if let Some(ref rid) = requestId.filter(|s| !s.trim().is_empty()) {
+    let (_guard, token) = InflightGuard::register(&inflight, rid)?;
+    connector::cancel::with_cancel(Some(&token), d.execute(&sql)).await
+  } else {
+    d.execute(&sql).await
+  };
+  let rows_affected = res.map_err(|e| e.to_string())?;
+  Ok(ExecuteResponse {
+    rows_affected,
+    elapsed: start.elapsed().as_millis(),
+  })
+}
+
+/// Run a query with `:name` / `$1` placeholders bound as a prepared statement.
+/// `values` are the text entered per parameter, typed by the `@params` block.
+#[tauri::command]
+pub async fn query_params(
+  registry: State<'_, ConnectionRegistry>,
+  sessions: State<'_, SessionManager>,
+  inflight: State<'_, InflightQueries>,
+  history: State<'_, QueryHistory>,
+  sql: String,
+  values: Option<BTreeMap<String, String>>,
+  dialect: DialectPayload,
+  #[allow(non_snake_case)]
+  requestId: Option<String>,
+) -> Result<ArrowResponse, String> {
+  let connection_id = dialect.connection_id.clone();
+  let (body, params) = sql_template::bind_template_params(&sql, &values.unwrap_or_default())?;
+  let resolved = connection_registry::resolve_payload(&registry, dialect)?;
+  let dname = if resolved.dialect.is_empty() {
+    "generic".to_string()
+  } else {
+    resolved.dialect.clone()
+  };
+  let d = resolve_connection(&registry, &sessions, resolved).await?;
+  let start = Instant::now();
+  let res = if let Some(ref rid) = requestId.filter(|s| !s.trim().is_empty()) {
+    let (_guard, token) = InflightGuard::register(&inflight, rid)?;
+    connector::cancel::with_cancel(Some(&token), d.query_params(&body, &params)).await
+  } else {
+    d.query_params(&body, &params).await
+  };
+  let elapsed = start.elapsed();
+  history.record(connection_id.as_deref(), "query", &sql, elapsed, &res, result_rows);
+  Ok(ArrowResponse::from_raw_data_with_dialect(
+    res,
+    Some(elapsed.as_millis()),
+    Some(sql),
+    Some(dname.as_str()),
+  ))
+}
+
+/// [`execute`] with bound parameters, see [`query_params`].
+#[tauri::command]
+pub async fn execute_params(
+  registry: State<'_, ConnectionRegistry>,
+  sessions: State<'_, SessionManager>,
+  inflight: State<'_, InflightQueries>,
+  sql: String,
+  values: Option<BTreeMap<String, String>>,
+  dialect: DialectPayload,
+  #[allow(non_snake_case)]
+  requestId: Option<String>,
+) -> Result<ExecuteResponse, String> {
+  let (body, params) = sql_template::bind_template_params(&sql, &values.unwrap_or_default())?;
+  let d = resolve_connection(&registry, &sessions, dialect).await?;
+  let start = Instant::now();
+  let res = if let Some(ref rid) = requestId.filter(|s| !s.trim().is_empty()) {
+    let (_guard, token) = InflightGuard::register(&inflight, rid)?;
+    connector::cancel::with_cancel(Some(&token), d.execute_params(&body, &params)).await
+  } else {
+    d.execute_params(&body, &params).await
+  };
+  let rows_affected = res.map_err(|e| e.to_string())?;
+  Ok(ExecuteResponse {
+    rows_affected,
+    elapsed: start.elapsed().as_millis(),
+  })
+}
+
+/// Begin an explicit transaction on the live session for `dialect.connectionId`.
+/// Statements run through `transaction_query` / `transaction_execute` until
+/// `commit` or `rollback`; idle eviction of the session rolls back.
+#[tauri::command]
+pub async fn begin_transaction(
+  registry: State<'_, ConnectionRegistry>,
+  sessions: State<'_, SessionManager>,
+  dialect: DialectPayload,
+) -> Result<(), String> {
+  let id = dialect
+    .connection_id
+    .as_ref()
+    .map(|s| s.trim().to_string())
+    .filter(|s| !s.is_empty())
+    .ok_or_else(|| "connection id is required for transactions".to_string())?;
+  if sessions.has_transaction(&id) {
+    return Err(format!("transaction already open for connection {id}"));
+  }
+  let d = resolve_connection(&registry, &sessions, dialect).await?;
+  let tx = d.begin_transaction().await.map_err(|e| e.to_string())?;
+  sessions.set_transaction(&id, Arc::from(tx))
+}
+
+#[tauri::command]
+pub async fn commit(
+  sessions: State<'_, SessionManager>,
+  connection_id: String,
+) -> Result<(), String> {
+  let tx = sessions.take_transaction(&connection_id)?;
+  tx.commit().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn rollback(
+  sessions: State<'_, SessionManager>,
+  connection_id: String,
+) -> Result<(), String> {
+  let tx = sessions.take_transaction(&connection_id)?;
+  tx.rollback().await.map_err(|e| e.to_string())
+}
+
+/// Whether `connection_id` has an open transaction.
+#[tauri::command]
+pub async fn transaction_status(
+  sessions: State<'_, SessionManager>,
+  connection_id: String,
+) -> Result<bool, String> {
+  Ok(sessions.has_transaction(&connection_id))
+}
+
+#[tauri::command]
+pub async fn transaction_query(
+  sessions: State<'_, SessionManager>,
+  inflight: State<'_, InflightQueries>,
+  connection_id: String,
+  sql: String,
+  #[allow(non_snake_case)]
+  requestId: Option<String>,
+) -> Result<ArrowResponse, String> {
+  let tx = sessions.transaction(&connection_id)?;
+  let start = Instant::now();
+  let res = if let Some(ref rid) = requestId.filter(|s| !s.trim().is_empty()) {
+    let (_guard, token) = InflightGuard::register(&inflight, rid)?;
+    connector::cancel::with_cancel(Some(&token), tx.query(&sql)).await
+  } else {
+    tx.query(&sql).await
+  };
+  let duration = start.elapsed().as_millis();
+  Ok(ArrowResponse::from_raw_data_with_dialect(
+    res,
+    Some(duration),
+    Some(sql),
+    Some(tx.dialect()),
+  ))
+}
+
+#[tauri::command]
+pub async fn transaction_execute(
+  sessions: State<'_, SessionManager>,
+  inflight: State<'_, InflightQueries>,
+  connection_id: String,
+  sql: String,
+  #[allow(non_snake_case)]
+  requestId: Option<String>,
+) -> Result<ExecuteResponse, String> {
+  let tx = sessions.transaction(&connection_id)?;
+  let start = Instant::now();
+  let res = if let Some(ref rid) = requestId.filter(|s| !s.trim().is_empty()) {
+    let (_guard, token) = InflightGuard::register(&inflight, rid)?;
+    connector::cancel::with_cancel(Some(&token), tx.execute(&sql)).await
+  } else {
+    tx.execute(&sql).await
+  };
+  let rows_affected = res.map_err(|e| e.to_string())?;
+  Ok(ExecuteResponse {
+    rows_affected,
+    elapsed: start.elapsed().as_millis(),
+  })
+}
+
+/// Rows per cursor page when the caller does not ask for a size.
+const CURSOR_PAGE_ROWS: usize = 500;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CursorPageResponse {
+  /// Pass to `fetch_cursor` / `close_cursor`; `None` once the result is exhausted.
+  pub cursor_id: Option<String>,
+  pub page: ArrowResponse,
+  pub done: bool,
+}
+
+/// Fetch one page; an exhausted or failed cursor is closed and reported `done`.
+async fn next_cursor_page(
+  sessions: &SessionManager,
+  connection_id: &str,
+  cursor_id: String,
+  cursor: Arc<dyn ResultCursor>,
+  page_size: Option<usize>,
+) -> CursorPageResponse {
+  let rows = page_size.filter(|n| *n > 0).unwrap_or(CURSOR_PAGE_ROWS);
+  let start = Instant::now();
+  let res = cursor.fetch(rows).await;
+  let done = !res.as_ref().is_ok_and(|page| page.batch.num_rows() >= rows);
+  if done && let Ok(cursor) = sessions.take_cursor(connection_id, &cursor_id) {
+    let _ = cursor.close().await;
+  }
+  let sql = res.as_ref().ok().and_then(|page| page.sql.clone());
+  CursorPageResponse {
+    cursor_id: (!done).then_some(cursor_id),
+    page: ArrowResponse::from_raw_data_with_dialect(
+      res,
+      Some(start.elapsed().as_millis()),
+      sql,
+      Some(cursor.dialect()),
+    ),
+    done,
+  }
+}
+
+/// Run `sql` once on the live session for `dialect.connectionId` and return its
+/// first page; later pages come from `fetch_cursor` without re-running the query.
+#[tauri::command]
+pub async fn open_cursor(
+  registry: State<'_, ConnectionRegistry>,
+  sessions: State<'_, SessionManager>,
+  dialect: DialectPayload,
+  sql: String,
+  #[allow(non_snake_case)]
+  pageSize: Option<usize>,
+) -> Result<CursorPageResponse, String> {
+  let id = dialect
+    .connection_id
+    .as_ref()
+    .map(|s| s.trim().to_string())
+    .filter(|s| !s.is_empty())
+    .ok_or_else(|| "connection id is required for cursors".to_string())?;
+  let d = resolve_connection(&registry, &sessions, dialect).await?;
+  let cursor: Arc<dyn ResultCursor> =
+    Arc::from(d.open_cursor(&sql).await.map_err(|e| e.to_string())?);
+  let cursor_id = sessions.add_cursor(&id, Arc::clone(&cursor))?;
+  Ok(next_cursor_page(&sessions, &id, cursor_id, cursor, pageSize).await)
+}
+
+#[tauri::command]
+pub async fn fetch_cursor(
+  sessions: State<'_, SessionManager>,
+  connection_id: String,
+  cursor_id: String,
+  #[allow(non_snake_case)]
+  pageSize: Option<usize>,
+) -> Result<CursorPageResponse, String> {
+  let cursor = sessions.cursor(&connection_id, &cursor_id)?;
+  Ok(next_cursor_page(&sessions, &connection_id, cursor_id, cursor, pageSize).await)
+}
+
+/// Release a cursor before it is exhausted, e.g. when the grid is closed.
+#[tauri::command]
+pub async fn close_cursor(
+  sessions: State<'_, SessionManager>,
+  connection_id: String,
+  cursor_id: String,
+) -> Result<(), String> {
+  let cursor = sessions.take_cursor(&connection_id, &cursor_id)?;
+  cursor.close().await.map_err(|e| e.to_string())
+}
+
+/// Plan of `sql` as a dialect-neutral operator tree; `analyze` executes it.
+#[tauri::command]
+pub async fn explain(
+  registry: State<'_, ConnectionRegistry>,
+  sessions: State<'_, SessionManager>,
+  dialect: DialectPayload,
+  sql: String,
+  analyze: Option<bool>,
+) -> Result<QueryPlan, String> {
+  let d = resolve_connection(&registry, &sessions, dialect).await?;
+  d.explain(&sql, analyze.unwrap_or(false))
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Primary-key columns of `table`, in key order (empty when it has none).
+#[tauri::command]
+pub async fn table_primary_key(
+  registry: State<'_, ConnectionRegistry>,
+  sessions: State<'_, SessionManager>,
+  schema: Option<String>,
+  table: String,
+  dialect: DialectPayload,
+) -> Result<Vec<String>, String> {
+  let d = resolve_connection(&registry, &sessions, dialect).await?;
+  d.primary_key(schema.as_deref(), &table)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Columns, keys, indexes and foreign keys of `table` for ER views and join completion.
+#[tauri::command]
+pub async fn table_detail(
+  registry: State<'_, ConnectionRegistry>,
+  sessions: State<'_, SessionManager>,
+  schema: Option<String>,
+  table: String,
+  dialect: DialectPayload,
+) -> Result<TableDetail, String> {
+  let d = resolve_connection(&registry, &sessions, dialect).await?;
+  d.table_detail(schema.as_deref(), &table)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// CREATE statement(s) for a table, view or function.
+#[tauri::command]
+pub async fn object_ddl(
+  registry: State<'_, ConnectionRegistry>,
+  sessions: State<'_, SessionManager>,
+  schema: Option<String>,
+  name: String,
+  kind: ObjectKind,
+  dialect: DialectPayload,
+) -> Result<String, String> {
+  let d = resolve_connection(&registry, &sessions, dialect).await?;
+  d.object_ddl(schema.as_deref(), &name, kind)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaDiffResponse {
+  pub diff: SchemaDiff,
+  /// Statements that bring the target in line with the source (when requested).
+  pub migration: Option<Vec<String>>,
+}
+
+/// Compare the schemas of two connections (any dialect pair).
+#[tauri::command]
+#[allow(non_snake_case)]
+pub async fn schema_diff(
+  registry: State<'_, ConnectionRegistry>,
+  sessions: State<'_, SessionManager>,
+  source: DialectPayload,
+  target: DialectPayload,
+  sourceSchema: Option<String>,
+  targetSchema: Option<String>,
+  migration: Option<bool>,
+) -> Result<SchemaDiffResponse, String> {
+  let src = resolve_connection(&registry, &sessions, source).await?;
+  let dst = resolve_connection(&registry, &sessions, target).await?;
+  let diff = schema_diff::diff_schemas(
+    src.as_ref(),
+    sourceSchema.as_deref(),
+    dst.as_ref(),
+    targetSchema.as_deref(),
+  )
+  .await
+  .map_err(|e| e.to_string())?;
+  let migration = migration
+    .unwrap_or(false)
+    .then(|| schema_diff::migration_sql(dst.as_ref(), targetSchema.as_deref(), &diff));
+  Ok(SchemaDiffResponse { diff, migration })
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataDiffResponse {
+  pub key: Vec<String>,
+  pub inserted: usize,
+  pub deleted: usize,
+  pub changed: usize,
+  pub unchanged: usize,
+  pub truncated: bool,
+  pub source_only_columns: Vec<String>,
+  pub target_only_columns: Vec<String>,
+  /// Differing rows (`_diff`, `_changed`, key columns, `source.*` / `target.*`).
+  pub rows: ArrowResponse,
+  /// Per-column change counts (`column`, `changes`).
+  pub column_changes: ArrowResponse,
+  pub elapsed: u128,
+}
+
+/// Compare a table's rows across two connections by primary key (or `key`).
+#[tauri::command]
+#[allow(non_snake_case)]
+pub async fn data_diff(
+  registry: State<'_, ConnectionRegistry>,
+  sessions: State<'_, SessionManager>,
+  source: DialectPayload,
+  target: DialectPayload,
+  sourceTable: String,
+  targetTable: Option<String>,
+  key: Option<Vec<String>>,
+  pageSize: Option<usize>,
+  maxRows: Option<usize>,
+) -> Result<DataDiffResponse, String> {
+  let src = resolve_connection(&registry, &sessions, source).await?;
+  let dst = resolve_connection(&registry, &sessions, target).await?;
+  let defaults = DataDiffOptions::default();
+  let options = DataDiffOptions {
+    key: key.unwrap_or_default(),
+    page_size: pageSize.unwrap_or(defaults.page_size),
+    max_rows: maxRows.unwrap_or(defaults.max_rows),
+  };
+  let start = Instant::now();
+  let diff = data_diff::diff_table_data(
+    src.as_ref(),
+    &sourceTable,
+    dst.as_ref(),
+    targetTable.as_deref().unwrap_or(&sourceTable),
+    &options,
+  )
+  .await
+  .map_err(|e| format!("{e:#}"))?;
+  let elapsed = start.elapsed().as_millis();
+  Ok(DataDiffResponse {
+    key: diff.key,
+    inserted: diff.inserted,
+    deleted: diff.deleted,
+    changed: diff.changed,
+    unchanged: diff.unchanged,
+    truncated: diff.truncated,
+    source_only_columns: diff.source_only_columns,
+    target_only_columns: diff.target_only_columns,
+    rows: ArrowResponse::from_raw_data(Ok(diff.rows), Some(elapsed), None),
+    column_changes: ArrowResponse::from_raw_data(Ok(diff.column_changes), Some(elapsed), None),
+    elapsed,
+  })
+}
+
+/// Payload of the `transfer-progress` and `import-progress` events.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferProgressEvent {
+  pub request_id: Option<String>,
+  #[serde(flatten)]
+  pub progress: TransferProgress,
+}
+
+/// Copy the result of `sql` on `source` into a table on `target`, emitting
+/// `transfer-progress` events; cancel with `cancel_query(requestId)`.
+#[tauri::command]
+#[allow(non_snake_case)]
+pub async fn transfer_data(
+  app: AppHandle,
+  registry: State<'_, ConnectionRegistry>,
+  sessions: State<'_, SessionManager>,
+  inflight: State<'_, InflightQueries>,
+  source: DialectPayload,
+  target: DialectPayload,
+  sql: String,
+  options: TransferOptions,
+  requestId: Option<String>,
+) -> Result<TransferProgress, String> {
+  let src = resolve_connection(&registry, &sessions, source).await?;
+  let dst = resolve_connection(&registry, &sessions, target).await?;
+  let request_id = requestId.filter(|s| !s.trim().is_empty());
+  let emit = |progress: &TransferProgress| {
+    let event = TransferProgressEvent {
+      request_id: request_id.clone(),
+      progress: progress.clone(),
+    };
+    if let Err(e) = app.emit("transfer-progress", event) {
+      log::warn!("emit transfer-progress failed: {e}");
+    }
+  };
+  let res = if let Some(ref rid) = request_id {
+    let (_guard, token) = InflightGuard::register(&inflight, rid)?;
+    transfer::transfer(src.as_ref(), &sql, dst.as_ref(), &options, Some(&token), &emit).await
+  } else {
+    transfer::transfer(src.as_ref(), &sql, dst.as_ref(), &options, None, &emit).await
+  };
+  res.map_err(|e| format!("{e:#}"))
+}
+
+/// Columns DuckDB infers for a file, for the import wizard's mapping step.
+#[tauri::command]
+pub async fn import_columns(file: String, format: Option<String>) -> Result<Vec<FileColumn>, String> {
+  import::infer_columns(&file, format.as_deref().filter(|f| !f.is_empty()))
+    .await
+    .map_err(|e| format!("{e:#}"))
+}
+
+/// Load a file into a table on `dialect`, emitting `import-progress` events;
+/// cancel with `cancel_query(requestId)`.
+#[tauri::command]
+#[allow(non_snake_case)]
+pub async fn import_file(
+  app: AppHandle,
+  registry: State<'_, ConnectionRegistry>,
+  sessions: State<'_, SessionManager>,
+  inflight: State<'_, InflightQueries>,
+  dialect: DialectPayload,
+  options: ImportOptions,
+  requestId: Option<String>,
+) -> Result<ImportResult, String> {
+  let d = resolve_connection(&registry, &sessions, dialect).await?;
+  let request_id = requestId.filter(|s| !s.trim().is_empty());
+  let emit = |progress: &TransferProgress| {
+    let event = TransferProgressEvent {
+      request_id: request_id.clone(),
+      progress: progress.clone(),
+    };
+    if let Err(e) = app.emit("import-progress", event) {
+      log::warn!("emit import-progress failed: {e}");
+    }
+  };
+  let res = if let Some(ref rid) = request_id {
+    let (_guard, token) = InflightGuard::register(&inflight, rid)?;
+    import::import_file(d.as_ref(), &options, Some(&token), &emit).await
+  } else {
+    import::import_file(d.as_ref(), &options, None, &emit).await
+  };
+  res.map_err(|e| format!("{e:#}"))
+}
+
+async fn change_statements(
+  d: &dyn Connection,
+  changes: &ChangeSet,
+) -> Result<Vec<String>, String> {
+  // Inserts alone do not need the key; skip the lookup for tables without one.
+  let pk = if changes.updates.is_empty() && changes.deletes.is_empty() {
+    vec![]
+  } else {
+    d.primary_key(changes.schema.as_deref(), &changes.table)
+      .await
+      .map_err(|e| e.to_string())?
+  };
+  build_statements(d, changes, &pk).map_err(|e| e.to_string())
+}
+
+/// Generated DML for pending grid edits, without running it.
+#[tauri::command]
+pub async fn preview_changes(
+  registry: State<'_, ConnectionRegistry>,
+  sessions: State<'_, SessionManager>,
+  changes: ChangeSet,
+  dialect: DialectPayload,
+) -> Result<Vec<String>, String> {
+  let d = resolve_connection(&registry, &sessions, dialect).await?;
+  change_statements(d.as_ref(), &changes).await
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplyChangesResponse {
+  pub statements: Vec<String>,
+  pub rows_affected: Vec<usize>,
+  /// True when the edits joined an open transaction and still need `commit`.
+  pub pending_commit: bool,
+  pub elapsed: u128,
+}
+
+/// Apply grid edits atomically. With an open transaction on the connection the
+/// statements run inside it (left for the user to commit); otherwise they run in
+/// a transaction of their own. Either transaction rolls back on the first
+/// failure, including an edit whose row changed or vanished since it was read.
+#[tauri::command]
+pub async fn apply_changes(
+  registry: State<'_, ConnectionRegistry>,
+  sessions: State<'_, SessionManager>,
+  changes: ChangeSet,
+  dialect: DialectPayload,
+) -> Result<ApplyChangesResponse, String> {
+  let open_id = dialect
+    .connection_id
+    .as_deref()
+    .map(str::trim)
+    .filter(|id| sessions.has_transaction(id))
+    .map(str::to_string);
+  let open_tx = open_id.as_deref().map(|id| sessions.transaction(id)).transpose()?;
+  let d = resolve_connection(&registry, &sessions, dialect).await?;
+  let statements = change_statements(d.as_ref(), &changes).await?;
+  let start = Instant::now();
+  let rows_affected = match (&open_tx, &open_id) {
+    (Some(tx), Some(id)) => match edit::execute_checked(tx.as_ref(), &changes, &statements).await {
+      Ok(affected) => affected,
+      Err(err) => {
+        // The edits already applied cannot be undone alone.
+        let rolled_back = match sessions.take_transaction(id) {
+          Ok(tx) => tx.rollback().await.map_err(|e| e.to_string()),
+          Err(e) => Err(e),
+        };
+        if let Err(e) = rolled_back {
+          log::warn!("rollback failed: {e}");
+        }
+        return Err(format!("{err:#} (transaction rolled back)"));
+      }
+    },
+    _ => {
+      let tx = d.begin_transaction().await.map_err(|e| format!("{e:#}"))?;
+      match edit::execute_checked(tx.as_ref(), &changes, &statements).await {
+        Ok(affected) => {
+          tx.commit().await.map_err(|e| format!("{e:#}"))?;
+          affected
+        }
+        Err(err) => {
+          if let Err(e) = tx.rollback().await {
+            log::warn!("rollback failed: {e}");
+          }
+          return Err(format!("{err:#}"));
+        }
+      }
+    }
+  };
+  Ok(ApplyChangesResponse {
+    statements,
+    rows_affected,
+    pending_commit: open_tx.is_some(),
+    elapsed: start.elapsed().as_millis(),
+  })
+}
+
 #[tauri::command]
 pub async fn query_table(
   registry: State<'_, ConnectionRegistry>,
@@ -314,30 +1076,49 @@ pub async fn query_table(
   table: &str,
   limit: usize,
   offset: usize,
+  after: Option<String>,
   #[allow(non_snake_case)] orderBy: Option<String>,
   r#where: Option<String>,
   #[allow(non_snake_case)]
   selectExtras: Option<String>,
+  count: Option<CountOptions>,
   dialect: DialectPayload,
   #[allow(non_snake_case)]
   requestId: Option<String>,
 ) -> Result<ArrowResponse, String> {
-  let d = resolve_connection(&registry, &sessions, dialect).await?;
+  let resolved = connection_registry::resolve_payload(&registry, dialect)?;
+  // Table pages honour the connection's caps; a request sets them for `query` only.
+  let caps = request_limits(None, &resolved);
+  let d = resolve_connection(&registry, &sessions, resolved).await?;
   let where_s = r#where.unwrap_or_default();
   let order_s = orderBy.unwrap_or_default();
   let select_s = selectExtras.unwrap_or_default();
+  let count = count.unwrap_or_default();
 
+  let run = async {
+    let page = d.query_table(
+      table,
+      limits::page_limit(limit, &caps),
+      offset,
+      after.as_deref(),
+      &where_s,
+      &order_s,
+      &select_s,
+      &count,
+    );
+    let mut raw = match caps.timeout() {
+      Some(timeout) => connector::cancel::with_timeout(timeout, page).await?,
+      None => page.await?,
+    };
+    limits::truncate(&mut raw, &caps);
+    Ok::<_, anyhow::Error>(raw)
+  };
   let start = Instant::now();
   let res = if let Some(ref rid) = requestId.filter(|s| !s.trim().is_empty()) {
     let (_guard, token) = InflightGuard::register(&inflight, rid)?;
-    connector::cancel::with_cancel(
-      Some(&token),
-      d.query_table(table, limit, offset, &where_s, &order_s, &select_s),
-    )
-    .await
+    connector::cancel::with_cancel(Some(&token), run).await
   } else {
-    d.query_table(table, limit, offset, &where_s, &order_s, &select_s)
-      .await
+    run.await
   };
   let duration = start.elapsed().as_millis();
   // Prefer backend-built SQL on success; on failure still surface a browse-style SQL.
@@ -382,6 +1163,7 @@ pub async fn export(
   registry: State<'_, ConnectionRegistry>,
   sessions: State<'_, SessionManager>,
   inflight: State<'_, InflightQueries>,
+  history: State<'_, QueryHistory>,
   sql: String,
   file: String,
   format: Option<String>,
@@ -390,6 +1172,7 @@ pub async fn export(
   #[allow(non_snake_case)]
   requestId: Option<String>,
 ) -> Result<(), String> {
+  let connection_id = dialect.connection_id.clone();
   let d = resolve_connection(&registry, &sessions, dialect).await?;
   let format = if let Some(format) = format {
     format
@@ -397,17 +1180,15 @@ pub async fn export(
     file.split('.').next_back().unwrap_or("csv").to_string()
   };
   let options = options.unwrap_or_default();
-  if let Some(ref rid) = requestId.filter(|s| !s.trim().is_empty()) {
+  let start = Instant::now();
+  let res = if let Some(ref rid) = requestId.filter(|s| !s.trim().is_empty()) {
     let (_guard, token) = InflightGuard::register(&inflight, rid)?;
-    d.export(&sql, &file, &format, &options, Some(&token))
-      .await
-      .map_err(|e| e.to_string())?;
+    d.export(&sql, &file, &format, &options, Some(&token)).await
   } else {
-    d.export(&sql, &file, &format, &options, None)
-      .await
-      .map_err(|e| e.to_string())?;
-  }
-  Ok(())
+    d.export(&sql, &file, &format, &options, None).await
+  };
+  history.record(connection_id.as_deref(), "export", &sql, start.elapsed(), &res, |_| None);
+  res.map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -561,6 +1342,7 @@ mod tests {
     let _ = get_ast_dialect("file");
     let _ = get_ast_dialect("quack");
     let _ = get_ast_dialect("clickhouse");
+    let _ = get_ast_dialect("mssql");
     let _ = get_ast_dialect("unknown-dialect");
   }
 
@@ -690,7 +1472,7 @@ mod tests {
 
   #[test]
   fn get_dialect_from_payload_builds_network_dialects() {
-    for dialect in ["mysql", "postgres", "clickhouse"] {
+    for dialect in ["mysql", "postgres", "clickhouse", "mssql"] {
       let payload = DialectPayload {
         dialect: dialect.into(),
         host: Some("127.0.0.1".into()),
@@ -698,6 +1480,8 @@ mod tests {
           "5432".into()
         } else if dialect == "clickhouse" {
           "8123".into()
+        } else if dialect == "mssql" {
+          "1433".into()
         } else {
           "3306".into()
         }),
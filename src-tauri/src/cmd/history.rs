@@ -0,0 +1,142 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use tauri::{AppHandle, Emitter, State};
+
+use connector::error::error_code;
+use connector::history::{HistoryEntry, HistoryFilter, HistoryRecord, HistoryStore};
+use connector::utils::RawArrowData;
+
+use super::app::scratch_dir;
+
+const HISTORY_FILE: &str = "history.db";
+/// Emitted with the new entry's id after a run is recorded.
+const HISTORY_CHANGED: &str = "history-changed";
+
+/// Query history kept in the app data dir, next to the scratch SQL folder.
+/// `store` is `None` when the database could not be opened; recording is
+/// then a no-op. The store is SQLite, so every access runs on the blocking
+/// pool.
+pub struct QueryHistory {
+  store: Option<Arc<HistoryStore>>,
+  app: AppHandle,
+}
+
+impl QueryHistory {
+  pub fn open(app: &AppHandle) -> Self {
+    let store = scratch_dir(app)
+      .map(|dir| dir.with_file_name(HISTORY_FILE))
+      .and_then(|path| HistoryStore::open(&path).map_err(|e| e.to_string()));
+    let store = match store {
+      Ok(store) => Some(Arc::new(store)),
+      Err(e) => {
+        log::warn!("query history disabled: {e}");
+        None
+      }
+    };
+    Self {
+      store,
+      app: app.clone(),
+    }
+  }
+
+  /// Run `f` against the store off the async runtime.
+  async fn with_store<T, F>(&self, f: F) -> Result<T, String>
+  where
+    T: Send + 'static,
+    F: FnOnce(&HistoryStore) -> anyhow::Result<T> + Send + 'static,
+  {
+    let store = self
+      .store
+      .clone()
+      .ok_or_else(|| "query history is unavailable".to_string())?;
+    tauri::async_runtime::spawn_blocking(move || f(&store))
+      .await
+      .map_err(|e| e.to_string())?
+      .map_err(|e| e.to_string())
+  }
+
+  /// Record one run in the background; failures are logged, never surfaced
+  /// to the query.
+  pub fn record<T>(
+    &self,
+    connection_id: Option<&str>,
+    kind: &str,
+    sql: &str,
+    elapsed: Duration,
+    res: &anyhow::Result<T>,
+    rows: impl FnOnce(&T) -> Option<usize>,
+  ) {
+    let Some(store) = &self.store else {
+      return;
+    };
+    let (rows, error_code, message) = match res {
+      Ok(value) => (rows(value), None, None),
+      Err(e) => (None, Some(error_code(e).to_string()), Some(e.to_string())),
+    };
+    let record = HistoryRecord {
+      connection_id: connection_id
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(str::to_string),
+      kind: kind.to_string(),
+      sql: sql.to_string(),
+      elapsed_ms: elapsed.as_millis() as u64,
+      rows,
+      error_code,
+      message,
+    };
+    let store = Arc::clone(store);
+    let app = self.app.clone();
+    tauri::async_runtime::spawn_blocking(move || match store.record(&record) {
+      Ok(id) => {
+        let _ = app.emit(HISTORY_CHANGED, id);
+      }
+      Err(e) => log::warn!("failed to record query history: {e}"),
+    });
+  }
+}
+
+/// Row count of a result for [`QueryHistory::record`]: the total selected,
+/// not the rows of the page returned. Estimated totals are not recorded.
+pub fn result_rows(raw: &RawArrowData) -> Option<usize> {
+  (!raw.estimated).then_some(raw.total)
+}
+
+/// Newest-first history entries matching `filter`.
+#[tauri::command]
+pub async fn search_history(
+  history: State<'_, QueryHistory>,
+  filter: Option<HistoryFilter>,
+) -> Result<Vec<HistoryEntry>, String> {
+  let filter = filter.unwrap_or_default();
+  history.with_store(move |store| store.search(&filter)).await
+}
+
+#[tauri::command]
+pub async fn pin_history(
+  history: State<'_, QueryHistory>,
+  id: i64,
+  pinned: bool,
+) -> Result<bool, String> {
+  history
+    .with_store(move |store| store.set_pinned(id, pinned))
+    .await
+}
+
+#[tauri::command]
+pub async fn delete_history(history: State<'_, QueryHistory>, id: i64) -> Result<bool, String> {
+  history.with_store(move |store| store.delete(id)).await
+}
+
+/// Remove unpinned entries, all or only those of `connectionId`.
+#[tauri::command]
+pub async fn clear_history(
+  history: State<'_, QueryHistory>,
+  #[allow(non_snake_case)]
+  connectionId: Option<String>,
+) -> Result<usize, String> {
+  history
+    .with_store(move |store| store.clear(connectionId.as_deref()))
+    .await
+}
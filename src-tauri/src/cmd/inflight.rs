@@ -24,20 +24,25 @@ impl InflightQueries {
     Ok(token)
   }
 
+  /// Cancel the request; drivers that registered a server-side hook on its
+  /// token (interrupt, `KILL QUERY`) stop the running statement too.
   pub fn cancel(&self, request_id: &str) -> Result<bool, String> {
     let id = request_id.trim();
     if id.is_empty() {
       return Err("request_id is required".into());
     }
-    let map = self
+    let token = self
       .map
       .lock()
-      .map_err(|_| "inflight lock poisoned".to_string())?;
-    if let Some(token) = map.get(id) {
-      token.cancel();
-      Ok(true)
-    } else {
-      Ok(false)
+      .map_err(|_| "inflight lock poisoned".to_string())?
+      .get(id)
+      .cloned();
+    match token {
+      Some(token) => {
+        token.cancel();
+        Ok(true)
+      }
+      None => Ok(false),
     }
   }
 
@@ -2,7 +2,9 @@ pub mod app;
 pub mod connection_registry;
 pub mod db;
 pub mod diagnostics;
+pub mod history;
 pub mod inflight;
+pub mod saved_queries;
 pub mod secret_store;
 pub mod session_manager;
 pub mod sql_template;
@@ -10,5 +12,7 @@ pub mod updater;
 
 pub use app::OpenedFiles;
 pub use connection_registry::ConnectionRegistry;
+pub use history::QueryHistory;
 pub use inflight::InflightQueries;
+pub use saved_queries::SavedQueries;
 pub use session_manager::SessionManager;
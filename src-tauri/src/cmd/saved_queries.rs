@@ -0,0 +1,484 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value as YamlValue;
+use tauri::{AppHandle, Manager, State};
+
+use super::sql_template::{self, ExpandSqlTemplateResult, TemplateVars, VarSpec, VarType};
+
+const LIBRARY_FILE: &str = "saved_queries.json";
+/// Bundle format version written by [`export_saved_queries`].
+const BUNDLE_VERSION: u32 = 1;
+
+/// A named query in the library. `sql` is the template body; the `@vars`
+/// block it was saved with lives in `params` as structured defaults.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedQuery {
+  pub id: String,
+  pub name: String,
+  #[serde(default)]
+  pub description: String,
+  /// Slash-separated folder path, `""` for the root.
+  #[serde(default)]
+  pub folder: String,
+  #[serde(default)]
+  pub tags: Vec<String>,
+  /// Registered connection the query targets, if any.
+  #[serde(default)]
+  pub connection_id: Option<String>,
+  pub sql: String,
+  #[serde(default)]
+  pub params: BTreeMap<String, Vec<String>>,
+  /// Types of the typed `@vars` entries.
+  #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+  pub var_types: BTreeMap<String, VarSpec>,
+  /// Unix milliseconds.
+  pub created_at: i64,
+  pub updated_at: i64,
+}
+
+/// Fields set by the editor when saving; `sql` may start with an `@vars` block.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveQueryRequest {
+  /// Existing query to update; a new one is created when absent.
+  pub id: Option<String>,
+  pub name: String,
+  #[serde(default)]
+  pub description: String,
+  #[serde(default)]
+  pub folder: String,
+  #[serde(default)]
+  pub tags: Vec<String>,
+  pub connection_id: Option<String>,
+  pub sql: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedQueryBundle {
+  pub version: u32,
+  pub exported_at: i64,
+  pub queries: Vec<SavedQuery>,
+}
+
+/// What to do with a bundled query whose id is already in the library.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ImportConflict {
+  /// Keep both; the imported query gets a new id.
+  #[default]
+  Copy,
+  Replace,
+  Skip,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportSummary {
+  pub added: usize,
+  pub replaced: usize,
+  pub skipped: usize,
+}
+
+/// Saved-query library persisted as JSON in the app data dir.
+#[derive(Default)]
+pub struct SavedQueries(Mutex<()>);
+
+impl SavedQueries {
+  fn lock(&self) -> Result<std::sync::MutexGuard<'_, ()>, String> {
+    self.0.lock().map_err(|_| "saved queries lock poisoned".to_string())
+  }
+}
+
+fn now_ms() -> i64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map_or(0, |d| d.as_millis() as i64)
+}
+
+fn new_id() -> String {
+  format!("{:016x}", rand::random::<u64>())
+}
+
+fn library_path(app: &AppHandle) -> Result<PathBuf, String> {
+  let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+  std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+  Ok(dir.join(LIBRARY_FILE))
+}
+
+fn load(path: &Path) -> Result<Vec<SavedQuery>, String> {
+  if !path.is_file() {
+    return Ok(vec![]);
+  }
+  let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+  serde_json::from_str(&text).map_err(|e| format!("invalid saved query library: {e}"))
+}
+
+/// Write through a temp file so a crash never leaves a half-written library.
+fn store(path: &Path, queries: &[SavedQuery]) -> Result<(), String> {
+  let text = serde_json::to_string_pretty(queries).map_err(|e| e.to_string())?;
+  let tmp = path.with_extension("json.tmp");
+  std::fs::write(&tmp, text).map_err(|e| e.to_string())?;
+  std::fs::rename(&tmp, path).map_err(|e| e.to_string())
+}
+
+fn normalize_folder(folder: &str) -> String {
+  folder
+    .split(['/', '\\'])
+    .map(str::trim)
+    .filter(|s| !s.is_empty())
+    .collect::<Vec<_>>()
+    .join("/")
+}
+
+fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+  let set: BTreeSet<String> = tags
+    .into_iter()
+    .map(|t| t.trim().to_string())
+    .filter(|t| !t.is_empty())
+    .collect();
+  set.into_iter().collect()
+}
+
+fn save(queries: &mut Vec<SavedQuery>, request: SaveQueryRequest) -> Result<SavedQuery, String> {
+  let name = request.name.trim();
+  if name.is_empty() {
+    return Err("query name is required".into());
+  }
+  let (vars, body) = sql_template::split_vars(&request.sql)?;
+  let now = now_ms();
+  let existing = match request.id.as_deref() {
+    Some(id) => Some(
+      queries
+        .iter()
+        .position(|q| q.id == id)
+        .ok_or_else(|| format!("saved query not found: {id}"))?,
+    ),
+    None => None,
+  };
+  let query = SavedQuery {
+    id: request.id.unwrap_or_else(new_id),
+    name: name.to_string(),
+    description: request.description.trim().to_string(),
+    folder: normalize_folder(&request.folder),
+    tags: normalize_tags(request.tags),
+    connection_id: request.connection_id.filter(|id| !id.trim().is_empty()),
+    sql: body.trim().to_string(),
+    params: vars.values,
+    var_types: vars.types,
+    created_at: existing.map_or(now, |i| queries[i].created_at),
+    updated_at: now,
+  };
+  match existing {
+    Some(i) => queries[i] = query.clone(),
+    None => queries.push(query.clone()),
+  }
+  Ok(query)
+}
+
+impl SavedQuery {
+  fn vars(&self) -> TemplateVars {
+    TemplateVars {
+      values: self.params.clone(),
+      types: self.var_types.clone(),
+    }
+  }
+}
+
+/// Editor text for a saved query: its defaults as an `@vars` block, then the body.
+fn editor_sql(query: &SavedQuery) -> Result<String, String> {
+  if query.params.is_empty() && query.var_types.is_empty() {
+    return Ok(query.sql.clone());
+  }
+  let mut vars = serde_yaml::Mapping::new();
+  let names: BTreeSet<&String> = query.params.keys().chain(query.var_types.keys()).collect();
+  for name in names {
+    let values = query.params.get(name);
+    let value = match query.var_types.get(name) {
+      None => serde_yaml::to_value(values),
+      Some(spec) => typed_var_yaml(spec, values),
+    };
+    vars.insert(name.as_str().into(), value.map_err(|e| e.to_string())?);
+  }
+  let yaml = serde_yaml::to_string(&vars).map_err(|e| e.to_string())?;
+  Ok(format!("/* @vars\n{}*/\n{}", yaml, query.sql))
+}
+
+/// `{ type, ..., value }` for a typed var; a list or several values as a sequence.
+fn typed_var_yaml(spec: &VarSpec, values: Option<&Vec<String>>) -> serde_yaml::Result<YamlValue> {
+  let mut entry = serde_yaml::to_value(spec)?;
+  let value = match values {
+    Some(v) if v.len() == 1 && spec.ty != VarType::List => serde_yaml::to_value(&v[0])?,
+    Some(v) => serde_yaml::to_value(v)?,
+    None => return Ok(entry),
+  };
+  if let YamlValue::Mapping(map) = &mut entry {
+    map.insert("value".into(), value);
+  }
+  Ok(entry)
+}
+
+fn import(
+  queries: &mut Vec<SavedQuery>,
+  bundle: SavedQueryBundle,
+  conflict: ImportConflict,
+) -> Result<ImportSummary, String> {
+  if bundle.version > BUNDLE_VERSION {
+    return Err(format!(
+      "unsupported saved query bundle version {} (max {BUNDLE_VERSION})",
+      bundle.version
+    ));
+  }
+  let mut summary = ImportSummary::default();
+  for mut query in bundle.queries {
+    query.folder = normalize_folder(&query.folder);
+    query.tags = normalize_tags(query.tags);
+    match queries.iter().position(|q| q.id == query.id) {
+      None => {
+        queries.push(query);
+        summary.added += 1;
+      }
+      Some(i) => match conflict {
+        ImportConflict::Replace => {
+          queries[i] = query;
+          summary.replaced += 1;
+        }
+        ImportConflict::Skip => summary.skipped += 1,
+        ImportConflict::Copy => {
+          query.id = new_id();
+          queries.push(query);
+          summary.added += 1;
+        }
+      },
+    }
+  }
+  Ok(summary)
+}
+
+/// All saved queries, ordered by folder then name.
+#[tauri::command]
+pub async fn list_saved_queries(
+  app: AppHandle,
+  library: State<'_, SavedQueries>,
+) -> Result<Vec<SavedQuery>, String> {
+  let _guard = library.lock()?;
+  let mut queries = load(&library_path(&app)?)?;
+  queries.sort_by(|a, b| (&a.folder, &a.name).cmp(&(&b.folder, &b.name)));
+  Ok(queries)
+}
+
+/// Create or update a query; a leading `@vars` block becomes its `params`.
+#[tauri::command]
+pub async fn save_query(
+  app: AppHandle,
+  library: State<'_, SavedQueries>,
+  request: SaveQueryRequest,
+) -> Result<SavedQuery, String> {
+  let _guard = library.lock()?;
+  let path = library_path(&app)?;
+  let mut queries = load(&path)?;
+  let query = save(&mut queries, request)?;
+  store(&path, &queries)?;
+  Ok(query)
+}
+
+#[tauri::command]
+pub async fn delete_saved_query(
+  app: AppHandle,
+  library: State<'_, SavedQueries>,
+  id: String,
+) -> Result<bool, String> {
+  let _guard = library.lock()?;
+  let path = library_path(&app)?;
+  let mut queries = load(&path)?;
+  let before = queries.len();
+  queries.retain(|q| q.id != id);
+  if queries.len() == before {
+    return Ok(false);
+  }
+  store(&path, &queries)?;
+  Ok(true)
+}
+
+/// SQL to open in the editor, with the saved defaults restored as `@vars`.
+#[tauri::command]
+pub async fn load_saved_query(
+  app: AppHandle,
+  library: State<'_, SavedQueries>,
+  id: String,
+) -> Result<String, String> {
+  let _guard = library.lock()?;
+  let queries = load(&library_path(&app)?)?;
+  let query = queries
+    .iter()
+    .find(|q| q.id == id)
+    .ok_or_else(|| format!("saved query not found: {id}"))?;
+  editor_sql(query)
+}
+
+/// Expand a saved query over its defaults, with `overrides` taking precedence
+/// and literals spelled for `dialect`.
+#[tauri::command]
+pub async fn expand_saved_query(
+  app: AppHandle,
+  library: State<'_, SavedQueries>,
+  id: String,
+  dialect: Option<String>,
+  overrides: Option<BTreeMap<String, Vec<String>>>,
+) -> Result<ExpandSqlTemplateResult, String> {
+  let query = {
+    let _guard = library.lock()?;
+    load(&library_path(&app)?)?
+      .into_iter()
+      .find(|q| q.id == id)
+      .ok_or_else(|| format!("saved query not found: {id}"))?
+  };
+  let dialect = dialect.unwrap_or_default();
+  sql_template::expand_template(&query.vars(), query.sql, &dialect, overrides.as_ref())
+}
+
+/// Write the given queries (all when `ids` is absent) to a shareable bundle file.
+#[tauri::command]
+pub async fn export_saved_queries(
+  app: AppHandle,
+  library: State<'_, SavedQueries>,
+  file: String,
+  ids: Option<Vec<String>>,
+) -> Result<usize, String> {
+  let queries = {
+    let _guard = library.lock()?;
+    load(&library_path(&app)?)?
+  };
+  let queries: Vec<SavedQuery> = match ids {
+    Some(ids) => queries.into_iter().filter(|q| ids.contains(&q.id)).collect(),
+    None => queries,
+  };
+  let count = queries.len();
+  let bundle = SavedQueryBundle {
+    version: BUNDLE_VERSION,
+    exported_at: now_ms(),
+    queries,
+  };
+  let text = serde_json::to_string_pretty(&bundle).map_err(|e| e.to_string())?;
+  std::fs::write(&file, text).map_err(|e| e.to_string())?;
+  Ok(count)
+}
+
+/// Merge a bundle file into the library.
+#[tauri::command]
+pub async fn import_saved_queries(
+  app: AppHandle,
+  library: State<'_, SavedQueries>,
+  file: String,
+  conflict: Option<ImportConflict>,
+) -> Result<ImportSummary, String> {
+  let text = std::fs::read_to_string(&file).map_err(|e| e.to_string())?;
+  let bundle: SavedQueryBundle =
+    serde_json::from_str(&text).map_err(|e| format!("invalid saved query bundle: {e}"))?;
+  let _guard = library.lock()?;
+  let path = library_path(&app)?;
+  let mut queries = load(&path)?;
+  let summary = import(&mut queries, bundle, conflict.unwrap_or_default())?;
+  store(&path, &queries)?;
+  Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn request(sql: &str) -> SaveQueryRequest {
+    SaveQueryRequest {
+      id: None,
+      name: " Orders by day ".into(),
+      description: String::new(),
+      folder: "/reports//daily/".into(),
+      tags: vec!["b".into(), "a".into(), "b".into(), " ".into()],
+      connection_id: Some("pg".into()),
+      sql: sql.into(),
+    }
+  }
+
+  #[test]
+  fn save_keeps_vars_as_params_and_round_trips_to_editor() {
+    let mut queries = vec![];
+    let sql = "/* @vars\nday: ['2024-01-01', '2024-01-02']\n*/
+select * from t where d = '{{ day }}'";
+    let saved = save(&mut queries, request(sql)).unwrap();
+    assert_eq!(saved.name, "Orders by day");
+    assert_eq!(saved.folder, "reports/daily");
+    assert_eq!(saved.tags, ["a", "b"]);
+    assert_eq!(saved.sql, "select * from t where d = '{{ day }}'");
+    assert_eq!(saved.params["day"], ["2024-01-01", "2024-01-02"]);
+
+    let (vars, body) = sql_template::split_vars(&editor_sql(&saved).unwrap()).unwrap();
+    assert_eq!((vars, body.trim()), (saved.vars(), saved.sql.as_str()));
+    let expanded = sql_template::expand_template(&saved.vars(), saved.sql.clone(), "", None).unwrap();
+    assert_eq!(expanded.statements.len(), 2);
+
+    let typed = "/* @vars
+ids: { type: list, item: int, value: [1, 2] }
+kind: { type: enum, options: [a, b], value: a }
+day: { type: date }
+*/
+select * from t where id in ({{ ids | sqlquote }}) and k = '{{ kind }}' and d = '{{ day }}'";
+    let saved = save(&mut vec![], request(typed)).unwrap();
+    assert_eq!(saved.var_types["ids"].ty, VarType::List);
+    let (vars, _) = sql_template::split_vars(&editor_sql(&saved).unwrap()).unwrap();
+    assert_eq!(vars, saved.vars());
+
+    let update = SaveQueryRequest {
+      id: Some(saved.id.clone()),
+      sql: "select 1".into(),
+      ..request("")
+    };
+    let updated = save(&mut queries, update).unwrap();
+    assert_eq!((queries.len(), updated.created_at), (1, saved.created_at));
+    assert!(updated.params.is_empty());
+    assert!(save(&mut queries, SaveQueryRequest { name: " ".into(), ..request("") }).is_err());
+  }
+
+  #[test]
+  fn import_resolves_id_conflicts() {
+    let mut queries = vec![];
+    let saved = save(&mut queries, request("select 1")).unwrap();
+    let bundle = || SavedQueryBundle {
+      version: BUNDLE_VERSION,
+      exported_at: 0,
+      queries: vec![SavedQuery {
+        name: "shared".into(),
+        ..saved.clone()
+      }],
+    };
+
+    let skip = import(&mut queries, bundle(), ImportConflict::Skip).unwrap();
+    assert_eq!((skip.skipped, queries[0].name.as_str()), (1, "Orders by day"));
+    let replace = import(&mut queries, bundle(), ImportConflict::Replace).unwrap();
+    assert_eq!((replace.replaced, queries[0].name.as_str()), (1, "shared"));
+    let copy = import(&mut queries, bundle(), ImportConflict::Copy).unwrap();
+    assert_eq!((copy.added, queries.len()), (1, 2));
+    assert_ne!(queries[0].id, queries[1].id);
+
+    let future = SavedQueryBundle {
+      version: BUNDLE_VERSION + 1,
+      ..bundle()
+    };
+    assert!(import(&mut queries, future, ImportConflict::Copy).is_err());
+  }
+
+  #[test]
+  fn library_file_round_trips() {
+    let path = std::env::temp_dir().join(format!("duckling-saved-{}.json", new_id()));
+    assert!(load(&path).unwrap().is_empty());
+    let mut queries = vec![];
+    save(&mut queries, request("select 1")).unwrap();
+    store(&path, &queries).unwrap();
+    assert_eq!(load(&path).unwrap(), queries);
+    let _ = std::fs::remove_file(&path);
+  }
+}
@@ -1,26 +1,84 @@
 use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
-use connector::dialect::Connection;
+use connector::dialect::{Connection, ResultCursor, Transaction};
 
 use super::db::DialectPayload;
 
 /// Default idle TTL before a live session is dropped (15 minutes).
 pub const DEFAULT_IDLE_TTL: Duration = Duration::from_secs(15 * 60);
 
+/// An open cursor not fetched for this long is closed (5 minutes).
+pub const CURSOR_IDLE_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Open cursors kept per session; opening another closes the least recently used.
+pub const MAX_CURSORS: usize = 8;
+
 struct CachedSession {
   fingerprint: u64,
   conn: Arc<dyn Connection>,
+  /// Explicit transaction opened via `begin_transaction`, if any.
+  transaction: Option<Arc<dyn Transaction>>,
+  /// Result cursors opened via `open_cursor`, keyed by cursor id.
+  cursors: HashMap<String, OpenCursor>,
+  last_used: Instant,
+}
+
+struct OpenCursor {
+  cursor: Arc<dyn ResultCursor>,
   last_used: Instant,
 }
 
+impl CachedSession {
+  /// Roll back an abandoned transaction in the background. Dropping the handle
+  /// would also roll back at the driver level; this makes it explicit and logged.
+  fn discard(self) {
+    for (_, open) in self.cursors {
+      discard_cursor(open.cursor);
+    }
+    if let Some(tx) = self.transaction {
+      tauri::async_runtime::spawn(async move {
+        if let Err(e) = tx.rollback().await {
+          log::warn!("rollback of discarded {} transaction failed: {e}", tx.dialect());
+        }
+      });
+    }
+  }
+
+  /// Close cursors idle for [`CURSOR_IDLE_TTL`] at `now`.
+  fn expire_cursors(&mut self, now: Instant) {
+    let expired: Vec<String> = self
+      .cursors
+      .iter()
+      .filter(|(_, c)| now.duration_since(c.last_used) >= CURSOR_IDLE_TTL)
+      .map(|(id, _)| id.clone())
+      .collect();
+    for id in expired {
+      if let Some(open) = self.cursors.remove(&id) {
+        discard_cursor(open.cursor);
+      }
+    }
+  }
+}
+
+/// Close a cursor that is no longer reachable, in the background.
+fn discard_cursor(cursor: Arc<dyn ResultCursor>) {
+  tauri::async_runtime::spawn(async move {
+    if let Err(e) = cursor.close().await {
+      log::debug!("close of discarded {} cursor failed: {e}", cursor.dialect());
+    }
+  });
+}
+
 /// Process-lifetime live connections keyed by connection_id.
 pub struct SessionManager {
   sessions: Mutex<HashMap<String, CachedSession>>,
   /// `None` or zero means never auto-evict by idle time.
   idle_ttl: Mutex<Option<Duration>>,
+  next_cursor_id: AtomicU64,
 }
 
 impl Default for SessionManager {
@@ -28,6 +86,7 @@ impl Default for SessionManager {
     Self {
       sessions: Mutex::new(HashMap::new()),
       idle_ttl: Mutex::new(Some(DEFAULT_IDLE_TTL)),
+      next_cursor_id: AtomicU64::new(1),
     }
   }
 }
@@ -38,6 +97,7 @@ impl SessionManager {
     Self {
       sessions: Mutex::new(HashMap::new()),
       idle_ttl: Mutex::new(Some(idle_ttl).filter(|d| !d.is_zero())),
+      next_cursor_id: AtomicU64::new(1),
     }
   }
 
@@ -75,6 +135,7 @@ impl SessionManager {
     payload.uri.hash(&mut hasher);
     payload.token.hash(&mut hasher);
     payload.disable_ssl.hash(&mut hasher);
+    payload.trust_server_certificate.hash(&mut hasher);
     payload.ssl_mode.hash(&mut hasher);
     payload.ssh_enabled.hash(&mut hasher);
     payload.ssh_host.hash(&mut hasher);
@@ -93,16 +154,27 @@ impl SessionManager {
   }
 
   fn evict_idle_at(&self, now: Instant) -> usize {
+    let Ok(mut map) = self.sessions.lock() else {
+      return 0;
+    };
+    for session in map.values_mut() {
+      session.expire_cursors(now);
+    }
     let ttl = match self.idle_ttl.lock().ok().and_then(|g| *g) {
       Some(d) if !d.is_zero() => d,
       _ => return 0,
     };
-    let Ok(mut map) = self.sessions.lock() else {
-      return 0;
-    };
-    let before = map.len();
-    map.retain(|_, s| now.duration_since(s.last_used) < ttl);
-    before.saturating_sub(map.len())
+    let stale: Vec<String> = map
+      .iter()
+      .filter(|(_, s)| now.duration_since(s.last_used) >= ttl)
+      .map(|(id, _)| id.clone())
+      .collect();
+    for id in &stale {
+      if let Some(session) = map.remove(id) {
+        session.discard();
+      }
+    }
+    stale.len()
   }
 
   pub fn get_or_insert(
@@ -129,7 +201,9 @@ impl SessionManager {
         entry.last_used = Instant::now();
         return Ok(Arc::clone(&entry.conn));
       }
-      map.remove(id);
+      if let Some(session) = map.remove(id) {
+        session.discard();
+      }
     }
 
     let conn: Arc<dyn Connection> = Arc::from(create()?);
@@ -138,6 +212,8 @@ impl SessionManager {
       CachedSession {
         fingerprint: fp,
         conn: Arc::clone(&conn),
+        transaction: None,
+        cursors: HashMap::new(),
         last_used: Instant::now(),
       },
     );
@@ -149,16 +225,172 @@ impl SessionManager {
     if id.is_empty() {
       return;
     }
-    if let Ok(mut map) = self.sessions.lock() {
-      map.remove(id);
+    if let Ok(mut map) = self.sessions.lock()
+      && let Some(session) = map.remove(id)
+    {
+      session.discard();
     }
   }
 
   #[allow(dead_code)]
   pub fn clear(&self) {
     if let Ok(mut map) = self.sessions.lock() {
-      map.clear();
+      for (_, session) in map.drain() {
+        session.discard();
+      }
+    }
+  }
+
+  /// Attach an open transaction to the live session for `connection_id`.
+  pub fn set_transaction(
+    &self,
+    connection_id: &str,
+    tx: Arc<dyn Transaction>,
+  ) -> Result<(), String> {
+    let mut map = self
+      .sessions
+      .lock()
+      .map_err(|_| "session manager lock poisoned".to_string())?;
+    let Some(entry) = map.get_mut(connection_id.trim()) else {
+      return Err(format!("no live session for connection {connection_id}"));
+    };
+    if entry.transaction.is_some() {
+      return Err(format!("transaction already open for connection {connection_id}"));
+    }
+    entry.transaction = Some(tx);
+    entry.last_used = Instant::now();
+    Ok(())
+  }
+
+  /// Open transaction for `connection_id`; counts as session activity.
+  pub fn transaction(&self, connection_id: &str) -> Result<Arc<dyn Transaction>, String> {
+    let mut map = self
+      .sessions
+      .lock()
+      .map_err(|_| "session manager lock poisoned".to_string())?;
+    let entry = map
+      .get_mut(connection_id.trim())
+      .filter(|e| e.transaction.is_some())
+      .ok_or_else(|| format!("no open transaction for connection {connection_id}"))?;
+    entry.last_used = Instant::now();
+    Ok(Arc::clone(entry.transaction.as_ref().unwrap()))
+  }
+
+  /// Detach the open transaction so the caller can commit or roll it back.
+  pub fn take_transaction(&self, connection_id: &str) -> Result<Arc<dyn Transaction>, String> {
+    let mut map = self
+      .sessions
+      .lock()
+      .map_err(|_| "session manager lock poisoned".to_string())?;
+    map
+      .get_mut(connection_id.trim())
+      .and_then(|e| {
+        e.last_used = Instant::now();
+        e.transaction.take()
+      })
+      .ok_or_else(|| format!("no open transaction for connection {connection_id}"))
+  }
+
+  pub fn has_transaction(&self, connection_id: &str) -> bool {
+    self
+      .sessions
+      .lock()
+      .map(|m| {
+        m.get(connection_id.trim())
+          .is_some_and(|e| e.transaction.is_some())
+      })
+      .unwrap_or(false)
+  }
+
+  /// Keep `cursor` on the live session for `connection_id`; returns its id.
+  /// Past [`MAX_CURSORS`] the least recently used cursor is closed.
+  pub fn add_cursor(
+    &self,
+    connection_id: &str,
+    cursor: Arc<dyn ResultCursor>,
+  ) -> Result<String, String> {
+    let mut map = self
+      .sessions
+      .lock()
+      .map_err(|_| "session manager lock poisoned".to_string())?;
+    let Some(entry) = map.get_mut(connection_id.trim()) else {
+      return Err(format!("no live session for connection {connection_id}"));
+    };
+    let now = Instant::now();
+    entry.expire_cursors(now);
+    while entry.cursors.len() >= MAX_CURSORS {
+      let oldest = entry
+        .cursors
+        .iter()
+        .min_by_key(|(_, c)| c.last_used)
+        .map(|(id, _)| id.clone());
+      if let Some(open) = oldest.and_then(|id| entry.cursors.remove(&id)) {
+        discard_cursor(open.cursor);
+      }
     }
+    let id = format!("cursor-{}", self.next_cursor_id.fetch_add(1, Ordering::Relaxed));
+    entry.cursors.insert(
+      id.clone(),
+      OpenCursor {
+        cursor,
+        last_used: now,
+      },
+    );
+    entry.last_used = now;
+    Ok(id)
+  }
+
+  /// Open cursor `cursor_id`; counts as session and cursor activity.
+  pub fn cursor(
+    &self,
+    connection_id: &str,
+    cursor_id: &str,
+  ) -> Result<Arc<dyn ResultCursor>, String> {
+    let mut map = self
+      .sessions
+      .lock()
+      .map_err(|_| "session manager lock poisoned".to_string())?;
+    let entry = map
+      .get_mut(connection_id.trim())
+      .ok_or_else(|| format!("no live session for connection {connection_id}"))?;
+    let now = Instant::now();
+    entry.last_used = now;
+    entry.expire_cursors(now);
+    let open = entry
+      .cursors
+      .get_mut(cursor_id)
+      .ok_or_else(|| format!("no open cursor {cursor_id} (closed or expired)"))?;
+    open.last_used = now;
+    Ok(Arc::clone(&open.cursor))
+  }
+
+  /// Detach cursor `cursor_id` so the caller can close it.
+  pub fn take_cursor(
+    &self,
+    connection_id: &str,
+    cursor_id: &str,
+  ) -> Result<Arc<dyn ResultCursor>, String> {
+    let mut map = self
+      .sessions
+      .lock()
+      .map_err(|_| "session manager lock poisoned".to_string())?;
+    map
+      .get_mut(connection_id.trim())
+      .and_then(|e| {
+        e.last_used = Instant::now();
+        e.cursors.remove(cursor_id)
+      })
+      .map(|open| open.cursor)
+      .ok_or_else(|| format!("no open cursor {cursor_id}"))
+  }
+
+  #[cfg(test)]
+  fn cursor_count(&self, connection_id: &str) -> usize {
+    self
+      .sessions
+      .lock()
+      .map(|m| m.get(connection_id.trim()).map_or(0, |e| e.cursors.len()))
+      .unwrap_or(0)
   }
 
   pub fn len(&self) -> usize {
@@ -302,6 +534,134 @@ mod tests {
     assert_eq!(again.dialect(), "file");
   }
 
+  fn sqlite_session(mgr: &SessionManager, id: &str) -> (Arc<dyn Connection>, std::path::PathBuf) {
+    let path = std::env::temp_dir().join(format!(
+      "duckling_session_{}_{id}.db",
+      std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+    let path_s = path.to_string_lossy().to_string();
+    let payload = DialectPayload {
+      connection_id: Some(id.into()),
+      dialect: "sqlite".into(),
+      path: Some(path_s.clone()),
+      ..Default::default()
+    };
+    let conn = mgr
+      .get_or_insert(id, &payload, || {
+        Ok(Box::new(connector::dialect::sqlite::SqliteConnection {
+          path: path_s.clone(),
+        }))
+      })
+      .unwrap();
+    tauri::async_runtime::block_on(async {
+      conn.execute("create table t (id integer)").await.unwrap();
+      conn.execute("insert into t values (1), (2)").await.unwrap();
+    });
+    (conn, path)
+  }
+
+  #[test]
+  fn transaction_lifecycle_is_tracked_per_connection() {
+    let mgr = SessionManager::default();
+    let (conn, path) = sqlite_session(&mgr, "tx-life");
+    let begin = || {
+      let tx = tauri::async_runtime::block_on(conn.begin_transaction()).unwrap();
+      Arc::from(tx)
+    };
+    assert!(mgr.set_transaction("missing", begin()).is_err());
+    assert!(mgr.transaction("tx-life").is_err());
+    mgr.set_transaction("tx-life", begin()).unwrap();
+    assert!(mgr.has_transaction("tx-life"));
+    assert!(mgr.set_transaction("tx-life", begin()).is_err());
+
+    let tx = mgr.transaction("tx-life").unwrap();
+    tauri::async_runtime::block_on(async {
+      assert_eq!(tx.execute("delete from t where id = 1").await.unwrap(), 1);
+    });
+    let tx = mgr.take_transaction("tx-life").unwrap();
+    assert!(!mgr.has_transaction("tx-life"));
+    assert!(mgr.take_transaction("tx-life").is_err());
+    tauri::async_runtime::block_on(async {
+      tx.commit().await.unwrap();
+      assert_eq!(conn.table_row_count("t", "").await.unwrap(), 1);
+    });
+    let _ = std::fs::remove_file(path);
+  }
+
+  #[test]
+  fn evicting_idle_session_rolls_back_transaction() {
+    let mgr = SessionManager::with_idle_ttl(Duration::from_millis(50));
+    let (conn, path) = sqlite_session(&mgr, "tx-evict");
+    let tx: Arc<dyn Transaction> =
+      Arc::from(tauri::async_runtime::block_on(conn.begin_transaction()).unwrap());
+    tauri::async_runtime::block_on(async {
+      assert_eq!(tx.execute("delete from t").await.unwrap(), 2);
+    });
+    mgr.set_transaction("tx-evict", Arc::clone(&tx)).unwrap();
+    mgr.set_last_used_for_test("tx-evict", Instant::now() - Duration::from_secs(1));
+    assert_eq!(mgr.evict_idle(), 1);
+    // The detached rollback finishes the transaction; later use fails.
+    let mut finished = false;
+    for _ in 0..100 {
+      if tauri::async_runtime::block_on(tx.query("select 1")).is_err() {
+        finished = true;
+        break;
+      }
+      std::thread::sleep(Duration::from_millis(10));
+    }
+    assert!(finished);
+    let count = tauri::async_runtime::block_on(conn.table_row_count("t", "")).unwrap();
+    assert_eq!(count, 2);
+    let _ = std::fs::remove_file(path);
+  }
+
+  #[test]
+  fn cursors_are_tracked_per_session() {
+    let mgr = SessionManager::default();
+    let (conn, path) = sqlite_session(&mgr, "cursor-life");
+    let cursor: Arc<dyn ResultCursor> =
+      Arc::from(tauri::async_runtime::block_on(conn.open_cursor("select * from t")).unwrap());
+    assert!(mgr.add_cursor("missing", Arc::clone(&cursor)).is_err());
+    let id = mgr.add_cursor("cursor-life", cursor).unwrap();
+    assert_eq!(mgr.cursor_count("cursor-life"), 1);
+
+    let cursor = mgr.cursor("cursor-life", &id).unwrap();
+    tauri::async_runtime::block_on(async {
+      assert_eq!(cursor.fetch(1).await.unwrap().batch.num_rows(), 1);
+    });
+    let cursor = mgr.take_cursor("cursor-life", &id).unwrap();
+    assert!(mgr.cursor("cursor-life", &id).is_err());
+    tauri::async_runtime::block_on(cursor.close()).unwrap();
+
+    let other: Arc<dyn ResultCursor> =
+      Arc::from(tauri::async_runtime::block_on(conn.open_cursor("select * from t")).unwrap());
+    mgr.add_cursor("cursor-life", other).unwrap();
+    mgr.invalidate("cursor-life");
+    assert_eq!(mgr.cursor_count("cursor-life"), 0);
+    let _ = std::fs::remove_file(path);
+  }
+
+  #[test]
+  fn cursors_are_capped_and_expire() {
+    let mgr = SessionManager::default();
+    let (conn, path) = sqlite_session(&mgr, "cursor-cap");
+    let open = || -> Arc<dyn ResultCursor> {
+      Arc::from(tauri::async_runtime::block_on(conn.open_cursor("select * from t")).unwrap())
+    };
+    let first = mgr.add_cursor("cursor-cap", open()).unwrap();
+    for _ in 0..MAX_CURSORS {
+      mgr.add_cursor("cursor-cap", open()).unwrap();
+    }
+    assert_eq!(mgr.cursor_count("cursor-cap"), MAX_CURSORS);
+    assert!(mgr.cursor("cursor-cap", &first).is_err());
+
+    mgr.evict_idle_at(Instant::now() + CURSOR_IDLE_TTL);
+    assert_eq!(mgr.cursor_count("cursor-cap"), 0);
+    assert_eq!(mgr.len(), 1);
+    let _ = std::fs::remove_file(path);
+  }
+
   #[test]
   fn set_idle_ttl_secs_updates_and_zero_disables() {
     let mgr = SessionManager::default();
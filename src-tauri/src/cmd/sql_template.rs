@@ -1,6 +1,10 @@
 use std::collections::{BTreeMap, BTreeSet};
 
-use minijinja::{AutoEscape, Environment, UndefinedBehavior, Value};
+use connector::dialect::edit::quote_string;
+use connector::dialect::params::{ParamRef, placeholders as bind_placeholders};
+use connector::dialect::{ParamType, ParamValue, Params};
+use minijinja::value::ValueKind;
+use minijinja::{AutoEscape, Environment, Error, ErrorKind, UndefinedBehavior, Value};
 use serde::{Deserialize, Serialize};
 use serde_yaml::Value as YamlValue;
 
@@ -10,7 +14,15 @@ pub const SOFT_EXPAND_LIMIT: usize = 20;
 /// Hard limit: expand fails above this.
 pub const HARD_EXPAND_LIMIT: usize = 50;
 
+/// Fuel for one render; bounds loops (`{% for %}`, `range`) in a template.
+const TEMPLATE_FUEL: u64 = 100_000;
+/// Largest SQL one statement may render to.
+const MAX_RENDERED_LEN: usize = 1 << 20;
+
 const VARS_MARKER: &str = "@vars";
+const PARAMS_MARKER: &str = "@params";
+/// Template globals that are not user variables.
+const BUILTIN_GLOBALS: [&str; 4] = ["range", "dict", "namespace", "debug"];
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -20,6 +32,118 @@ pub struct AnalyzeSqlTemplateResult {
   pub provided: BTreeMap<String, Vec<String>>,
   pub missing: Vec<String>,
   pub template_body: String,
+  /// Declared types of typed `@vars`.
+  pub var_types: BTreeMap<String, VarSpec>,
+  /// Bind placeholders (`:name` / `$1`) in the body, typed by the `@params` block.
+  pub bind_params: Vec<BindParam>,
+}
+
+/// A bind placeholder and its declared type; values are bound, not spliced.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BindParam {
+  /// `id` for `:id`, `1` for `$1`.
+  pub name: String,
+  #[serde(rename = "type")]
+  pub ty: ParamType,
+  pub default: Option<String>,
+}
+
+/// Type of a typed `@vars` entry; values are checked before rendering.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VarType {
+  #[default]
+  Text,
+  Int,
+  /// `YYYY-MM-DD`.
+  Date,
+  /// One of `options`.
+  Enum,
+  /// Bare or dotted name (`schema.table`), safe to splice unquoted.
+  Identifier,
+  /// All values form one list (of `item`) instead of one statement each.
+  List,
+}
+
+impl VarType {
+  fn name(self) -> &'static str {
+    match self {
+      Self::Text => "text",
+      Self::Int => "int",
+      Self::Date => "date",
+      Self::Enum => "enum",
+      Self::Identifier => "identifier",
+      Self::List => "list",
+    }
+  }
+}
+
+/// `name: { type: int, value: 5 }`, `{ type: enum, options: [a, b], value: a }`
+/// or `{ type: list, item: int, value: [1, 2] }`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VarSpec {
+  #[serde(rename = "type")]
+  pub ty: VarType,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub options: Vec<String>,
+  /// Element type of a `list`; defaults to text.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub item: Option<VarType>,
+}
+
+impl VarSpec {
+  /// Check one value; an empty value leaves the variable unset.
+  fn validate(&self, name: &str, value: &str) -> Result<(), String> {
+    if value.is_empty() {
+      return Ok(());
+    }
+    let ty = match self.ty {
+      VarType::List => self.item.unwrap_or_default(),
+      ty => ty,
+    };
+    let valid = match ty {
+      VarType::Text | VarType::List => true,
+      VarType::Int => value.trim().parse::<i64>().is_ok(),
+      VarType::Date => is_date(value.trim()),
+      VarType::Enum => self.options.iter().any(|o| o == value),
+      VarType::Identifier => is_dotted_ident(value),
+    };
+    if valid {
+      return Ok(());
+    }
+    Err(match ty {
+      VarType::Enum => format!(
+        "@vars '{name}': '{value}' is not one of {}",
+        self.options.join(", ")
+      ),
+      _ => format!("@vars '{name}': '{value}' is not a valid {}", ty.name()),
+    })
+  }
+
+  /// Template value: ints as numbers, unset as none, lists as sequences.
+  fn to_value(&self, values: &[String]) -> Value {
+    let scalar = |ty: VarType, v: &str| match ty {
+      _ if v.is_empty() => Value::from(()),
+      VarType::Int => v.trim().parse::<i64>().map_or_else(|_| Value::from(v), Value::from),
+      _ => Value::from(v),
+    };
+    match self.ty {
+      VarType::List => {
+        let item = self.item.unwrap_or_default();
+        Value::from(values.iter().map(|v| scalar(item, v)).collect::<Vec<_>>())
+      }
+      ty => values.first().map_or(Value::from(()), |v| scalar(ty, v)),
+    }
+  }
+}
+
+/// `@vars` defaults plus the declared types of typed entries.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TemplateVars {
+  pub values: BTreeMap<String, Vec<String>>,
+  pub types: BTreeMap<String, VarSpec>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,25 +160,25 @@ pub struct ExpandSqlTemplateResult {
 }
 
 /// Leading block comment that contains `@vars`, with YAML after the marker line.
-fn split_vars_block(sql: &str) -> Result<(BTreeMap<String, Vec<String>>, String), String> {
+fn split_vars_block(sql: &str) -> Result<(TemplateVars, String), String> {
   let trimmed = sql.trim_start();
   let lead_ws_len = sql.len() - trimmed.len();
   let lead_ws = &sql[..lead_ws_len];
 
   if !trimmed.starts_with("/*") {
-    return Ok((BTreeMap::new(), sql.to_string()));
+    return Ok((TemplateVars::default(), sql.to_string()));
   }
 
   let after_open = &trimmed[2..];
   let Some(close_rel) = after_open.find("*/") else {
-    return Ok((BTreeMap::new(), sql.to_string()));
+    return Ok((TemplateVars::default(), sql.to_string()));
   };
   let comment_body = &after_open[..close_rel];
   let after_comment = &after_open[close_rel + 2..];
 
   let marker_pos = match comment_body.find(VARS_MARKER) {
     Some(p) => p,
-    None => return Ok((BTreeMap::new(), sql.to_string())),
+    None => return Ok((TemplateVars::default(), sql.to_string())),
   };
 
   // YAML starts after the line that contains `@vars`.
@@ -65,17 +189,17 @@ fn split_vars_block(sql: &str) -> Result<(BTreeMap<String, Vec<String>>, String)
     after_marker.trim()
   };
 
-  let provided = if yaml_src.is_empty() {
-    BTreeMap::new()
+  let vars = if yaml_src.is_empty() {
+    TemplateVars::default()
   } else {
     parse_vars_yaml(yaml_src)?
   };
 
   let template_body = format!("{}{}", lead_ws, after_comment.trim_start());
-  Ok((provided, template_body))
+  Ok((vars, template_body))
 }
 
-fn parse_vars_yaml(src: &str) -> Result<BTreeMap<String, Vec<String>>, String> {
+fn parse_vars_yaml(src: &str) -> Result<TemplateVars, String> {
   let root: YamlValue =
     serde_yaml::from_str(src).map_err(|e| format!("Invalid @vars YAML: {e}"))?;
 
@@ -83,7 +207,7 @@ fn parse_vars_yaml(src: &str) -> Result<BTreeMap<String, Vec<String>>, String> {
     "Invalid @vars YAML: root must be a mapping of variable names to values".to_string()
   })?;
 
-  let mut out = BTreeMap::new();
+  let mut out = TemplateVars::default();
   for (k, v) in map {
     let key = k
       .as_str()
@@ -94,15 +218,46 @@ fn parse_vars_yaml(src: &str) -> Result<BTreeMap<String, Vec<String>>, String> {
         "Invalid @vars key '{key}': use a simple identifier (letters, digits, underscore)"
       ));
     }
+    if let YamlValue::Mapping(m) = v {
+      let spec = parse_var_spec(&key, v)?;
+      // A typed var without `value` is declared but still has to be supplied.
+      if let Some(value) = m.get("value").filter(|v| !v.is_null()) {
+        let values = yaml_value_to_strings(&key, value)?;
+        for value in &values {
+          spec.validate(&key, value)?;
+        }
+        out.values.insert(key.clone(), values);
+      }
+      out.types.insert(key, spec);
+      continue;
+    }
     let values = yaml_value_to_strings(&key, v)?;
     if values.is_empty() {
       return Err(format!("@vars '{key}' must not be an empty list"));
     }
-    out.insert(key, values);
+    out.values.insert(key, values);
   }
   Ok(out)
 }
 
+fn parse_var_spec(key: &str, v: &YamlValue) -> Result<VarSpec, String> {
+  let spec: VarSpec = serde_yaml::from_value(v.clone()).map_err(|e| {
+    format!(
+      "@vars '{key}': {e} (type is one of text, int, date, enum, identifier, list)"
+    )
+  })?;
+  if spec.ty == VarType::Enum && spec.options.is_empty() {
+    return Err(format!("@vars '{key}': an enum needs `options`"));
+  }
+  if spec.item == Some(VarType::List) {
+    return Err(format!("@vars '{key}': lists cannot be nested"));
+  }
+  if spec.item == Some(VarType::Enum) && spec.options.is_empty() {
+    return Err(format!("@vars '{key}': a list of enum needs `options`"));
+  }
+  Ok(spec)
+}
+
 fn yaml_value_to_strings(key: &str, v: &YamlValue) -> Result<Vec<String>, String> {
   match v {
     YamlValue::String(s) => Ok(vec![s.clone()]),
@@ -133,11 +288,118 @@ fn yaml_value_to_strings(key: &str, v: &YamlValue) -> Result<Vec<String>, String
       Ok(items)
     }
     YamlValue::Mapping(_) | YamlValue::Tagged(_) => Err(format!(
-      "@vars '{key}' must be a scalar or a list of scalars (no nested maps)"
+      "@vars '{key}' must be a scalar, a list of scalars or {{ type, value }}"
     )),
   }
 }
 
+/// Leading block comment that contains `@params`, wherever it sits among the
+/// leading comments. Returns the declarations, the comment text and `sql`
+/// without it.
+fn split_params_block(
+  sql: &str,
+) -> Result<(BTreeMap<String, BindParam>, String, String), String> {
+  let mut pos = 0;
+  loop {
+    let rest = &sql[pos..];
+    let start = pos + (rest.len() - rest.trim_start().len());
+    if !sql[start..].starts_with("/*") {
+      return Ok((BTreeMap::new(), String::new(), sql.to_string()));
+    }
+    let Some(close_rel) = sql[start + 2..].find("*/") else {
+      return Ok((BTreeMap::new(), String::new(), sql.to_string()));
+    };
+    let end = start + 2 + close_rel + 2;
+    let comment_body = &sql[start + 2..end - 2];
+    if let Some(marker_pos) = comment_body.find(PARAMS_MARKER) {
+      let after_marker = &comment_body[marker_pos + PARAMS_MARKER.len()..];
+      let yaml_src = match after_marker.find('\n') {
+        Some(nl) => after_marker[nl + 1..].trim(),
+        None => after_marker.trim(),
+      };
+      let decls = if yaml_src.is_empty() {
+        BTreeMap::new()
+      } else {
+        parse_params_yaml(yaml_src)?
+      };
+      let block = format!("{}\n", &sql[start..end]);
+      let body = format!("{}{}", &sql[..start], sql[end..].trim_start());
+      return Ok((decls, block, body));
+    }
+    pos = end;
+  }
+}
+
+/// `name: int` or `name: { type: int, default: 5 }`; `$1` is declared as `1`.
+fn parse_params_yaml(src: &str) -> Result<BTreeMap<String, BindParam>, String> {
+  let root: YamlValue =
+    serde_yaml::from_str(src).map_err(|e| format!("Invalid @params YAML: {e}"))?;
+  let map = root.as_mapping().ok_or_else(|| {
+    "Invalid @params YAML: root must be a mapping of parameter names to types".to_string()
+  })?;
+
+  let mut out = BTreeMap::new();
+  for (k, v) in map {
+    let name = match k {
+      YamlValue::String(s) => s.trim_start_matches([':', '$']).to_string(),
+      YamlValue::Number(n) => n.to_string(),
+      _ => return Err("Invalid @params YAML: keys must be names or positions".to_string()),
+    };
+    let invalid = |what: &str| format!("@params '{name}': {what}");
+    let (ty, default) = match v {
+      YamlValue::String(_) | YamlValue::Null => (v.clone(), None),
+      YamlValue::Mapping(m) => {
+        let default = match m.get("default") {
+          None | Some(YamlValue::Null) => None,
+          Some(YamlValue::String(s)) => Some(s.clone()),
+          Some(YamlValue::Number(n)) => Some(n.to_string()),
+          Some(YamlValue::Bool(b)) => Some(b.to_string()),
+          Some(_) => return Err(invalid("default must be a scalar")),
+        };
+        (m.get("type").cloned().unwrap_or(YamlValue::Null), default)
+      }
+      _ => return Err(invalid("expected a type name or { type, default }")),
+    };
+    let ty = match ty {
+      YamlValue::Null => ParamType::default(),
+      ty => serde_yaml::from_value(ty)
+        .map_err(|_| invalid("type must be one of text, int, float, bool"))?,
+    };
+    let param = BindParam {
+      name: name.clone(),
+      ty,
+      default,
+    };
+    if let Some(default) = &param.default {
+      ty.parse(default).map_err(|e| invalid(&e.to_string()))?;
+    }
+    out.insert(name, param);
+  }
+  Ok(out)
+}
+
+fn param_key(param: &ParamRef) -> String {
+  match param {
+    ParamRef::Index(i) => i.to_string(),
+    ParamRef::Name(name) => name.clone(),
+  }
+}
+
+/// Placeholders used in `body`, in order of first use, with their declarations.
+fn bind_params_of(decls: &BTreeMap<String, BindParam>, body: &str) -> Vec<BindParam> {
+  bind_placeholders(body)
+    .iter()
+    .map(|param| {
+      let name = param_key(param);
+      decls.get(&name).cloned().unwrap_or(BindParam {
+        name,
+        ty: ParamType::default(),
+        default: None,
+      })
+    })
+    .collect()
+}
+
 fn is_ident(s: &str) -> bool {
   let mut chars = s.chars();
   match chars.next() {
@@ -147,67 +409,49 @@ fn is_ident(s: &str) -> bool {
   chars.all(|c| c == '_' || c.is_ascii_alphanumeric())
 }
 
-/// Reject control-flow / comments so v1 stays variable-interpolation only.
-fn reject_control_flow(template: &str) -> Result<(), String> {
-  if template.contains("{%") {
-    return Err(
-      "SQL templates support Jinja variable interpolation ({{ name }}) only; \
-       control tags ({% ... %}) are not enabled"
-        .to_string(),
-    );
-  }
-  if template.contains("{#") {
-    return Err(
-      "SQL templates support Jinja variable interpolation ({{ name }}) only; \
-       comments ({# ... #}) are not enabled"
-        .to_string(),
-    );
-  }
-  Ok(())
+/// `schema.table`: one or more identifiers joined by dots.
+fn is_dotted_ident(s: &str) -> bool {
+  s.split('.').all(is_ident)
 }
 
-fn extract_placeholders(template: &str) -> Result<Vec<String>, String> {
-  reject_control_flow(template)?;
-
-  let mut names = BTreeSet::new();
-  let bytes = template.as_bytes();
-  let mut i = 0;
-  while i + 1 < bytes.len() {
-    if bytes[i] == b'{' && bytes[i + 1] == b'{' {
-      let start = i + 2;
-      let mut j = start;
-      let mut found = false;
-      while j + 1 < bytes.len() {
-        if bytes[j] == b'}' && bytes[j + 1] == b'}' {
-          found = true;
-          break;
-        }
-        j += 1;
-      }
-      if !found {
-        return Err("Unclosed Jinja variable delimiter {{".to_string());
-      }
-      let inner = template[start..j].trim();
-      if inner.is_empty() {
-        return Err("Empty Jinja variable {{ }}".to_string());
-      }
-      if !is_ident(inner) {
-        return Err(format!(
-          "Unsupported Jinja expression '{{{{ {inner} }}}}': only simple variable names are allowed"
-        ));
-      }
-      names.insert(inner.to_string());
-      i = j + 2;
-      continue;
-    }
-    i += 1;
+fn is_date(s: &str) -> bool {
+  let parts: Vec<&str> = s.split('-').collect();
+  let [y, m, d] = parts[..] else {
+    return false;
+  };
+  if y.len() != 4 || m.len() != 2 || d.len() != 2 {
+    return false;
   }
+  let (Ok(y), Ok(m), Ok(d)) = (y.parse::<u32>(), m.parse::<u32>(), d.parse::<u32>()) else {
+    return false;
+  };
+  let leap = y % 4 == 0 && (y % 100 != 0 || y % 400 == 0);
+  let days = match m {
+    1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+    4 | 6 | 9 | 11 => 30,
+    2 if leap => 29,
+    2 => 28,
+    _ => return false,
+  };
+  (1..=days).contains(&d)
+}
 
-  Ok(names.into_iter().collect())
+fn has_jinja(template: &str) -> bool {
+  template.contains("{{") || template.contains("{%") || template.contains("{#")
 }
 
-fn has_jinja_vars(template: &str) -> bool {
-  template.contains("{{")
+/// Variables the template reads, sorted; loop and `set` locals are excluded.
+fn extract_placeholders(template: &str) -> Result<Vec<String>, String> {
+  let env = make_env();
+  let tmpl = env
+    .template_from_str(template)
+    .map_err(|e| format!("Invalid SQL template: {e}"))?;
+  let names: BTreeSet<String> = tmpl
+    .undeclared_variables(false)
+    .into_iter()
+    .filter(|name| !BUILTIN_GLOBALS.contains(&name.as_str()))
+    .collect();
+  Ok(names.into_iter().collect())
 }
 
 fn merge_values(
@@ -261,34 +505,102 @@ fn cartesian_product(
   Ok(combos)
 }
 
-fn make_env() -> Environment<'static> {
+/// Template environment whose `sqlquote` spells literals for `dialect`.
+fn make_env(dialect: &str) -> Environment<'static> {
   let mut env = Environment::new();
   env.set_auto_escape_callback(|_| AutoEscape::None);
   env.set_undefined_behavior(UndefinedBehavior::Strict);
+  env.set_fuel(Some(TEMPLATE_FUEL));
+  let dialect = dialect.to_string();
+  env.add_filter("sqlquote", move |value: Value| sqlquote(&dialect, value));
+  env.add_filter("identifier", identifier);
   env
 }
 
-fn render_one(template: &str, binding: &BTreeMap<String, String>) -> Result<String, String> {
-  let env = make_env();
+/// `{{ v | sqlquote }}`: a SQL literal. Strings are single-quoted (and
+/// backslashes escaped where `dialect` reads them as escapes), numbers and
+/// booleans kept, none is `NULL`, and a list becomes a comma-separated list of
+/// literals (`NULL` when empty, so `IN (...)` stays valid).
+fn sqlquote(dialect: &str, value: Value) -> Result<String, Error> {
+  Ok(match value.kind() {
+    ValueKind::Undefined | ValueKind::None => "NULL".to_string(),
+    ValueKind::Bool | ValueKind::Number => value.to_string(),
+    ValueKind::Seq | ValueKind::Iterable => {
+      let items = value
+        .try_iter()?
+        .map(|item| sqlquote(dialect, item))
+        .collect::<Result<Vec<_>, _>>()?;
+      if items.is_empty() {
+        "NULL".to_string()
+      } else {
+        items.join(", ")
+      }
+    }
+    ValueKind::String => quote_string(dialect, value.as_str().unwrap_or_default()),
+    _ => {
+      return Err(Error::new(
+        ErrorKind::InvalidOperation,
+        format!("sqlquote cannot quote a {} value", value.kind()),
+      ));
+    }
+  })
+}
+
+/// `{{ t | identifier }}` checks `t` is a (dotted) identifier;
+/// `{{ t | identifier('"') }}` also quotes each part with `"`, `` ` `` or `[`.
+fn identifier(value: String, quote: Option<String>) -> Result<String, Error> {
+  if !is_dotted_ident(&value) {
+    return Err(Error::new(
+      ErrorKind::InvalidOperation,
+      format!("'{value}' is not a valid identifier"),
+    ));
+  }
+  let (open, close) = match quote.as_deref() {
+    None | Some("") => return Ok(value),
+    Some("\"") => ('"', '"'),
+    Some("`") => ('`', '`'),
+    Some("[") => ('[', ']'),
+    Some(q) => {
+      return Err(Error::new(
+        ErrorKind::InvalidOperation,
+        format!("unsupported identifier quote {q:?}"),
+      ));
+    }
+  };
+  let parts: Vec<String> = value.split('.').map(|p| format!("{open}{p}{close}")).collect();
+  Ok(parts.join("."))
+}
+
+fn render_one(
+  dialect: &str,
+  template: &str,
+  ctx: BTreeMap<&str, Value>,
+) -> Result<String, String> {
+  let env = make_env(dialect);
   let tmpl = env
     .template_from_str(template)
     .map_err(|e| format!("Invalid SQL template: {e}"))?;
 
-  let mut ctx = BTreeMap::new();
-  for (k, v) in binding {
-    ctx.insert(k.as_str(), Value::from(v.as_str()));
-  }
-
-  tmpl
+  let sql = tmpl
     .render(ctx)
-    .map_err(|e| format!("Failed to expand SQL template: {e}"))
+    .map_err(|e| format!("Failed to expand SQL template: {e}"))?;
+  if sql.len() > MAX_RENDERED_LEN {
+    return Err(format!(
+      "Expanded SQL is too large ({} bytes); limit is {MAX_RENDERED_LEN}",
+      sql.len()
+    ));
+  }
+  Ok(sql)
 }
 
 pub fn analyze_sql_template_inner(sql: &str) -> Result<AnalyzeSqlTemplateResult, String> {
-  let (provided, template_body) = split_vars_block(sql)?;
-  // Always reject control-flow tags when present (even without {{ }}).
-  reject_control_flow(&template_body)?;
-  let placeholders = if has_jinja_vars(&template_body) {
+  let (decls, _, sql) = split_params_block(sql)?;
+  let (vars, template_body) = split_vars_block(&sql)?;
+  let TemplateVars {
+    values: provided,
+    types: var_types,
+  } = vars;
+  let placeholders = if has_jinja(&template_body) {
     extract_placeholders(&template_body)?
   } else {
     Vec::new()
@@ -300,7 +612,9 @@ pub fn analyze_sql_template_inner(sql: &str) -> Result<AnalyzeSqlTemplateResult,
     .cloned()
     .collect();
 
-  let has_template = !placeholders.is_empty() || !provided.is_empty();
+  let has_template =
+    has_jinja(&template_body) || !provided.is_empty() || !var_types.is_empty();
+  let bind_params = bind_params_of(&decls, &template_body);
 
   Ok(AnalyzeSqlTemplateResult {
     has_template,
@@ -308,23 +622,83 @@ pub fn analyze_sql_template_inner(sql: &str) -> Result<AnalyzeSqlTemplateResult,
     provided,
     missing,
     template_body,
+    var_types,
+    bind_params,
   })
 }
 
+/// Split `sql` into its `@vars` block and the template body after it.
+pub fn split_vars(sql: &str) -> Result<(TemplateVars, String), String> {
+  split_vars_block(sql)
+}
+
 pub fn expand_sql_template_inner(
   sql: &str,
+  dialect: &str,
   overrides: Option<&BTreeMap<String, Vec<String>>>,
 ) -> Result<ExpandSqlTemplateResult, String> {
-  let (provided, template_body) = split_vars_block(sql)?;
-  reject_control_flow(&template_body)?;
-  let placeholders = if has_jinja_vars(&template_body) {
-    extract_placeholders(&template_body)?
-  } else {
-    Vec::new()
+  // The `@params` block stays on every statement so it can still be bound.
+  let (_, params_block, sql) = split_params_block(sql)?;
+  let (vars, template_body) = split_vars_block(&sql)?;
+  let mut expanded = expand_template(&vars, template_body, dialect, overrides)?;
+  for statement in &mut expanded.statements {
+    statement.sql.insert_str(0, &params_block);
+  }
+  Ok(expanded)
+}
+
+/// Typed values for the bind placeholders of `sql`, from text entered per
+/// parameter (falling back to the declared default). Returns the SQL without
+/// its `@params` block.
+pub fn bind_template_params(
+  sql: &str,
+  values: &BTreeMap<String, String>,
+) -> Result<(String, Params), String> {
+  let (decls, _, body) = split_params_block(sql)?;
+  let mut positional = vec![];
+  let mut named = BTreeMap::new();
+  for param in bind_params_of(&decls, &body) {
+    let raw = values
+      .get(&param.name)
+      .or(param.default.as_ref())
+      .ok_or_else(|| format!("Missing value for parameter '{}'", param.name))?;
+    let value = param
+      .ty
+      .parse(raw)
+      .map_err(|e| format!("Parameter '{}': {e}", param.name))?;
+    match param.name.parse::<usize>() {
+      Ok(i) => {
+        if positional.len() < i {
+          positional.resize(i, ParamValue::Null);
+        }
+        positional[i - 1] = value;
+      }
+      Err(_) => {
+        named.insert(param.name, value);
+      }
+    }
+  }
+  let params = match (positional.is_empty(), named.is_empty()) {
+    (_, true) => Params::Positional(positional),
+    (true, false) => Params::Named(named),
+    (false, false) => {
+      return Err("Use either $n or :name placeholders in one statement, not both".to_string());
+    }
   };
+  Ok((body, params))
+}
 
-  // No template vars: return body as-is (vars block stripped if present).
-  if placeholders.is_empty() {
+/// Expand a template body (no `@vars` block) over its `@vars` and `overrides`:
+/// one statement per combination of values, a `list` var counting as one.
+/// `sqlquote` spells its literals for `dialect`.
+pub fn expand_template(
+  vars: &TemplateVars,
+  template_body: String,
+  dialect: &str,
+  overrides: Option<&BTreeMap<String, Vec<String>>>,
+) -> Result<ExpandSqlTemplateResult, String> {
+  // Plain SQL: return body as-is (vars block stripped if present).
+  if !has_jinja(&template_body) {
     return Ok(ExpandSqlTemplateResult {
       statements: vec![ExpandedStatement {
         sql: template_body,
@@ -332,16 +706,25 @@ pub fn expand_sql_template_inner(
       }],
     });
   }
+  let placeholders = extract_placeholders(&template_body)?;
 
-  let merged = merge_values(&provided, overrides);
+  let merged = merge_values(&vars.values, overrides);
   for p in &placeholders {
-    if !merged.contains_key(p) {
+    let Some(values) = merged.get(p) else {
       return Err(format!("Missing value for template variable '{p}'"));
+    };
+    if let Some(spec) = vars.types.get(p) {
+      for value in values {
+        spec.validate(p, value)?;
+      }
     }
   }
 
   // Only expand over placeholders used in the template (stable key order).
-  let combos = cartesian_product(&placeholders, &merged)?;
+  let is_list = |p: &String| vars.types.get(p).is_some_and(|t| t.ty == VarType::List);
+  let (lists, scalars): (Vec<String>, Vec<String>) =
+    placeholders.iter().cloned().partition(is_list);
+  let combos = cartesian_product(&scalars, &merged)?;
   if combos.len() > HARD_EXPAND_LIMIT {
     return Err(format!(
       "Too many combinations ({}); limit is {HARD_EXPAND_LIMIT}",
@@ -350,8 +733,22 @@ pub fn expand_sql_template_inner(
   }
 
   let mut statements = Vec::with_capacity(combos.len());
-  for binding in combos {
-    let sql = render_one(&template_body, &binding)?;
+  for mut binding in combos {
+    let mut ctx = BTreeMap::new();
+    for (k, v) in &binding {
+      let value = match vars.types.get(k) {
+        Some(spec) => spec.to_value(std::slice::from_ref(v)),
+        None => Value::from(v.as_str()),
+      };
+      ctx.insert(k.as_str(), value);
+    }
+    for k in &lists {
+      ctx.insert(k.as_str(), vars.types[k].to_value(&merged[k]));
+    }
+    let sql = render_one(dialect, &template_body, ctx)?;
+    for k in &lists {
+      binding.insert(k.clone(), merged[k].join(", "));
+    }
     statements.push(ExpandedStatement { sql, binding });
   }
 
@@ -366,9 +763,11 @@ pub async fn analyze_sql_template(sql: String) -> Result<AnalyzeSqlTemplateResul
 #[tauri::command]
 pub async fn expand_sql_template(
   sql: String,
+  dialect: Option<String>,
   overrides: Option<BTreeMap<String, Vec<String>>>,
 ) -> Result<ExpandSqlTemplateResult, String> {
-  expand_sql_template_inner(&sql, overrides.as_ref())
+  let dialect = dialect.unwrap_or_default();
+  expand_sql_template_inner(&sql, &dialect, overrides.as_ref())
 }
 
 #[cfg(test)]
@@ -381,7 +780,7 @@ mod tests {
     let a = analyze_sql_template_inner(sql).unwrap();
     assert!(!a.has_template);
     assert!(a.placeholders.is_empty());
-    let e = expand_sql_template_inner(sql, None).unwrap();
+    let e = expand_sql_template_inner(sql, "postgres", None).unwrap();
     assert_eq!(e.statements.len(), 1);
     assert_eq!(e.statements[0].sql, "SELECT 1");
   }
@@ -404,7 +803,7 @@ SELECT * FROM {{ schema }}.{{ table }}
     assert!(a.missing.is_empty());
     assert_eq!(a.provided.get("table").unwrap().len(), 2);
 
-    let e = expand_sql_template_inner(sql, None).unwrap();
+    let e = expand_sql_template_inner(sql, "postgres", None).unwrap();
     assert_eq!(e.statements.len(), 2);
     assert!(e.statements[0].sql.contains("public.orders") || e.statements[0].sql.contains("public.items"));
     assert!(!e.statements[0].sql.contains("@vars"));
@@ -416,26 +815,84 @@ SELECT * FROM {{ schema }}.{{ table }}
     let sql = "SELECT * FROM {{ table }}";
     let a = analyze_sql_template_inner(sql).unwrap();
     assert_eq!(a.missing, vec!["table".to_string()]);
-    assert!(expand_sql_template_inner(sql, None).is_err());
+    assert!(expand_sql_template_inner(sql, "postgres", None).is_err());
 
     let mut ov = BTreeMap::new();
     ov.insert("table".to_string(), vec!["t1".to_string(), "t2".to_string()]);
-    let e = expand_sql_template_inner(sql, Some(&ov)).unwrap();
+    let e = expand_sql_template_inner(sql, "postgres", Some(&ov)).unwrap();
     assert_eq!(e.statements.len(), 2);
     assert_eq!(e.statements[0].sql.trim(), "SELECT * FROM t1");
     assert_eq!(e.statements[1].sql.trim(), "SELECT * FROM t2");
   }
 
   #[test]
-  fn rejects_control_flow() {
-    let sql = "{% for t in tables %}SELECT 1{% endfor %}";
-    assert!(analyze_sql_template_inner(sql).is_err());
+  fn control_flow_and_filters() {
+    let sql = r#"/*
+@vars
+status: { type: enum, options: [open, closed], value: "" }
+ids: { type: list, item: int, value: [1, 2, 3] }
+tbl: { type: identifier, value: app.orders }
+*/
+SELECT * FROM {{ tbl | identifier('"') }} WHERE id IN ({{ ids | sqlquote }})
+{% if status %}AND status = {{ status | sqlquote }}{% endif %}
+{% for c in ['a', "b'c"] %}{{ loop.index }}={{ c | sqlquote }} {% endfor %}"#;
+    let a = analyze_sql_template_inner(sql).unwrap();
+    assert_eq!(a.placeholders, ["ids", "status", "tbl"]);
+    assert_eq!(a.var_types["ids"].ty, VarType::List);
+
+    let e = expand_sql_template_inner(sql, "postgres", None).unwrap();
+    assert_eq!(e.statements.len(), 1);
+    let out = &e.statements[0].sql;
+    assert!(out.contains(r#"FROM "app"."orders" WHERE id IN (1, 2, 3)"#), "{out}");
+    assert!(!out.contains("status ="));
+    assert!(out.contains("1='a' 2='b''c'"));
+    assert_eq!(e.statements[0].binding["ids"], "1, 2, 3");
+
+    let mut ov = BTreeMap::new();
+    ov.insert("status".to_string(), vec!["open".to_string(), "closed".to_string()]);
+    let e = expand_sql_template_inner(sql, "postgres", Some(&ov)).unwrap();
+    assert_eq!(e.statements.len(), 2);
+    assert!(e.statements[0].sql.contains("AND status = 'open'"));
+
+    ov.insert("status".to_string(), vec!["gone".to_string()]);
+    assert!(expand_sql_template_inner(sql, "postgres", Some(&ov)).is_err());
+    ov.insert("status".to_string(), vec![]);
+    ov.insert("tbl".to_string(), vec!["orders; drop table x".to_string()]);
+    assert!(expand_sql_template_inner(sql, "postgres", Some(&ov)).is_err());
+  }
+
+  #[test]
+  fn sqlquote_escapes_backslashes_per_dialect() {
+    let sql = "SELECT * FROM t WHERE name = {{ name | sqlquote }}";
+    let ov = BTreeMap::from([("name".to_string(), vec![r"\' OR 1=1 -- ".to_string()])]);
+    let quoted = |dialect| {
+      let e = expand_sql_template_inner(sql, dialect, Some(&ov)).unwrap();
+      e.statements[0].sql.clone()
+    };
+    assert_eq!(quoted("mysql"), r"SELECT * FROM t WHERE name = '\\'' OR 1=1 -- '");
+    assert_eq!(quoted("clickhouse"), r"SELECT * FROM t WHERE name = '\\'' OR 1=1 -- '");
+    assert_eq!(quoted("postgres"), r"SELECT * FROM t WHERE name = '\'' OR 1=1 -- '");
+  }
+
+  #[test]
+  fn typed_vars_validate_values() {
+    let bad = ["day: { type: date, value: 2024-02-30 }", "n: { type: int, value: x }"];
+    for vars in bad {
+      let sql = format!("/* @vars\n{vars}\n*/\nSELECT {{{{ n }}}}");
+      assert!(analyze_sql_template_inner(&sql).is_err(), "{vars}");
+    }
+    let sql = "/* @vars\nn: { type: int }\n*/\nSELECT {{ n + 1 }}";
+    let a = analyze_sql_template_inner(sql).unwrap();
+    assert_eq!(a.missing, ["n"]);
+    let ov = BTreeMap::from([("n".to_string(), vec!["41".to_string()])]);
+    let e = expand_sql_template_inner(sql, "postgres", Some(&ov)).unwrap();
+    assert_eq!(e.statements[0].sql, "SELECT 42");
   }
 
   #[test]
-  fn rejects_expression() {
-    let sql = "SELECT {{ a + b }}";
-    assert!(analyze_sql_template_inner(sql).is_err());
+  fn runaway_loops_are_stopped() {
+    let sql = "{% for i in range(10000) %}{% for j in range(10000) %}x{% endfor %}{% endfor %}";
+    assert!(expand_sql_template_inner(sql, "postgres", None).is_err());
   }
 
   #[test]
@@ -448,7 +905,49 @@ SELECT {{ table }}
 "#;
     let mut ov = BTreeMap::new();
     ov.insert("table".to_string(), vec!["new".to_string()]);
-    let e = expand_sql_template_inner(sql, Some(&ov)).unwrap();
+    let e = expand_sql_template_inner(sql, "postgres", Some(&ov)).unwrap();
     assert_eq!(e.statements[0].sql.trim(), "SELECT new");
   }
+
+  #[test]
+  fn params_block_types_bound_values() {
+    let sql = r#"/*
+@vars
+table: orders
+*/
+/* @params
+id: int
+since: { type: text, default: '2024-01-01' }
+*/
+SELECT * FROM {{ table }} WHERE id = :id AND created >= :since AND note = ':id'
+"#;
+    let a = analyze_sql_template_inner(sql).unwrap();
+    assert_eq!(a.placeholders, vec!["table".to_string()]);
+    let names: Vec<_> = a.bind_params.iter().map(|p| (p.name.as_str(), p.ty)).collect();
+    assert_eq!(names, [("id", ParamType::Int), ("since", ParamType::Text)]);
+
+    let e = expand_sql_template_inner(sql, "postgres", None).unwrap();
+    assert!(e.statements[0].sql.starts_with("/* @params"));
+    assert!(e.statements[0].sql.contains("FROM orders"));
+
+    let mut values = BTreeMap::new();
+    assert!(bind_template_params(&e.statements[0].sql, &values).is_err());
+    values.insert("id".to_string(), "42".to_string());
+    let (body, params) = bind_template_params(&e.statements[0].sql, &values).unwrap();
+    assert!(!body.contains("@params"));
+    let Params::Named(params) = params else {
+      panic!("expected named params");
+    };
+    assert_eq!(params["id"], ParamValue::Int(42));
+    assert_eq!(params["since"], ParamValue::Text("2024-01-01".into()));
+
+    values.insert("id".to_string(), "x".to_string());
+    assert!(bind_template_params(&e.statements[0].sql, &values).is_err());
+
+    let positional = BTreeMap::from([("2".to_string(), "b".to_string())]);
+    let (body, params) = bind_template_params("SELECT $2", &positional).unwrap();
+    assert_eq!(body, "SELECT $2");
+    let text = ParamValue::Text("b".into());
+    assert_eq!(params, Params::Positional(vec![ParamValue::Null, text]));
+  }
 }
@@ -3,7 +3,9 @@
 
 use std::env;
 
-use cmd::{ConnectionRegistry, InflightQueries, OpenedFiles, SessionManager};
+use cmd::{
+  ConnectionRegistry, InflightQueries, OpenedFiles, QueryHistory, SavedQueries, SessionManager,
+};
 use std::path::PathBuf;
 use std::sync::Mutex;
 use tauri::Emitter;
@@ -107,6 +109,7 @@ fn main() {
     .manage(ConnectionRegistry::default())
     .manage(SessionManager::default())
     .manage(InflightQueries::default())
+    .manage(SavedQueries::default())
     .plugin(tauri_plugin_clipboard_manager::init())
     .plugin(tauri_plugin_dialog::init())
     .plugin(tauri_plugin_shell::init())
@@ -130,6 +133,7 @@ fn main() {
     )
     .setup(|app| {
       let _ = handle_menu(app);
+      app.manage(QueryHistory::open(app.handle()));
 
       #[cfg(any(windows, target_os = "linux"))]
       handle_open_files(app);
@@ -141,6 +145,30 @@ fn main() {
     .invoke_handler(tauri::generate_handler![
       cmd::db::query,
       cmd::db::paging_query,
+      cmd::db::execute,
+      cmd::db::query_params,
+      cmd::db::execute_params,
+      cmd::db::run_script,
+      cmd::db::begin_transaction,
+      cmd::db::commit,
+      cmd::db::rollback,
+      cmd::db::transaction_status,
+      cmd::db::transaction_query,
+      cmd::db::transaction_execute,
+      cmd::db::open_cursor,
+      cmd::db::fetch_cursor,
+      cmd::db::close_cursor,
+      cmd::db::explain,
+      cmd::db::table_primary_key,
+      cmd::db::table_detail,
+      cmd::db::object_ddl,
+      cmd::db::schema_diff,
+      cmd::db::data_diff,
+      cmd::db::transfer_data,
+      cmd::db::import_columns,
+      cmd::db::import_file,
+      cmd::db::preview_changes,
+      cmd::db::apply_changes,
       cmd::db::query_table,
       cmd::db::export,
       cmd::db::table_row_count,
@@ -157,6 +185,10 @@ fn main() {
       cmd::db::cancel_query,
       cmd::db::set_session_idle_ttl,
       cmd::db::get_session_idle_ttl,
+      cmd::history::search_history,
+      cmd::history::pin_history,
+      cmd::history::delete_history,
+      cmd::history::clear_history,
       cmd::diagnostics::get_memory_snapshot,
       cmd::diagnostics::append_memory_log,
       cmd::diagnostics::read_memory_log_tail,
@@ -167,6 +199,13 @@ fn main() {
       cmd::app::format_sql_sqlfmt,
       cmd::sql_template::analyze_sql_template,
       cmd::sql_template::expand_sql_template,
+      cmd::saved_queries::list_saved_queries,
+      cmd::saved_queries::save_query,
+      cmd::saved_queries::delete_saved_query,
+      cmd::saved_queries::load_saved_query,
+      cmd::saved_queries::expand_saved_query,
+      cmd::saved_queries::export_saved_queries,
+      cmd::saved_queries::import_saved_queries,
       cmd::app::opened_files,
       cmd::app::list_sql_dir,
       cmd::app::read_text_file,
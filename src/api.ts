@@ -1,5 +1,6 @@
 import { Data, Table, Utf8, tableFromIPC } from '@apache-arrow/ts';
 import { invoke } from '@tauri-apps/api/core';
+import { UnlistenFn, listen } from '@tauri-apps/api/event';
 import { Update } from '@tauri-apps/plugin-updater';
 import { uniqBy } from 'es-toolkit';
 import { nanoid } from 'nanoid';
@@ -39,6 +40,12 @@ export type ResultType<T = unknown> = {
   message: string;
   /** sqlparser location relative to executed SQL (when parse fails on error). */
   parseLocation?: SqlParseLocation;
+  /** `total` is an estimate rather than an exact count. */
+  estimated?: boolean;
+  /** `queryTable` only: pass back as `after` to seek to the next page. */
+  cursor?: string;
+  /** Rows were dropped to stay within the row / byte limits. */
+  truncated?: boolean;
 };
 
 function bigIntReplacer(_key: string, value: any) {
@@ -78,6 +85,17 @@ export function convertArrow(arrowData: Array<number>, titles?: TitleType[]) {
   };
 }
 
+/** Arrow extension name the backend sets on columns holding JSON text. */
+const JSON_EXTENSION = 'arrow.json';
+
+export function isJsonField(field?: SchemaType) {
+  const metadata = field?.metadata;
+  return (
+    metadata instanceof Map &&
+    metadata.get('ARROW:extension:name') === JSON_EXTENSION
+  );
+}
+
 /** Convert backend rows payload into the same shape as convertArrow. */
 export function convertRows(
   rows: Record<string, unknown>[] | undefined,
@@ -192,10 +210,34 @@ function convert(res: ArrowResponse): ResultType {
   };
 }
 
+/** How `total` is computed for paged results. */
+export type CountOptions = {
+  /** Use catalog statistics for whole-table results instead of `count(*)`. */
+  estimate?: boolean;
+  /** Give up on an exact count after this long and report an estimate. */
+  timeoutMs?: number;
+};
+
+/**
+ * Guards against unbounded results. Per request, or per connection as
+ * `limits` in its config; request fields win.
+ */
+export type QueryLimits = {
+  /** Stop the statement on the server after this long. */
+  timeoutMs?: number;
+  /** `query` only: keep at most this many rows and flag the result `truncated`. */
+  maxRows?: number;
+  /** `query` only: keep at most this many bytes of Arrow data. */
+  maxBytes?: number;
+};
+
 export type QueryParams = {
   sql: string;
   limit: number;
   offset: number;
+  /** `pagingQuery` only. */
+  count?: CountOptions;
+  limits?: QueryLimits;
   dialect?: DialectRef;
   /** When set, backend registers an inflight token; call `cancelQuery` with the same id. */
   requestId?: string;
@@ -205,6 +247,12 @@ export type QueryTableParams = {
   table: string;
   limit: number;
   offset: number;
+  /**
+   * `cursor` of the previous page. Seeks past its last key instead of skipping
+   * `offset` rows; only issued when the order is a unique key.
+   */
+  after?: string;
+  count?: CountOptions;
   where?: string;
   orderBy?: string;
   /** Appended after `*` in `SELECT *… FROM table`, e.g. `, a+1 AS a1`. */
@@ -217,26 +265,532 @@ export type QueryTableParams = {
 export async function query(params: QueryParams): Promise<ResultType> {
   console.debug('query sql params:', params);
   const res = await invoke<ArrowResponse>('query', params);
-  return convert(res);
+  return { ...convert(res), truncated: res.truncated };
 }
 
 export async function pagingQuery(params: QueryParams): Promise<ResultType> {
   console.debug('query sql params:', params);
   const res = await invoke<ArrowResponse>('paging_query', params);
+  return { ...convert(res), estimated: res.estimated };
+}
+
+export type ExecuteParams = {
+  sql: string;
+  dialect?: DialectRef;
+  /** When set, backend registers an inflight token; call `cancelQuery` with the same id. */
+  requestId?: string;
+};
+
+export type ExecuteResult = {
+  rowsAffected: number;
+  elapsed: number;
+};
+
+/** Run a DDL/DML statement; resolves with rows affected. */
+export async function execute(params: ExecuteParams): Promise<ExecuteResult> {
+  return invoke<ExecuteResult>('execute', params);
+}
+
+export type BoundParams = {
+  sql: string;
+  /** Text per parameter name (`id`, or `1` for `$1`); `null` binds SQL NULL. */
+  values?: Record<string, string>;
+  dialect?: DialectRef;
+  /** When set, backend registers an inflight token; call `cancelQuery` with the same id. */
+  requestId?: string;
+};
+
+/** Run a query with its parameters bound as a prepared statement. */
+export async function queryParams(params: BoundParams): Promise<ResultType> {
+  const res = await invoke<ArrowResponse>('query_params', params);
   return convert(res);
 }
 
+/** `execute` with bound parameters. */
+export async function executeParams(params: BoundParams): Promise<ExecuteResult> {
+  return invoke<ExecuteResult>('execute_params', params);
+}
+
+export type RunScriptParams = {
+  sql: string;
+  /** Max rows per result set; omitted = full result. */
+  limit?: number;
+  /** Keep running after a failed statement (default: stop). */
+  continueOnError?: boolean;
+  dialect?: DialectRef;
+  /** When set, backend registers an inflight token; call `cancelQuery` with the same id. */
+  requestId?: string;
+};
+
+export type ScriptStatementResult = {
+  index: number;
+  sql: string;
+  /** 1-based start position of the statement within the script. */
+  line: number;
+  column: number;
+  result?: ResultType;
+  rowsAffected?: number;
+  elapsed: number;
+  code: number;
+  message: string;
+  /** sqlparser location relative to the whole script. */
+  parseLocation?: SqlParseLocation;
+};
+
+type ScriptStatementResponse = Omit<ScriptStatementResult, 'result'> & {
+  result?: ArrowResponse | null;
+};
+
+/** Run a multi-statement script; one entry per executed statement. */
+export async function runScript(
+  params: RunScriptParams,
+): Promise<ScriptStatementResult[]> {
+  const res = await invoke<ScriptStatementResponse[]>('run_script', params);
+  return res.map(({ result, rowsAffected, parseLocation, ...rest }) => ({
+    ...rest,
+    result: result ? convert(result) : undefined,
+    rowsAffected: rowsAffected ?? undefined,
+    parseLocation: parseLocation ?? undefined,
+  }));
+}
+
+/** Open an explicit transaction on the live session for `dialect.connectionId`. */
+export async function beginTransaction(dialect: DialectRef): Promise<void> {
+  return invoke('begin_transaction', { dialect });
+}
+
+export async function commitTransaction(connectionId: string): Promise<void> {
+  return invoke('commit', { connectionId });
+}
+
+export async function rollbackTransaction(connectionId: string): Promise<void> {
+  return invoke('rollback', { connectionId });
+}
+
+export async function transactionStatus(connectionId: string): Promise<boolean> {
+  return invoke<boolean>('transaction_status', { connectionId });
+}
+
+/** Run a query inside the open transaction for `connectionId`. */
+export async function transactionQuery(params: {
+  connectionId: string;
+  sql: string;
+  requestId?: string;
+}): Promise<ResultType> {
+  const res = await invoke<ArrowResponse>('transaction_query', params);
+  return convert(res);
+}
+
+/** Run DDL/DML inside the open transaction for `connectionId`. */
+export async function transactionExecute(params: {
+  connectionId: string;
+  sql: string;
+  requestId?: string;
+}): Promise<ExecuteResult> {
+  return invoke<ExecuteResult>('transaction_execute', params);
+}
+
+export type CursorPage = {
+  /** Pass to `fetchCursor` / `closeCursor`; absent once the result is exhausted. */
+  cursorId: string | null;
+  page: ResultType;
+  done: boolean;
+};
+
+type CursorPageResponse = Omit<CursorPage, 'page'> & { page: ArrowResponse };
+
+/**
+ * Run `sql` once on the live session for `dialect.connectionId` and return the
+ * first page; `fetchCursor` pulls the following pages without re-running it.
+ */
+export async function openCursor(params: {
+  dialect: DialectRef;
+  sql: string;
+  pageSize?: number;
+}): Promise<CursorPage> {
+  const res = await invoke<CursorPageResponse>('open_cursor', params);
+  return { ...res, page: convert(res.page) };
+}
+
+export async function fetchCursor(params: {
+  connectionId: string;
+  cursorId: string;
+  pageSize?: number;
+}): Promise<CursorPage> {
+  const res = await invoke<CursorPageResponse>('fetch_cursor', params);
+  return { ...res, page: convert(res.page) };
+}
+
+export async function closeCursor(params: {
+  connectionId: string;
+  cursorId: string;
+}): Promise<void> {
+  return invoke('close_cursor', params);
+}
+
+export type PlanNode = {
+  operator: string;
+  detail: string | null;
+  estimatedRows: number | null;
+  /** Rows across all loops; only when the plan was analyzed. */
+  actualRows: number | null;
+  cost: number | null;
+  timeMs: number | null;
+  children: PlanNode[];
+};
+
+export type QueryPlan = {
+  /** False when the engine cannot analyze and returned an estimated plan. */
+  analyze: boolean;
+  root: PlanNode;
+  raw: string;
+};
+
+/** Plan of `sql`; `analyze` executes it (single SELECT queries only). */
+export async function explain(params: {
+  dialect: DialectRef;
+  sql: string;
+  analyze?: boolean;
+}): Promise<QueryPlan> {
+  return invoke('explain', params);
+}
+
+export type ColumnDetail = {
+  name: string;
+  type: string;
+  nullable: boolean;
+  default: string | null;
+  comment: string | null;
+};
+
+export type IndexDetail = {
+  name: string;
+  /** Columns or expressions in key order. */
+  columns: string[];
+  unique: boolean;
+  method: string | null;
+};
+
+export type ForeignKeyDetail = {
+  name: string | null;
+  columns: string[];
+  ref_schema: string | null;
+  ref_table: string;
+  ref_columns: string[];
+  on_update: string | null;
+  on_delete: string | null;
+};
+
+/** Structured table metadata (field names follow the backend's snake_case). */
+export type TableDetail = {
+  schema: string | null;
+  table: string;
+  comment: string | null;
+  columns: ColumnDetail[];
+  primary_key: string[];
+  unique_constraints: { name: string | null; columns: string[] }[];
+  indexes: IndexDetail[];
+  foreign_keys: ForeignKeyDetail[];
+  /** ClickHouse only. */
+  sorting_key: string[];
+  partition_key: string | null;
+  engine: string | null;
+};
+
+export async function tableDetail(
+  params: { schema?: string; table: string },
+  dialect: DialectRef,
+): Promise<TableDetail> {
+  return invoke<TableDetail>('table_detail', { ...params, dialect });
+}
+
+export type ObjectKind = 'table' | 'view' | 'function';
+
+/** CREATE statement(s) for a catalog object, ready to show in an editor. */
+export async function objectDdl(
+  params: { schema?: string; name: string; kind: ObjectKind },
+  dialect: DialectRef,
+): Promise<string> {
+  return invoke<string>('object_ddl', { ...params, dialect });
+}
+
+export type SchemaColumn = { name: string; type: string; normalized: string };
+
+export type SchemaTable = { table: string; columns: SchemaColumn[] };
+
+/** Differences to apply to the target so it matches the source (snake_case fields). */
+export type SchemaDiff = {
+  source_dialect: string;
+  target_dialect: string;
+  added_tables: SchemaTable[];
+  removed_tables: SchemaTable[];
+  changed_tables: {
+    table: string;
+    added_columns: SchemaColumn[];
+    removed_columns: SchemaColumn[];
+    changed_columns: { name: string; source: SchemaColumn; target: SchemaColumn }[];
+  }[];
+  unchanged_tables: number;
+};
+
+export async function schemaDiff(params: {
+  source: DialectRef;
+  target: DialectRef;
+  sourceSchema?: string;
+  targetSchema?: string;
+  /** Also generate migration SQL for the target dialect. */
+  migration?: boolean;
+}): Promise<{ diff: SchemaDiff; migration: string[] | null }> {
+  return invoke('schema_diff', params);
+}
+
+export type DataDiff = {
+  key: string[];
+  inserted: number;
+  deleted: number;
+  changed: number;
+  unchanged: number;
+  /** More differing rows exist than `maxRows`; counts still cover all of them. */
+  truncated: boolean;
+  sourceOnlyColumns: string[];
+  targetOnlyColumns: string[];
+  /** `_diff` (insert/delete/change), `_changed`, key columns, `source.*` / `target.*`. */
+  rows: ResultType;
+  /** `column`, `changes` per compared column. */
+  columnChanges: ResultType;
+  elapsed: number;
+};
+
+export async function dataDiff(params: {
+  source: DialectRef;
+  target: DialectRef;
+  sourceTable: string;
+  /** Defaults to `sourceTable`. */
+  targetTable?: string;
+  /** Defaults to the primary key. */
+  key?: string[];
+  pageSize?: number;
+  maxRows?: number;
+}): Promise<DataDiff> {
+  const res = await invoke<
+    Omit<DataDiff, 'rows' | 'columnChanges'> & {
+      rows: ArrowResponse;
+      columnChanges: ArrowResponse;
+    }
+  >('data_diff', params);
+  return { ...res, rows: convert(res.rows), columnChanges: convert(res.columnChanges) };
+}
+
+export type TransferMode = 'create' | 'append' | 'replace';
+
+export type TransferProgress = {
+  rows: number;
+  batches: number;
+  done: boolean;
+};
+
+/** Copy the result of `sql` on `source` into `options.table` on `target`. */
+export async function transferData(params: {
+  source: DialectRef;
+  target: DialectRef;
+  sql: string;
+  options: {
+    schema?: string;
+    table: string;
+    /** Defaults to `create` (fails when the table exists). */
+    mode?: TransferMode;
+    batchRows?: number;
+  };
+  /** Enables `cancelQuery(requestId)` and tags progress events. */
+  requestId?: string;
+}): Promise<TransferProgress> {
+  return invoke('transfer_data', params);
+}
+
+export function onTransferProgress(
+  handler: (progress: TransferProgress & { requestId: string | null }) => void,
+): Promise<UnlistenFn> {
+  return listen<TransferProgress & { requestId: string | null }>(
+    'transfer-progress',
+    (event) => handler(event.payload),
+  );
+}
+
+/** A file column as DuckDB infers it. */
+export type FileColumn = { name: string; type: string; normalized: string };
+
+export async function importColumns(params: {
+  file: string;
+  format?: string;
+}): Promise<FileColumn[]> {
+  return invoke('import_columns', params);
+}
+
+export type ImportResult = {
+  rows: number;
+  rejectedCount: number;
+  /** First 1000 rejected rows; `row` is the 1-based data row in the file. */
+  rejected: { row: number; reason: string }[];
+};
+
+/** Load a CSV/TSV/Parquet/JSON/XLSX file into a table on `dialect`. */
+export async function importFile(params: {
+  dialect: DialectRef;
+  options: {
+    file: string;
+    format?: string;
+    schema?: string;
+    table: string;
+    mode?: TransferMode;
+    columns?: { source: string; name?: string; type?: string; skip?: boolean }[];
+    batchRows?: number;
+  };
+  /** Enables `cancelQuery(requestId)` and tags progress events. */
+  requestId?: string;
+}): Promise<ImportResult> {
+  return invoke('import_file', params);
+}
+
+export function onImportProgress(
+  handler: (progress: TransferProgress & { requestId: string | null }) => void,
+): Promise<UnlistenFn> {
+  return listen<TransferProgress & { requestId: string | null }>(
+    'import-progress',
+    (event) => handler(event.payload),
+  );
+}
+
+/** Column name -> cell value for one grid row. */
+export type GridRow = Record<string, unknown>;
+
+/** Pending grid edits for one table; update/delete keys are primary-key values. */
+export type ChangeSet = {
+  schema?: string;
+  table: string;
+  updates?: { key: GridRow; changes: GridRow }[];
+  inserts?: GridRow[];
+  deletes?: GridRow[];
+};
+
+export type ApplyChangesResult = {
+  statements: string[];
+  rowsAffected: number[];
+  /** Edits joined an open transaction and still need `commitTransaction`. */
+  pendingCommit: boolean;
+  elapsed: number;
+};
+
+export async function tablePrimaryKey(
+  params: { schema?: string; table: string },
+  dialect: DialectRef,
+): Promise<string[]> {
+  return invoke<string[]>('table_primary_key', { ...params, dialect });
+}
+
+export async function previewChanges(
+  changes: ChangeSet,
+  dialect: DialectRef,
+): Promise<string[]> {
+  return invoke<string[]>('preview_changes', { changes, dialect });
+}
+
+export async function applyChanges(
+  changes: ChangeSet,
+  dialect: DialectRef,
+): Promise<ApplyChangesResult> {
+  return invoke<ApplyChangesResult>('apply_changes', { changes, dialect });
+}
+
 /** Cancel an in-flight query previously started with `requestId`. */
 export async function cancelQuery(requestId: string): Promise<boolean> {
   return invoke<boolean>('cancel_query', { requestId });
 }
 
+export type HistoryEntry = {
+  id: number;
+  connectionId: string | null;
+  kind: 'query' | 'paging_query' | 'export';
+  sql: string;
+  /** Unix milliseconds. */
+  executedAt: number;
+  elapsedMs: number;
+  rows: number | null;
+  /** `ConnectorError` code when the run failed. */
+  errorCode: string | null;
+  message: string | null;
+  pinned: boolean;
+};
+
+export type HistoryFilter = {
+  /** Words that must all appear in the SQL (prefix match). */
+  text?: string;
+  connectionId?: string;
+  /** Unix milliseconds, inclusive. */
+  from?: number;
+  /** Unix milliseconds, exclusive. */
+  to?: number;
+  pinnedOnly?: boolean;
+  limit?: number;
+  offset?: number;
+};
+
+/** Recorded query/paging_query/export runs, newest first. */
+export async function searchHistory(
+  filter?: HistoryFilter,
+): Promise<HistoryEntry[]> {
+  return invoke<HistoryEntry[]>('search_history', { filter });
+}
+
+export async function pinHistory(id: number, pinned: boolean): Promise<boolean> {
+  return invoke<boolean>('pin_history', { id, pinned });
+}
+
+export async function deleteHistory(id: number): Promise<boolean> {
+  return invoke<boolean>('delete_history', { id });
+}
+
+/** Remove unpinned entries, all or only those of one connection. */
+export async function clearHistory(connectionId?: string): Promise<number> {
+  return invoke<number>('clear_history', { connectionId });
+}
+
+/** Fires with the new entry's id whenever a run is recorded. */
+export function onHistoryChanged(
+  handler: (id: number) => void,
+): Promise<UnlistenFn> {
+  return listen<number>('history-changed', (event) => handler(event.payload));
+}
+
 export type AnalyzeSqlTemplateResult = {
   hasTemplate: boolean;
   placeholders: string[];
   provided: Record<string, string[]>;
   missing: string[];
   templateBody: string;
+  /** Declared types of typed `@vars`. */
+  varTypes: Record<string, VarSpec>;
+  /** Bind placeholders (`:name` / `$1`) typed by the `@params` block. */
+  bindParams: BindParam[];
+};
+
+export type VarType = 'text' | 'int' | 'date' | 'enum' | 'identifier' | 'list';
+
+/** Typed `@vars` entry: `name: { type: int, value: 5 }`. */
+export type VarSpec = {
+  type: VarType;
+  /** Allowed values of an `enum` (or a list of enum). */
+  options?: string[];
+  /** Element type of a `list`; defaults to text. */
+  item?: VarType;
+};
+
+export type ParamType = 'text' | 'int' | 'float' | 'bool';
+
+export type BindParam = {
+  /** `id` for `:id`, `1` for `$1`. */
+  name: string;
+  type: ParamType;
+  default?: string;
 };
 
 export type ExpandedStatement = {
@@ -255,17 +809,107 @@ export async function analyzeSqlTemplate(
   return invoke<AnalyzeSqlTemplateResult>('analyze_sql_template', { sql });
 }
 
-/** Expand SQL template with comment vars + optional overrides (cartesian product). */
+/**
+ * Expand SQL template with comment vars + optional overrides (cartesian product).
+ * `sqlquote` literals are spelled for `dialect`.
+ */
 export async function expandSqlTemplate(
   sql: string,
+  dialect?: string,
   overrides?: Record<string, string[]>,
 ): Promise<ExpandSqlTemplateResult> {
   return invoke<ExpandSqlTemplateResult>('expand_sql_template', {
     sql,
+    dialect: dialect ?? null,
     overrides: overrides ?? null,
   });
 }
 
+export type SavedQuery = {
+  id: string;
+  name: string;
+  description: string;
+  /** Slash-separated folder path, `''` for the root. */
+  folder: string;
+  tags: string[];
+  connectionId: string | null;
+  /** Template body without the `@vars` block. */
+  sql: string;
+  /** `@vars` defaults the query was saved with. */
+  params: Record<string, string[]>;
+  /** Types of the typed `@vars` entries. */
+  varTypes?: Record<string, VarSpec>;
+  createdAt: number;
+  updatedAt: number;
+};
+
+export type SaveQueryRequest = {
+  /** Existing query to update; omit to create one. */
+  id?: string;
+  name: string;
+  description?: string;
+  folder?: string;
+  tags?: string[];
+  connectionId?: string | null;
+  /** Editor text; a leading `@vars` block is stored as `params`. */
+  sql: string;
+};
+
+export type ImportConflict = 'copy' | 'replace' | 'skip';
+
+export type ImportSummary = {
+  added: number;
+  replaced: number;
+  skipped: number;
+};
+
+export async function listSavedQueries(): Promise<SavedQuery[]> {
+  return invoke<SavedQuery[]>('list_saved_queries');
+}
+
+export async function saveQuery(request: SaveQueryRequest): Promise<SavedQuery> {
+  return invoke<SavedQuery>('save_query', { request });
+}
+
+export async function deleteSavedQuery(id: string): Promise<boolean> {
+  return invoke<boolean>('delete_saved_query', { id });
+}
+
+/** Editor text for a saved query, with its defaults restored as `@vars`. */
+export async function loadSavedQuery(id: string): Promise<string> {
+  return invoke<string>('load_saved_query', { id });
+}
+
+export async function expandSavedQuery(
+  id: string,
+  dialect?: string,
+  overrides?: Record<string, string[]>,
+): Promise<ExpandSqlTemplateResult> {
+  return invoke<ExpandSqlTemplateResult>('expand_saved_query', {
+    id,
+    dialect: dialect ?? null,
+    overrides: overrides ?? null,
+  });
+}
+
+/** Write queries (all when `ids` is omitted) to a shareable bundle file. */
+export async function exportSavedQueries(
+  file: string,
+  ids?: string[],
+): Promise<number> {
+  return invoke<number>('export_saved_queries', { file, ids: ids ?? null });
+}
+
+export async function importSavedQueries(
+  file: string,
+  conflict?: ImportConflict,
+): Promise<ImportSummary> {
+  return invoke<ImportSummary>('import_saved_queries', {
+    file,
+    conflict: conflict ?? null,
+  });
+}
+
 export type CapabilitiesResponse = {
   dialect: string;
   capabilities: string[];
@@ -353,7 +997,7 @@ export async function queryTable(
 ): Promise<ResultType> {
   console.debug('query table params:', params);
   const res = await invoke<ArrowResponse>('query_table', params);
-  return convert(res);
+  return { ...convert(res), estimated: res.estimated, cursor: res.cursor };
 }
 
 export type ExportFormat = 'csv' | 'tsv' | 'json' | 'parquet' | 'xlsx';
@@ -30,6 +30,8 @@ export function toSqlFormatterLanguage(
       return 'sqlite';
     case 'clickhouse':
       return 'clickhouse';
+    case 'mssql':
+      return 'transactsql';
     case 'duckdb':
     case 'quack':
     case 'folder':
@@ -1,4 +1,4 @@
-import { DataType } from "@apache-arrow/ts";
+import { DataType, IntervalUnit, TimeUnit } from "@apache-arrow/ts";
 import dayjs from "dayjs";
 import utc from "dayjs/plugin/utc";
 dayjs.extend(utc);
@@ -11,6 +11,89 @@ export type FieldFormatParamsType = {
   precision?: number;
 };
 
+/** Nanoseconds per unit of a Time / Timestamp column. */
+const NANOS_PER_UNIT: Record<TimeUnit, bigint> = {
+  [TimeUnit.SECOND]: BigInt(1_000_000_000),
+  [TimeUnit.MILLISECOND]: BigInt(1_000_000),
+  [TimeUnit.MICROSECOND]: BigInt(1_000),
+  [TimeUnit.NANOSECOND]: BigInt(1),
+};
+
+/** `HH:mm:ss[.ffffff]` for a signed span of nanoseconds. */
+const formatClock = (nanos: bigint): string => {
+  const sign = nanos < BigInt(0) ? "-" : "";
+  const abs = nanos < BigInt(0) ? -nanos : nanos;
+  const second = BigInt(1_000_000_000);
+  const total = abs / second;
+  const pad = (n: bigint) => n.toString().padStart(2, "0");
+  const hh = pad(total / BigInt(3600));
+  const mm = pad((total / BigInt(60)) % BigInt(60));
+  const ss = pad(total % BigInt(60));
+  const fraction = (abs % second)
+    .toString()
+    .padStart(9, "0")
+    .replace(/0+$/, "");
+  return `${sign}${hh}:${mm}:${ss}${fraction ? `.${fraction}` : ""}`;
+};
+
+/** 16 bytes as `xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx`, anything else as hex. */
+const formatFixedBinary = (value: Uint8Array): string => {
+  const hex = Array.from(value, (b) => b.toString(16).padStart(2, "0")).join(
+    "",
+  );
+  if (value.length !== 16) {
+    return `0x${hex}`;
+  }
+  return [8, 12, 16, 20].reduceRight(
+    (s, i) => `${s.slice(0, i)}-${s.slice(i)}`,
+    hex,
+  );
+};
+
+/**
+ * Postgres-style `1 year 2 mons 3 days 04:05:06`. MonthDayNano values arrive
+ * as `{ months, days, nanoseconds }` or as four int32 words, DayTime as
+ * `[days, ms]` and YearMonth as `[years, months]`.
+ */
+const formatInterval = (value: unknown, unit: IntervalUnit): string => {
+  let months = 0;
+  let days = 0;
+  let nanos = BigInt(0);
+  const words = value as ArrayLike<number>;
+  if (unit === IntervalUnit.YEAR_MONTH) {
+    months = words[0] * 12 + words[1];
+  } else if (unit === IntervalUnit.DAY_TIME) {
+    days = words[0];
+    nanos = BigInt(words[1]) * BigInt(1_000_000);
+  } else if (typeof value === "object" && value && "months" in value) {
+    const v = value as { months: number; days: number; nanoseconds: unknown };
+    months = v.months;
+    days = v.days;
+    nanos = BigInt(v.nanoseconds as bigint | number);
+  } else {
+    months = words[0];
+    days = words[1];
+    nanos = BigInt(words[2] >>> 0) + (BigInt(words[3]) << BigInt(32));
+  }
+  const plural = (n: number, one: string, many: string) =>
+    `${n} ${Math.abs(n) === 1 ? one : many}`;
+  const parts: string[] = [];
+  const years = Math.trunc(months / 12);
+  if (years) {
+    parts.push(plural(years, "year", "years"));
+  }
+  if (months % 12) {
+    parts.push(plural(months % 12, "mon", "mons"));
+  }
+  if (days) {
+    parts.push(plural(days, "day", "days"));
+  }
+  if (nanos || !parts.length) {
+    parts.push(formatClock(nanos));
+  }
+  return parts.join(" ");
+};
+
 const formatArrowValue = (
   value: unknown,
   dataType: DataType,
@@ -39,6 +122,17 @@ const formatArrowValue = (
     return formattedStruct;
   }
 
+  if (DataType.isFixedSizeBinary(dataType)) {
+    return formatFixedBinary(value as Uint8Array);
+  }
+  if (DataType.isInterval(dataType)) {
+    return formatInterval(value, dataType.unit);
+  }
+  if (DataType.isTime(dataType)) {
+    const ticks = BigInt(value as bigint | number);
+    return formatClock(ticks * NANOS_PER_UNIT[dataType.unit]);
+  }
+
   if (typeof value === "bigint") {
     return value.toString();
   }
@@ -1,11 +1,9 @@
 import { useCallback, useEffect, useRef, useState } from 'react';
-import { useSetAtom } from 'jotai';
 import { nanoid } from 'nanoid';
 
 import { useDialog } from '@/components/custom/use-dialog';
 import { isQueryErrorCode } from '@/lib/capabilities';
 import { filterRows } from '@/lib/filterRows';
-import { runsAtom } from '@/stores/app';
 import {
   mapParseLocationToDocument,
   useEditorSqlErrorStore,
@@ -35,7 +33,6 @@ export function QueryView({
     (s) => s.byEditor[editorId]?.byId[queryId],
   );
   const patchChild = useQuerySessionStore((s) => s.patchChild);
-  const setRuns = useSetAtom(runsAtom);
   const [loading, setLoading] = useState(false);
   const [error, setError] = useState<string | null>(null);
   const requestIdRef = useRef<string | null>(null);
@@ -51,35 +48,6 @@ export function QueryView({
     [editorId, patchChild, queryId],
   );
 
-  const patchHistoryResult = useCallback(
-    (
-      queryIdKey: string,
-      result: {
-        elapsed?: number;
-        total?: number;
-        code?: number;
-        message?: string;
-        sql?: string;
-      },
-    ) => {
-      setRuns((prev) =>
-        (prev ?? []).map((item) =>
-          item.id === queryIdKey
-            ? {
-                ...item,
-                elapsed: result.elapsed ?? item.elapsed,
-                total: result.total ?? item.total,
-                code: result.code ?? item.code,
-                message: result.message ?? item.message,
-                sql: result.sql ?? item.sql,
-              }
-            : item,
-        ),
-      );
-    },
-    [setRuns],
-  );
-
   const handleCancel = useCallback(async () => {
     const rid = requestIdRef.current;
     if (!rid) return;
@@ -103,19 +71,11 @@ export function QueryView({
           return;
         }
         const res = await executeSQL(current, { requestId });
-        const failedSql = res?.sql || current.stmt || current.sql;
         patch((prev) => ({
           ...prev,
           ...res,
           sql: res?.sql || prev.sql || current.stmt,
         }));
-        patchHistoryResult(current.id ?? queryId, {
-          elapsed: res?.elapsed,
-          total: res?.total,
-          code: res?.code,
-          message: res?.message,
-          sql: failedSql,
-        });
         if (isQueryErrorCode(res?.code) && res?.message) {
           setError(res.message);
           const loc = res.parseLocation;
@@ -136,11 +96,6 @@ export function QueryView({
         setError(msg);
         const current = getQueryChild(editorId, queryId);
         patch({ sql: current?.stmt || current?.sql });
-        patchHistoryResult(queryId, {
-          message: msg,
-          code: -1,
-          sql: current?.stmt || current?.sql,
-        });
       } finally {
         if (requestIdRef.current === requestId) {
           requestIdRef.current = null;
@@ -148,7 +103,7 @@ export function QueryView({
         setLoading(false);
       }
     },
-    [editorId, patch, patchHistoryResult, queryId],
+    [editorId, patch, queryId],
   );
 
   useEffect(() => {
@@ -141,6 +141,7 @@ export function TableDataPanel({
               <ValueViewer
                 selectedCell={selectedCell}
                 selectedCellInfos={selectedCellInfos}
+                schema={schema}
                 setShowValue={setShowValue}
                 setDirection={setDirection}
                 direction={direction}
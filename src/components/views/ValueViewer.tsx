@@ -8,7 +8,7 @@ import { ChevronsDownUpIcon, ChevronsUpDownIcon, LetterTextIcon, PanelBottomIcon
 import { editor } from 'monaco-editor';
 import { useCallback, useEffect, useMemo, useRef, useState } from 'react';
 
-import { arrowToJSON } from '@/api';
+import { arrowToJSON, isJsonField } from '@/api';
 import { DropdownMenu, DropdownMenuItem } from '@/components/custom/dropdown-menu';
 import { TooltipButton } from '@/components/custom/tooltip';
 import { DropdownMenuContent } from '@/components/custom/ui/dropdown-menu';
@@ -17,7 +17,7 @@ import ErrorBoundary from '@/components/ErrorBoundary';
 import { Table, TableBody, TableCell, TableHeader, TableRow } from '@/components/ui/table';
 import { formatRecordAsJson } from '@/lib/recordJson';
 import { cn } from '@/lib/utils';
-import { Direction } from '@/stores/dataset';
+import { Direction, SchemaType } from '@/stores/dataset';
 import {
   useCodeFontFamily,
   useCodeFontSize,
@@ -145,15 +145,28 @@ export function FormatTypeDropdown({ type, setType }: FormatTypeDropdownProps) {
 interface ValueViewerProps {
   selectedCell?: SelectedCellType | null;
   selectedCellInfos?: SelectedCellType[][] | null;
+  schema?: SchemaType[];
   setShowValue: () => void;
   setDirection: () => void;
   direction: Direction;
 }
 
-function displayValue(value: Data, type: string) {
+/** Indent JSON text; leaves it as-is when it does not parse. */
+function prettyJson(text: string) {
+  try {
+    return JSON.stringify(JSON.parse(text), null, 2);
+  } catch {
+    return text;
+  }
+}
+
+function displayValue(value: Data, type: string, jsonColumn = false) {
   if (value === null || value === undefined) {
     return value;
   }
+  if (jsonColumn && type === 'Raw' && typeof value === 'string') {
+    return prettyJson(value);
+  }
   if (type === 'JSON') {
     return arrowToJSON(value);
   }
@@ -175,6 +188,7 @@ const monacoBaseOptions: editor.IStandaloneEditorConstructionOptions = {
 export function ValueViewer({
   selectedCell,
   selectedCellInfos,
+  schema,
   setShowValue,
   setDirection,
   direction,
@@ -210,9 +224,14 @@ export function ValueViewer({
     }
   };
 
+  const jsonColumn = useMemo(
+    () => isJsonField(schema?.find((f) => f.name === selectedCell?.field)),
+    [schema, selectedCell?.field],
+  );
+
   const value = useMemo(
-    () => displayValue(selectedCell?.value as Data, type),
-    [selectedCell?.value, type],
+    () => displayValue(selectedCell?.value as Data, type, jsonColumn),
+    [selectedCell?.value, type, jsonColumn],
   );
 
   const recordJson = useMemo(() => {
@@ -279,7 +298,7 @@ export function ValueViewer({
               <FormatTypeDropdown type={type} setType={setType} />
               <TooltipButton
                 icon={<LetterTextIcon className="size-5" />}
-                disabled={!type.includes('JSON')}
+                disabled={!type.includes('JSON') && !jsonColumn}
                 onClick={handleFormat}
                 tooltip={t`Format`}
               />
@@ -339,7 +358,7 @@ export function ValueViewer({
         ) : (
           <MonacoEditor
             theme={theme}
-            language={type.includes('JSON') ? 'json' : 'plaintext'}
+            language={type.includes('JSON') || jsonColumn ? 'json' : 'plaintext'}
             value={value}
             onMount={(ed) => {
               valueEditorRef.current = ed;
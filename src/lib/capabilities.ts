@@ -11,7 +11,9 @@ export type Capability =
   | 'find'
   | 'drop_table'
   | 'execute'
-  | 'table_browse';
+  | 'table_browse'
+  | 'transaction'
+  | 'edit';
 
 const SQL_CORE: Capability[] = [
   'query',
@@ -28,15 +30,29 @@ export function capsForDialect(dialect: string | undefined | null): Set<Capabili
   switch (d) {
     case 'mysql':
     case 'postgres':
+      list = [...SQL_CORE, 'execute', 'transaction', 'edit'];
+      break;
     case 'clickhouse':
+    case 'mssql':
+      list = [...SQL_CORE, 'execute'];
+      break;
     case 'quack':
       list = [...SQL_CORE];
       break;
     case 'duckdb':
-      list = [...SQL_CORE, 'drop_table'];
+      list = [...SQL_CORE, 'drop_table', 'execute', 'edit'];
       break;
     case 'sqlite':
-      list = ['query', 'metadata', 'paging', 'export', 'table_browse'];
+      list = [
+        'query',
+        'metadata',
+        'paging',
+        'export',
+        'table_browse',
+        'execute',
+        'transaction',
+        'edit',
+      ];
       break;
     case 'folder':
       list = [
@@ -81,6 +97,18 @@ export function canMetadata(dialect: string | undefined | null): boolean {
   return hasCapability(dialect, 'metadata');
 }
 
+export function canExecute(dialect: string | undefined | null): boolean {
+  return hasCapability(dialect, 'execute');
+}
+
+export function canTransaction(dialect: string | undefined | null): boolean {
+  return hasCapability(dialect, 'transaction');
+}
+
+export function canEdit(dialect: string | undefined | null): boolean {
+  return hasCapability(dialect, 'edit');
+}
+
 export function canTableBrowse(dialect: string | undefined | null): boolean {
   return hasCapability(dialect, 'table_browse');
 }
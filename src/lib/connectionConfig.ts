@@ -95,7 +95,12 @@ export function normalizeDialectConfig<
   if (!config) {
     return config;
   }
-  if (config.dialect !== 'mysql' && config.dialect !== 'postgres' && config.dialect !== 'clickhouse') {
+  if (
+    config.dialect !== 'mysql' &&
+    config.dialect !== 'postgres' &&
+    config.dialect !== 'clickhouse' &&
+    config.dialect !== 'mssql'
+  ) {
     return config;
   }
 
@@ -201,7 +206,9 @@ export async function resolveConnectionSecretsForRegister(
 
   const tunnel =
     config &&
-    (config.dialect === 'mysql' || config.dialect === 'postgres') &&
+    (config.dialect === 'mysql' ||
+      config.dialect === 'postgres' ||
+      config.dialect === 'mssql') &&
     isRecord((config as Record<string, unknown>).ssh_tunnel)
       ? ((config as Record<string, unknown>).ssh_tunnel as SshTunnelConfig)
       : undefined;
@@ -52,7 +52,7 @@ export function buildQuickFilterWhere(
   const q = query.trim();
   if (!q || !columns.length) return '';
   const d = dialect.toLowerCase();
-  const useIlike = !['mysql', 'sqlite', 'clickhouse'].includes(d);
+  const useIlike = !['mysql', 'sqlite', 'clickhouse', 'mssql'].includes(d);
   const op = useIlike ? 'ILIKE' : 'LIKE';
   const escaped = q.replaceAll("'", "''");
   const pattern = `'%${escaped}%'`;
@@ -62,7 +62,9 @@ export function buildQuickFilterWhere(
       const cast =
         d === 'postgres' || d === 'duckdb'
           ? `${col}::text`
-          : `CAST(${col} AS CHAR)`;
+          : d === 'mssql'
+            ? `CAST(${col} AS NVARCHAR(MAX))`
+            : `CAST(${col} AS CHAR)`;
       return `${cast} ${op} ${pattern}`;
     })
     .join(' OR ');
@@ -4,6 +4,7 @@
 
 import { msg } from '@lingui/core/macro';
 
+import type { HistoryEntry } from '@/api';
 import { i18n } from '@/i18n';
 
 export type QueryHistoryItem = {
@@ -24,6 +25,11 @@ export type QueryHistoryItem = {
   /** Executed SQL if different from stmt (e.g. with limit). */
   sql?: string;
   displayName?: string;
+  /** Row id in the backend history store. */
+  historyId?: number;
+  /** `ConnectorError` code when the run failed. */
+  errorCode?: string;
+  pinned?: boolean;
 };
 
 /** Collapse whitespace and take a short one-line summary. */
@@ -128,6 +134,23 @@ export function normalizeHistoryItem(raw: unknown): QueryHistoryItem | null {
   };
 }
 
+/** A backend history entry as shown in the History panel. */
+export function fromHistoryEntry(entry: HistoryEntry): QueryHistoryItem {
+  return {
+    id: String(entry.id),
+    type: 'query',
+    dbId: entry.connectionId ?? '',
+    stmt: entry.sql,
+    createdAt: entry.executedAt,
+    elapsed: entry.elapsedMs,
+    total: entry.rows ?? undefined,
+    message: entry.message ?? undefined,
+    historyId: entry.id,
+    errorCode: entry.errorCode ?? undefined,
+    pinned: entry.pinned,
+  };
+}
+
 export function filterHistory(
   items: QueryHistoryItem[],
   query: string,
@@ -179,6 +202,7 @@ export function groupHistoryByConnection(
 }
 
 export function isHistoryError(item: QueryHistoryItem): boolean {
+  if (item.errorCode) return true;
   if (item.code != null && item.code !== 0) return true;
   if (item.message && /error|fail|cancel/i.test(item.message)) {
     // only treat as error if no successful total when code missing
@@ -4,6 +4,9 @@
 
 export function quoteIdent(name: string, dialect: string): string {
   const d = dialect.toLowerCase();
+  if (d === 'mssql') {
+    return `[${name.replaceAll(']', ']]')}]`;
+  }
   const q = d === 'mysql' || d === 'clickhouse' ? '`' : '"';
   return `${q}${name.replaceAll(q, q + q)}${q}`;
 }
@@ -39,6 +42,14 @@ export function buildCountByColumnSql(opts: CountByColumnSqlOptions): string {
       ? ` WHERE ${opts.where.trim()}`
       : '';
   const lim = opts.limit ?? 1000;
+  if (dialect.toLowerCase() === 'mssql') {
+    return (
+      `SELECT TOP ${lim} ${col} AS value, COUNT(*) AS count` +
+      ` FROM ${from}${where}` +
+      ` GROUP BY ${col}` +
+      ` ORDER BY count DESC`
+    );
+  }
   return (
     `SELECT ${col} AS value, COUNT(*) AS count` +
     ` FROM ${from}${where}` +
@@ -23,6 +23,9 @@ export function buildSampleSql(opts: {
   if (dialect === 'clickhouse') {
     return `SELECT * FROM ${from} SAMPLE 0.01 LIMIT ${lim}`;
   }
+  if (dialect === 'mssql') {
+    return `SELECT TOP ${lim} * FROM ${from} ORDER BY NEWID()`;
+  }
   // MySQL / SQLite / generic
   if (dialect === 'mysql') {
     return `SELECT * FROM ${from} ORDER BY RAND() LIMIT ${lim}`;
@@ -24,7 +24,6 @@ import {
   findStatementAtOffset,
   statementSliceToSourceRange,
 } from '@/lib/sql/splitStatements';
-import type { QueryHistoryItem } from '@/lib/queryHistory';
 import { useDefaultStatementSplit } from '@/stores/setting';
 import {
   flushWriteScratch,
@@ -39,7 +38,7 @@ import {
   formatMacroLabel,
   mergeMacroValues,
 } from '@/lib/sql/macros';
-import { bookmarksAtom, docsAtom, runsAtom } from '@/stores/app';
+import { bookmarksAtom, docsAtom } from '@/stores/app';
 import { DBType, useConnection, useConnectionMeta } from '@/stores/dbList';
 import { useEditorDirtyStore } from '@/stores/editorDirty';
 import {
@@ -289,7 +288,6 @@ export default function Editor({ context }: { context: EditorContextType }) {
     };
   };
 
-  const setRuns = useSetAtom(runsAtom);
   const setBookmarks = useSetAtom(bookmarksAtom);
 
   const handleBookmark = () => {
@@ -421,22 +419,6 @@ export default function Editor({ context }: { context: EditorContextType }) {
         ?.activeKey;
       let baseCount = childCount;
 
-      const historyEntry = (entry: {
-        id: string;
-        stmt: string;
-        displayName: string;
-      }): QueryHistoryItem => ({
-        id: entry.id,
-        type: 'query',
-        dbId,
-        schema: context.schema,
-        tableId: context.tableId,
-        stmt: entry.stmt,
-        hasLimit,
-        createdAt: Date.now(),
-        displayName: entry.displayName,
-      });
-
       let lastId = '';
       statements.forEach((item, index) => {
         const childId = `${id}@${nanoid()}`;
@@ -489,11 +471,6 @@ export default function Editor({ context }: { context: EditorContextType }) {
           });
           appendChild(id, subContext);
         }
-
-        setRuns((prev) => [
-          historyEntry({ id: childId, stmt: item.sql, displayName }),
-          ...(prev ?? []),
-        ]);
       });
 
       if (lastId) {
@@ -510,7 +487,6 @@ export default function Editor({ context }: { context: EditorContextType }) {
       id,
       setActiveKey,
       setChildren,
-      setRuns,
       t,
     ],
   );
@@ -570,7 +546,11 @@ export default function Editor({ context }: { context: EditorContextType }) {
           }
         }
 
-        const expanded = await expandSqlTemplate(expandSource, overrides);
+        const expanded = await expandSqlTemplate(
+          expandSource,
+          db?.dialect,
+          overrides,
+        );
         statements = expanded.statements;
         if (statements.length === 0) {
           toast.error(t`Empty SQL`);
@@ -1,23 +1,24 @@
-import { useMemo, useState } from 'react';
-import { useAtom, useSetAtom } from 'jotai';
+import { useCallback, useEffect, useMemo, useRef, useState } from 'react';
+import { useSetAtom } from 'jotai';
 import { useLingui } from '@lingui/react/macro';
 import {
   ChevronRight,
   DatabaseIcon,
+  PinIcon,
   SearchIcon,
   Trash2Icon,
   XIcon,
 } from 'lucide-react';
 
+import { clearHistory, onHistoryChanged, searchHistory } from '@/api';
 import { Input } from '@/components/custom/ui/input';
 import {
-  filterHistory,
   formatAbsoluteTime,
   formatElapsedMs,
   formatRelativeTime,
+  fromHistoryEntry,
   groupHistoryByConnection,
   isHistoryError,
-  normalizeHistoryItem,
   sqlVerb,
   summarizeSql,
   type QueryHistoryItem,
@@ -28,12 +29,15 @@ import {
 } from '@/lib/scratchSql';
 import { cn } from '@/lib/utils';
 import { HistoryContextMenu } from '@/pages/sidebar/context-menu/HistoryContextMenu';
-import { docsAtom, runsAtom } from '@/stores/app';
+import { docsAtom } from '@/stores/app';
 import { getStoredDB } from '@/stores/dbList';
 import { useTabsStore } from '@/stores/tabs';
 
 import { Container } from './Favorite';
 
+/** Newest entries loaded into the panel. */
+const HISTORY_LIMIT = 500;
+
 function connectionLabel(
   dbId: string,
   unknownLabel: string,
@@ -51,9 +55,11 @@ function connectionDialect(dbId: string): string | undefined {
 function HistoryItemRow({
   item,
   onOpen,
+  onChanged,
 }: {
   item: QueryHistoryItem;
   onOpen: (item: QueryHistoryItem) => void;
+  onChanged: () => void;
 }) {
   const { t } = useLingui();
   const err = isHistoryError(item);
@@ -76,7 +82,7 @@ function HistoryItemRow({
   ].filter(Boolean);
 
   return (
-    <HistoryContextMenu ctx={item}>
+    <HistoryContextMenu ctx={item} onChanged={onChanged}>
       <button
         type="button"
         className={cn(
@@ -104,8 +110,13 @@ function HistoryItemRow({
             {verb.slice(0, 6)}
           </span>
           <div className="min-w-0 flex-1">
-            <div className="truncate font-mono text-xs leading-snug">
-              {summary}
+            <div className="flex items-center gap-1">
+              <span className="truncate font-mono text-xs leading-snug">
+                {summary}
+              </span>
+              {item.pinned ? (
+                <PinIcon className="size-3 shrink-0 text-muted-foreground" />
+              ) : null}
             </div>
             <div className="mt-0.5 flex flex-wrap items-center gap-x-2 gap-y-0.5 text-[10px] text-muted-foreground">
               {when ? <span title={abs}>{when}</span> : null}
@@ -136,12 +147,14 @@ function ConnectionGroup({
   expanded,
   onToggle,
   onOpen,
+  onChanged,
 }: {
   dbId: string;
   items: QueryHistoryItem[];
   expanded: boolean;
   onToggle: () => void;
   onOpen: (item: QueryHistoryItem) => void;
+  onChanged: () => void;
 }) {
   const { t } = useLingui();
   const name = connectionLabel(dbId, t`Unknown`);
@@ -175,7 +188,12 @@ function ConnectionGroup({
       </button>
       {expanded
         ? items.map((item) => (
-            <HistoryItemRow key={item.id} item={item} onOpen={onOpen} />
+            <HistoryItemRow
+              key={item.id}
+              item={item}
+              onOpen={onOpen}
+              onChanged={onChanged}
+            />
           ))
         : null}
     </div>
@@ -184,7 +202,6 @@ function ConnectionGroup({
 
 export function History() {
   const { t } = useLingui();
-  const [rawItems, setRuns] = useAtom(runsAtom);
   const setDocs = useSetAtom(docsAtom);
   const append = useTabsStore((state) => state.append);
   const active = useTabsStore((state) => state.active);
@@ -192,27 +209,45 @@ export function History() {
   const tabs = useTabsStore((state) => state.tabs);
   const patch = useTabsStore((state) => state.patch);
 
+  const [items, setItems] = useState<QueryHistoryItem[]>([]);
   const [search, setSearch] = useState('');
   const [collapsed, setCollapsed] = useState<Record<string, boolean>>({});
+  const text = search.trim();
+  // Drops responses that arrive after a newer search was sent.
+  const latest = useRef(0);
+
+  const reload = useCallback(async () => {
+    const request = ++latest.current;
+    try {
+      const entries = await searchHistory({
+        text: text || undefined,
+        limit: HISTORY_LIMIT,
+      });
+      if (request === latest.current) {
+        setItems(entries.map(fromHistoryEntry));
+      }
+    } catch (e) {
+      console.warn('failed to load query history', e);
+    }
+  }, [text]);
 
-  const items = useMemo(() => {
-    return (rawItems ?? [])
-      .map((r) => normalizeHistoryItem(r))
-      .filter((x): x is QueryHistoryItem => x != null);
-  }, [rawItems]);
+  // Search as the user types, after a short pause.
+  useEffect(() => {
+    const timer = setTimeout(() => void reload(), text ? 200 : 0);
+    return () => clearTimeout(timer);
+  }, [reload, text]);
 
-  const filtered = useMemo(
-    () => filterHistory(items, search),
-    [items, search],
-  );
+  useEffect(() => {
+    const unlisten = onHistoryChanged(() => void reload());
+    return () => {
+      void unlisten.then((off) => off());
+    };
+  }, [reload]);
 
-  const groups = useMemo(
-    () => groupHistoryByConnection(filtered),
-    [filtered],
-  );
+  const groups = useMemo(() => groupHistoryByConnection(items), [items]);
 
   // When searching, expand all groups so matches are visible.
-  const searching = search.trim().length > 0;
+  const searching = text.length > 0;
 
   const handleOpen = (item: QueryHistoryItem) => {
     const stmt = item.stmt ?? '';
@@ -244,9 +279,10 @@ export function History() {
     });
   };
 
+  // Pinned entries are kept.
   const handleClearAll = () => {
     if (!items.length) return;
-    setRuns([]);
+    void clearHistory().then(reload);
   };
 
   return (
@@ -287,9 +323,7 @@ export function History() {
             ) : null}
           </div>
           <div className="mt-1 px-0.5 text-[10px] text-muted-foreground">
-            {searching
-              ? t`${filtered.length} of ${items.length} runs`
-              : t`${items.length} run(s)`}
+            {t`${items.length} run(s)`}
           </div>
         </div>
         <div className="flex-1 min-h-0 overflow-auto">
@@ -313,6 +347,7 @@ export function History() {
                     }))
                   }
                   onOpen={handleOpen}
+                  onChanged={reload}
                 />
               );
             })
@@ -1,4 +1,5 @@
-import { docsAtom, runsAtom } from '@/stores/app';
+import { deleteHistory, pinHistory } from '@/api';
+import { docsAtom } from '@/stores/app';
 import { useTabsStore, type EditorContextType } from '@/stores/tabs';
 import { Trans, useLingui } from '@lingui/react/macro';
 import { writeText } from '@tauri-apps/plugin-clipboard-manager';
@@ -24,9 +25,9 @@ import {
 export function HistoryContextMenu({
   children,
   ctx,
-}: PropsWithChildren<{ ctx: QueryHistoryItem }>) {
+  onChanged,
+}: PropsWithChildren<{ ctx: QueryHistoryItem; onChanged: () => void }>) {
   const { t } = useLingui();
-  const setItems = useSetAtom(runsAtom);
   const setDocs = useSetAtom(docsAtom);
   const append = useTabsStore((s) => s.append);
   const active = useTabsStore((s) => s.active);
@@ -39,7 +40,15 @@ export function HistoryContextMenu({
   };
 
   const handleDelete = async () => {
-    setItems((prev) => (prev ?? []).filter((p) => p.id != ctx.id));
+    if (ctx.historyId == null) return;
+    await deleteHistory(ctx.historyId);
+    onChanged();
+  };
+
+  const handlePin = async () => {
+    if (ctx.historyId == null) return;
+    await pinHistory(ctx.historyId, !ctx.pinned);
+    onChanged();
   };
 
   const handleOpenInEditor = () => {
@@ -97,6 +106,9 @@ export function HistoryContextMenu({
         <ContextMenuItem onSelect={handleCopy}>
           <Trans>Copy</Trans>
         </ContextMenuItem>
+        <ContextMenuItem onSelect={handlePin}>
+          {ctx.pinned ? <Trans>Unpin</Trans> : <Trans>Pin</Trans>}
+        </ContextMenuItem>
         <ContextMenuItem onSelect={handleDelete}>
           <Trans>Delete</Trans>
         </ContextMenuItem>
@@ -126,7 +126,11 @@ export function DatabaseForm({ form, handleSubmit, isNew = true, availableDataba
   const [sshHosts, setSshHosts] = useState<SshConfigHost[]>([]);
   const [dbSearch, setDbSearch] = useState('');
   const sshProfiles = useSshProfileStore((s) => s.profiles);
-  const supportsSsh = watchDialect === 'mysql' || watchDialect === 'postgres' || watchDialect === 'clickhouse';
+  const supportsSsh =
+    watchDialect === 'mysql' ||
+    watchDialect === 'postgres' ||
+    watchDialect === 'clickhouse' ||
+    watchDialect === 'mssql';
   const useSshProfile =
     !!watchSshProfileId && watchSshProfileId !== '__manual__';
   const selectedSshProfile = sshProfiles.find((p) => p.id === watchSshProfileId);
@@ -165,6 +169,7 @@ export function DatabaseForm({ form, handleSubmit, isNew = true, availableDataba
       { label: 'MySQL', value: 'mysql' },
       { label: 'Postgres', value: 'postgres' },
       { label: 'Clickhouse', value: 'clickhouse' },
+      { label: 'SQL Server', value: 'mssql' },
     ],
     [t],
   );
@@ -284,7 +289,15 @@ export function DatabaseForm({ form, handleSubmit, isNew = true, availableDataba
                           form.getValues('disable_ssl') ?? true,
                         );
                       }
-                      if (value !== 'mysql' && value !== 'postgres' && value !== 'clickhouse') {
+                      if (value === 'mssql') {
+                        form.setValue('disable_ssl', false);
+                      }
+                      if (
+                        value !== 'mysql' &&
+                        value !== 'postgres' &&
+                        value !== 'clickhouse' &&
+                        value !== 'mssql'
+                      ) {
                         form.setValue('ssh_tunnel', undefined, {
                           shouldDirty: false,
                         });
@@ -321,7 +334,8 @@ export function DatabaseForm({ form, handleSubmit, isNew = true, availableDataba
             />
             {watchDialect == 'clickhouse' ||
             watchDialect == 'mysql' ||
-            watchDialect == 'postgres' ? (
+            watchDialect == 'postgres' ||
+            watchDialect == 'mssql' ? (
               <>
                 <div className="flex">
                   <FormField
@@ -430,6 +444,46 @@ export function DatabaseForm({ form, handleSubmit, isNew = true, availableDataba
                     )}
                   />
                 ) : null}
+                {watchDialect === 'mssql' ? (
+                  <FormField
+                    control={form.control}
+                    name="disable_ssl"
+                    render={({ field }) => (
+                      <FormItem className="flex items-center w-[62.5%]">
+                        <FormLabel className="w-1/5 mr-2 mt-2">
+                          <Trans>Disable SSL</Trans>
+                        </FormLabel>
+                        <FormControl>
+                          <Switch
+                            checked={field.value}
+                            onCheckedChange={field.onChange}
+                          />
+                        </FormControl>
+                        <FormMessage />
+                      </FormItem>
+                    )}
+                  />
+                ) : null}
+                {watchDialect === 'mssql' ? (
+                  <FormField
+                    control={form.control}
+                    name="trust_server_certificate"
+                    render={({ field }) => (
+                      <FormItem className="flex items-center w-[62.5%]">
+                        <FormLabel className="w-1/5 mr-2 mt-2">
+                          <Trans>Trust server certificate</Trans>
+                        </FormLabel>
+                        <FormControl>
+                          <Switch
+                            checked={field.value}
+                            onCheckedChange={field.onChange}
+                          />
+                        </FormControl>
+                        <FormMessage />
+                      </FormItem>
+                    )}
+                  />
+                ) : null}
                 <FormField
                   control={form.control}
                   name="password"
@@ -1,4 +1,3 @@
-import type { QueryHistoryItem } from '@/lib/queryHistory';
 import {
   useWorkspaceStore,
   type SqlBookmark,
@@ -89,13 +88,6 @@ export const favoriteAtom = workspaceSliceAtom(
   workspaceSubscribe,
 );
 
-/** Query run history (newest items typically appended; UI sorts by createdAt). */
-export const runsAtom = workspaceSliceAtom(
-  () => useWorkspaceStore.getState().runs,
-  (next) => useWorkspaceStore.getState().setRuns(next),
-  workspaceSubscribe,
-);
-
 /** SQL editor bodies (scratch id → text, or absolute path → text). Session cache. */
 export const docsAtom = atomWithStorage<Record<string, string>>(
   'docs',
@@ -127,6 +119,3 @@ export const themeAtom = atomWithStorage<ThemeType>(
 export type ThemeType = 'light' | 'dark' | 'system';
 
 export const isDev = import.meta.env.MODE === 'development';
-
-// Re-export type used by consumers that imported QueryHistoryItem via runs only
-export type { QueryHistoryItem };
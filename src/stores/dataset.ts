@@ -52,6 +52,12 @@ export interface ArrowResponse {
   columns?: TitleType[];
   /** Row objects when format is rows. */
   rows?: Record<string, unknown>[];
+  /** `total` is an estimate rather than an exact count. */
+  estimated?: boolean;
+  /** Keyset token for the next `query_table` page. */
+  cursor?: string;
+  /** Rows were dropped to stay within the row / byte limits. */
+  truncated?: boolean;
 }
 export type Direction = 'horizontal' | 'vertical';
 
@@ -88,6 +94,18 @@ export type DatasetState = {
   computedColumns: ComputedColumn[];
   /** In-flight refresh request id for cancel. */
   refreshRequestId?: string;
+  /** Keyset cursor of the last page loaded, for seeking to the one after it. */
+  seek?: PageSeek;
+};
+
+/**
+ * `cursor` returned for `page`; `key` ties it to the page size, filter and
+ * order it was read with.
+ */
+type PageSeek = {
+  page: number;
+  key: string;
+  cursor: string;
 };
 
 export type DatasetAction = {
@@ -239,6 +257,17 @@ export const createDatasetStore = (context: TabContextType) =>
         orderClause = orderByClause(orderBy.name, orderBy.desc, dialect);
       }
 
+      // Seek past the previous page when stepping forward one page.
+      const key = JSON.stringify([
+        perPage,
+        sqlWhere,
+        orderClause,
+        computedColumns,
+      ]);
+      const { seek } = get();
+      const after =
+        seek?.page === page - 1 && seek.key === key ? seek.cursor : undefined;
+
       const ctx: QueryParamType = {
         type: context?.type,
         dbId: context?.dbId,
@@ -246,6 +275,7 @@ export const createDatasetStore = (context: TabContextType) =>
         tableName: context?.tableName,
         page,
         perPage,
+        after,
         sqlWhere,
         sqlOrderBy: orderClause,
         computedColumns,
@@ -257,6 +287,7 @@ export const createDatasetStore = (context: TabContextType) =>
         set({
           ...data,
           sql: data?.sql ?? get().sql,
+          seek: data?.cursor ? { page, key, cursor: data.cursor } : undefined,
           loading: false,
           refreshRequestId: undefined,
         });
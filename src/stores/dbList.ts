@@ -3,7 +3,7 @@ import { toast } from 'sonner';
 import { create, useStore } from 'zustand';
 import { createJSONStorage, persist } from 'zustand/middleware';
 
-import { getDB, type FunctionMetaDto } from '@/api';
+import { getDB, type FunctionMetaDto, type QueryLimits } from '@/api';
 import {
   normalizeDialectConfig,
   pickSecrets,
@@ -36,6 +36,7 @@ export type DialectType =
   | 'duckdb'
   | 'quack'
   | 'clickhouse'
+  | 'mssql'
   | 'sqlite'
   | 'postgres'
   | 'mysql';
@@ -112,6 +113,19 @@ export type MySqlDialectType = {
   ssh_tunnel?: SshTunnelConfig;
 };
 
+export type MssqlDialectType = {
+  host: string;
+  port: string;
+  password: string;
+  username: string;
+  database: string;
+  dialect: 'mssql';
+  disable_ssl?: boolean;
+  /** Accept any server certificate instead of verifying it. */
+  trust_server_certificate?: boolean;
+  ssh_tunnel?: SshTunnelConfig;
+};
+
 export type QuackConfig = {
   uri: string;
   token?: string;
@@ -121,14 +135,19 @@ export type QuackConfig = {
   dialect: 'quack';
 };
 
-export type DialectConfig =
+export type DialectConfig = (
   | DuckdbConfig
   | ClickhouseDialectType
   | FolderConfig
   | FileConfig
   | PostgresDialectType
   | MySqlDialectType
-  | QuackConfig;
+  | MssqlDialectType
+  | QuackConfig
+) & {
+  /** Default statement timeout and result caps for this connection. */
+  limits?: QueryLimits;
+};
 
 export type DBType = {
   id: string;
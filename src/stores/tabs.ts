@@ -78,6 +78,8 @@ export type QueryParamType = {
 
   page: number;
   perPage: number;
+  /** Previous table page's `cursor`: seek past it instead of offsetting. */
+  after?: string;
 
   hasLimit?: boolean;
 
@@ -542,6 +544,7 @@ export async function getParams(
     where: sqlWhere,
     orderBy: sqlOrderBy,
     ...(selectExtras ? { selectExtras } : {}),
+    ...(ctx.after ? { after: ctx.after } : {}),
     ...param,
   };
 }
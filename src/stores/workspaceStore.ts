@@ -22,6 +22,7 @@ export type SqlBookmark = {
 type WorkspaceState = {
   sqlFolders: string[];
   bookmarks: SqlBookmark[];
+  /** Runs recorded before history moved to the backend; no longer written. */
   runs: QueryHistoryItem[];
   favorite: TabContextType[];
 };
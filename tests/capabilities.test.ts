@@ -2,10 +2,13 @@ import { describe, expect, it } from 'vitest';
 
 import {
   canDropTable,
+  canEdit,
+  canExecute,
   canExport,
   canFind,
   canMetadata,
   canTableBrowse,
+  canTransaction,
   capsForDialect,
   hasCapability,
   isQueryErrorCode,
@@ -13,7 +16,7 @@ import {
 
 describe('capsForDialect', () => {
   it('matches product expectations for network SQL dialects', () => {
-    for (const d of ['mysql', 'postgres', 'clickhouse', 'quack']) {
+    for (const d of ['mysql', 'postgres', 'clickhouse', 'mssql', 'quack']) {
       expect(canExport(d)).toBe(true);
       expect(canDropTable(d)).toBe(false);
       expect(canFind(d)).toBe(false);
@@ -48,6 +51,32 @@ describe('capsForDialect', () => {
     expect(canExport(null)).toBe(false);
   });
 
+  it('execute is advertised for writable SQL dialects only', () => {
+    for (const d of ['mysql', 'postgres', 'clickhouse', 'mssql', 'duckdb', 'sqlite']) {
+      expect(canExecute(d)).toBe(true);
+    }
+    expect(canExecute('quack')).toBe(false);
+    expect(canExecute('folder')).toBe(false);
+    expect(canExecute('file')).toBe(false);
+  });
+
+  it('transaction is limited to dialects with dedicated connections', () => {
+    for (const d of ['mysql', 'postgres', 'sqlite']) {
+      expect(canTransaction(d)).toBe(true);
+    }
+    expect(canTransaction('clickhouse')).toBe(false);
+    expect(canTransaction('mssql')).toBe(false);
+    expect(canTransaction('duckdb')).toBe(false);
+  });
+
+  it('grid editing needs primary-key discovery', () => {
+    for (const d of ['mysql', 'postgres', 'sqlite', 'duckdb']) {
+      expect(canEdit(d)).toBe(true);
+    }
+    expect(canEdit('clickhouse')).toBe(false);
+    expect(canEdit('folder')).toBe(false);
+  });
+
   it('metadata and table_browse flags', () => {
     expect(canMetadata('mysql')).toBe(true);
     expect(canMetadata('file')).toBe(false);
@@ -23,6 +23,16 @@ describe('countByColumn SQL', () => {
     expect(quoteIdent('userId', 'mysql')).toBe('`userId`');
   });
 
+  it('quotes mssql identifiers with brackets', () => {
+    expect(quoteIdent('order', 'mssql')).toBe('[order]');
+    expect(quoteIdent('a]b', 'mssql')).toBe('[a]]b]');
+    expect(
+      buildCountByColumnSql({ tableExpr: 'dbo.t', column: 'c', dialect: 'mssql', limit: 5 }),
+    ).toBe(
+      'SELECT TOP 5 [c] AS value, COUNT(*) AS count FROM [dbo].[t] GROUP BY [c] ORDER BY count DESC',
+    );
+  });
+
   it('quotes bare table paths but not table functions', () => {
     expect(quoteTableExpr('public.items', 'postgres')).toBe(
       '"public"."items"',
@@ -3,6 +3,7 @@ import { describe, expect, it } from 'vitest';
 import {
   filterHistory,
   formatElapsedMs,
+  fromHistoryEntry,
   groupHistoryByConnection,
   isHistoryError,
   normalizeHistoryItem,
@@ -80,4 +81,24 @@ describe('normalize / filter / group', () => {
     expect(isHistoryError(sample({ code: 1, message: 'fail' }))).toBe(true);
     expect(isHistoryError(sample({ code: 0, total: 10 }))).toBe(false);
   });
+
+  it('maps backend entries', () => {
+    const item = fromHistoryEntry({
+      id: 7,
+      connectionId: 'db-a',
+      kind: 'query',
+      sql: 'select 1',
+      executedAt: 1000,
+      elapsedMs: 5,
+      rows: null,
+      errorCode: 'timeout',
+      message: 'statement timed out',
+      pinned: true,
+    });
+    expect(item.id).toBe('7');
+    expect(item.historyId).toBe(7);
+    expect(item.dbId).toBe('db-a');
+    expect(item.total).toBeUndefined();
+    expect(isHistoryError(item)).toBe(true);
+  });
 });
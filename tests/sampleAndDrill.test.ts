@@ -17,6 +17,9 @@ describe('buildSampleSql', () => {
     expect(buildSampleSql({ tableExpr: 'public.t', dialect: 'sqlite' })).toContain(
       'RANDOM()',
     );
+    expect(buildSampleSql({ tableExpr: 'dbo.t', dialect: 'mssql', limit: 10 })).toBe(
+      'SELECT TOP 10 * FROM [dbo].[t] ORDER BY NEWID()',
+    );
   });
 });
 